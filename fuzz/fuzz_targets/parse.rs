@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use yaml_peg::{dump, parser::Loader, repr::RcRepr};
+
+fuzz_target!(|data: &[u8]| {
+    // The parser must return an error instead of panicking
+    let mut loader = Loader::<RcRepr>::new(data);
+    if let Ok(nodes) = loader.parse() {
+        let _ = dump(&nodes, &loader.get_anchors());
+    }
+    if let Ok(doc) = core::str::from_utf8(data) {
+        let _ = yaml_peg::parse_documents::<RcRepr>(doc);
+    }
+});
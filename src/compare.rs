@@ -0,0 +1,111 @@
+use crate::{
+    repr::Repr,
+    yaml::{to_f64, to_i128},
+    *,
+};
+use alloc::collections::BTreeSet;
+
+/// The options of [`semantic_eq`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SemanticOptions {
+    ordered_maps: bool,
+    numeric: bool,
+    tags: bool,
+}
+
+impl SemanticOptions {
+    /// Create the default options.
+    pub const fn new() -> Self {
+        Self { ordered_maps: false, numeric: true, tags: false }
+    }
+
+    /// Compare the map items in order, default to false.
+    pub fn ordered_maps(self, ordered_maps: bool) -> Self {
+        Self { ordered_maps, ..self }
+    }
+
+    /// Compare the integers and the floats by their values, e.g., `1.0`
+    /// equals `1`, default to true.
+    pub fn numeric(self, numeric: bool) -> Self {
+        Self { numeric, ..self }
+    }
+
+    /// Compare the tags, default to false.
+    ///
+    /// The nodes without an explicit tag use the tag of their kind, see
+    /// [`Node::tag`].
+    pub fn tags(self, tags: bool) -> Self {
+        Self { tags, ..self }
+    }
+}
+
+impl Default for SemanticOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compare two trees by their data, for asserting the YAML equality in the
+/// tests.
+///
+/// The positions, the anchor definitions, the scalar notations (e.g., `0x10`
+/// and `16`, `'a'` and `a`) and the collection styles are always ignored.
+/// The aliases are compared by names, please resolve them before
+/// comparing, see [`Anchors::expand`](crate::parser::Anchors::expand).
+///
+/// ```
+/// use yaml_peg::{semantic_eq, NodeRc, SemanticOptions};
+///
+/// let a: NodeRc = "{a: 1.0, b: [0x10, 'x']}".parse().unwrap();
+/// let b: NodeRc = "b:\n  - 16\n  - !!str x\na: 1\n".parse().unwrap();
+/// assert_ne!(a, b);
+/// assert!(semantic_eq(&a, &b, SemanticOptions::new()));
+/// assert!(!semantic_eq(&a, &b, SemanticOptions::new().ordered_maps(true)));
+/// assert!(!semantic_eq(&a, &b, SemanticOptions::new().numeric(false)));
+/// ```
+pub fn semantic_eq<R: Repr>(a: &Node<R>, b: &Node<R>, opts: SemanticOptions) -> bool {
+    if opts.tags && a.tag() != b.tag() {
+        return false;
+    }
+    match (a.yaml(), b.yaml()) {
+        (Yaml::Int(n), Yaml::Float(f)) | (Yaml::Float(f), Yaml::Int(n)) if opts.numeric => {
+            match (to_i128(n), to_f64(f)) {
+                (Ok(n), Ok(f)) => n as f64 == f && f as i128 == n,
+                _ => false,
+            }
+        }
+        (Yaml::Seq(v1), Yaml::Seq(v2)) => {
+            v1.len() == v2.len() && v1.iter().zip(v2).all(|(a, b)| semantic_eq(a, b, opts))
+        }
+        (Yaml::Map(m1), Yaml::Map(m2)) if m1.len() != m2.len() => false,
+        (Yaml::Map(m1), Yaml::Map(m2)) if opts.ordered_maps => m1
+            .iter()
+            .zip(m2)
+            .all(|((k1, v1), (k2, v2))| semantic_eq(k1, k2, opts) && semantic_eq(v1, v2, opts)),
+        (Yaml::Map(m1), Yaml::Map(m2)) => {
+            let item_eq = |(k1, v1): (&Node<R>, &Node<R>), (k2, v2): (&Node<R>, &Node<R>)| {
+                semantic_eq(k1, k2, opts) && semantic_eq(v1, v2, opts)
+            };
+            // The addresses of the matched keys of `m2`
+            let mut used = BTreeSet::new();
+            'items: for item in m1.iter() {
+                // Check the same key first, then find the equivalent keys
+                if let Some(other) = m2.raw_entry().from_key(item.0) {
+                    if item_eq(item, other) && used.insert(other.0 as *const Node<R>) {
+                        continue;
+                    }
+                }
+                for other in m2.iter() {
+                    if !used.contains(&(other.0 as *const Node<R>)) && item_eq(item, other) {
+                        used.insert(other.0 as *const Node<R>);
+                        continue 'items;
+                    }
+                }
+                return false;
+            }
+            true
+        }
+        (a, b) => a == b,
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{parser::Anchors, repr::*, *};
+use alloc::{collections::BTreeMap, string::String};
+use core::fmt::{Debug, Formatter};
+
+/// A document with [`alloc::rc::Rc`] holder.
+pub type DocumentRc = Document<RcRepr>;
+/// A document with [`alloc::sync::Arc`] holder.
+pub type DocumentArc = Document<ArcRepr>;
+
+/// A YAML document, including the root node and its context.
+///
+/// The function [`parse_documents`] returns this type,
+/// which keeps the directives and anchors of each document.
+///
+/// ```
+/// use yaml_peg::{node, parse_documents};
+///
+/// let doc = "\
+/// %YAML 1.2
+/// %TAG !x! tag:my.prefix:
+/// ---
+/// a: &b c
+/// ...
+/// ";
+/// let docs = parse_documents(doc).unwrap();
+/// assert_eq!(docs[0].root, node!({"a" => "c"}));
+/// assert_eq!(docs[0].anchors["b"], node!("c"));
+/// assert_eq!(docs[0].version.as_deref(), Some("1.2"));
+/// assert_eq!(docs[0].tags["!x!"], "tag:my.prefix:");
+/// assert!(docs[0].explicit_start);
+/// assert!(docs[0].explicit_end);
+/// ```
+pub struct Document<R: Repr> {
+    /// The root node.
+    pub root: Node<R>,
+    /// The anchors defined in this document.
+    pub anchors: Anchors<R>,
+    /// The version from `%YAML` directive.
+    pub version: Option<String>,
+    /// The tag handles from `%TAG` directives, maps handle to prefix.
+    ///
+    /// The default handles `!` and `!!` are excluded unless they are
+    /// overridden.
+    pub tags: BTreeMap<String, String>,
+    /// The document is started with `---` marker.
+    pub explicit_start: bool,
+    /// The document is ended with `...` marker.
+    pub explicit_end: bool,
+}
+
+impl<R: Repr> Document<R> {
+    /// Create a document without directives and markers.
+    pub fn new(root: Node<R>) -> Self {
+        Self {
+            root,
+            anchors: Anchors::new(),
+            version: None,
+            tags: BTreeMap::new(),
+            explicit_start: false,
+            explicit_end: false,
+        }
+    }
+}
+
+impl<R: Repr> Debug for Document<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Document")
+            .field("root", &self.root)
+            .field("anchors", &self.anchors)
+            .field("version", &self.version)
+            .field("tags", &self.tags)
+            .field("explicit_start", &self.explicit_start)
+            .field("explicit_end", &self.explicit_end)
+            .finish()
+    }
+}
+
+impl<R: Repr> Clone for Document<R> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            anchors: self.anchors.clone(),
+            version: self.version.clone(),
+            tags: self.tags.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<R: Repr> From<Node<R>> for Document<R> {
+    fn from(root: Node<R>) -> Self {
+        Self::new(root)
+    }
+}
@@ -1,11 +1,17 @@
 //! Dumper components.
-use crate::{parser::Anchors, repr::Repr, *};
+use crate::{
+    parser::Anchors,
+    repr::{RcRepr, Repr},
+    yaml::{to_f64, to_i128},
+    *,
+};
 use alloc::{
+    collections::{BTreeMap, BTreeSet},
     format,
     string::{String, ToString},
     vec::Vec,
 };
-use core::fmt::Write;
+use core::fmt::{self, Write};
 
 /// Newline symbol in common platforms.
 ///
@@ -15,131 +21,1368 @@ use core::fmt::Write;
 /// Please be aware that your storage can be used the symbol of Windows.
 pub const NL: &str = if cfg!(windows) { "\r\n" } else { "\n" };
 
-#[derive(Eq, PartialEq)]
+static NO_TAGS: BTreeMap<String, String> = BTreeMap::new();
+static DEFAULT_OPTIONS: DumpOptions = DumpOptions::new();
+
+/// The quoting policy of the strings, see [`DumpOptions::quote`].
+///
+/// The strings are always quoted if their plain form will be parsed as
+/// another value, such as `1.0`, `no`, `0x10` and `*a`, under YAML 1.2 or
+/// YAML 1.1 schema.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum QuotePolicy {
+    /// Use plain string if possible.
+    #[default]
+    Minimal,
+    /// Always quote the strings.
+    Always,
+    /// Quote the strings if the function returns true.
+    Custom(fn(&str) -> bool),
+}
+
+/// The quote symbol of the strings, see [`DumpOptions::quote_style`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Double quoted string `"..."`.
+    #[default]
+    Double,
+    /// Single quoted string `'...'`.
+    ///
+    /// The strings with escaped characters will be double quoted.
+    Single,
+}
+
+/// The representation of null value, see [`DumpOptions::null`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NullStyle {
+    /// `null`
+    #[default]
+    Lower,
+    /// `Null`
+    Title,
+    /// `NULL`
+    Upper,
+    /// `~`
+    Tilde,
+    /// Empty value.
+    Empty,
+}
+
+/// The casing of boolean value, see [`DumpOptions::bool_case`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoolCase {
+    /// `true` / `false`
+    #[default]
+    Lower,
+    /// `True` / `False`
+    Title,
+    /// `TRUE` / `FALSE`
+    Upper,
+}
+
+/// The style of the multi-line strings, see [`DumpOptions::block`].
+///
+/// The chomping indicator is chosen by the trailing newlines of the string,
+/// `-` for none, `+` for more than one.
+/// The indentation indicator is added if the first line starts with white
+/// spaces.
+/// The strings that cannot be represented as a block scalar,
+/// like the strings containing control characters, will be double quoted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockStyle {
+    /// Use the literal style `|`.
+    #[default]
+    Literal,
+    /// Use the folded style `>`, the long lines will be wrapped,
+    /// see [`DumpOptions::width`].
+    ///
+    /// The strings with more-indented lines will use the literal style.
+    Folded,
+    /// Double quoted string with escaped newlines `"...\n..."`.
+    Quoted,
+}
+
+/// The notation of the floats, see [`DumpOptions::float_notation`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FloatNotation {
+    /// Keep the original text, such as `1e3` and `1000.0`.
+    #[default]
+    Original,
+    /// Always use the fixed notation, such as `1000`.
+    Fixed,
+    /// Always use the scientific notation, such as `1e3`.
+    Scientific,
+    /// Use the scientific notation if the decimal exponent is less than `min`
+    /// or not less than `max`, otherwise use the fixed notation.
+    Auto {
+        /// The minimum exponent of the fixed notation, such as `-4`.
+        min: i32,
+        /// The maximum exponent (exclusive) of the fixed notation, such as
+        /// `16`.
+        max: i32,
+    },
+}
+
+/// The line ending of the output, see [`DumpOptions::line_ending`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Use the symbol of the platform, see [`NL`].
+    #[default]
+    Native,
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+/// The style options of the [`Dumper`].
+///
+/// ```
+/// use yaml_peg::{dumper::{dump_with, DumpOptions, NullStyle, NL}, node};
+///
+/// let opts = DumpOptions::new().indent(4).null(NullStyle::Tilde);
+/// let doc = dump_with(&[node!({"a" => node!({"b" => ()})})], &[], opts);
+/// assert_eq!(doc, "a:\n    b: ~\n".replace('\n', NL));
+/// ```
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct DumpOptions {
+    indent: usize,
+    quote: QuotePolicy,
+    quote_style: QuoteStyle,
+    null: NullStyle,
+    bool_case: BoolCase,
+    block: BlockStyle,
+    width: usize,
+    line_ending: LineEnding,
+    float_notation: FloatNotation,
+    float_precision: Option<usize>,
+    float_point: bool,
+    canonical: bool,
+    auto_anchor: bool,
+    comments: Vec<(String, String)>,
+}
+
+impl DumpOptions {
+    /// Create the default options.
+    pub const fn new() -> Self {
+        Self {
+            indent: 2,
+            quote: QuotePolicy::Minimal,
+            quote_style: QuoteStyle::Double,
+            null: NullStyle::Lower,
+            bool_case: BoolCase::Lower,
+            block: BlockStyle::Literal,
+            width: 80,
+            line_ending: LineEnding::Native,
+            float_notation: FloatNotation::Original,
+            float_precision: None,
+            float_point: false,
+            canonical: false,
+            auto_anchor: false,
+            comments: Vec::new(),
+        }
+    }
+
+    /// Indent width of the nested maps, default to 2.
+    ///
+    /// The sequence items are always indented after the `- ` indicator.
+    pub fn indent(self, indent: usize) -> Self {
+        Self { indent: indent.max(1), ..self }
+    }
+
+    /// Quoting policy of the strings.
+    pub fn quote(self, quote: QuotePolicy) -> Self {
+        Self { quote, ..self }
+    }
+
+    /// Quote symbol of the strings.
+    pub fn quote_style(self, quote_style: QuoteStyle) -> Self {
+        Self { quote_style, ..self }
+    }
+
+    /// Representation of null value.
+    pub fn null(self, null: NullStyle) -> Self {
+        Self { null, ..self }
+    }
+
+    /// Casing of boolean value.
+    pub fn bool_case(self, bool_case: BoolCase) -> Self {
+        Self { bool_case, ..self }
+    }
+
+    /// Style of the multi-line strings.
+    ///
+    /// ```
+    /// use yaml_peg::{dumper::{dump_with, BlockStyle, DumpOptions, NL}, node};
+    ///
+    /// let n = node!({"a" => "b\nc\n"});
+    /// let doc = dump_with(&[n.clone()], &[], DumpOptions::new());
+    /// assert_eq!(doc, "a: |\n  b\n  c\n".replace('\n', NL));
+    /// let opts = DumpOptions::new().block(BlockStyle::Folded);
+    /// let doc = dump_with(&[n.clone()], &[], opts);
+    /// assert_eq!(doc, "a: >\n  b\n\n  c\n".replace('\n', NL));
+    /// let opts = DumpOptions::new().block(BlockStyle::Quoted);
+    /// let doc = dump_with(&[n], &[], opts);
+    /// assert_eq!(doc, "a: \"b\\nc\\n\"\n".replace('\n', NL));
+    /// ```
+    pub fn block(self, block: BlockStyle) -> Self {
+        Self { block, ..self }
+    }
+
+    /// Line width of the folded strings, default to 80.
+    ///
+    /// The lines are only wrapped at single spaces,
+    /// so the lines may be longer than the width.
+    pub fn width(self, width: usize) -> Self {
+        Self { width: width.max(1), ..self }
+    }
+
+    /// Line ending of the output, default to the symbol of the platform.
+    ///
+    /// ```
+    /// use yaml_peg::{dumper::{dump_with, DumpOptions, LineEnding}, node};
+    ///
+    /// let n = node!({"a" => "b\nc\n"});
+    /// let doc = dump_with(&[n.clone()], &[], DumpOptions::new().line_ending(LineEnding::Lf));
+    /// assert_eq!(doc, "a: |\n  b\n  c\n");
+    /// let doc = dump_with(&[n], &[], DumpOptions::new().line_ending(LineEnding::CrLf));
+    /// assert_eq!(doc, "a: |\r\n  b\r\n  c\r\n");
+    /// ```
+    pub fn line_ending(self, line_ending: LineEnding) -> Self {
+        Self { line_ending, ..self }
+    }
+
+    /// Notation of the floats, default to keep the original text.
+    ///
+    /// The converted floats use the shortest digits to represent the value
+    /// unless [`DumpOptions::float_precision`] is set.
+    ///
+    /// ```
+    /// use yaml_peg::{dumper::{dump_with, DumpOptions, FloatNotation, NL}, node};
+    ///
+    /// let n = node!({"a" => node!([1e20, 1.5e-7, 0.25])});
+    /// let opts = DumpOptions::new().float_notation(FloatNotation::Scientific);
+    /// let doc = dump_with(&[n.clone()], &[], opts);
+    /// assert_eq!(doc, "a:\n  - 1e20\n  - 1.5e-7\n  - 2.5e-1\n".replace('\n', NL));
+    /// let opts = DumpOptions::new().float_notation(FloatNotation::Auto { min: -4, max: 16 });
+    /// let doc = dump_with(&[n], &[], opts);
+    /// assert_eq!(doc, "a:\n  - 1e20\n  - 1.5e-7\n  - 0.25\n".replace('\n', NL));
+    /// ```
+    pub fn float_notation(self, float_notation: FloatNotation) -> Self {
+        Self { float_notation, ..self }
+    }
+
+    /// Maximum significant digits of the floats, default to `None` for
+    /// unlimited.
+    ///
+    /// The values are rounded to the digits, and the trailing zeros are
+    /// removed.
+    ///
+    /// ```
+    /// use yaml_peg::{dumper::{dump_with, DumpOptions, NL}, node};
+    ///
+    /// let opts = DumpOptions::new().float_precision(Some(3));
+    /// let doc = dump_with(&[node!({"a" => 3.14159, "b" => 2.5, "c" => 12345.})], &[], opts);
+    /// assert_eq!(doc, "a: 3.14\nb: 2.5\nc: 12300\n".replace('\n', NL));
+    /// ```
+    pub fn float_precision(self, float_precision: Option<usize>) -> Self {
+        Self {
+            float_precision: float_precision.map(|p| p.max(1)),
+            ..self
+        }
+    }
+
+    /// Always include the decimal point in the floats, default to false.
+    ///
+    /// So the floats like `1` will be re-parsed as floats rather than
+    /// integers.
+    ///
+    /// ```
+    /// use yaml_peg::{dumper::{dump_with, DumpOptions, FloatNotation, NL}, node};
+    ///
+    /// let n = node!({"a" => 1., "b" => 1e3});
+    /// let opts = DumpOptions::new().float_point(true);
+    /// let doc = dump_with(&[n.clone()], &[], opts.clone());
+    /// assert_eq!(doc, "a: 1.0\nb: 1000.0\n".replace('\n', NL));
+    /// let opts = opts.float_notation(FloatNotation::Scientific);
+    /// let doc = dump_with(&[n], &[], opts);
+    /// assert_eq!(doc, "a: 1.0e0\nb: 1.0e3\n".replace('\n', NL));
+    /// ```
+    pub fn float_point(self, float_point: bool) -> Self {
+        Self { float_point, ..self }
+    }
+
+    /// Dump the canonical form, default to false.
+    ///
+    /// Every node is written with its explicit tag in the flow style,
+    /// the scalars are all double-quoted, and the map keys use the explicit
+    /// `?` indicator, so the documents with same meaning have the same
+    /// output. The other style options except the indent width and line ending
+    /// are ignored.
+    ///
+    /// ```
+    /// use yaml_peg::{dumper::{dump_with, DumpOptions, LineEnding}, node};
+    ///
+    /// let opts = DumpOptions::new().canonical(true).line_ending(LineEnding::Lf);
+    /// let doc = dump_with(&[node!({"a" => node!([1, ()]), "b" => node!([])})], &[], opts);
+    /// let ans = "\
+    /// %YAML 1.2
+    /// --- !!map {
+    ///   ? !!str \"a\"
+    ///   : !!seq [
+    ///     !!int \"1\",
+    ///     !!null \"\",
+    ///   ],
+    ///   ? !!str \"b\"
+    ///   : !!seq [ ],
+    /// }
+    /// ";
+    /// assert_eq!(doc, ans);
+    /// ```
+    pub fn canonical(self, canonical: bool) -> Self {
+        Self { canonical, ..self }
+    }
+
+    /// Write the shared nodes as anchors and aliases, default to false.
+    ///
+    /// The nodes are shared if they are cloned from the same node, the
+    /// anchor is written at the first occurrence, and the others are
+    /// written as aliases. The anchors are named by the given anchors, or
+    /// `id001`, `id002` and so on. The nodes with a different tag are not
+    /// aliased.
+    ///
+    /// ```
+    /// use yaml_peg::{dumper::{dump_with, DumpOptions, NL}, node, parse, repr::RcRepr};
+    ///
+    /// let base = node!({"a" => 1});
+    /// let n = node!({"b" => base.clone(), "c" => base, "d" => node!({"a" => 1})});
+    /// let doc = dump_with(&[n], &[], DumpOptions::new().auto_anchor(true));
+    /// let ans = "\
+    /// b: &id001
+    ///   a: 1
+    /// c: *id001
+    /// d:
+    ///   a: 1
+    /// ";
+    /// assert_eq!(doc, ans.replace('\n', NL));
+    /// // The aliases of the parsed documents are shared
+    /// let n = parse::<RcRepr>("a: &x [1, 2]\nb: *x\n").unwrap();
+    /// let doc = dump_with(&n, &[], DumpOptions::new().auto_anchor(true));
+    /// assert_eq!(doc, "a: &id001\n  - 1\n  - 2\nb: *id001\n".replace('\n', NL));
+    /// ```
+    pub fn auto_anchor(self, auto_anchor: bool) -> Self {
+        Self { auto_anchor, ..self }
+    }
+
+    /// Write the comments above the map items and the sequence items,
+    /// default to none.
+    ///
+    /// The comments are matched by the paths in the form of
+    /// [`Node::path_of`], such as `spec.containers[0].image`, and the lines
+    /// of the text are written as the comment lines. The comments are
+    /// ignored in the flow collections and the canonical form.
+    ///
+    /// ```
+    /// use yaml_peg::{dumper::{dump_with, DumpOptions, NL}, node};
+    ///
+    /// let n = node!({"server" => node!({"port" => 8080, "hosts" => node!(["a", "b"])})});
+    /// let opts = DumpOptions::new().comments(&[
+    ///     ("server.port", "The listened port"),
+    ///     ("server.hosts[1]", "Backup\nhost"),
+    /// ]);
+    /// let ans = "\
+    /// server:
+    ///   ## The listened port
+    ///   port: 8080
+    ///   hosts:
+    ///     - a
+    ///     ## Backup
+    ///     ## host
+    ///     - b
+    /// ";
+    /// assert_eq!(dump_with(&[n], &[], opts), ans.replace('\n', NL));
+    /// ```
+    ///
+    /// The serialized structs can be documented by
+    /// [`to_string_with`](crate::serde::to_string_with) as well.
+    pub fn comments(mut self, comments: &[(&str, &str)]) -> Self {
+        let comments = comments.iter().map(|(p, t)| (p.to_string(), t.to_string()));
+        self.comments.extend(comments);
+        self
+    }
+
+    fn nl(&self) -> &'static str {
+        match self.line_ending {
+            LineEnding::Native => NL,
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    fn dump_null(&self) -> &'static str {
+        match self.null {
+            NullStyle::Lower => "null",
+            NullStyle::Title => "Null",
+            NullStyle::Upper => "NULL",
+            NullStyle::Tilde => "~",
+            NullStyle::Empty => "",
+        }
+    }
+
+    fn dump_float(&self, n: &str) -> String {
+        let v = match to_f64(n) {
+            Ok(v) if v.is_nan() => return ".nan".to_string(),
+            Ok(v) if v.is_infinite() => return if v > 0. { ".inf" } else { "-.inf" }.to_string(),
+            Ok(v) => v,
+            Err(_) => return n.to_string(),
+        };
+        let original_sci = n.contains(['e', 'E']);
+        let mut doc = match (self.float_notation, self.float_precision) {
+            (FloatNotation::Original, None) => n.to_string(),
+            (notation, precision) => {
+                let sci = match precision {
+                    Some(p) => format!("{:.*e}", p - 1, v),
+                    None => format!("{:e}", v),
+                };
+                let (mantissa, exp) = sci.split_once('e').unwrap();
+                let exp = exp.parse::<i32>().unwrap();
+                let mantissa = if mantissa.contains('.') {
+                    mantissa.trim_end_matches('0').trim_end_matches('.')
+                } else {
+                    mantissa
+                };
+                let sci = match notation {
+                    FloatNotation::Original => original_sci,
+                    FloatNotation::Fixed => false,
+                    FloatNotation::Scientific => true,
+                    FloatNotation::Auto { min, max } => exp < min || exp >= max,
+                };
+                if sci {
+                    format!("{mantissa}e{exp}")
+                } else {
+                    fixed_float(mantissa, exp)
+                }
+            }
+        };
+        if self.float_point && !doc.contains('.') {
+            let i = doc.find(['e', 'E']).unwrap_or(doc.len());
+            doc.insert_str(i, ".0");
+        }
+        doc
+    }
+
+    fn dump_bool(&self, b: bool) -> &'static str {
+        match (self.bool_case, b) {
+            (BoolCase::Lower, true) => "true",
+            (BoolCase::Lower, false) => "false",
+            (BoolCase::Title, true) => "True",
+            (BoolCase::Title, false) => "False",
+            (BoolCase::Upper, true) => "TRUE",
+            (BoolCase::Upper, false) => "FALSE",
+        }
+    }
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert the scientific notation into the fixed notation.
+fn fixed_float(mantissa: &str, exp: i32) -> String {
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => ("-", m),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+    if exp < 0 {
+        format!("{sign}0.{}{digits}", "0".repeat((-exp - 1) as usize))
+    } else {
+        let point = exp as usize + 1;
+        if digits.len() > point {
+            format!("{sign}{}.{}", &digits[..point], &digits[point..])
+        } else {
+            format!("{sign}{digits}{}", "0".repeat(point - digits.len()))
+        }
+    }
+}
+
+fn double_quoted(s: &str) -> String {
+    let mut doc = String::from('"');
+    for c in s.chars() {
+        match c {
+            '\\' => doc += "\\\\",
+            '"' => doc += "\\\"",
+            '\n' => doc += "\\n",
+            '\r' => doc += "\\r",
+            '\t' => doc += "\\t",
+            '\x08' => doc += "\\b",
+            '\x0C' => doc += "\\f",
+            '\0' => doc += "\\0",
+            c if c.is_control() => write!(doc, "\\x{:02X}", u32::from(c)).unwrap(),
+            c => doc.push(c),
+        }
+    }
+    doc.push('"');
+    doc
+}
+
+fn single_quoted(s: &str) -> Option<String> {
+    if s.contains(|c: char| c.is_control()) {
+        None
+    } else {
+        Some(format!("'{}'", s.replace('\'', "''")))
+    }
+}
+
+/// Wrap the line at the single spaces, the words are never split.
+fn fold_line(line: &str, width: usize, ind: &str, nl: &str) -> String {
+    let mut doc = String::new();
+    let mut start = 0;
+    let mut last = None;
+    let b = line.as_bytes();
+    for (i, c) in b.iter().enumerate() {
+        if *c == b' ' && i > start && b[i - 1] != b' ' && b.get(i + 1).is_some_and(|c| *c != b' ') {
+            last = Some(i);
+        }
+        if i - start >= width {
+            if let Some(j) = last.take() {
+                write!(doc, "{}{nl}{ind}", &line[start..j]).unwrap();
+                start = j + 1;
+            }
+        }
+    }
+    doc += &line[start..];
+    doc
+}
+
+/// Return the block scalar of the multi-line string,
+/// or `None` if it cannot be represented.
+///
+/// The `m` is the indentation from the parent node,
+/// which is used as the indentation indicator if required.
+fn block_scalar(s: &str, ind: &str, m: usize, opts: &DumpOptions) -> Option<String> {
+    let body = s.trim_end_matches('\n');
+    let chomp = match s.len() - body.len() {
+        0 => "-",
+        1 => "",
+        _ => "+",
+    };
+    if body.is_empty() || s.contains(|c: char| c.is_control() && c != '\n' && c != '\t') {
+        return None;
+    }
+    let lines = body.split('\n').collect::<Vec<_>>();
+    if ind.is_empty()
+        && lines
+            .iter()
+            .any(|s| s.starts_with("---") || s.starts_with("..."))
+    {
+        return None;
+    }
+    let indicator = if body.trim_start_matches('\n').starts_with([' ', '\t']) {
+        if m > 9 {
+            return None;
+        }
+        m.to_string()
+    } else {
+        String::new()
+    };
+    let folded = opts.block == BlockStyle::Folded
+        && !lines
+            .iter()
+            .any(|s| s.starts_with([' ', '\t']) || s.ends_with([' ', '\t']));
+    let mut doc = format!("{}{}{}", if folded { '>' } else { '|' }, indicator, chomp);
+    let nl = opts.nl();
+    let mut first = true;
+    for line in lines {
+        doc += nl;
+        if line.is_empty() {
+            continue;
+        }
+        if folded && !first {
+            doc += nl;
+        }
+        first = false;
+        doc += ind;
+        if folded {
+            doc += &fold_line(line, opts.width, ind, nl);
+        } else {
+            doc += line;
+        }
+    }
+    for _ in 1..s.len() - body.len() {
+        doc += nl;
+    }
+    Some(doc)
+}
+
+/// Return true if the string can be represented as a plain string,
+/// it will be parsed as the same string under YAML 1.2 and 1.1 schema.
+fn is_plain(s: &str) -> bool {
+    type Loader<'a> = parser::Loader<'a, RcRepr>;
+    // The YAML 1.1 scalars, other strings are resolved the same as YAML 1.2
+    if s.is_empty() || Loader::is_bool11_str(s) || Loader::is_int11(s) {
+        return false;
+    }
+    // The words are plain except the null and bool values
+    if s.starts_with(|c: char| c.is_ascii_alphabetic())
+        && s.bytes()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'.' | b'/'))
+    {
+        return !matches!(
+            s,
+            "null" | "Null" | "NULL" | "true" | "True" | "TRUE" | "false" | "False" | "FALSE"
+        );
+    }
+    if s.contains(|c: char| c.is_control())
+        // Reserved indicators, the directives and the document markers at
+        // the line start
+        || s.starts_with(['@', '`', '%'])
+        || s.starts_with("---")
+        || s.starts_with("...")
+    {
+        return false;
+    }
+    let mut loader = Loader::new(s.as_bytes());
+    let Ok(mut docs) = loader.parse() else {
+        return false;
+    };
+    match (
+        docs.pop(),
+        docs.is_empty(),
+        loader.get_anchors().iter().all(|a| a.is_empty()),
+    ) {
+        (Some(n), true, true) => {
+            n.tag() == concat!(parser::tag_prefix!(), "str") && n.as_str() == Ok(s)
+        }
+        _ => false,
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
 enum Root {
     Scalar,
     Map,
+    Key,
     Array,
 }
 
+/// The anchor names and the first occurrences of the shared nodes, see
+/// [`DumpOptions::auto_anchor`].
+type Shared<'a, R> = BTreeMap<*const Yaml<R>, (String, &'a Node<R>)>;
+
+/// The comments of the map keys and the sequence items, see
+/// [`DumpOptions::comments`].
+type Comments<'a, R> = BTreeMap<*const Node<R>, &'a str>;
+
+/// The anchor names of the nodes, true for the definitions and false for the
+/// aliases.
+type Defs<'a, R> = BTreeMap<*const Node<R>, (&'a String, bool)>;
+
 /// Dumper for nodes.
 pub struct Dumper<'a, R: Repr> {
     node: &'a Node<R>,
     root: Root,
     level: usize,
     anchors: &'a Anchors<R>,
+    tags: &'a BTreeMap<String, String>,
+    opts: &'a DumpOptions,
+    shared: Option<&'a Shared<'a, R>>,
+    comments: Option<&'a Comments<'a, R>>,
+    defs: Option<&'a Defs<'a, R>>,
 }
 
 impl<'a, R: Repr> Dumper<'a, R> {
     /// Create the dumper.
     pub fn new(node: &'a Node<R>, anchors: &'a Anchors<R>) -> Self {
-        Self { node, root: Root::Scalar, level: 0, anchors }
+        Self {
+            node,
+            root: Root::Scalar,
+            level: 0,
+            anchors,
+            tags: &NO_TAGS,
+            opts: &DEFAULT_OPTIONS,
+            shared: None,
+            comments: None,
+            defs: None,
+        }
+    }
+
+    /// Use the style options.
+    pub fn with_options(self, opts: &'a DumpOptions) -> Self {
+        Self { opts, ..self }
+    }
+
+    /// Use the tag handles to shorten the tags, maps handle to prefix.
+    ///
+    /// The handles are in the form of [`Document::tags`].
+    pub fn with_tags(self, tags: &'a BTreeMap<String, String>) -> Self {
+        Self { tags, ..self }
     }
 
     fn part(&self, node: &'a Node<R>, root: Root, level: usize) -> String {
-        Self { node, root, level, anchors: self.anchors }.dump()
+        Self { node, root, level, ..*self }.dump()
     }
 
-    /// Dump into string.
-    pub fn dump(&self) -> String {
+    fn tag_handle(&self, tag: &str) -> Option<String> {
+        self.tags.iter().find_map(|(handle, prefix)| {
+            let suffix = tag.strip_prefix(prefix.as_str())?;
+            if prefix.is_empty() || suffix.is_empty() {
+                return None;
+            }
+            Some(format!("{}{} ", handle, parser::escape_tag(suffix, false)))
+        })
+    }
+
+    fn anchor(&self) -> Option<&'a String> {
+        if let Some((name, first)) = self.shared_node() {
+            if core::ptr::eq(first, self.node) {
+                return Some(name);
+            }
+        }
+        match self.defs?.get(&(self.node as *const _))? {
+            (name, true) => Some(name),
+            (_, false) => None,
+        }
+    }
+
+    /// The alias of the shared node, except the first occurrence.
+    fn alias(&self) -> Option<&'a String> {
+        if let Some((name, false)) = self.defs.and_then(|d| d.get(&(self.node as *const _))) {
+            return Some(name);
+        }
+        let (name, first) = self.shared_node()?;
+        (!core::ptr::eq(first, self.node) && first.tag() == self.node.tag()).then_some(name)
+    }
+
+    /// The anchor node of the alias which is the first occurrence of the
+    /// anchor, it is written as the anchor definition.
+    fn def_node(&self, node: &'a Node<R>) -> &'a Node<R> {
+        match (
+            node.yaml(),
+            self.defs.and_then(|d| d.get(&(node as *const _))),
+        ) {
+            (Yaml::Alias(a), Some((_, true))) => self.anchors.get(a).unwrap_or(node),
+            _ => node,
+        }
+    }
+
+    /// The dumper of the anchor node if the alias is the anchor definition.
+    fn alias_def(&self) -> Option<Self> {
+        let node = self.def_node(self.node);
+        (!core::ptr::eq(node, self.node)).then_some(Self { node, ..*self })
+    }
+
+    /// Find the anchor definitions in the dumping order.
+    ///
+    /// The anchor is defined at its first alias or the first node shared with
+    /// it, and the later ones are the aliases. The anchors which are not
+    /// referenced by the tree are defined at the first node equal to them.
+    fn find_defs(&self) -> Defs<'a, R> {
+        struct Walker<'a, R: Repr> {
+            anchors: &'a Anchors<R>,
+            /// The anchor names of the nodes in the name order
+            names: BTreeMap<*const Yaml<R>, Vec<&'a String>>,
+            /// The anchor names of the unreferenced anchors by their values
+            values: BTreeMap<&'a Node<R>, Vec<&'a String>>,
+            referenced: BTreeSet<&'a String>,
+            defined: BTreeSet<&'a String>,
+            /// The defined nodes by their pointers and tags
+            shared: BTreeMap<(*const Yaml<R>, &'a str), &'a String>,
+            defs: Defs<'a, R>,
+        }
+
+        impl<'a, R: Repr> Walker<'a, R> {
+            fn define(&mut self, name: &'a String, node: &'a Node<R>) {
+                self.defined.insert(name);
+                self.shared.entry((node.yaml(), node.tag())).or_insert(name);
+                self.defs.insert(node, (name, true));
+            }
+
+            fn refs(&mut self, node: &'a Node<R>) {
+                let found = match node.yaml() {
+                    Yaml::Alias(a) => self.anchors.get_key_value(a).map(|(k, _)| k),
+                    yaml => self
+                        .names
+                        .get(&(yaml as *const _))
+                        .and_then(|v| v.first().copied()),
+                };
+                if let Some(name) = found {
+                    if !self.referenced.insert(name) {
+                        return;
+                    }
+                }
+                self.items(node, Self::refs);
+            }
+
+            fn walk(&mut self, node: &'a Node<R>) {
+                if let Yaml::Alias(a) = node.yaml() {
+                    if let Some((name, anchor)) = self.anchors.get_key_value(a) {
+                        if !self.defined.contains(name) {
+                            self.define(name, anchor);
+                            self.defs.insert(node, (name, true));
+                            self.items(anchor, Self::walk);
+                        }
+                    }
+                    return;
+                }
+                if let Some(name) = self.shared.get(&(node.yaml() as *const _, node.tag())) {
+                    self.defs.insert(node, (name, false));
+                    return;
+                }
+                let is_free = |name: &String| !self.defined.contains(name);
+                let by_ptr = self.names.get(&(node.yaml() as *const _)).and_then(|v| {
+                    v.iter()
+                        .find(|k| self.referenced.contains(*k) && is_free(k))
+                });
+                let by_value = self
+                    .values
+                    .get(node)
+                    .and_then(|v| v.iter().find(|k| is_free(k)));
+                let anchor = match (by_ptr, by_value) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                if let Some(name) = anchor.copied() {
+                    self.define(name, node);
+                }
+                self.items(node, Self::walk);
+            }
+
+            fn items(&mut self, node: &'a Node<R>, f: fn(&mut Self, &'a Node<R>)) {
+                match node.yaml() {
+                    Yaml::Seq(v) => v.iter().for_each(|n| f(self, n)),
+                    Yaml::Map(m) => m.iter().for_each(|(k, v)| {
+                        f(self, k);
+                        f(self, v);
+                    }),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut walker = Walker {
+            anchors: self.anchors,
+            names: BTreeMap::new(),
+            values: BTreeMap::new(),
+            referenced: BTreeSet::new(),
+            defined: BTreeSet::new(),
+            shared: BTreeMap::new(),
+            defs: BTreeMap::new(),
+        };
+        for (name, anchor) in self.anchors.iter() {
+            let ptr = anchor.yaml() as *const _;
+            walker.names.entry(ptr).or_default().push(name);
+        }
+        walker.refs(self.node);
+        for (name, anchor) in self.anchors.iter() {
+            if !walker.referenced.contains(name) {
+                walker.values.entry(anchor).or_default().push(name);
+            }
+        }
+        walker.walk(self.node);
+        walker.defs
+    }
+
+    fn shared_node(&self) -> Option<(&'a String, &'a Node<R>)> {
+        let (name, first) = self.shared?.get(&(self.node.yaml() as *const _))?;
+        Some((name, *first))
+    }
+
+    /// Find the shared nodes in the dumping order.
+    fn find_shared(&self) -> Shared<'a, R> {
+        type Seen<'a, R> = BTreeMap<*const Yaml<R>, (&'a Node<R>, usize, bool)>;
+        fn walk<'a, R: Repr>(node: &'a Node<R>, seen: &mut Seen<'a, R>) {
+            let ptr = node.yaml() as *const _;
+            let i = seen.len();
+            match seen.get_mut(&ptr) {
+                Some((first, _, shared)) if first.tag() == node.tag() => {
+                    // The children are not dumped again
+                    *shared = true;
+                    return;
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(ptr, (node, i, false));
+                }
+            }
+            match node.yaml() {
+                Yaml::Seq(v) => v.iter().for_each(|n| walk(n, seen)),
+                Yaml::Map(m) => m.iter().for_each(|(k, v)| {
+                    walk(k, seen);
+                    walk(v, seen);
+                }),
+                _ => {}
+            }
+        }
+        let mut seen = BTreeMap::new();
+        walk(self.node, &mut seen);
+        let mut firsts = seen
+            .into_iter()
+            .filter(|(_, (_, _, shared))| *shared)
+            .collect::<Vec<_>>();
+        firsts.sort_by_key(|(_, (_, i, _))| *i);
+        let mut id = 0;
+        firsts
+            .into_iter()
+            .map(|(ptr, (first, _, _))| {
+                let named = self
+                    .anchors
+                    .iter()
+                    .find(|(_, v)| core::ptr::eq(v.yaml(), ptr));
+                let name = match named {
+                    Some((k, _)) => k.clone(),
+                    None => loop {
+                        id += 1;
+                        let name = format!("id{id:03}");
+                        if !self.anchors.contains_key(&name) {
+                            break name;
+                        }
+                    },
+                };
+                (ptr, (name, first))
+            })
+            .collect()
+    }
+
+    /// Dump into the canonical form, see [`DumpOptions::canonical`].
+    fn write_canonical(&self, w: &mut dyn Write) -> fmt::Result {
+        if let Some(def) = self.alias_def() {
+            return def.write_canonical(w);
+        }
+        if let Yaml::Alias(a) = self.node.yaml() {
+            return write!(w, "*{a}");
+        }
+        if let Some(a) = self.alias() {
+            return write!(w, "*{a}");
+        }
+        if let Some(a) = self.anchor() {
+            write!(w, "&{a} ")?;
+        }
+        write!(w, "{} ", tag_notation(self.node.tag()))?;
+        let nl = self.opts.nl();
+        let ind = " ".repeat(self.level);
+        let sub_level = self.level + self.opts.indent;
+        let sub_ind = " ".repeat(sub_level);
+        let part = |node| Self {
+            node,
+            root: Root::Scalar,
+            level: sub_level,
+            ..*self
+        };
+        match self.node.yaml() {
+            Yaml::Null => w.write_str("\"\""),
+            Yaml::Bool(b) => write!(w, "\"{b}\""),
+            Yaml::Int(n) => match to_i128(n) {
+                Ok(n) => write!(w, "\"{n}\""),
+                Err(_) => w.write_str(&double_quoted(n)),
+            },
+            Yaml::Float(n) => match to_f64(n) {
+                Ok(n) if n.is_nan() => w.write_str("\".nan\""),
+                Ok(n) if n.is_infinite() => {
+                    w.write_str(if n > 0. { "\".inf\"" } else { "\"-.inf\"" })
+                }
+                Ok(n) => write!(w, "\"{n:?}\""),
+                Err(_) => w.write_str(&double_quoted(n)),
+            },
+            Yaml::Str(s) => w.write_str(&double_quoted(s)),
+            Yaml::Seq(v) if v.is_empty() => w.write_str("[ ]"),
+            Yaml::Map(m) if m.is_empty() => w.write_str("{ }"),
+            Yaml::Seq(v) => {
+                write!(w, "[{nl}")?;
+                for node in v {
+                    w.write_str(&sub_ind)?;
+                    part(node).write_canonical(w)?;
+                    write!(w, ",{nl}")?;
+                }
+                write!(w, "{ind}]")
+            }
+            Yaml::Map(m) => {
+                write!(w, "{{{nl}")?;
+                for (k, v) in m {
+                    write!(w, "{sub_ind}? ")?;
+                    part(k).write_canonical(w)?;
+                    write!(w, "{nl}{sub_ind}: ")?;
+                    part(v).write_canonical(w)?;
+                    write!(w, ",{nl}")?;
+                }
+                write!(w, "{ind}}}")
+            }
+            Yaml::Alias(_) => unreachable!(),
+        }
+    }
+
+    /// The anchor and the tag with a trailing space.
+    fn props(&self) -> String {
         let mut doc = String::new();
-        if let Some(a) = self
-            .anchors
-            .iter()
-            .find_map(|(k, v)| if v == self.node { Some(k) } else { None })
-        {
+        if let Some(a) = self.anchor() {
             write!(doc, "&{a} ").unwrap();
         }
         let tag = self.node.tag();
-        if !tag.is_empty() && !tag.starts_with(parser::tag_prefix!()) {
-            doc += &if tag.starts_with(parser::tag_prefix!()) {
-                format!("!!{} ", tag)
-            } else if parser::Parser::new(tag.as_bytes()).identifier().is_ok() {
-                format!("!{} ", tag)
-            } else {
-                format!("!<{}> ", tag)
+        // The default tag of the kind is omitted
+        let default = Node::<R>::new_repr(self.node.clone_yaml(), 0, "");
+        if !tag.is_empty() && tag != default.tag() {
+            doc += &self
+                .tag_handle(tag)
+                .unwrap_or_else(|| format!("{} ", tag_notation(tag)));
+        }
+        doc
+    }
+
+    /// Match the comments to the nodes by their paths.
+    fn find_comments(&self) -> Comments<'a, R> {
+        fn walk<'a, R: Repr>(
+            node: &'a Node<R>,
+            path: &mut String,
+            texts: &BTreeMap<&str, &'a str>,
+            comments: &mut Comments<'a, R>,
+        ) {
+            let len = path.len();
+            let mut item = |n: &'a Node<R>, v: &'a Node<R>, path: &mut String| {
+                if let Some(text) = texts.get(path.as_str()) {
+                    comments.insert(n, text);
+                }
+                walk(v, path, texts, comments);
+                path.truncate(len);
             };
+            match node.yaml() {
+                Yaml::Seq(v) => {
+                    for (i, n) in v.iter().enumerate() {
+                        write!(path, "[{i}]").unwrap();
+                        item(n, n, path);
+                    }
+                }
+                Yaml::Map(m) => {
+                    for (k, v) in m.iter() {
+                        k.push_path(path);
+                        item(k, v, path);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let texts = self
+            .opts
+            .comments
+            .iter()
+            .map(|(p, t)| (p.as_str(), t.as_str()))
+            .collect();
+        let mut comments = BTreeMap::new();
+        walk(self.node, &mut String::new(), &texts, &mut comments);
+        comments
+    }
+
+    /// The comment lines above the item, the key node is used for the map
+    /// items.
+    fn comment(&self, node: &Node<R>, ind: &str) -> String {
+        let nl = self.opts.nl();
+        let Some(text) = self.comments.and_then(|c| c.get(&(node as *const _))) else {
+            return String::new();
+        };
+        text.lines()
+            .map(|line| match line {
+                "" => format!("#{nl}{ind}"),
+                _ => format!("# {line}{nl}{ind}"),
+            })
+            .collect()
+    }
+
+    /// Return true if the node is a `!!set` map with the null values.
+    fn is_set(&self) -> bool {
+        self.node.tag() == concat!(parser::tag_prefix!(), "set")
+            && matches!(self.node.yaml(), Yaml::Map(m) if m.values().all(Node::is_null))
+    }
+
+    /// The sequence item with the `-` indicator.
+    fn seq_item(&self, node: &'a Node<R>) -> String {
+        let mut doc = String::new();
+        self.write_seq_item(node, &mut doc).unwrap();
+        doc
+    }
+
+    fn write_seq_item(&self, node: &'a Node<R>, w: &mut dyn Write) -> fmt::Result {
+        let mut w = Prefixed { w, prefix: Some("- "), skip: None };
+        Self {
+            node,
+            root: Root::Array,
+            level: self.level + 2,
+            ..*self
+        }
+        .write(&mut w)?;
+        match w.prefix {
+            Some(_) => w.w.write_str("-"),
+            None => Ok(()),
+        }
+    }
+
+    /// The map value behind the `:` indicator.
+    fn map_value(&self, v: &'a Node<R>, first: bool) -> String {
+        let mut doc = String::new();
+        self.write_map_value(v, first, &mut doc).unwrap();
+        doc
+    }
+
+    fn write_map_value(&self, v: &'a Node<R>, first: bool, w: &mut dyn Write) -> fmt::Result {
+        let v = self.def_node(v);
+        let sub_level = self.level + self.opts.indent;
+        let level = match v.yaml() {
+            Yaml::Map(m) if !m.is_empty() => sub_level,
+            Yaml::Seq(s)
+                if !s.is_empty()
+                    && self.root == Root::Array
+                    && first
+                    && Self { node: v, ..*self }.props().is_empty() =>
+            {
+                self.level
+            }
+            Yaml::Seq(s) if !s.is_empty() => sub_level,
+            Yaml::Null if self.opts.null == NullStyle::Empty => return Ok(()),
+            _ => {
+                w.write_str(" ")?;
+                return Self {
+                    node: v,
+                    root: Root::Map,
+                    level: sub_level,
+                    ..*self
+                }
+                .write(w);
+            }
+        };
+        // The properties of the block collections
+        let mut w = Prefixed { w, prefix: Some(" "), skip: Some(self.opts.nl()) };
+        Self { node: v, root: Root::Map, level, ..*self }.write(&mut w)
+    }
+
+    /// Dump in flow style, for the nodes inside the flow collections.
+    fn flow(&self) -> String {
+        let flow = |node| Self { node, root: Root::Key, ..*self }.flow();
+        match self.node.yaml() {
+            Yaml::Seq(v) => {
+                let v = v.iter().map(flow).collect::<Vec<_>>();
+                format!("{}[{}]", self.props(), v.join(", "))
+            }
+            Yaml::Map(m) => {
+                let set = self.is_set();
+                let m = m
+                    .iter()
+                    .map(|(k, v)| match set {
+                        true => flow(k),
+                        false => format!("{}: {}", flow(k), flow(v)),
+                    })
+                    .collect::<Vec<_>>();
+                format!("{}{{{}}}", self.props(), m.join(", "))
+            }
+            Yaml::Str(s) if s.contains([',', '[', ']', '{', '}']) => {
+                self.props() + &double_quoted(s)
+            }
+            _ => Self { root: Root::Key, ..*self }.dump(),
+        }
+    }
+
+    /// Dump into string.
+    pub fn dump(&self) -> String {
+        let mut doc = String::new();
+        self.write(&mut doc).unwrap();
+        doc
+    }
+
+    /// Dump into the sink, the block collections are written item by item.
+    fn write(&self, w: &mut dyn Write) -> fmt::Result {
+        if self.opts.auto_anchor && self.shared.is_none() {
+            let shared = self.find_shared();
+            return Dumper { shared: Some(&shared), ..*self }.write(w);
+        }
+        // The paths are relative to the root node
+        if !self.opts.comments.is_empty()
+            && self.comments.is_none()
+            && self.root == Root::Scalar
+            && self.level == 0
+        {
+            let comments = self.find_comments();
+            return Dumper { comments: Some(&comments), ..*self }.write(w);
+        }
+        if !self.anchors.is_empty() && self.defs.is_none() {
+            let defs = self.find_defs();
+            return Dumper { defs: Some(&defs), ..*self }.write(w);
+        }
+        if let Some(def) = self.alias_def() {
+            return def.write(w);
+        }
+        if let Some(a) = self.alias() {
+            return write!(w, "*{a}");
+        }
+        if self.opts.canonical {
+            return self.write_canonical(w);
+        }
+        let mut doc = self.props();
+        let nl = self.opts.nl();
+        let ind = " ".repeat(self.level);
+        let sub_level = self.level + self.opts.indent;
+        if !doc.is_empty() {
+            // The properties of the block collections are on their own line
+            match self.node.yaml() {
+                Yaml::Seq(v) if !v.is_empty() => doc.truncate(doc.len() - 1),
+                Yaml::Map(m) if !m.is_empty() && self.root != Root::Map => {
+                    doc.truncate(doc.len() - 1);
+                    doc += nl;
+                    doc += &ind;
+                }
+                Yaml::Map(m) if !m.is_empty() => doc.truncate(doc.len() - 1),
+                _ => {}
+            }
         }
-        let ind = "  ".repeat(self.level);
         doc += &match &self.node.yaml() {
-            Yaml::Null => "null".to_string(),
-            Yaml::Bool(b) => b.to_string(),
-            Yaml::Int(n) | Yaml::Float(n) => n.clone(),
+            Yaml::Null => self.opts.dump_null().to_string(),
+            Yaml::Bool(b) => self.opts.dump_bool(*b).to_string(),
+            Yaml::Int(n) => n.clone(),
+            Yaml::Float(n) => self.opts.dump_float(n),
             Yaml::Str(s) => {
-                if s.contains(NL) {
-                    let s = s
-                        .split(NL)
-                        .map(|s| {
-                            if s.is_empty() {
-                                String::new()
-                            } else {
-                                ind.to_string() + s.trim_end()
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                        .join(NL);
-                    format!("|{}{}{}", NL, ind, s.trim())
-                } else if s.contains("\r") || parser::Parser::new(s.as_bytes())
-                    .string_plain(0, false)
-                    .is_err()
-                {
-                    format!("\"{}\"", s.replace("\r", "\\r"))
+                if s.contains('\n') {
+                    match self.opts.block {
+                        BlockStyle::Quoted => None,
+                        _ if self.root == Root::Key => None,
+                        _ => {
+                            let m = match self.root {
+                                Root::Map => self.opts.indent,
+                                Root::Array => 2,
+                                _ => 1,
+                            };
+                            block_scalar(s, &ind, m, self.opts)
+                        }
+                    }
+                    .unwrap_or_else(|| double_quoted(s))
+                } else if match self.opts.quote {
+                    QuotePolicy::Minimal => !is_plain(s),
+                    QuotePolicy::Always => true,
+                    QuotePolicy::Custom(f) => f(s) || !is_plain(s),
+                } {
+                    match self.opts.quote_style {
+                        QuoteStyle::Single => single_quoted(s).unwrap_or_else(|| double_quoted(s)),
+                        QuoteStyle::Double => double_quoted(s),
+                    }
                 } else {
                     s.clone()
                 }
             }
+            Yaml::Seq(v) if v.is_empty() => "[]".to_string(),
             Yaml::Seq(v) => {
-                let mut doc = NL.to_string();
+                write!(w, "{doc}{nl}")?;
                 for (i, node) in v.iter().enumerate() {
+                    if i != 0 {
+                        w.write_str(nl)?;
+                    }
                     if i != 0 || self.level != 0 {
-                        doc += &ind;
+                        w.write_str(&ind)?;
                     }
-                    let s = self.part(node, Root::Array, self.level + 1);
-                    write!(doc, "- {s}{NL}").unwrap();
+                    w.write_str(&self.comment(node, &ind))?;
+                    self.write_seq_item(node, w)?;
                 }
-                doc.truncate(doc.len() - NL.len());
-                doc
+                return Ok(());
             }
+            Yaml::Map(m) if m.is_empty() => "{}".to_string(),
             Yaml::Map(m) => {
-                let mut doc = match self.root {
-                    Root::Map => NL.to_string(),
-                    _ => String::new(),
-                };
+                w.write_str(&doc)?;
+                if self.root == Root::Map {
+                    w.write_str(nl)?;
+                }
+                let set = self.is_set();
                 for (i, (k, v)) in m.iter().enumerate() {
+                    if i != 0 {
+                        w.write_str(nl)?;
+                    }
                     if i != 0 || self.root == Root::Map {
-                        doc += &ind;
+                        w.write_str(&ind)?;
                     }
-                    let s = self.part(k, Root::Map, self.level + 1);
-                    doc += &if let Yaml::Map(_) | Yaml::Seq(_) = k.yaml() {
-                        let pre_ind = "  ".repeat(self.level + 1);
-                        format!("?{}{}{}{}{}", pre_ind, NL, s, NL, ind)
+                    w.write_str(&self.comment(k, &ind))?;
+                    let k = self.def_node(k);
+                    if set {
+                        // The items of the set are written as the explicit keys
+                        let s = match k.yaml() {
+                            Yaml::Map(_) | Yaml::Seq(_) => self.part(k, Root::Map, sub_level),
+                            _ => self.part(k, Root::Key, sub_level),
+                        };
+                        let sep = if s.starts_with(nl) { "" } else { " " };
+                        write!(w, "?{sep}{s}")?;
+                        continue;
+                    }
+                    if let Yaml::Map(_) | Yaml::Seq(_) = k.yaml() {
+                        let s = self.part(k, Root::Map, sub_level);
+                        let sep = if s.starts_with(nl) { "" } else { " " };
+                        write!(w, "?{sep}{s}{nl}{ind}")?;
                     } else {
-                        s
-                    };
-                    doc += ":";
-                    doc += &match v.yaml() {
-                        Yaml::Map(_) => self.part(v, Root::Map, self.level + 1),
-                        Yaml::Seq(_) if self.root == Root::Array && i == 0 => {
-                            self.part(v, Root::Map, self.level)
-                        }
-                        Yaml::Seq(_) => self.part(v, Root::Map, self.level + 1),
-                        _ => format!(" {}", self.part(v, Root::Map, self.level + 1)),
-                    };
-                    doc += NL;
+                        w.write_str(&self.part(k, Root::Key, sub_level))?;
+                    }
+                    w.write_str(":")?;
+                    self.write_map_value(v, i == 0, w)?;
                 }
-                doc.truncate(doc.len() - NL.len());
-                doc
+                return Ok(());
             }
             Yaml::Alias(a) => format!("*{}", a),
         };
-        doc
+        w.write_str(&doc)
+    }
+}
+
+/// The sink which writes the prefix before the first non-empty text, unless
+/// the text starts with `skip`.
+struct Prefixed<'w> {
+    w: &'w mut dyn Write,
+    prefix: Option<&'static str>,
+    skip: Option<&'static str>,
+}
+
+impl Write for Prefixed<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+        if let Some(prefix) = self.prefix.take() {
+            if !self.skip.is_some_and(|skip| s.starts_with(skip)) {
+                self.w.write_str(prefix)?;
+            }
+        }
+        self.w.write_str(s)
+    }
+}
+
+/// The sink which skips the leading white spaces, same as
+/// [`str::trim_start`].
+struct TrimStart<'w> {
+    w: &'w mut dyn Write,
+    started: bool,
+}
+
+impl Write for TrimStart<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let s = if self.started { s } else { s.trim_start() };
+        self.started |= !s.is_empty();
+        self.w.write_str(s)
+    }
+}
+
+/// The [`std::io::Write`] sink as a [`Write`] sink, the I/O error is kept.
+#[cfg(feature = "std")]
+struct IoSink<'w, W: std::io::Write + ?Sized> {
+    w: &'w mut W,
+    err: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write + ?Sized> Write for IoSink<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.w.write_all(s.as_bytes()).map_err(|e| {
+            self.err = Some(e);
+            fmt::Error
+        })
     }
 }
 
 /// Dump the YAML data in to block format.
 ///
 /// Dumper will use plain string when the string is none-wrapped,
-/// otherwise it use literal string and trim the last white spaces.
+/// otherwise it use block scalar, see [`DumpOptions::block`].
 ///
 /// ```
 /// use yaml_peg::{dump, node, dumper::NL};
@@ -160,23 +1403,648 @@ impl<'a, R: Repr> Dumper<'a, R> {
 /// When calling [`parse`] function then [`dump`] the string, the string can be
 /// reformatted.
 ///
+/// The empty sequences and maps are written as `[]` and `{}`, since the
+/// block collections cannot be empty.
+///
 /// Anchors can pass with the result of the [`Loader`](crate::parser::Loader).
+/// Each anchor is defined at its first alias or the first node shared with
+/// it, and the later ones are written as `*name`, so the reference structure
+/// of the document is preserved. The anchors which are not referenced by the
+/// document are defined at the first node equal to them.
+///
+/// ```
+/// use yaml_peg::{dumper::{dump, NL}, parser::parse_cyclic, repr::RcRepr};
+///
+/// let (root, anchors) = parse_cyclic::<RcRepr>("a: &x [1]\nb: *x\nc: [1]\n").unwrap();
+/// let doc = dump(&root, &anchors);
+/// assert_eq!(doc, "a: &x\n  - 1\nb: *x\nc:\n  - 1\n".replace('\n', NL));
+/// ```
 pub fn dump<R: Repr>(nodes: &[Node<R>], anchors: &[Anchors<R>]) -> String {
+    dump_with(nodes, anchors, DumpOptions::new())
+}
+
+/// Same as [`dump`], but use the style options.
+///
+/// ```
+/// use yaml_peg::{dumper::{dump_with, BoolCase, DumpOptions, QuotePolicy, NL}, node};
+///
+/// let opts = DumpOptions::new().quote(QuotePolicy::Always).bool_case(BoolCase::Upper);
+/// let doc = dump_with(&[node!({"a" => node!(["b", true])})], &[], opts);
+/// assert_eq!(doc, "\"a\":\n  - \"b\"\n  - TRUE\n".replace('\n', NL));
+/// ```
+pub fn dump_with<R: Repr>(nodes: &[Node<R>], anchors: &[Anchors<R>], opts: DumpOptions) -> String {
+    let mut doc = String::new();
+    write_documents(&mut doc, nodes, anchors, &opts).unwrap();
+    doc
+}
+
+/// Same as [`dump_with`], but write the documents into a [`core::fmt::Write`]
+/// sink.
+///
+/// The block collections are written item by item, so the output is not
+/// buffered, except the scalars, the flow collections and the complex keys.
+///
+/// ```
+/// use yaml_peg::{dumper::{dump_fmt, DumpOptions, NL}, node};
+///
+/// let mut doc = String::from("# header") + NL;
+/// dump_fmt(&mut doc, &[node!(1), node!(2)], &[], DumpOptions::new()).unwrap();
+/// assert_eq!(doc, "# header\n1\n---\n2\n".replace('\n', NL));
+/// ```
+pub fn dump_fmt<W, R>(
+    w: &mut W,
+    nodes: &[Node<R>],
+    anchors: &[Anchors<R>],
+    opts: DumpOptions,
+) -> core::fmt::Result
+where
+    W: Write + ?Sized,
+    R: Repr,
+{
+    let mut w = w;
+    write_documents(&mut w, nodes, anchors, &opts)
+}
+
+/// Same as [`dump_with`], but write the documents into a [`std::io::Write`]
+/// sink, such as files and sockets.
+///
+/// The block collections are written item by item, so the output is not
+/// buffered, except the scalars, the flow collections and the complex keys.
+///
+/// ```
+/// use yaml_peg::{dumper::{dump_to, DumpOptions, NL}, node};
+///
+/// let mut buf = Vec::new();
+/// dump_to(&mut buf, &[node!({"a" => "b"})], &[], DumpOptions::new()).unwrap();
+/// assert_eq!(String::from_utf8(buf).unwrap(), "a: b\n".replace('\n', NL));
+/// ```
+#[cfg(feature = "std")]
+pub fn dump_to<W, R>(
+    w: &mut W,
+    nodes: &[Node<R>],
+    anchors: &[Anchors<R>],
+    opts: DumpOptions,
+) -> std::io::Result<()>
+where
+    W: std::io::Write + ?Sized,
+    R: Repr,
+{
+    let mut w = IoSink { w, err: None };
+    write_documents(&mut w, nodes, anchors, &opts).map_err(|_| {
+        w.err
+            .unwrap_or_else(|| std::io::Error::other("formatter error"))
+    })
+}
+
+/// Dump the documents separately, the anchors are matched by index.
+fn write_documents<R: Repr>(
+    w: &mut dyn Write,
+    nodes: &[Node<R>],
+    anchors: &[Anchors<R>],
+    opts: &DumpOptions,
+) -> fmt::Result {
     let anchors_empty = Anchors::new();
-    nodes
+    let nl = opts.nl();
+    for (i, node) in nodes.iter().enumerate() {
+        let anchors = anchors.get(i).unwrap_or(&anchors_empty);
+        let dumper = Dumper::new(node, anchors).with_options(opts);
+        match i {
+            0 if opts.canonical => write!(w, "%YAML 1.2{nl}--- ")?,
+            _ if opts.canonical => w.write_str("--- ")?,
+            0 => {}
+            _ => {
+                write!(w, "---{nl}")?;
+                let mut w = TrimStart { w, started: false };
+                dumper.write(&mut w)?;
+                w.write_str(nl)?;
+                continue;
+            }
+        }
+        dumper.write(w)?;
+        w.write_str(nl)?;
+    }
+    Ok(())
+}
+
+/// Dump the YAML documents in to block format, includes their directives and
+/// markers.
+///
+/// The `%YAML` and `%TAG` directives are written before the document,
+/// and the tags are shortened by the tag handles.
+///
+/// ```
+/// use yaml_peg::{dumper::{dump_documents, NL}, parse_documents, DocumentRc};
+///
+/// let doc = "\
+/// %YAML 1.2
+/// %TAG !x! tag:my.prefix:
+/// ---
+/// a: !x!b c
+/// ...
+/// ";
+/// let docs: Vec<DocumentRc> = parse_documents(doc).unwrap();
+/// assert_eq!(dump_documents(&docs), doc.replace('\n', NL));
+/// ```
+pub fn dump_documents<R: Repr>(docs: &[Document<R>]) -> String {
+    dump_documents_with(docs, DumpOptions::new())
+}
+
+/// Same as [`dump_documents`], but use the style options.
+///
+/// ```
+/// use yaml_peg::{dumper::{dump_documents_with, DumpOptions, LineEnding}, parse_documents, DocumentRc};
+///
+/// let docs: Vec<DocumentRc> = parse_documents("%YAML 1.2\n---\na: [b]\n").unwrap();
+/// let opts = DumpOptions::new().line_ending(LineEnding::CrLf).indent(4);
+/// let doc = dump_documents_with(&docs, opts);
+/// assert_eq!(doc, "%YAML 1.2\r\n---\r\na:\r\n    - b\r\n");
+/// ```
+pub fn dump_documents_with<R: Repr>(docs: &[Document<R>], opts: DumpOptions) -> String {
+    let nl = opts.nl();
+    let mut doc = String::new();
+    for (i, document) in docs.iter().enumerate() {
+        let directives = document.version.is_some() || !document.tags.is_empty();
+        if i > 0 && directives && !docs[i - 1].explicit_end {
+            write!(doc, "...{nl}").unwrap();
+        }
+        if let Some(version) = &document.version {
+            write!(doc, "%YAML {version}{nl}").unwrap();
+        }
+        for (handle, prefix) in &document.tags {
+            write!(doc, "%TAG {handle} {prefix}{nl}").unwrap();
+        }
+        if i > 0 || directives || document.explicit_start {
+            write!(doc, "---{nl}").unwrap();
+        }
+        let body = Dumper::new(&document.root, &document.anchors)
+            .with_options(&opts)
+            .with_tags(&document.tags)
+            .dump();
+        write!(doc, "{}{nl}", body.trim_start()).unwrap();
+        if document.explicit_end {
+            write!(doc, "...{nl}").unwrap();
+        }
+    }
+    doc
+}
+
+/// Dump the documents parsed in round-trip mode, the untouched nodes keep
+/// their original text, so the diff of the mutated tree is minimal.
+///
+/// The original text includes the comments, the quoting styles, the flow
+/// collections and the blank lines. Only the mutated subtrees are
+/// re-formatted, the added map entries and sequence items follow the
+/// indentation of their siblings.
+///
+/// The nodes are matched to the original nodes by the source map, see
+/// [`Loader::round_trip`](parser::Loader::round_trip). The mutated trees
+/// should be created by copying the parsed trees, such as the
+/// [`patch`] functions and [`Node::get_mut`].
+///
+/// ```
+/// use yaml_peg::{dumper::dump_preserving, node, parser::parse_round_trip, NodeRc};
+///
+/// let doc = "\
+/// server:  # The server settings
+///   host: 'localhost'  # local only
+///   port: 80
+///
+/// users: [alice, bob]
+/// ";
+/// let (mut root, map) = parse_round_trip::<yaml_peg::repr::RcRepr>(doc).unwrap();
+/// *root[0].get_mut(&["server", "port"]).unwrap() = node!(8080);
+/// root[0].get_mut(&["server"]).unwrap().entry("tls").unwrap().or_insert(true);
+/// let ans = "\
+/// server:  # The server settings
+///   host: 'localhost'  # local only
+///   port: 8080
+///   tls: true
+///
+/// users: [alice, bob]
+/// ";
+/// assert_eq!(dump_preserving(doc, &root, &map), ans);
+/// ```
+///
+/// The documents exceeding the original documents are appended, and the
+/// missing documents are removed.
+pub fn dump_preserving<R: Repr>(
+    src: &str,
+    nodes: &[Node<R>],
+    map: &parser::SourceMap<R>,
+) -> String {
+    let line_ending = if src.contains("\r\n") {
+        LineEnding::CrLf
+    } else {
+        LineEnding::Lf
+    };
+    let opts = DumpOptions::new().line_ending(line_ending);
+    let splicer = Splicer { src, map, opts: &opts, anchors: Anchors::new() };
+    let nl = opts.nl();
+    let mut doc = String::new();
+    let mut last = 0;
+    let mut roots = map.roots();
+    for node in nodes {
+        if let Some((orig, span)) = roots.next() {
+            let (start, _, end) = splicer.span(orig, span);
+            doc += &src[last..start];
+            doc += &splicer.root(node, orig, span);
+            last = end;
+        } else {
+            doc += &src[last..];
+            last = src.len();
+            if !doc.is_empty() && !doc.ends_with('\n') {
+                doc += nl;
+            }
+            let s = splicer.dumper(node, Root::Scalar, 0).dump();
+            write!(doc, "---{nl}{}{nl}", s.trim_start()).unwrap();
+        }
+    }
+    match roots.next() {
+        _ if nodes.is_empty() => {}
+        Some(_) => {
+            // Keep the rest of the line of the last document
+            let rest = &src[last..];
+            doc += &rest[..rest.find('\n').map_or(rest.len(), |i| i + 1)];
+        }
+        None => doc += &src[last..],
+    }
+    doc
+}
+
+/// Return true if the nodes are the same, includes the scalar notation and
+/// the order of the map.
+pub(crate) fn same<R: Repr>(a: &Node<R>, b: &Node<R>) -> bool {
+    if tag_of(a) != tag_of(b) {
+        return false;
+    }
+    if core::ptr::eq::<Yaml<R>>(a.yaml(), b.yaml()) {
+        return true;
+    }
+    match (a.yaml(), b.yaml()) {
+        (Yaml::Null, Yaml::Null) => true,
+        (Yaml::Bool(a), Yaml::Bool(b)) => a == b,
+        (Yaml::Int(a), Yaml::Int(b))
+        | (Yaml::Float(a), Yaml::Float(b))
+        | (Yaml::Str(a), Yaml::Str(b))
+        | (Yaml::Alias(a), Yaml::Alias(b)) => a == b,
+        (Yaml::Seq(a), Yaml::Seq(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b))
+        }
+        (Yaml::Map(a), Yaml::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|((k1, v1), (k2, v2))| same(k1, k2) && same(v1, v2))
+        }
+        _ => false,
+    }
+}
+
+/// The tag in the shorthand or the verbatim form, without the tag handles.
+pub(crate) fn tag_notation(tag: &str) -> String {
+    if let Some(name) = tag.strip_prefix(parser::tag_prefix!()) {
+        format!("!!{}", parser::escape_tag(name, false))
+    } else if tag.starts_with('!') || parser::is_global_tag(tag) {
+        format!("!<{}>", parser::escape_tag(tag, true))
+    } else {
+        format!("!{}", parser::escape_tag(tag, false))
+    }
+}
+
+/// The tag written by the dumper.
+fn tag_of<R: Repr>(node: &Node<R>) -> &str {
+    match node.tag() {
+        tag if tag.starts_with(parser::tag_prefix!()) => "",
+        tag => tag,
+    }
+}
+
+fn untagged<R: Repr>(node: &Node<R>) -> Node<R> {
+    Node::new_repr(node.clone_yaml(), node.pos(), "")
+}
+
+fn is_collection<R: Repr>(node: &Node<R>) -> bool {
+    matches!(node.yaml(), Yaml::Seq(_) | Yaml::Map(_))
+}
+
+fn line_start(src: &str, i: usize) -> usize {
+    src[..i].rfind('\n').map_or(0, |p| p + 1)
+}
+
+/// Skip the white spaces and the comments.
+fn skip_gap(src: &str, mut i: usize) -> usize {
+    loop {
+        i = src.len() - src[i..].trim_start().len();
+        if !src[i..].starts_with('#') {
+            break i;
+        }
+        i += src[i..].find('\n').unwrap_or(src.len() - i);
+    }
+}
+
+/// The context of a node in the original document.
+#[derive(Clone, Copy)]
+struct Ctx {
+    flow: bool,
+    root: Root,
+    level: usize,
+}
+
+/// The original text of a collection item, the map entry is ended by its
+/// value.
+struct Item {
+    start: usize,
+    end: usize,
+}
+
+struct Splicer<'a, R: Repr> {
+    src: &'a str,
+    map: &'a parser::SourceMap<R>,
+    opts: &'a DumpOptions,
+    anchors: Anchors<R>,
+}
+
+impl<R: Repr> Splicer<'_, R> {
+    fn dumper<'b>(&'b self, node: &'b Node<R>, root: Root, level: usize) -> Dumper<'b, R> {
+        Dumper {
+            node,
+            root,
+            level,
+            anchors: &self.anchors,
+            tags: &NO_TAGS,
+            opts: self.opts,
+            shared: None,
+            comments: None,
+            defs: None,
+        }
+    }
+
+    /// The original range, the trailing white spaces are excluded.
+    fn span(&self, orig: &Node<R>, span: parser::Span) -> (usize, usize, usize) {
+        let (start, pos) = (span.start as usize, span.pos as usize);
+        let text = self.src[pos..span.end as usize].trim_end();
+        let empty = matches!(orig.yaml(), Yaml::Null)
+            && matches!(text.trim_start().as_bytes().first(), None | Some(b'#'));
+        (start, pos, if empty { pos } else { pos + text.len() })
+    }
+
+    fn find(&self, orig: &Node<R>) -> Option<(usize, usize, usize)> {
+        self.map.span(orig).map(|span| self.span(orig, span))
+    }
+
+    fn root(&self, node: &Node<R>, orig: &Node<R>, span: parser::Span) -> String {
+        let (start, pos, _) = self.span(orig, span);
+        let ctx = Ctx { flow: false, root: Root::Scalar, level: 0 };
+        match self.content(node, orig, ctx) {
+            Some(s) => self.src[start..pos].to_string() + &s,
+            None => self
+                .dumper(node, Root::Scalar, 0)
+                .dump()
+                .trim_start()
+                .to_string(),
+        }
+    }
+
+    /// Replace the content of the original node, return `None` if the
+    /// properties and the indicators should be regenerated.
+    fn content(&self, node: &Node<R>, orig: &Node<R>, ctx: Ctx) -> Option<String> {
+        let (_, pos, end) = self.find(orig)?;
+        if tag_of(node) != tag_of(orig) {
+            return None;
+        }
+        if same(node, orig) {
+            return Some(self.src[pos..end].to_string());
+        }
+        let node = untagged(node);
+        let flow = matches!(
+            self.src.as_bytes().get(skip_gap(self.src, pos)),
+            Some(b'[' | b'{')
+        );
+        match (node.yaml(), orig.yaml()) {
+            (Yaml::Seq(_), Yaml::Seq(_)) | (Yaml::Map(_), Yaml::Map(_)) if flow => Some(
+                self.flow_splice(&node, orig)
+                    .unwrap_or_else(|| self.dumper(&node, Root::Key, 0).flow()),
+            ),
+            _ if ctx.flow => {
+                if is_collection(&node) || is_collection(orig) {
+                    None
+                } else {
+                    Some(self.dumper(&node, Root::Key, 0).flow())
+                }
+            }
+            (Yaml::Seq(v), Yaml::Seq(_)) if !v.is_empty() => {
+                Some(self.block_splice(&node, orig, pos, end))
+            }
+            (Yaml::Map(m), Yaml::Map(_)) if !m.is_empty() => {
+                Some(self.block_splice(&node, orig, pos, end))
+            }
+            _ if is_collection(&node) || is_collection(orig) => None,
+            _ => Some(self.dumper(&node, ctx.root, ctx.level).dump()),
+        }
+    }
+
+    /// Replace the items of the flow collection if the structure is not
+    /// changed.
+    fn flow_splice(&self, node: &Node<R>, orig: &Node<R>) -> Option<String> {
+        let ctx = Ctx { flow: true, root: Root::Key, level: 0 };
+        let (_, mut last, end) = self.find(orig)?;
+        let mut doc = String::new();
+        let mut replace = |n: &Node<R>, o: &Node<R>| {
+            if !same(n, o) {
+                let (_, pos, end) = self.find(o)?;
+                doc += &self.src[last..pos];
+                doc += &self.content(n, o, ctx)?;
+                last = end;
+            }
+            Some(())
+        };
+        match (node.yaml(), orig.yaml()) {
+            (Yaml::Seq(v1), Yaml::Seq(v2)) if v1.len() == v2.len() => {
+                for (n, o) in v1.iter().zip(v2) {
+                    replace(n, o)?;
+                }
+            }
+            (Yaml::Map(m1), Yaml::Map(m2)) if m1.len() == m2.len() => {
+                for ((k1, v1), (k2, v2)) in m1.iter().zip(m2.iter()) {
+                    if !same(k1, k2) {
+                        return None;
+                    }
+                    replace(v1, v2)?;
+                }
+            }
+            _ => return None,
+        }
+        doc += &self.src[last..end];
+        Some(doc)
+    }
+
+    /// Replace the items of the block collection, the unchanged items and
+    /// their following comments are kept.
+    fn block_splice(&self, node: &Node<R>, orig: &Node<R>, pos: usize, end: usize) -> String {
+        let src = self.src;
+        let first = skip_gap(src, pos);
+        let c = first - line_start(src, first);
+        let item = |i: usize, o: &Node<R>| {
+            let (start, _, _) = self.find(o).unwrap_or((first, first, first));
+            if i == 0 {
+                first
+            } else {
+                (line_start(src, start) + c).min(start)
+            }
+        };
+        let mut items = Vec::new();
+        let mut texts = Vec::new();
+        match (node.yaml(), orig.yaml()) {
+            (Yaml::Seq(v1), Yaml::Seq(v2)) => {
+                for (i, o) in v2.iter().enumerate() {
+                    let end = self.find(o).map_or(first, |(_, _, end)| end);
+                    items.push(Item { start: item(i, o), end });
+                }
+                let ctx = Ctx { flow: false, root: Root::Array, level: c + 2 };
+                for (j, i) in align(v1, v2) {
+                    let n = &v1[j];
+                    let text = i.and_then(|i| {
+                        let (_, pos, _) = self.find(&v2[i])?;
+                        let s = self.content(n, &v2[i], ctx)?;
+                        Some(src[items[i].start..pos].to_string() + &s)
+                    });
+                    let text = text.unwrap_or_else(|| self.dumper(n, Root::Array, c).seq_item(n));
+                    texts.push((i, text));
+                }
+            }
+            (Yaml::Map(m1), Yaml::Map(m2)) => {
+                let m2 = m2.iter().collect::<Vec<_>>();
+                for (i, (k, v)) in m2.iter().enumerate() {
+                    let end = self.find(v).map_or(first, |(_, _, end)| end);
+                    items.push(Item { start: item(i, k), end });
+                }
+                let ctx = Ctx {
+                    flow: false,
+                    root: Root::Map,
+                    level: c + self.opts.indent,
+                };
+                let mut used = alloc::vec![false; m2.len()];
+                for (k, v) in m1.iter() {
+                    let i = (0..m2.len()).find(|i| !used[*i] && same(k, m2[*i].0));
+                    let text = i.and_then(|i| {
+                        used[i] = true;
+                        let (_, _, key_end) = self.find(m2[i].0)?;
+                        let head = &src[items[i].start..key_end];
+                        Some(match self.find(m2[i].1) {
+                            Some((_, pos, _)) => match self.content(v, m2[i].1, ctx) {
+                                Some(s) => src[items[i].start..pos].to_string() + &s,
+                                None => format!(
+                                    "{head}:{}",
+                                    self.dumper(v, Root::Scalar, c).map_value(v, false)
+                                ),
+                            },
+                            None => format!(
+                                "{head}:{}",
+                                self.dumper(v, Root::Scalar, c).map_value(v, false)
+                            ),
+                        })
+                    });
+                    let text = text.unwrap_or_else(|| {
+                        let entry =
+                            Node::from(Yaml::Map([(k.clone(), v.clone())].into_iter().collect()));
+                        self.dumper(&entry, Root::Scalar, c).dump()
+                    });
+                    texts.push((i, text));
+                }
+            }
+            _ => unreachable!(),
+        }
+        let nl = format!("{}{}", self.opts.nl(), " ".repeat(c));
+        let last = items.last().map_or(first, |item| item.end);
+        let mut doc = src[pos..first].to_string();
+        for (j, (i, text)) in texts.iter().enumerate() {
+            doc += text;
+            if j + 1 < texts.len() {
+                match i {
+                    Some(i) if *i + 1 < items.len() => {
+                        doc += &src[items[*i].end..items[*i + 1].start]
+                    }
+                    _ => doc += &nl,
+                }
+            }
+        }
+        doc + &src[last..end]
+    }
+}
+
+/// The maximum cells of the table in [`common_pairs`].
+const LCS_CELLS: usize = 1 << 20;
+
+/// The index pairs of a longest common subsequence of the slices.
+///
+/// The common prefix and suffix are matched first, and the rest is matched
+/// by a table of the quadratic size. If the table is larger than
+/// [`LCS_CELLS`], the rest is left unmatched.
+pub(crate) fn common_pairs<T>(
+    a: &[T],
+    b: &[T],
+    eq: impl Fn(&T, &T) -> bool,
+) -> Vec<(usize, usize)> {
+    let pre = a.iter().zip(b).take_while(|(x, y)| eq(x, y)).count();
+    let suf = a[pre..]
         .iter()
-        .enumerate()
-        .map(|(i, node)| {
-            let anchors = if i < anchors.len() {
-                &anchors[i]
+        .rev()
+        .zip(b[pre..].iter().rev())
+        .take_while(|(x, y)| eq(x, y))
+        .count();
+    let (n, m) = (a.len() - pre - suf, b.len() - pre - suf);
+    let mut pairs = (0..pre).map(|i| (i, i)).collect::<Vec<_>>();
+    if n.saturating_mul(m) <= LCS_CELLS {
+        let (a, b) = (&a[pre..pre + n], &b[pre..pre + m]);
+        let mut lcs = alloc::vec![alloc::vec![0usize; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if eq(&a[i], &b[j]) {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if eq(&a[i], &b[j]) {
+                pairs.push((pre + i, pre + j));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                i += 1;
             } else {
-                &anchors_empty
-            };
-            let doc = Dumper::new(node, anchors).dump() + NL;
-            match i {
-                0 => doc,
-                _ => format!("---{}{}", NL, doc.trim_start()),
+                j += 1;
             }
-        })
-        .collect()
+        }
+    }
+    pairs.extend((0..suf).map(|k| (a.len() - suf + k, b.len() - suf + k)));
+    pairs
+}
+
+/// Match the new items to the original items, the unchanged items are
+/// matched by the longest common subsequence, see [`common_pairs`], and the
+/// others are matched in order between them.
+fn align<R: Repr>(new: &[Node<R>], orig: &[Node<R>]) -> Vec<(usize, Option<usize>)> {
+    let (n, m) = (new.len(), orig.len());
+    let mut pairs = common_pairs(new, orig, |a, b| same(a, b));
+    pairs.push((n, m));
+    let mut ret = Vec::new();
+    let (mut j, mut i) = (0, 0);
+    for (pj, pi) in pairs {
+        while j < pj {
+            ret.push((j, (i < pi).then_some(i)));
+            j += 1;
+            if i < pi {
+                i += 1;
+            }
+        }
+        if pj < n {
+            ret.push((pj, Some(pi)));
+        }
+        j = pj + 1;
+        i = pi + 1;
+    }
+    ret
 }
@@ -0,0 +1,185 @@
+//! Flatten the nodes into the key path and value pairs, for the environment
+//! variables, the spreadsheets and the other flat key-value systems.
+//!
+//! ```
+//! use yaml_peg::{flatten::{flatten, unflatten}, node, NodeRc};
+//!
+//! let doc = "\
+//! db:
+//!   host: localhost
+//!   port: 5432
+//! servers: [a, b]
+//! debug: true
+//! ";
+//! let n: NodeRc = doc.parse().unwrap();
+//! let pairs = flatten(&n, "__");
+//! let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+//! assert_eq!(
+//!     pairs,
+//!     [
+//!         pair("db__host", "localhost"),
+//!         pair("db__port", "5432"),
+//!         pair("servers__0", "a"),
+//!         pair("servers__1", "b"),
+//!         pair("debug", "true"),
+//!     ]
+//! );
+//! assert_eq!(unflatten::<yaml_peg::repr::RcRepr, _, _, _>(pairs, "__"), n);
+//! ```
+//!
+//! The conversion is lossy in some cases:
+//!
+//! + The values are typed by the YAML schema again, so the strings like
+//!   `"true"` and `"1"` become a boolean and an integer.
+//! + The maps with the keys `0`, `1`, ... are converted to sequences.
+//! + The keys that contain the separator are split.
+use crate::{repr::Repr, *};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use ritelinked::LinkedHashMap;
+
+/// Flatten the scalars of the node into the key path and value pairs,
+/// the path segments are joined by `sep`.
+///
+/// The map keys are the segments, and the sequence items use their indices.
+/// The null values are written as empty strings, and the empty collections
+/// are written as `[]` and `{}`. The root scalar has an empty path.
+pub fn flatten<R: Repr>(node: &Node<R>, sep: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    flatten_inner(node, sep, &mut String::new(), &mut pairs);
+    pairs
+}
+
+fn flatten_inner<R: Repr>(
+    node: &Node<R>,
+    sep: &str,
+    path: &mut String,
+    pairs: &mut Vec<(String, String)>,
+) {
+    let mut push = |path: &mut String, seg: &str, node: &Node<R>| {
+        let len = path.len();
+        if !path.is_empty() {
+            path.push_str(sep);
+        }
+        path.push_str(seg);
+        flatten_inner(node, sep, path, pairs);
+        path.truncate(len);
+    };
+    let value = match node.yaml() {
+        Yaml::Seq(v) if !v.is_empty() => {
+            for (i, n) in v.iter().enumerate() {
+                push(path, &i.to_string(), n);
+            }
+            return;
+        }
+        Yaml::Map(m) if !m.is_empty() => {
+            for (k, v) in m.iter() {
+                match k.yaml() {
+                    Yaml::Str(s) => push(path, s, v),
+                    _ => push(path, &k.to_string(), v),
+                }
+            }
+            return;
+        }
+        Yaml::Seq(_) => "[]".to_string(),
+        Yaml::Map(_) => "{}".to_string(),
+        Yaml::Null => String::new(),
+        Yaml::Bool(b) => b.to_string(),
+        Yaml::Int(s) | Yaml::Float(s) | Yaml::Str(s) => s.clone(),
+        Yaml::Alias(a) => alloc::format!("*{}", a),
+    };
+    pairs.push((path.clone(), value));
+}
+
+/// The inverse of [`flatten`], build the node from the key path and value
+/// pairs.
+///
+/// The later pairs replace the conflicting earlier pairs, e.g., `a.b`
+/// replaces `a`.
+///
+/// ```
+/// use yaml_peg::{flatten::unflatten, node, repr::RcRepr};
+///
+/// let pairs = [("a.1", "x"), ("a.0", "0x10"), ("b.c", ""), ("b.d", "a b")];
+/// let n = unflatten::<RcRepr, _, _, _>(pairs, ".");
+/// assert_eq!(n, node!({"a" => node!([16, "x"]), "b" => node!({"c" => (), "d" => "a b"})}));
+/// ```
+pub fn unflatten<R, I, K, V>(pairs: I, sep: &str) -> Node<R>
+where
+    R: Repr,
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut root = Tree::Branch(LinkedHashMap::new());
+    for (path, value) in pairs {
+        let (path, value) = (path.as_ref(), value.as_ref());
+        let mut tree = &mut root;
+        if !path.is_empty() {
+            for seg in path.split(sep) {
+                if !matches!(tree, Tree::Branch(_)) {
+                    *tree = Tree::Branch(LinkedHashMap::new());
+                }
+                tree = match tree {
+                    Tree::Branch(m) => m.entry(seg.to_string()).or_insert(Tree::Leaf(Yaml::Null)),
+                    Tree::Leaf(_) => unreachable!(),
+                };
+            }
+        }
+        *tree = Tree::Leaf(scalar(value));
+    }
+    root.into_node()
+}
+
+enum Tree<R: Repr> {
+    Leaf(Yaml<R>),
+    Branch(LinkedHashMap<String, Tree<R>>),
+}
+
+impl<R: Repr> Tree<R> {
+    fn into_node(self) -> Node<R> {
+        let m = match self {
+            Self::Leaf(yaml) => return Node::from(yaml),
+            Self::Branch(m) => m,
+        };
+        let is_seq = !m.is_empty()
+            && (0..m.len()).all(|i| m.contains_key(&i.to_string()))
+            && m.keys().all(|k| !k.starts_with('0') || k == "0");
+        if is_seq {
+            let mut v = m
+                .into_iter()
+                .map(|(k, t)| (k.parse::<usize>().unwrap(), t))
+                .collect::<Vec<_>>();
+            v.sort_by_key(|(i, _)| *i);
+            Node::from(Yaml::Seq(
+                v.into_iter().map(|(_, t)| t.into_node()).collect(),
+            ))
+        } else {
+            Node::from(Yaml::Map(
+                m.into_iter()
+                    .map(|(k, t)| (Node::from(k), t.into_node()))
+                    .collect(),
+            ))
+        }
+    }
+}
+
+/// Type the value by the YAML schema, the other texts are strings.
+fn scalar<R: Repr>(s: &str) -> Yaml<R> {
+    match s {
+        "" | "null" | "~" => return Yaml::Null,
+        "[]" => return Yaml::Seq(Vec::new()),
+        "{}" => return Yaml::Map(Map::new()),
+        _ if s.contains(|c: char| c.is_whitespace() || "#:,[]{}&*!|>'\"%@`".contains(c)) => {}
+        _ => {
+            if let Ok([n]) = parse::<R>(s).as_deref() {
+                if let Yaml::Bool(_) | Yaml::Int(_) | Yaml::Float(_) = n.yaml() {
+                    return n.yaml().clone();
+                }
+            }
+        }
+    }
+    Yaml::Str(s.to_string())
+}
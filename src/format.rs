@@ -0,0 +1,367 @@
+//! Format the YAML documents with the consistent indentation and spacing.
+//!
+//! The formatter works on the tokens of the [`Lexer`], the comments, the
+//! scalar notations and the flow collections are kept, only the white spaces
+//! are changed:
+//!
+//! + The nested block collections are re-indented with the chosen width.
+//! + The indicators `-`, `?` and `:` are followed by one space.
+//! + The items of the flow collections are separated by `, `.
+//! + The trailing comments of the consecutive lines are aligned.
+//! + The trailing white spaces are removed.
+//!
+//! ```
+//! use yaml_peg::format::{format, FormatOptions};
+//!
+//! let doc = "\
+//! server:
+//!     host:   localhost # host
+//!     ports: [80 ,443]   # ports
+//! users:
+//!   - name:  alice
+//!     tags:
+//!     -    admin
+//! ";
+//! let ans = "\
+//! server:
+//!   host: localhost  # host
+//!   ports: [80, 443] # ports
+//! users:
+//!   - name: alice
+//!     tags:
+//!     - admin
+//! ";
+//! assert_eq!(format(doc, FormatOptions::new()), ans);
+//! ```
+//!
+//! The formatted documents are parsed again, the original text is returned
+//! if it is not a valid YAML document or the data is changed.
+use crate::{
+    parser::{Lexer, Token, TokenKind},
+    repr::RcRepr,
+    *,
+};
+use alloc::{string::String, vec::Vec};
+
+/// The options of the [`format`] function.
+///
+/// ```
+/// use yaml_peg::format::{format, FormatOptions};
+///
+/// let opts = FormatOptions::new().indent(4).align_comments(false);
+/// assert_eq!(format("a:\n  b: 1  # b\n  cc: 2 # c\n", opts), "a:\n    b: 1 # b\n    cc: 2 # c\n");
+/// ```
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct FormatOptions {
+    indent: usize,
+    align_comments: bool,
+}
+
+impl FormatOptions {
+    /// Create the default options.
+    pub const fn new() -> Self {
+        Self { indent: 2, align_comments: true }
+    }
+
+    /// Indent width of the nested collections, default to 2.
+    ///
+    /// The compact collections in the sequence items are always indented
+    /// after the `- ` indicator.
+    pub fn indent(self, indent: usize) -> Self {
+        Self { indent: indent.max(1), ..self }
+    }
+
+    /// Align the trailing comments of the consecutive lines, default to
+    /// true. Otherwise, the comments are separated by one space.
+    pub fn align_comments(self, align_comments: bool) -> Self {
+        Self { align_comments, ..self }
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Format the YAML documents, see [the module level document](self).
+pub fn format(src: &str, opts: FormatOptions) -> String {
+    let orig = match parse::<RcRepr>(src) {
+        Ok(orig) => orig,
+        Err(_) => return src.into(),
+    };
+    let doc = Formatter::new(src, opts).format();
+    match parse::<RcRepr>(&doc) {
+        Ok(nodes) if nodes == orig => doc,
+        _ => src.into(),
+    }
+}
+
+/// An output line, the trailing comment is aligned later.
+struct Line {
+    text: String,
+    comment: Option<String>,
+}
+
+struct Formatter<'a> {
+    src: &'a str,
+    opts: FormatOptions,
+    lines: Vec<Line>,
+    /// The original and the new columns of the block collections.
+    stack: Vec<(usize, usize)>,
+    /// The indentation of the current flow collection.
+    flow_indent: usize,
+    flow: usize,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(src: &'a str, opts: FormatOptions) -> Self {
+        Self {
+            src,
+            opts,
+            lines: Vec::new(),
+            stack: Vec::new(),
+            flow_indent: 0,
+            flow: 0,
+        }
+    }
+
+    fn text(&self, t: &Token) -> &'a str {
+        &self.src[t.start as usize..t.end as usize]
+    }
+
+    fn format(mut self) -> String {
+        let tokens = Lexer::new(self.src.as_bytes()).collect::<Vec<_>>();
+        let mut pending = Vec::new();
+        for line in tokens.split(|t| t.kind == TokenKind::Newline) {
+            let start = line.first().map_or(0, |t| t.start as usize);
+            let ind = match line.first() {
+                Some(t) if t.kind == TokenKind::Indent => (t.end - t.start) as usize,
+                _ => 0,
+            };
+            // Keep the white space flags for the separators
+            let mut items = Vec::new();
+            let mut ws = false;
+            for t in line {
+                match t.kind {
+                    TokenKind::Indent | TokenKind::Whitespace => ws = true,
+                    _ => {
+                        items.push((*t, ws));
+                        ws = false;
+                    }
+                }
+            }
+            match items.as_slice() {
+                [] => {
+                    self.push_line(String::new(), None);
+                    continue;
+                }
+                [(t, _)] if t.kind == TokenKind::Comment => {
+                    // Indent by the next line
+                    pending.push(self.lines.len());
+                    self.push_line(String::new(), Some(self.text(t).into()));
+                    continue;
+                }
+                _ => {}
+            }
+            let new_ind = self.indent(items[0].0, ind);
+            for i in pending.drain(..) {
+                self.lines[i].text = " ".repeat(new_ind);
+            }
+            self.line(&items, start, new_ind);
+        }
+        let nl = if self.src.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        self.align();
+        let lines = self
+            .lines
+            .into_iter()
+            .map(|line| match line.comment {
+                Some(c) => line.text + &c,
+                None => line.text,
+            })
+            .collect::<Vec<_>>();
+        lines.join(nl)
+    }
+
+    fn push_line(&mut self, text: String, comment: Option<String>) {
+        self.lines.push(Line { text, comment });
+    }
+
+    /// The new indentation of a line, the original indentation is `ind`.
+    fn indent(&mut self, first: Token, ind: usize) -> usize {
+        if self.flow > 0 {
+            return self.flow_indent;
+        }
+        if matches!(
+            first.kind,
+            TokenKind::DocStart | TokenKind::DocEnd | TokenKind::Directive
+        ) {
+            self.stack.clear();
+            return 0;
+        }
+        while matches!(self.stack.last(), Some((orig, _)) if *orig > ind) {
+            self.stack.pop();
+        }
+        let new_ind = match self.stack.last() {
+            Some((orig, new)) if *orig == ind => *new,
+            Some((_, new)) => new + self.opts.indent,
+            None => 0,
+        };
+        if matches!(self.stack.last(), Some((orig, _)) if *orig == ind) {
+            self.stack.pop();
+        }
+        self.stack.push((ind, new_ind));
+        new_ind
+    }
+
+    fn line(&mut self, items: &[(Token, bool)], start: usize, new_ind: usize) {
+        let mut cur = " ".repeat(new_ind);
+        let mut comment = None;
+        // The original and the new column of the last block collection
+        let mut parent = (items[0].0.start as usize - start, new_ind);
+        let mut prev: Option<TokenKind> = None;
+        for (t, ws) in items {
+            let text = self.text(t);
+            if t.kind == TokenKind::Comment {
+                comment = Some(text.into());
+                break;
+            }
+            cur += self.sep(prev, t.kind, *ws);
+            let block_start =
+                self.flow == 0 && matches!(prev, Some(TokenKind::Dash | TokenKind::Question));
+            if block_start {
+                let orig = t.start as usize - start;
+                parent = (orig, cur.len());
+                self.stack.push(parent);
+            }
+            match t.kind {
+                TokenKind::SeqStart | TokenKind::MapStart => {
+                    if self.flow == 0 {
+                        self.flow_indent = parent.1 + self.opts.indent;
+                    }
+                    self.flow += 1;
+                }
+                TokenKind::SeqEnd | TokenKind::MapEnd => self.flow = self.flow.saturating_sub(1),
+                _ => {}
+            }
+            if !text.contains('\n') {
+                cur += text;
+            } else if matches!(t.kind, TokenKind::Literal | TokenKind::Folded) {
+                self.block_scalar(&mut cur, text, parent);
+            } else {
+                // Multi-line quoted scalar, the leading spaces are folded
+                let ind = match self.flow {
+                    0 => parent.1 + self.opts.indent,
+                    _ => self.flow_indent,
+                };
+                let mut lines = text.split('\n');
+                cur += lines.next().unwrap_or_default();
+                for line in lines {
+                    let line = line.trim();
+                    let text = core::mem::take(&mut cur);
+                    self.push_line(text.trim_end().into(), None);
+                    if !line.is_empty() {
+                        cur = " ".repeat(ind) + line;
+                    }
+                }
+            }
+            prev = Some(t.kind);
+        }
+        let text = cur.trim_end().into();
+        self.push_line(text, comment);
+    }
+
+    /// Re-indent the content lines of the block scalar.
+    fn block_scalar(&mut self, cur: &mut String, text: &str, parent: (usize, usize)) {
+        let mut lines = text.split('\n').map(|s| s.trim_end_matches('\r'));
+        let header = lines.next().unwrap_or_default();
+        let lines = lines.collect::<Vec<_>>();
+        let explicit = header.bytes().any(|c| c.is_ascii_digit());
+        let orig = lines
+            .iter()
+            .find(|s| !s.trim().is_empty())
+            .map_or(0, |s| s.len() - s.trim_start_matches(' ').len());
+        let new = parent.1 + self.opts.indent;
+        let shift = |ind: usize| {
+            if explicit {
+                (ind + parent.1).checked_sub(parent.0)
+            } else {
+                (ind + new).checked_sub(orig)
+            }
+        };
+        let texts = lines
+            .iter()
+            .map(|line| {
+                let ind = line.len() - line.trim_start_matches(' ').len();
+                if line.trim().is_empty() {
+                    Some(String::new())
+                } else {
+                    Some(" ".repeat(shift(ind)?) + &line[ind..])
+                }
+            })
+            .collect::<Option<Vec<_>>>()
+            // The indentation indicator cannot be re-based, keep the original text
+            .unwrap_or_else(|| lines.iter().map(|&line| line.into()).collect());
+        cur.push_str(header);
+        let text = core::mem::take(cur);
+        self.push_line(text.trim_end().into(), None);
+        let n = texts.len();
+        for (i, text) in texts.into_iter().enumerate() {
+            if i + 1 < n {
+                self.push_line(text, None);
+            } else {
+                *cur = text;
+            }
+        }
+    }
+
+    /// The normalized spaces between the tokens.
+    fn sep(&self, prev: Option<TokenKind>, cur: TokenKind, ws: bool) -> &'static str {
+        use TokenKind::*;
+        let Some(prev) = prev else {
+            return "";
+        };
+        match (prev, cur) {
+            (SeqStart | MapStart, _) | (_, SeqEnd | MapEnd | Comma) => "",
+            (_, Colon) if self.flow > 0 || matches!(prev, Plain | Alias) => "",
+            (Colon | Comma | Dash | Question | Tag | Anchor, _) => " ",
+            _ if ws => " ",
+            _ => "",
+        }
+    }
+
+    /// Align the trailing comments of the consecutive lines.
+    fn align(&mut self) {
+        let mut i = 0;
+        while i < self.lines.len() {
+            let has_comment = |line: &Line| line.comment.is_some() && !line.text.trim().is_empty();
+            if !has_comment(&self.lines[i]) {
+                i += 1;
+                continue;
+            }
+            let mut j = i;
+            while j < self.lines.len() && has_comment(&self.lines[j]) {
+                j += 1;
+            }
+            let width = self.lines[i..j]
+                .iter()
+                .map(|line| line.text.chars().count())
+                .max()
+                .unwrap_or_default();
+            for line in &mut self.lines[i..j] {
+                let pad = if self.opts.align_comments {
+                    width - line.text.chars().count() + 1
+                } else {
+                    1
+                };
+                line.text += &" ".repeat(pad);
+            }
+            i = j;
+        }
+    }
+}
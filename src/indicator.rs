@@ -1,4 +1,4 @@
-use alloc::{format, string::String};
+use alloc::{format, string::String, vec::Vec};
 
 /// Indicate the position of the documentation.
 /// This function will show the line number and column number of the position.
@@ -20,7 +20,10 @@ use alloc::{format, string::String};
 ///
 /// This may be what you need if you went to indicate an error on the invalid
 /// data.
-pub fn indicated_msg(doc: &[u8], mut pos: u64) -> String {
+///
+/// The position out of the document is indicated at the end of the document.
+pub fn indicated_msg(doc: &[u8], pos: u64) -> String {
+    let mut pos = pos.min(doc.len() as u64);
     for (line, str_line) in doc.split(|c| *c == b'\n').enumerate() {
         let full_line = str_line.len() as u64 + 1;
         if full_line > pos {
@@ -58,3 +61,81 @@ pub fn indicated_msg(doc: &[u8], mut pos: u64) -> String {
 pub fn indicated_msg_file(path: &str, doc: &[u8], pos: u64) -> String {
     format!("{}:{}", path, indicated_msg(doc, pos))
 }
+
+/// The line starts of a document, for converting the document positions
+/// and the line/column numbers repeatedly.
+///
+/// The numbers are 1-based as [`indicated_msg`], and the columns are counted
+/// in bytes.
+///
+/// ```
+/// use yaml_peg::{indicated_msg, LineIndex};
+///
+/// let doc = b"{\"a\": \n[\"b\", \"c\", \"d\"]}";
+/// let index = LineIndex::new(doc);
+/// assert_eq!(index.line_count(), 2);
+/// assert_eq!(index.offset_to_line_col(13), (2, 7));
+/// assert!(indicated_msg(doc, 13).starts_with("2:7\n"));
+/// assert_eq!(index.line_col_to_offset(2, 7), Some(13));
+/// assert_eq!(index.line_col_to_offset(3, 1), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineIndex {
+    starts: Vec<u64>,
+    len: u64,
+}
+
+impl LineIndex {
+    /// Index the line starts of the document.
+    pub fn new(doc: &[u8]) -> Self {
+        let starts = core::iter::once(0)
+            .chain(
+                doc.iter()
+                    .enumerate()
+                    .filter(|(_, c)| **c == b'\n')
+                    .map(|(i, _)| i as u64 + 1),
+            )
+            .collect();
+        Self { starts, len: doc.len() as u64 }
+    }
+
+    /// The number of the lines, the empty document has one line.
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Convert the document position to the line and column numbers.
+    ///
+    /// The position out of the document is converted from the end of the
+    /// document.
+    pub fn offset_to_line_col(&self, pos: u64) -> (usize, usize) {
+        let pos = pos.min(self.len);
+        let line = self.starts.partition_point(|start| *start <= pos);
+        (line, (pos - self.starts[line - 1]) as usize + 1)
+    }
+
+    /// Convert the line and column numbers to the document position.
+    ///
+    /// Return none if the line does not exist or the column is out of the line,
+    /// the column of the line break is valid.
+    pub fn line_col_to_offset(&self, line: usize, col: usize) -> Option<u64> {
+        let start = *self.starts.get(line.checked_sub(1)?)?;
+        let end = self.starts.get(line).map_or(self.len, |next| next - 1);
+        let pos = start + col.checked_sub(1)? as u64;
+        (pos <= end).then_some(pos)
+    }
+
+    /// Same as [`indicated_msg`], but find the line by the index.
+    pub(crate) fn indicated_msg(&self, doc: &[u8], pos: u64) -> String {
+        let (line, col) = self.offset_to_line_col(pos);
+        let start = self.starts[line - 1];
+        let end = self.starts.get(line).map_or(self.len, |next| next - 1);
+        format!(
+            "{}:{}\n{}\n{}^",
+            line,
+            col,
+            String::from_utf8_lossy(&doc[start as usize..end as usize]),
+            " ".repeat(col - 1)
+        )
+    }
+}
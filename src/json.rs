@@ -0,0 +1,137 @@
+//! Convert the nodes to the JSON text, for the interoperation with
+//! JavaScript and the other JSON tools.
+//!
+//! Since YAML 1.2 is a superset of JSON, the JSON text can be loaded by
+//! [`parse`] or [`str::parse`], this module only provides the other
+//! direction. The web tools can pass the result to `JSON.parse` to create
+//! the JavaScript values.
+//!
+//! ```
+//! use yaml_peg::{json::to_json, NodeRc};
+//!
+//! let n: NodeRc = "a: [1, 0x10, 2.50, .inf]\nb: {~: null, 3: \"x\\ty\"}".parse().unwrap();
+//! let s = to_json(&n);
+//! assert_eq!(s, r#"{"a":[1,16,2.50,null],"b":{"null":null,"3":"x\ty"}}"#);
+//! assert_eq!(s.parse::<NodeRc>().unwrap()["b"]["3"], n["b"][3]);
+//! ```
+use crate::{repr::Repr, *};
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Convert the node to a compact JSON text.
+///
+/// + The integers and the floats keep their decimal text if it is a valid
+///   JSON number, the other notations are converted, and the infinity and
+///   NaN are converted to `null`.
+/// + The map keys are converted to strings, the collection keys are written
+///   as their JSON text.
+/// + The aliases of the cyclic mode are converted to `null`, please resolve
+///   them before converting, see
+///   [`Anchors::resolve_all`](crate::parser::Anchors::resolve_all), or
+///   [`Anchors::expand`](crate::parser::Anchors::expand) to limit the size of
+///   the untrusted documents.
+pub fn to_json<R: Repr>(node: &Node<R>) -> String {
+    let mut doc = String::new();
+    write_json(&mut doc, node);
+    doc
+}
+
+fn write_json<R: Repr>(doc: &mut String, node: &Node<R>) {
+    match node.yaml() {
+        Yaml::Null | Yaml::Alias(_) => *doc += "null",
+        Yaml::Bool(b) => *doc += if *b { "true" } else { "false" },
+        Yaml::Int(s) => match yaml::to_i128(s) {
+            _ if is_number(s, false) => *doc += s,
+            Ok(n) => write!(doc, "{n}").unwrap(),
+            Err(_) => string(doc, s),
+        },
+        Yaml::Float(s) => match yaml::to_f64(s) {
+            _ if is_number(s, true) => *doc += s,
+            Ok(f) if f.is_finite() => write!(doc, "{f}").unwrap(),
+            Ok(_) => *doc += "null",
+            Err(_) => string(doc, s),
+        },
+        Yaml::Str(s) => string(doc, s),
+        Yaml::Seq(v) => {
+            doc.push('[');
+            for (i, n) in v.iter().enumerate() {
+                if i > 0 {
+                    doc.push(',');
+                }
+                write_json(doc, n);
+            }
+            doc.push(']');
+        }
+        Yaml::Map(m) => {
+            doc.push('{');
+            for (i, (k, v)) in m.iter().enumerate() {
+                if i > 0 {
+                    doc.push(',');
+                }
+                match k.yaml() {
+                    Yaml::Str(s) => string(doc, s),
+                    _ => string(doc, &to_json(k)),
+                }
+                doc.push(':');
+                write_json(doc, v);
+            }
+            doc.push('}');
+        }
+    }
+}
+
+/// Return true if the text is a JSON number.
+fn is_number(s: &str, float: bool) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let int_len = s.bytes().take_while(u8::is_ascii_digit).count();
+    if int_len == 0 || int_len > 1 && s.starts_with('0') {
+        return false;
+    }
+    let mut rest = &s[int_len..];
+    if !float {
+        return rest.is_empty();
+    }
+    if let Some(frac) = rest.strip_prefix('.') {
+        let n = frac.bytes().take_while(u8::is_ascii_digit).count();
+        if n == 0 {
+            return false;
+        }
+        rest = &frac[n..];
+    }
+    if let Some(exp) = rest.strip_prefix(['e', 'E']) {
+        let exp = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+        return !exp.is_empty() && exp.bytes().all(|c| c.is_ascii_digit());
+    }
+    rest.is_empty()
+}
+
+fn string(doc: &mut String, s: &str) {
+    doc.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => *doc += "\\\\",
+            '"' => *doc += "\\\"",
+            '\n' => *doc += "\\n",
+            '\r' => *doc += "\\r",
+            '\t' => *doc += "\\t",
+            '\x08' => *doc += "\\b",
+            '\x0C' => *doc += "\\f",
+            c if u32::from(c) < 0x20 => write!(doc, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => doc.push(c),
+        }
+    }
+    doc.push('"');
+}
+
+impl<R: Repr> Node<R> {
+    /// Convert the node to a compact JSON text, see [`to_json`].
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// assert_eq!(node!({"a" => node!([true, ()])}).to_json(), r#"{"a":[true,null]}"#);
+    /// ```
+    pub fn to_json(&self) -> String {
+        to_json(self)
+    }
+}
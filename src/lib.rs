@@ -6,8 +6,9 @@
 //! This parser is not ensuring about YAML spec but almost functions are
 //! well-implemented.
 //!
-//! The buffer reader has also not been implemented, but sub-parsers can read
-//! the chunks.
+//! The buffer reader has also not been implemented, but the
+//! [`parser::StreamLoader`] can be fed by the chunks and yields the documents
+//! once they are complete.
 //!
 //! **WARNING: YAML 1.2 is compatible with [JSON (JavaScript Object Notation) format](https://www.json.org/),
 //! but not in the strict mode.**
@@ -20,6 +21,16 @@
 //! [`NodeRc`]/[`NodeArc`] and [`YamlRc`]/[`YamlArc`]. To get back as string,
 //! please use [`dump`] function.
 //!
+//! Function [`parse_documents`] keeps the directives and anchors of each
+//! document in the [`Document`] type. Functions [`split_documents`] and
+//! [`join_documents`] split and join the multi-document streams without
+//! parsing them.
+//!
+//! Function [`parser::parse_round_trip`] records the source ranges of the
+//! nodes, then [`dumper::dump_preserving`] writes the mutated trees back with
+//! the original text of the untouched parts. The [`format`] module
+//! re-indents the documents with the comments kept.
+//!
 //! There are also have some macros for building [`Node`] structure from Rust
 //! data. Especially [`node!`] macro, almost data can be built by the macro
 //! literally.
@@ -27,11 +38,20 @@
 //! If you went to rise your own error message, [`indicated_msg`] might be a
 //! good choice.
 //!
+//! The [`query`] module provides a small expression language like
+//! `.items[*].name` to select the nodes, and the [`patch`] module applies
+//! the JSON Patch and the JSON Merge Patch to the nodes. The [`redact`]
+//! module masks the sensitive values before logging. The [`json`] module
+//! converts the nodes to the JSON text for the web tools, and the [`flatten`]
+//! module converts them to the path/value pairs for the environment variables.
+//! Function [`analyze`] counts the nodes, the depth and the aliases of a tree
+//! before accepting it.
+//!
 //! ## Anchor Parsing
 //!
 //! + [`parse`]: The parser will replace the anchors during parsing.
 //! + [`parse_cyclic`]: Cyclic data means that a parent alias is inserted at the
-//! child node.   Keep the alias to avoid having undefined anchors when parsing.
+//!   child node. Keep the alias to avoid having undefined anchors when parsing.
 //!
 //! # No Standard Library
 //!
@@ -40,25 +60,58 @@
 //!
 //! # Serialization and Deserialization
 //!
-//! Enable `serde` feature to use `serde` crate,
+//! The `serde` feature is a default feature to use `serde` crate,
 //! which provides a set of protocol traits to convert between custom Rust data.
 //! Please be aware that the additional fields will be discarded when convert to
 //! a fix-sized structure. For example, the structure fields can be turned into
 //! map keys as well.
 //!
+//! Enable `arbitrary-precision` feature to keep the oversized integers and
+//! the high-precision decimals, see [`Node::as_big_int`] and
+//! [`Node::as_decimal`]. Their types [`BigInt`] and [`Decimal`] are lossless
+//! through the serialization as well.
+//!
+//! Enable `toml` feature to convert the nodes to the TOML documents, see
+//! [`toml::to_toml`].
+//!
+//! Enable `units` feature to read the durations and the byte sizes of the
+//! configurations, see [`Node::as_duration`] and [`Node::as_byte_size`].
+//!
+//! Enable `siphash` feature to build the lookup tables hashed by the
+//! randomly keyed SipHash of the standard library, see [`SipMap`].
+//!
+//! Enable `arena` feature to allocate the nodes of the large documents in
+//! chunks instead of one by one, see [`repr::ArenaRepr`].
+//!
+//! Enable `testing` feature to compare the parsed fixtures with the expected
+//! trees or the golden files in the tests, see the [`testing`] module.
+//!
 //! On the other hand, the primitive types are still able to transform to YAML
 //! data without serialization, according to built-in `From` and `Into` traits.
+//!
+//! The nodes, parser and dumper do not depend on `serde`, so the minimal
+//! builds can disable it by `default-features = false, features = ["std"]`.
 #![cfg_attr(doc_cfg, feature(doc_cfg))]
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 extern crate alloc;
 extern crate core;
 
+#[cfg(feature = "arbitrary-precision")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "arbitrary-precision")))]
+pub use crate::number::*;
+#[cfg(feature = "units")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "units")))]
+pub use crate::units::*;
 pub use crate::{
+    compare::*,
+    document::*,
     dumper::dump,
     indicator::*,
+    mutation::*,
     node::*,
-    parser::{parse, parse_cyclic},
+    parser::{join_documents, load_layered, parse, parse_cyclic, parse_documents, split_documents},
+    stats::*,
     yaml::*,
 };
 
@@ -120,14 +173,75 @@ macro_rules! node {
     };
 }
 
+/// Create [`Anchors`](parser::Anchors) literally, for the trees that use the
+/// aliases created by [`node!`].
+///
+/// The values are converted by [`Node::from`], and the anchor sets behind
+/// `..` are merged in order, the later anchors replace the earlier ones with
+/// the same name.
+///
+/// ```
+/// use yaml_peg::{anchors, node};
+///
+/// let base = anchors!["a" => node!([1, 2]), "b" => 2];
+/// let anchors = anchors![..base, "b" => node!(*"a"), "c" => "x"];
+/// assert_eq!(anchors.len(), 3);
+/// assert_eq!(anchors["b"], node!(*"a"));
+/// let n = node!({"k" => node!(*"b")});
+/// assert_eq!(
+///     anchors.resolve_all(&n).unwrap(),
+///     node!({"k" => node!([1, 2])})
+/// );
+/// ```
+#[macro_export]
+macro_rules! anchors {
+    (@$a:ident) => {};
+    (@$a:ident .. $base:expr $(, $($tt:tt)*)?) => {
+        $a.merge($base);
+        $($crate::anchors!(@$a $($tt)*);)?
+    };
+    (@$a:ident $k:expr => $v:expr $(, $($tt:tt)*)?) => {
+        $a.insert($k.into(), $crate::Node::from($v));
+        $($crate::anchors!(@$a $($tt)*);)?
+    };
+    () => {
+        $crate::parser::Anchors::new()
+    };
+    ($($tt:tt)+) => {{
+        let mut anchors = $crate::parser::Anchors::new();
+        $crate::anchors!(@anchors $($tt)*);
+        anchors
+    }};
+}
+
+mod compare;
+mod document;
 pub mod dumper;
+pub mod flatten;
+pub mod format;
 mod indicator;
+pub mod json;
+mod mutation;
 mod node;
+#[cfg(feature = "arbitrary-precision")]
+mod number;
 pub mod parser;
+pub mod patch;
+pub mod query;
+pub mod redact;
 pub mod repr;
 #[cfg(feature = "serde")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "serde")))]
 pub mod serde;
+mod stats;
+#[cfg(feature = "testing")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "testing")))]
+pub mod testing;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "toml")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "toml")))]
+pub mod toml;
+#[cfg(feature = "units")]
+mod units;
 mod yaml;
@@ -0,0 +1,255 @@
+use crate::{
+    patch::{index, is_key},
+    repr::Repr,
+    *,
+};
+use alloc::vec::Vec;
+use core::{
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+};
+
+enum Step<R: Repr> {
+    Ind(usize),
+    Key(Node<R>),
+}
+
+/// The mutable guard of a node inside the tree, created by [`Node::get_mut`]
+/// and [`Entry::or_insert`].
+///
+/// The guard holds a copy of the target node, which is cheap since the YAML
+/// data is reference counted. If the guard is mutably dereferenced, the node
+/// will be written back to the tree when dropped, only the ancestors on the
+/// path are copied, and the other branches are still shared. Otherwise, the
+/// tree is untouched.
+///
+/// ```
+/// use std::rc::Rc;
+/// use yaml_peg::{node, NodeRc};
+///
+/// let mut root: NodeRc = "spec: {replicas: 1, ports: [80]}".parse().unwrap();
+/// let ports = root["spec"]["ports"].clone_yaml();
+/// *root.get_mut(&["spec", "replicas"]).unwrap() = node!(3);
+/// assert_eq!(root, node!({"spec" => node!({"replicas" => 3, "ports" => node!([80])})}));
+/// // The unchanged branch is still shared
+/// assert!(Rc::ptr_eq(&ports, root["spec"]["ports"].rc_ref()));
+/// // Reading through the guard does not copy the tree
+/// let spec = root["spec"].clone_yaml();
+/// assert_eq!(*root.get_mut(&["spec", "replicas"]).unwrap(), node!(3));
+/// assert!(Rc::ptr_eq(&spec, root["spec"].rc_ref()));
+/// ```
+pub struct NodeMut<'a, R: Repr> {
+    root: &'a mut Node<R>,
+    path: Vec<Step<R>>,
+    node: Node<R>,
+    dirty: bool,
+}
+
+impl<'a, R: Repr> NodeMut<'a, R> {
+    fn new(root: &'a mut Node<R>, path: Vec<Step<R>>, node: Node<R>) -> Self {
+        Self { root, path, node, dirty: false }
+    }
+
+    /// Return true if the guard was mutably dereferenced.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl<R: Repr> Deref for NodeMut<'_, R> {
+    type Target = Node<R>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl<R: Repr> DerefMut for NodeMut<'_, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dirty = true;
+        &mut self.node
+    }
+}
+
+impl<R: Repr> Drop for NodeMut<'_, R> {
+    fn drop(&mut self) {
+        if self.dirty {
+            write_back(self.root, &self.path, self.node.clone());
+        }
+    }
+}
+
+impl<R: Repr> Debug for NodeMut<'_, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+fn write_back<R: Repr>(node: &mut Node<R>, path: &[Step<R>], value: Node<R>) {
+    let Some((step, path)) = path.split_first() else {
+        *node = value;
+        return;
+    };
+    let mut yaml = node.yaml().clone();
+    let child = match (&mut yaml, step) {
+        (Yaml::Seq(v), Step::Ind(i)) => v.get_mut(*i),
+        (Yaml::Map(m), Step::Key(k)) => m.get_mut(k),
+        _ => None,
+    };
+    if let Some(child) = child {
+        write_back(child, path, value);
+        node.set_yaml(yaml);
+    }
+}
+
+/// A view of a map key, created by [`Node::entry`].
+///
+/// ```
+/// use yaml_peg::{node, NodeRc};
+///
+/// let mut root: NodeRc = "a: 1".parse().unwrap();
+/// assert_eq!(*root.entry("a").unwrap().or_insert(2), node!(1));
+/// assert_eq!(*root.entry("b").unwrap().or_insert(2), node!(2));
+/// root.entry("c").unwrap().or_insert(node!([])).set_repr(node!([3]).clone_yaml());
+/// assert_eq!(root, node!({"a" => 1, "b" => 2, "c" => node!([3])}));
+/// ```
+pub struct Entry<'a, R: Repr> {
+    map: &'a mut Node<R>,
+    key: Node<R>,
+}
+
+impl<'a, R: Repr> Entry<'a, R> {
+    /// The key of this entry.
+    pub fn key(&self) -> &Node<R> {
+        &self.key
+    }
+
+    /// Return true if the key is in the map.
+    pub fn is_occupied(&self) -> bool {
+        self.map.get(self.key.clone()).is_ok()
+    }
+
+    /// Insert the default value if the key is missing, then return the guard
+    /// of the value.
+    pub fn or_insert(self, default: impl Into<Node<R>>) -> NodeMut<'a, R> {
+        self.or_insert_with(|| default.into())
+    }
+
+    /// Insert the value returned by the function if the key is missing, then
+    /// return the guard of the value.
+    pub fn or_insert_with<F>(self, f: F) -> NodeMut<'a, R>
+    where
+        F: FnOnce() -> Node<R>,
+    {
+        let Self { map, key } = self;
+        let found = match map.yaml() {
+            Yaml::Map(m) => m.get_key_value(&key).map(|(k, v)| (k.clone(), v.clone())),
+            _ => unreachable!(),
+        };
+        let (key, value) = match found {
+            Some(kv) => kv,
+            None => {
+                let value = f();
+                let mut yaml = map.yaml().clone();
+                if let Yaml::Map(m) = &mut yaml {
+                    m.insert(key.clone(), value.clone());
+                }
+                map.set_yaml(yaml);
+                (key, value)
+            }
+        };
+        NodeMut::new(map, alloc::vec![Step::Key(key)], value)
+    }
+
+    /// Insert the default value of the node (null) if the key is missing.
+    pub fn or_default(self) -> NodeMut<'a, R> {
+        self.or_insert_with(|| Node::from(Yaml::Null))
+    }
+
+    /// Call the function on the value if the key exists.
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc};
+    ///
+    /// let mut root: NodeRc = "a: 1".parse().unwrap();
+    /// for k in ["a", "b"] {
+    ///     root.entry(k).unwrap().and_modify(|n| *n = node!(10)).or_insert(0);
+    /// }
+    /// assert_eq!(root, node!({"a" => 10, "b" => 0}));
+    /// ```
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut Node<R>),
+    {
+        if self.is_occupied() {
+            let Self { map, key } = self;
+            let value = map.get(key.clone()).unwrap().clone();
+            let mut guard = NodeMut::new(map, alloc::vec![Step::Key(key.clone())], value);
+            f(&mut guard);
+            drop(guard);
+            Self { map, key }
+        } else {
+            self
+        }
+    }
+}
+
+impl<R: Repr> Debug for Entry<'_, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Entry").field("key", &self.key).finish()
+    }
+}
+
+impl<R: Repr> Node<R> {
+    /// Get the entry of the key for in-place manipulation.
+    ///
+    /// Return `Err` with the node position if the node is not a map.
+    pub fn entry(&mut self, key: impl Into<Self>) -> Result<Entry<'_, R>, u64> {
+        if let Yaml::Map(_) = self.yaml() {
+            Ok(Entry { map: self, key: key.into() })
+        } else {
+            Err(self.pos())
+        }
+    }
+
+    /// Get the mutable guard of the node by the path tokens.
+    ///
+    /// The tokens are matched to the string keys or the scalar keys with the
+    /// same text, and the indices of the sequences, same as the JSON Pointer
+    /// of the [`patch`] module. Return `None` if the path does not exist.
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc};
+    ///
+    /// let mut root: NodeRc = "servers:\n- {host: a, port: 80}".parse().unwrap();
+    /// let mut port = root.get_mut(&["servers", "0", "port"]).unwrap();
+    /// if *port == node!(80) {
+    ///     *port = node!(8080);
+    /// }
+    /// drop(port);
+    /// assert_eq!(root["servers"][yaml_peg::Ind(0)]["port"], node!(8080));
+    /// assert!(root.get_mut(&["servers", "1"]).is_none());
+    /// ```
+    pub fn get_mut<S: AsRef<str>>(&mut self, path: &[S]) -> Option<NodeMut<'_, R>> {
+        let mut steps = Vec::with_capacity(path.len());
+        let mut node = &*self;
+        for token in path {
+            let token = token.as_ref();
+            node = match node.yaml() {
+                Yaml::Seq(v) => {
+                    let i = index(token, v.len())?;
+                    steps.push(Step::Ind(i));
+                    &v[i]
+                }
+                Yaml::Map(m) => {
+                    let (k, v) = m.iter().find(|(k, _)| is_key(k, token))?;
+                    steps.push(Step::Key(k.clone()));
+                    v
+                }
+                _ => return None,
+            };
+        }
+        let node = node.clone();
+        Some(NodeMut::new(self, steps, node))
+    }
+}
@@ -1,11 +1,23 @@
-use crate::{parser::Anchors, repr::*, *};
-use alloc::string::{String, ToString};
+use crate::{
+    parser::Anchors,
+    repr::*,
+    yaml::{to_f64, to_i128},
+    *,
+};
+use alloc::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{
-    fmt::{Debug, Formatter},
+    cmp::Ordering,
+    fmt::{Debug, Display, Formatter},
     hash::{Hash, Hasher},
     iter::FromIterator,
     marker::PhantomData,
     ops::Index,
+    str::FromStr,
 };
 
 macro_rules! as_method {
@@ -23,6 +35,35 @@ macro_rules! as_method {
     )+};
 }
 
+macro_rules! as_int_method {
+    ($($(#[$meta:meta])* fn $id:ident -> $ty:ty)+) => {$(
+        $(#[$meta])*
+        pub fn $id(&self) -> Result<$ty, IntError> {
+            match self.yaml() {
+                Yaml::Int(s) => to_i128(s)
+                    .ok()
+                    .and_then(|n| <$ty>::try_from(n).ok())
+                    .ok_or_else(|| IntError::OutOfRange { value: s.clone(), pos: self.pos }),
+                _ => Err(IntError::NotInt(self.pos)),
+            }
+        }
+    )+};
+}
+
+macro_rules! get_method {
+    ($($(#[$meta:meta])* fn $id:ident = $as:ident($expected:literal) -> $r:ty)+) => {$(
+        $(#[$meta])*
+        pub fn $id(&self, path: &[&str]) -> Result<$r, GetError> {
+            let node = self.get_path(path)?;
+            node.$as().map_err(|pos| GetError::WrongType {
+                path: path.join("."),
+                expected: $expected,
+                pos,
+            })
+        }
+    )+};
+}
+
 macro_rules! impl_iter {
     ($(impl $item:ty)+) => {
         $(impl<R: Repr> FromIterator<$item> for Node<R> {
@@ -33,6 +74,93 @@ macro_rules! impl_iter {
     };
 }
 
+/// The error of the range-checked integer conversions, such as
+/// [`Node::as_u8`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IntError {
+    /// The node is not an integer, with node position.
+    NotInt(u64),
+    /// The integer is out of the range of the target type.
+    OutOfRange {
+        /// The original value.
+        value: String,
+        /// Node position.
+        pos: u64,
+    },
+}
+
+impl IntError {
+    /// Node position.
+    pub fn pos(&self) -> u64 {
+        match self {
+            Self::NotInt(pos) | Self::OutOfRange { pos, .. } => *pos,
+        }
+    }
+}
+
+impl Display for IntError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotInt(pos) => write!(f, "not an integer at {}", pos),
+            Self::OutOfRange { value, pos } => {
+                write!(f, "integer `{}` out of range at {}", value, pos)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IntError {}
+
+/// The error of the path getters, such as [`Node::get_str`].
+///
+/// The paths are the segments joined with dots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GetError {
+    /// The key or the index is missing.
+    Missing {
+        /// The path to the missing key.
+        path: String,
+        /// The position of the parent node.
+        pos: u64,
+    },
+    /// The node has a wrong type, includes the parent nodes that are not
+    /// collections.
+    WrongType {
+        /// The path to the node.
+        path: String,
+        /// The expected type.
+        expected: &'static str,
+        /// Node position.
+        pos: u64,
+    },
+}
+
+impl GetError {
+    /// Node position.
+    pub fn pos(&self) -> u64 {
+        match self {
+            Self::Missing { pos, .. } | Self::WrongType { pos, .. } => *pos,
+        }
+    }
+}
+
+impl Display for GetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Missing { path, pos } => write!(f, "missing `{}` at {}", path, pos),
+            Self::WrongType { path, expected, pos } => {
+                write!(f, "expected {} at `{}` at {}", expected, path, pos)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GetError {}
+
 /// A node with [`alloc::rc::Rc`] holder.
 pub type NodeRc = Node<RcRepr>;
 /// A node with [`alloc::sync::Arc`] holder.
@@ -117,6 +245,7 @@ pub struct Node<R: Repr> {
     pos: u64,
     tag: String,
     yaml: R::Rc,
+    meta: R::Meta,
     _marker: PhantomData<R>,
 }
 
@@ -126,12 +255,50 @@ impl<R: Repr> Node<R> {
         Self::new_repr(R::new_rc(yaml.into()), pos, tag)
     }
 
+    /// Create a null node without the position and the tag.
+    ///
+    /// The scalar constructors are the shorthands of [`Node::from`].
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc};
+    ///
+    /// assert_eq!(NodeRc::null(), node!(()));
+    /// assert_eq!(NodeRc::bool(true), node!(true));
+    /// assert_eq!(NodeRc::int(-3), node!(-3));
+    /// assert_eq!(NodeRc::float(0.5), node!(0.5));
+    /// assert_eq!(NodeRc::str("a"), node!("a"));
+    /// ```
+    pub fn null() -> Self {
+        Self::from(Yaml::Null)
+    }
+
+    /// Create a boolean node.
+    pub fn bool(b: bool) -> Self {
+        Self::from(Yaml::Bool(b))
+    }
+
+    /// Create an integer node.
+    pub fn int(n: i64) -> Self {
+        Self::from(n)
+    }
+
+    /// Create a float node.
+    pub fn float(n: f64) -> Self {
+        Self::from(n)
+    }
+
+    /// Create a string node.
+    pub fn str(s: impl Into<String>) -> Self {
+        Self::from(Yaml::Str(s.into()))
+    }
+
     /// Create from a representation.
     pub fn new_repr(yaml: R::Rc, pos: u64, tag: impl ToString) -> Self {
         Self {
             yaml,
             pos,
             tag: tag.to_string(),
+            meta: R::Meta::default(),
             _marker: PhantomData,
         }
     }
@@ -151,6 +318,69 @@ impl<R: Repr> Node<R> {
         self.pos
     }
 
+    /// Custom metadata, see [`Repr::Meta`].
+    pub fn meta(&self) -> &R::Meta {
+        &self.meta
+    }
+
+    /// Mutable reference of the custom metadata.
+    pub fn meta_mut(&mut self) -> &mut R::Meta {
+        &mut self.meta
+    }
+
+    /// Replace the custom metadata.
+    pub fn with_meta(self, meta: R::Meta) -> Self {
+        Self { meta, ..self }
+    }
+
+    /// Replace the YAML data, the position, the tag and the metadata are
+    /// kept, unlike [`Node::from`].
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc, Yaml};
+    ///
+    /// let n = NodeRc::str("a").with_pos(3).with_tag("x");
+    /// let n = n.with_yaml(Yaml::from(1));
+    /// assert_eq!(n, node!(1));
+    /// assert_eq!((n.pos(), n.tag()), (3, "x"));
+    /// ```
+    pub fn with_yaml(mut self, yaml: impl Into<Yaml<R>>) -> Self {
+        self.set_yaml(yaml);
+        self
+    }
+
+    /// Replace the document position.
+    pub fn with_pos(self, pos: u64) -> Self {
+        Self { pos, ..self }
+    }
+
+    /// Replace the tag, the empty string is the default tag, see
+    /// [`Node::tag`].
+    pub fn with_tag(self, tag: impl ToString) -> Self {
+        Self { tag: tag.to_string(), ..self }
+    }
+
+    /// Define the node as an anchor in the anchor set, since the anchors are
+    /// not stored in the nodes.
+    ///
+    /// The dumper writes the anchor name for the node, and the aliases can
+    /// be resolved by the anchor set, see [`Anchors::resolve_all`].
+    ///
+    /// ```
+    /// use yaml_peg::{dump, node, parser::Anchors};
+    ///
+    /// let mut anchors = Anchors::new();
+    /// let base = node!({"a" => 1}).with_anchor("base", &mut anchors);
+    /// let n = node!({"b" => base, "c" => node!(*"base")});
+    /// let doc = dump(&[n.clone()], &[anchors.clone()]);
+    /// assert_eq!(doc, "b: &base\n  a: 1\nc: *base\n".replace('\n', yaml_peg::dumper::NL));
+    /// assert_eq!(anchors.resolve_all(&n).unwrap()["c"], node!({"a" => 1}));
+    /// ```
+    pub fn with_anchor(self, name: impl ToString, anchors: &mut Anchors<R>) -> Self {
+        anchors.insert(name.to_string(), self.clone());
+        self
+    }
+
     /// Tag. If the tag is not specified, returns a default tag from core
     /// schema.
     ///
@@ -171,6 +401,30 @@ impl<R: Repr> Node<R> {
         }
     }
 
+    /// The tag URI, same as [`Node::tag`] except the local tags are resolved
+    /// with the leading `!`, because their names are stored without it.
+    ///
+    /// The tags without a URI scheme like `tag:` are local tags.
+    ///
+    /// ```
+    /// use yaml_peg::NodeRc;
+    ///
+    /// let n = "%TAG !e! tag:example.com,2000:app/\n---\n[!e!a%21, !foo, !<!bar>, 1]"
+    ///     .parse::<NodeRc>()
+    ///     .unwrap();
+    /// let tags = n.as_seq().unwrap().iter().map(|n| n.resolved_tag().into_owned()).collect::<Vec<_>>();
+    /// assert_eq!(tags, ["tag:example.com,2000:app/a!", "!foo", "!bar", "tag:yaml.org,2002:int"]);
+    /// assert_eq!(n[yaml_peg::Ind(1)].tag(), "foo");
+    /// ```
+    pub fn resolved_tag(&self) -> Cow<'_, str> {
+        let tag = self.tag();
+        if tag.is_empty() || tag.starts_with('!') || parser::is_global_tag(tag) {
+            Cow::Borrowed(tag)
+        } else {
+            Cow::Owned(alloc::format!("!{}", tag))
+        }
+    }
+
     /// YAML data.
     pub fn yaml(&self) -> &Yaml<R> {
         &self.yaml
@@ -221,6 +475,53 @@ impl<R: Repr> Node<R> {
         }
     }
 
+    as_int_method! {
+        /// Convert to [`i8`] with range checking.
+        ///
+        /// ```
+        /// use yaml_peg::{node, IntError};
+        ///
+        /// assert_eq!(Ok(-128), node!(-128).as_i8());
+        /// assert_eq!(
+        ///     Err(IntError::OutOfRange { value: "128".to_string(), pos: 0 }),
+        ///     node!(128).as_i8()
+        /// );
+        /// assert_eq!(Err(IntError::NotInt(0)), node!("128").as_i8());
+        /// ```
+        fn as_i8 -> i8
+        /// Convert to [`i16`] with range checking.
+        fn as_i16 -> i16
+        /// Convert to [`i32`] with range checking.
+        fn as_i32 -> i32
+        /// Convert to [`i64`] with range checking.
+        fn as_i64 -> i64
+        /// Convert to [`isize`] with range checking.
+        fn as_isize -> isize
+        /// Convert to [`u8`] with range checking.
+        ///
+        /// ```
+        /// use yaml_peg::node;
+        ///
+        /// assert_eq!(Ok(255), node!(0xff).as_u8());
+        /// assert!(node!(-1).as_u8().is_err());
+        /// ```
+        fn as_u8 -> u8
+        /// Convert to [`u16`] with range checking.
+        fn as_u16 -> u16
+        /// Convert to [`u32`] with range checking.
+        fn as_u32 -> u32
+        /// Convert to [`u64`] with range checking.
+        ///
+        /// ```
+        /// use yaml_peg::node;
+        ///
+        /// assert_eq!(Ok(u64::MAX), node!(u64::MAX).as_u64());
+        /// ```
+        fn as_u64 -> u64
+        /// Convert to [`usize`] with range checking.
+        fn as_usize -> usize
+    }
+
     /// Convert to float.
     ///
     /// ```
@@ -251,6 +552,199 @@ impl<R: Repr> Node<R> {
         }
     }
 
+    /// Convert to integer with the coercion of the sloppy values.
+    ///
+    /// | Value | Result |
+    /// |-------|--------|
+    /// | Integer | The integer |
+    /// | Float without fractional part | The integer, e.g., `42.0` is `42` |
+    /// | Boolean | `1` or `0` |
+    /// | String | The integer or the float of the trimmed string, e.g., `"42"`, `" 0x10 "` and `"1e3"` |
+    ///
+    /// Other values are failed, and the out of range values are failed.
+    /// The strict version is [`Node::as_int`].
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// assert_eq!(node!("42").as_int_lenient(), Ok(42));
+    /// assert_eq!(node!(42.0).as_int_lenient(), Ok(42));
+    /// assert_eq!(node!(true).as_int_lenient(), Ok(1));
+    /// assert!(node!(4.2).as_int_lenient().is_err());
+    /// assert!(node!("abc").as_int_lenient().is_err());
+    /// ```
+    pub fn as_int_lenient(&self) -> Result<i64, u64> {
+        let float = |f: f64| {
+            // The range of i64 is [-2^63, 2^63)
+            let n = f as i64;
+            (-9.223_372_036_854_776e18..9.223_372_036_854_776e18).contains(&f) && n as f64 == f
+        };
+        match self.yaml() {
+            Yaml::Int(s) => to_i64(s).ok(),
+            Yaml::Float(s) => to_f64(s).ok().filter(|f| float(*f)).map(|f| f as i64),
+            Yaml::Bool(b) => Some(*b as i64),
+            Yaml::Str(s) => {
+                let s = s.trim();
+                to_i64(s)
+                    .ok()
+                    .or_else(|| to_f64(s).ok().filter(|f| float(*f)).map(|f| f as i64))
+            }
+            _ => None,
+        }
+        .ok_or(self.pos)
+    }
+
+    /// Convert to float with the coercion of the sloppy values.
+    ///
+    /// | Value | Result |
+    /// |-------|--------|
+    /// | Integer or float | The number, see [`Node::as_number`] |
+    /// | String | The number of the trimmed string, e.g., `"4.2"` and `".inf"` |
+    ///
+    /// Other values are failed.
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// assert_eq!(node!(" 4.2 ").as_float_lenient(), Ok(4.2));
+    /// assert_eq!(node!(4).as_float_lenient(), Ok(4.));
+    /// assert!(node!(true).as_float_lenient().is_err());
+    /// ```
+    pub fn as_float_lenient(&self) -> Result<f64, u64> {
+        match self.yaml() {
+            Yaml::Str(s) => to_f64(s.trim()).map_err(|_| self.pos),
+            _ => self.as_number(),
+        }
+    }
+
+    /// Convert to boolean with the coercion of the sloppy values.
+    ///
+    /// | Value | Result |
+    /// |-------|--------|
+    /// | Boolean | The boolean |
+    /// | Integer | `true` if not zero |
+    /// | String `true`, `yes`, `y`, `on` or `1` | `true` |
+    /// | String `false`, `no`, `n`, `off`, `0` or empty | `false` |
+    /// | Null | `false` |
+    ///
+    /// The strings are trimmed and case-insensitive. Other values are failed.
+    /// The strict version is [`Node::as_bool`].
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// assert_eq!(node!("Yes").as_bool_lenient(), Ok(true));
+    /// assert_eq!(node!(1).as_bool_lenient(), Ok(true));
+    /// assert_eq!(node!(()).as_bool_lenient(), Ok(false));
+    /// assert!(node!("maybe").as_bool_lenient().is_err());
+    /// ```
+    pub fn as_bool_lenient(&self) -> Result<bool, u64> {
+        match self.yaml() {
+            Yaml::Bool(b) => Ok(*b),
+            Yaml::Int(s) => to_i128(s).map(|n| n != 0).map_err(|_| self.pos),
+            Yaml::Null => Ok(false),
+            Yaml::Str(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "y" | "on" | "1" => Ok(true),
+                "false" | "no" | "n" | "off" | "0" | "" => Ok(false),
+                _ => Err(self.pos),
+            },
+            _ => Err(self.pos),
+        }
+    }
+
+    /// Convert to duration, such as `30s`, `5m`, `1h30m` and `1.5h`.
+    ///
+    /// The units are `ns`, `us` (or `µs`), `ms`, `s`, `m`, `h` and `d`, and
+    /// the numbers without unit are in seconds, includes the integer and
+    /// float nodes.
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use yaml_peg::{node, UnitError};
+    ///
+    /// assert_eq!(node!("1h30m").as_duration(), Ok(Duration::from_secs(5400)));
+    /// assert_eq!(node!("250ms").as_duration(), Ok(Duration::from_millis(250)));
+    /// assert_eq!(node!(30).as_duration(), Ok(Duration::from_secs(30)));
+    /// assert!(matches!(node!("5 min").as_duration(), Err(UnitError::Invalid { .. })));
+    /// ```
+    #[cfg(feature = "units")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "units")))]
+    pub fn as_duration(&self) -> Result<core::time::Duration, UnitError> {
+        let (s, r) = match self.yaml() {
+            Yaml::Str(s) => (s, crate::units::to_duration(s)),
+            Yaml::Int(s) => (
+                s,
+                crate::units::int_to_u64(s).map(core::time::Duration::from_secs),
+            ),
+            Yaml::Float(s) => (s, crate::units::float_to_duration(s)),
+            _ => return Err(UnitError::NotScalar(self.pos)),
+        };
+        r.map_err(|e| e.into_error(s, self.pos))
+    }
+
+    /// Convert to the number of bytes, such as `512`, `10MiB` and `1GB`.
+    ///
+    /// The decimal units are `k`, `M`, `G`, `T` and `P`, the binary units are
+    /// `Ki`, `Mi`, `Gi`, `Ti` and `Pi`, with an optional `B` suffix. The units
+    /// are case-insensitive. The integer and float nodes are converted by
+    /// their values, the fraction under a byte is truncated.
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// assert_eq!(node!("10MiB").as_byte_size(), Ok(10 << 20));
+    /// assert_eq!(node!("1.5 GB").as_byte_size(), Ok(1_500_000_000));
+    /// assert_eq!(node!(512).as_byte_size(), Ok(512));
+    /// assert!(node!("10 apples").as_byte_size().is_err());
+    /// ```
+    #[cfg(feature = "units")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "units")))]
+    pub fn as_byte_size(&self) -> Result<u64, UnitError> {
+        let (s, r) = match self.yaml() {
+            Yaml::Str(s) => (s, crate::units::to_byte_size(s)),
+            Yaml::Int(s) => (s, crate::units::int_to_u64(s)),
+            Yaml::Float(s) => (s, crate::units::float_to_u64(s)),
+            _ => return Err(UnitError::NotScalar(self.pos)),
+        };
+        r.map_err(|e| e.into_error(s, self.pos))
+    }
+
+    /// Convert to integer without precision limit.
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// let n = node!(u128::MAX);
+    /// assert_eq!(n.as_big_int().unwrap().to_u128(), Some(u128::MAX));
+    /// ```
+    #[cfg(feature = "arbitrary-precision")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "arbitrary-precision")))]
+    pub fn as_big_int(&self) -> Result<BigInt, IntError> {
+        match self.yaml() {
+            Yaml::Int(s) => s
+                .parse()
+                .map_err(|_| IntError::OutOfRange { value: s.clone(), pos: self.pos }),
+            _ => Err(IntError::NotInt(self.pos)),
+        }
+    }
+
+    /// Convert to decimal number without precision limit for any number.
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// assert_eq!(node!(60).as_decimal().unwrap().as_str(), "60");
+    /// assert_eq!(node!(20.06).as_decimal().unwrap().as_str(), "20.06");
+    /// ```
+    #[cfg(feature = "arbitrary-precision")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "arbitrary-precision")))]
+    pub fn as_decimal(&self) -> Result<Decimal, u64> {
+        match self.yaml() {
+            Yaml::Int(s) | Yaml::Float(s) => s.parse().map_err(|_| self.pos),
+            _ => Err(self.pos),
+        }
+    }
+
     as_method! {
         /// Convert to boolean.
         ///
@@ -302,6 +796,82 @@ impl<R: Repr> Node<R> {
         fn as_map = Map(clone) -> Map<R>
     }
 
+    /// Convert to map with the string keys, return the position of the first
+    /// non-string key if failed.
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// let n = node!({"a" => 1, "b" => 2});
+    /// let m = n.as_str_map().unwrap().collect::<Vec<_>>();
+    /// assert_eq!(m, [("a", &node!(1)), ("b", &node!(2))]);
+    /// assert!(node!({1 => 2}).as_str_map().is_err());
+    /// ```
+    pub fn as_str_map(&self) -> Result<impl Iterator<Item = (&str, &Self)>, u64> {
+        let Yaml::Map(m) = self.yaml() else {
+            return Err(self.pos);
+        };
+        if let Some(k) = m.keys().find(|k| !matches!(k.yaml(), Yaml::Str(_))) {
+            return Err(k.pos);
+        }
+        Ok(m.iter().map(|(k, v)| match k.yaml() {
+            Yaml::Str(k) => (k.as_str(), v),
+            _ => unreachable!(),
+        }))
+    }
+
+    /// Convert the `!!set` map to the items, the values of the map must be
+    /// null.
+    ///
+    /// The tag is not checked, so the untagged maps are also accepted.
+    /// Return the position of the first non-null value if failed.
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc};
+    ///
+    /// let n = "!!set {a, b}".parse::<NodeRc>().unwrap();
+    /// assert_eq!(n.tag(), "tag:yaml.org,2002:set");
+    /// assert_eq!(n.as_set().unwrap(), [node!("a"), node!("b")]);
+    /// assert!(node!({"a" => 1}).as_set().is_err());
+    /// ```
+    pub fn as_set(&self) -> Result<Seq<R>, u64> {
+        let m = self.as_map()?;
+        match m.values().find(|v| !v.is_null()) {
+            Some(v) => Err(v.pos),
+            None => Ok(m.into_iter().map(|(k, _)| k).collect()),
+        }
+    }
+
+    /// Convert the `!!omap` or `!!pairs` sequence to the key-value pairs in
+    /// order, the items of the sequence must be single-pair maps.
+    ///
+    /// The tag is not checked, and the duplicated keys are kept, the unique
+    /// keys of `!!omap` can be checked by the caller. Return the position of
+    /// the first invalid item if failed.
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc};
+    ///
+    /// let n = "!!pairs [a: 1, b: 2, a: 3]".parse::<NodeRc>().unwrap();
+    /// assert_eq!(n.tag(), "tag:yaml.org,2002:pairs");
+    /// let pairs = n.as_pairs().unwrap();
+    /// assert_eq!(pairs[2], (node!("a"), node!(3)));
+    /// assert!(node!([node!({1 => 2, 3 => 4})]).as_pairs().is_err());
+    /// ```
+    pub fn as_pairs(&self) -> Result<Vec<(Self, Self)>, u64> {
+        self.as_seq()?
+            .into_iter()
+            .map(|item| match item.yaml() {
+                Yaml::Map(m) if m.len() == 1 => Ok(m
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .next()
+                    .unwrap()),
+                _ => Err(item.pos),
+            })
+            .collect()
+    }
+
     /// Convert to string pointer for string, null, bool, int, and float type.
     ///
     /// This method is useful when the option mixed with digit values.
@@ -442,6 +1012,764 @@ impl<R: Repr> Node<R> {
             Err(self.pos)
         }
     }
+
+    /// Get the node through the path of the map keys and the sequence
+    /// indices, such as `&["servers", "0", "host"]`.
+    ///
+    /// The typed getters [`Node::get_bool`], [`Node::get_str`],
+    /// [`Node::get_int`] and [`Node::get_float`] call this method then
+    /// convert the node.
+    ///
+    /// ```
+    /// use yaml_peg::{GetError, NodeRc};
+    ///
+    /// let root: NodeRc = "servers:\n  - host: a\n    port: 80\n".parse().unwrap();
+    /// assert_eq!(root.get_path(&["servers", "0", "host"]).unwrap().as_str(), Ok("a"));
+    /// assert_eq!(root.get_str(&["servers", "0", "host"]), Ok("a"));
+    /// assert_eq!(root.get_int(&["servers", "0", "port"]), Ok(80));
+    /// assert_eq!(
+    ///     root.get_int(&["servers", "1", "port"]),
+    ///     Err(GetError::Missing { path: "servers.1".to_string(), pos: 8 })
+    /// );
+    /// assert_eq!(
+    ///     root.get_bool(&["servers", "0", "port"]),
+    ///     Err(GetError::WrongType {
+    ///         path: "servers.0.port".to_string(),
+    ///         expected: "bool",
+    ///         pos: 31,
+    ///     })
+    /// );
+    /// ```
+    pub fn get_path(&self, path: &[&str]) -> Result<&Self, GetError> {
+        let mut node = self;
+        for (i, seg) in path.iter().enumerate() {
+            let next = match node.yaml() {
+                Yaml::Map(m) => m.get_key(*seg),
+                Yaml::Seq(v) => seg.parse::<usize>().ok().and_then(|i| v.get(i)),
+                _ => {
+                    return Err(GetError::WrongType {
+                        path: path[..i].join("."),
+                        expected: "map or seq",
+                        pos: node.pos,
+                    })
+                }
+            };
+            node = next
+                .ok_or_else(|| GetError::Missing { path: path[..=i].join("."), pos: node.pos })?;
+        }
+        Ok(node)
+    }
+
+    get_method! {
+        /// Get the boolean through the path, see [`Node::get_path`].
+        fn get_bool = as_bool("bool") -> bool
+        /// Get the string through the path, the null value is an empty
+        /// string, see [`Node::get_path`].
+        fn get_str = as_str("str") -> &str
+        /// Get the integer through the path, see [`Node::get_path`].
+        fn get_int = as_int("int") -> i64
+        /// Get the number through the path, the integers are converted, see
+        /// [`Node::get_path`].
+        fn get_float = as_number("float") -> f64
+    }
+
+    /// Find the key path of the node at the document position from this
+    /// root, such as `spec.containers[2].image`.
+    ///
+    /// The position is usually came from the `Err` of the getters, or
+    /// [`SerdeError::pos`](crate::serde::SerdeError). The outermost node is
+    /// chosen if the nodes have the same position, and the root path is an
+    /// empty string. Return `None` if not found.
+    ///
+    /// The string keys are joined with dots, the special keys and other types
+    /// of keys are wrapped with the brackets.
+    ///
+    /// ```
+    /// use yaml_peg::{Ind, NodeRc};
+    ///
+    /// let doc = "spec:\n  containers:\n  - image: a\n  - image: b\n    \"tag.name\": c";
+    /// let root: NodeRc = doc.parse().unwrap();
+    /// let n = root["spec"]["containers"].get_ind(Ind(1)).unwrap();
+    /// assert_eq!(root.path_of(n["image"].pos()).unwrap(), "spec.containers[1].image");
+    /// assert_eq!(root.path_of(n["tag.name"].pos()).unwrap(), "spec.containers[1][\"tag.name\"]");
+    /// let pos = n.get("port").unwrap_err();
+    /// assert_eq!(root.path_of(pos).unwrap(), "spec.containers[1]");
+    /// assert_eq!(root.path_of(root.pos()).unwrap(), "");
+    /// assert_eq!(root.path_of(1000), None);
+    /// ```
+    pub fn path_of(&self, pos: u64) -> Option<String> {
+        let mut path = String::new();
+        self.path_inner(pos, &mut path).then_some(path)
+    }
+
+    fn path_inner(&self, pos: u64, path: &mut String) -> bool {
+        if self.pos == pos {
+            return true;
+        }
+        let len = path.len();
+        match self.yaml() {
+            Yaml::Seq(v) => {
+                for (i, n) in v.iter().enumerate() {
+                    path.push_str(&alloc::format!("[{}]", i));
+                    if n.path_inner(pos, path) {
+                        return true;
+                    }
+                    path.truncate(len);
+                }
+            }
+            Yaml::Map(m) => {
+                for (k, v) in m.iter() {
+                    k.push_path(path);
+                    if k.pos == pos || v.path_inner(pos, path) {
+                        return true;
+                    }
+                    path.truncate(len);
+                }
+            }
+            _ => (),
+        }
+        false
+    }
+
+    /// Append this node to the path as a map key.
+    pub(crate) fn push_path(&self, path: &mut String) {
+        match self.yaml() {
+            Yaml::Str(s)
+                if !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || "_-".contains(c)) =>
+            {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(s);
+            }
+            Yaml::Str(s) => path.push_str(&alloc::format!("[{:?}]", s)),
+            _ => path.push_str(&alloc::format!("[{}]", self)),
+        }
+    }
+
+    /// Compare two trees with the aliases resolved from `anchors`.
+    ///
+    /// The aliases are compared by their referenced nodes rather than names,
+    /// and an alias expanded inside itself (cyclic data) is assumed equal.
+    /// Undefined aliases are only equal to the alias with the same name.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parse_cyclic};
+    ///
+    /// let (a, mut anchors_a) = parse_cyclic("- &x {a: b}\n- *x\n").unwrap();
+    /// let (b, mut anchors_b) = parse_cyclic("- {a: b}\n- &y {a: b}\n").unwrap();
+    /// assert_ne!(a[0], b[0]);
+    /// let mut anchors = anchors_a.remove(0);
+    /// anchors.append(&mut anchors_b.remove(0));
+    /// assert!(a[0].structural_eq(&b[0], &anchors));
+    /// assert!(!a[0].structural_eq(&node!([1, 2]), &anchors));
+    /// ```
+    pub fn structural_eq(&self, rhs: &Self, anchors: &Anchors<R>) -> bool {
+        self.structural_eq_inner(rhs, anchors, &mut BTreeSet::new(), &mut Vec::new())
+    }
+
+    /// The pairs of the node data which are known to be equal are skipped,
+    /// so the shared nodes are compared once.
+    fn structural_eq_inner<'a>(
+        &'a self,
+        rhs: &'a Self,
+        anchors: &'a Anchors<R>,
+        equal: &mut BTreeSet<(*const Yaml<R>, *const Yaml<R>)>,
+        visited: &mut Vec<(&'a str, &'a str)>,
+    ) -> bool {
+        let pair = (self.yaml() as *const _, rhs.yaml() as *const _);
+        if pair.0 == pair.1 || equal.contains(&pair) {
+            return true;
+        }
+        let (lhs, rhs) = match (self.yaml(), rhs.yaml()) {
+            (Yaml::Alias(a1), Yaml::Alias(a2)) => {
+                if visited.contains(&(a1, a2)) {
+                    return true;
+                }
+                match (anchors.get(a1), anchors.get(a2)) {
+                    (Some(lhs), Some(rhs)) => {
+                        visited.push((a1, a2));
+                        let eq = lhs.structural_eq_inner(rhs, anchors, equal, visited);
+                        visited.pop();
+                        return eq;
+                    }
+                    _ => return a1 == a2,
+                }
+            }
+            (Yaml::Alias(a), _) => {
+                return match anchors.get(a) {
+                    Some(lhs) => lhs.structural_eq_inner(rhs, anchors, equal, visited),
+                    None => false,
+                }
+            }
+            (_, Yaml::Alias(a)) => {
+                return match anchors.get(a) {
+                    Some(rhs) => self.structural_eq_inner(rhs, anchors, equal, visited),
+                    None => false,
+                }
+            }
+            (lhs, rhs) => (lhs, rhs),
+        };
+        let eq = match (lhs, rhs) {
+            (Yaml::Seq(s1), Yaml::Seq(s2)) => {
+                s1.len() == s2.len()
+                    && s1
+                        .iter()
+                        .zip(s2)
+                        .all(|(a, b)| a.structural_eq_inner(b, anchors, equal, visited))
+            }
+            (Yaml::Map(m1), Yaml::Map(m2)) => {
+                m1.len() == m2.len()
+                    && m1.iter().zip(m2).all(|((k1, v1), (k2, v2))| {
+                        k1.structural_eq_inner(k2, anchors, equal, visited)
+                            && v1.structural_eq_inner(v2, anchors, equal, visited)
+                    })
+            }
+            (lhs, rhs) => return lhs == rhs,
+        };
+        if eq {
+            equal.insert(pair);
+        }
+        eq
+    }
+
+    /// Hash the content of the tree with the aliases resolved from `anchors`.
+    ///
+    /// The result is deterministic across runs and platforms if the hasher
+    /// is, and it only depends on the content: the anchor names, the order of
+    /// the map items, the tags, the positions and the number formats such as
+    /// `0x10` and `16` are ignored. So two trees have the same hash if they
+    /// are [`structural_eq`](Self::structural_eq), but not vice versa.
+    ///
+    /// An alias expanded inside itself (cyclic data) is hashed as a marker,
+    /// and undefined aliases are hashed by their names.
+    ///
+    /// The tree is hashed by its [`digest`](Self::digest). The shared nodes
+    /// and the anchors are hashed once (the cyclic ones are hashed once per
+    /// alias path), so the aliases of the untrusted documents cannot slow it
+    /// down.
+    ///
+    /// ```
+    /// use yaml_peg::{parse_cyclic, repr::RcRepr};
+    ///
+    /// let (a, anchors_a) = parse_cyclic::<RcRepr>("- &x {a: 0x10, b: c}\n- *x\n").unwrap();
+    /// let (b, anchors_b) = parse_cyclic::<RcRepr>("- {b: c, a: 16}\n- &y {b: c, a: 16}\n").unwrap();
+    /// assert_eq!(a[0].digest(&anchors_a[0]), b[0].digest(&anchors_b[0]));
+    /// ```
+    pub fn content_hash<H: Hasher>(&self, anchors: &Anchors<R>, state: &mut H) {
+        state.write(&self.digest(anchors).to_le_bytes());
+    }
+
+    /// Return the content digest of the tree in the 64-bit FNV-1a hash,
+    /// which is stable between versions, see
+    /// [`content_hash`](Self::content_hash).
+    pub fn digest(&self, anchors: &Anchors<R>) -> u64 {
+        self.digest_inner(anchors, &mut BTreeMap::new(), &mut Vec::new())
+            .0
+    }
+
+    /// Return the digest and whether it contains the cyclic marker.
+    ///
+    /// The digests are memoized by the node data. The ones without the
+    /// marker are the same in any context, and the others are only reused in
+    /// the same alias path.
+    fn digest_inner<'a>(
+        &'a self,
+        anchors: &'a Anchors<R>,
+        memo: &mut Digests<'a, R>,
+        visited: &mut Vec<&'a str>,
+    ) -> (u64, bool) {
+        fn write_str<H: Hasher>(state: &mut H, s: &str) {
+            state.write(&(s.len() as u64).to_le_bytes());
+            state.write(s.as_bytes());
+        }
+        let ptr = self.yaml() as *const _;
+        for (path, digest) in memo.get(&ptr).into_iter().flatten() {
+            match path {
+                None => return (*digest, false),
+                Some(path) if path == visited => return (*digest, true),
+                Some(_) => {}
+            }
+        }
+        let mut state = Fnv::default();
+        let mut cyclic = false;
+        match self.yaml() {
+            Yaml::Null => state.write_u8(0),
+            Yaml::Bool(b) => state.write(&[1, *b as u8]),
+            Yaml::Int(n) => {
+                state.write_u8(2);
+                match to_i128(n) {
+                    Ok(n) => state.write(&n.to_le_bytes()),
+                    Err(_) => write_str(&mut state, n),
+                }
+            }
+            Yaml::Float(n) => {
+                state.write_u8(3);
+                match to_f64(n) {
+                    Ok(n) => {
+                        // Normalize the NaN payloads and the negative zero
+                        let n = if n.is_nan() {
+                            f64::NAN
+                        } else if n == 0. {
+                            0.
+                        } else {
+                            n
+                        };
+                        state.write(&n.to_bits().to_le_bytes());
+                    }
+                    Err(_) => write_str(&mut state, n),
+                }
+            }
+            Yaml::Str(s) => {
+                state.write_u8(4);
+                write_str(&mut state, s);
+            }
+            Yaml::Seq(v) => {
+                state.write_u8(5);
+                state.write(&(v.len() as u64).to_le_bytes());
+                for node in v {
+                    let (digest, c) = node.digest_inner(anchors, memo, visited);
+                    cyclic |= c;
+                    state.write(&digest.to_le_bytes());
+                }
+            }
+            Yaml::Map(m) => {
+                state.write_u8(6);
+                state.write(&(m.len() as u64).to_le_bytes());
+                // Hash the items separately to ignore the order
+                let mut items = m
+                    .iter()
+                    .map(|(k, v)| {
+                        let mut state = Fnv::default();
+                        for node in [k, v] {
+                            let (digest, c) = node.digest_inner(anchors, memo, visited);
+                            cyclic |= c;
+                            state.write(&digest.to_le_bytes());
+                        }
+                        state.finish()
+                    })
+                    .collect::<Vec<_>>();
+                items.sort_unstable();
+                for item in items {
+                    state.write(&item.to_le_bytes());
+                }
+            }
+            Yaml::Alias(a) => {
+                if visited.contains(&a.as_str()) {
+                    state.write_u8(7);
+                    cyclic = true;
+                } else if let Some(node) = anchors.get(a) {
+                    visited.push(a);
+                    let r = node.digest_inner(anchors, memo, visited);
+                    visited.pop();
+                    return r;
+                } else {
+                    state.write_u8(8);
+                    write_str(&mut state, a);
+                }
+            }
+        }
+        let digest = state.finish();
+        let path = if cyclic { Some(visited.clone()) } else { None };
+        memo.entry(ptr).or_default().push((path, digest));
+        (digest, cyclic)
+    }
+
+    /// Find the repeated subtrees that can be replaced by the anchors and
+    /// aliases.
+    ///
+    /// The subtrees are identical if they have the same tags, scalar
+    /// notations and map orders, and the size is the number of the nodes
+    /// in the subtree, includes the map keys. The subtrees smaller than
+    /// `min_size` are ignored.
+    ///
+    /// Each item is the path of the first occurrence, where the anchor is
+    /// defined, and the paths of the others, which are replaced by the
+    /// aliases. The occurrences inside the other aliased subtrees are not
+    /// counted. The paths are in the form of [`Node::path_of`], and the
+    /// items are in the document order.
+    ///
+    /// ```
+    /// use yaml_peg::NodeRc;
+    ///
+    /// let doc = "\
+    /// dev: {image: app, ports: [80, 443]}
+    /// prod: {image: app, ports: [80, 443]}
+    /// test: {image: test, ports: [80, 443]}
+    /// ";
+    /// let root: NodeRc = doc.parse().unwrap();
+    /// let v = root.suggest_anchors(3);
+    /// assert_eq!(v.len(), 2);
+    /// assert_eq!(v[0], ("dev".to_string(), vec!["prod".to_string()]));
+    /// assert_eq!(v[1], ("dev.ports".to_string(), vec!["test.ports".to_string()]));
+    /// ```
+    pub fn suggest_anchors(&self, min_size: usize) -> Vec<(String, Vec<String>)> {
+        struct Sub<'a, R: Repr> {
+            path: String,
+            node: &'a Node<R>,
+            end: usize,
+        }
+        /// Collect the subtrees in the pre-order, `end` is the exclusive end
+        /// index of the descendants.
+        fn walk<'a, R: Repr>(node: &'a Node<R>, path: String, subs: &mut Vec<Sub<'a, R>>) {
+            let i = subs.len();
+            subs.push(Sub { path, node, end: 0 });
+            let path = &subs[i].path.clone();
+            match node.yaml() {
+                Yaml::Seq(v) => {
+                    for (j, n) in v.iter().enumerate() {
+                        walk(n, alloc::format!("{}[{}]", path, j), subs);
+                    }
+                }
+                Yaml::Map(m) => {
+                    for (k, v) in m.iter() {
+                        let mut path = path.clone();
+                        k.push_path(&mut path);
+                        walk(k, path.clone(), subs);
+                        walk(v, path, subs);
+                    }
+                }
+                _ => {}
+            }
+            subs[i].end = subs.len();
+        }
+        let mut subs = Vec::new();
+        walk(self, String::new(), &mut subs);
+        // Group by the order first, since the order ignores the tags
+        let mut groups = BTreeMap::<&Self, Vec<Vec<usize>>>::new();
+        for (i, sub) in subs.iter().enumerate() {
+            if sub.end - i < min_size.max(1) {
+                continue;
+            }
+            let v = groups.entry(sub.node).or_default();
+            match v
+                .iter_mut()
+                .find(|g| dumper::same(subs[g[0]].node, sub.node))
+            {
+                Some(g) => g.push(i),
+                None => v.push(alloc::vec![i]),
+            }
+        }
+        let mut groups = groups
+            .into_values()
+            .flatten()
+            .filter(|g| g.len() > 1)
+            .collect::<Vec<_>>();
+        // The larger subtrees are aliased first
+        groups.sort_by_key(|g| (core::cmp::Reverse(subs[g[0]].end - g[0]), g[0]));
+        let mut aliased = Vec::new();
+        let mut found = Vec::new();
+        for g in groups {
+            let g = g
+                .into_iter()
+                .filter(|i| !aliased.iter().any(|(s, e)| s <= i && i < e))
+                .collect::<Vec<_>>();
+            if g.len() > 1 {
+                aliased.extend(g[1..].iter().map(|i| (*i, subs[*i].end)));
+                found.push(g);
+            }
+        }
+        found.sort_unstable_by_key(|g| g[0]);
+        found
+            .into_iter()
+            .map(|g| {
+                let others = g[1..].iter().map(|i| subs[*i].path.clone()).collect();
+                (subs[g[0]].path.clone(), others)
+            })
+            .collect()
+    }
+
+    /// Return a new sequence sorted by the comparator, the sort is stable.
+    ///
+    /// The position, tag and metadata of the sequence are kept.
+    /// Return `Err` with node position if the node is not a sequence.
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// let n = node!([3, 1, 2]);
+    /// let n = n.sorted_by(|a, b| b.as_int().unwrap().cmp(&a.as_int().unwrap()));
+    /// assert_eq!(n.unwrap(), node!([3, 2, 1]));
+    /// ```
+    pub fn sorted_by<F>(&self, f: F) -> Result<Self, u64>
+    where
+        F: FnMut(&Self, &Self) -> Ordering,
+    {
+        let mut v = self.as_seq()?;
+        v.sort_by(f);
+        Ok(Self::new(v, self.pos, &self.tag).with_meta(self.meta.clone()))
+    }
+
+    /// Return a new sequence sorted by the first matched node of the query
+    /// in each item, the sort is stable.
+    ///
+    /// The matched nodes are compared by their total ordering (see [`Yaml`]),
+    /// and the items without the matched node are placed first. Return `Err`
+    /// with node position if the node is not a sequence.
+    ///
+    /// ```
+    /// use yaml_peg::{node, query::Query};
+    ///
+    /// let n = node!([
+    ///     node!({"name" => "b", "port" => 0x10}),
+    ///     node!({"name" => "a", "port" => 8}),
+    ///     node!({"name" => "c"}),
+    /// ]);
+    /// let q = "port".parse::<Query>().unwrap();
+    /// let names = n
+    ///     .sorted_by_query(&q)
+    ///     .unwrap()
+    ///     .as_seq()
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .map(|n| n["name"].as_str().unwrap().to_string())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(names, ["c", "a", "b"]);
+    /// ```
+    pub fn sorted_by_query(&self, query: &crate::query::Query) -> Result<Self, u64> {
+        let mut v = self
+            .as_seq()?
+            .into_iter()
+            .map(|n| (query.eval(&n).first().map(|m| m.node.clone()), n))
+            .collect::<Vec<_>>();
+        v.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        });
+        let v = v.into_iter().map(|(_, n)| n).collect::<Seq<R>>();
+        Ok(Self::new(v, self.pos, &self.tag).with_meta(self.meta.clone()))
+    }
+
+    /// Return a new sequence without the duplicated items, the first one is
+    /// kept.
+    ///
+    /// The items are compared by their [`digest`](Self::digest) and
+    /// [`structural_eq`](Self::structural_eq) with the aliases resolved from
+    /// `anchors`. Return `Err` with node position if the node is not a
+    /// sequence.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parse_cyclic, repr::RcRepr};
+    ///
+    /// let (root, anchors) = parse_cyclic::<RcRepr>("[&a {b: c}, 1, *a, {b: c}, 1]").unwrap();
+    /// let n = root[0].dedup(&anchors[0]).unwrap();
+    /// assert_eq!(n, node!([node!({"b" => "c"}), 1]));
+    /// ```
+    pub fn dedup(&self, anchors: &Anchors<R>) -> Result<Self, u64> {
+        let items = self.as_seq()?;
+        let mut memo = BTreeMap::new();
+        let mut seen = BTreeMap::<u64, Vec<&Self>>::new();
+        let mut v = Seq::new();
+        for n in &items {
+            let (digest, _) = n.digest_inner(anchors, &mut memo, &mut Vec::new());
+            let same = seen.entry(digest).or_default();
+            if !same.iter().any(|m| m.structural_eq(n, anchors)) {
+                same.push(n);
+                v.push(n.clone());
+            }
+        }
+        Ok(Self::new(v, self.pos, &self.tag).with_meta(self.meta.clone()))
+    }
+
+    /// Return a new tree with the values of the sequence items and map items
+    /// transformed by `f`, the keys are kept.
+    ///
+    /// The function takes the key path of the value (see [`Node::path_of`])
+    /// and the value itself. Only the direct children are transformed, see
+    /// [`Node::map_values_deep`] for the recursive version. Return a clone if
+    /// the node is not a collection.
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// let n = node!({"a" => 1, "b" => node!([2])});
+    /// let n = n.map_values(|path, v| if path == "a" { node!(10) } else { v.clone() });
+    /// assert_eq!(n, node!({"a" => 10, "b" => node!([2])}));
+    /// ```
+    pub fn map_values<F>(&self, mut f: F) -> Self
+    where
+        F: FnMut(&str, &Self) -> Self,
+    {
+        self.map_values_inner(false, &mut String::new(), &mut f)
+    }
+
+    /// Same as [`Node::map_values`], but the transformation is applied to all
+    /// the scalar values in the tree recursively, the collections are rebuilt
+    /// with their positions, tags and metadata.
+    ///
+    /// ```
+    /// use yaml_peg::node;
+    ///
+    /// let n = node!({
+    ///     "name" => "app",
+    ///     "secrets" => node!({"token" => "abc", "keys" => node!(["k1", "k2"])}),
+    /// });
+    /// let n = n.map_values_deep(|path, v| {
+    ///     if path.starts_with("secrets.") {
+    ///         node!("***")
+    ///     } else {
+    ///         v.clone()
+    ///     }
+    /// });
+    /// assert_eq!(
+    ///     n,
+    ///     node!({
+    ///         "name" => "app",
+    ///         "secrets" => node!({"token" => "***", "keys" => node!(["***", "***"])}),
+    ///     })
+    /// );
+    /// ```
+    pub fn map_values_deep<F>(&self, mut f: F) -> Self
+    where
+        F: FnMut(&str, &Self) -> Self,
+    {
+        self.map_values_inner(true, &mut String::new(), &mut f)
+    }
+
+    fn map_values_inner<F>(&self, deep: bool, path: &mut String, f: &mut F) -> Self
+    where
+        F: FnMut(&str, &Self) -> Self,
+    {
+        let len = path.len();
+        let mut child = |n: &Self, path: &mut String| {
+            let n = if deep {
+                n.map_values_inner(deep, path, f)
+            } else {
+                f(path, n)
+            };
+            path.truncate(len);
+            n
+        };
+        let yaml: Yaml<R> = match self.yaml() {
+            Yaml::Seq(v) => v
+                .iter()
+                .enumerate()
+                .map(|(i, n)| {
+                    path.push_str(&alloc::format!("[{}]", i));
+                    child(n, path)
+                })
+                .collect::<Seq<R>>()
+                .into(),
+            Yaml::Map(m) => m
+                .iter()
+                .map(|(k, v)| {
+                    k.push_path(path);
+                    (k.clone(), child(v, path))
+                })
+                .collect::<Map<R>>()
+                .into(),
+            _ if deep => return f(path, self),
+            _ => return self.clone(),
+        };
+        Self::new(yaml, self.pos, &self.tag).with_meta(self.meta.clone())
+    }
+
+    /// Return a new tree with the keys of the map transformed by `f`, the
+    /// values are kept.
+    ///
+    /// Only the keys of this map are transformed, see [`Node::map_keys_deep`]
+    /// for the recursive version. If the transformed keys are duplicated, the
+    /// latter item wins. Return a clone if the node is not a map.
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc};
+    ///
+    /// let n = node!({"A" => node!({"B" => 1})});
+    /// let lower = |k: &NodeRc| node!(k.as_str().unwrap().to_lowercase());
+    /// assert_eq!(n.map_keys(lower), node!({"a" => node!({"B" => 1})}));
+    /// assert_eq!(n.map_keys_deep(lower), node!({"a" => node!({"b" => 1})}));
+    /// ```
+    pub fn map_keys<F>(&self, mut f: F) -> Self
+    where
+        F: FnMut(&Self) -> Self,
+    {
+        self.map_keys_inner(false, &mut f)
+    }
+
+    /// Same as [`Node::map_keys`], but the transformation is applied to all
+    /// the maps in the tree recursively, includes the maps in the sequences.
+    ///
+    /// The keys themselves are not traversed.
+    pub fn map_keys_deep<F>(&self, mut f: F) -> Self
+    where
+        F: FnMut(&Self) -> Self,
+    {
+        self.map_keys_inner(true, &mut f)
+    }
+
+    fn map_keys_inner<F>(&self, deep: bool, f: &mut F) -> Self
+    where
+        F: FnMut(&Self) -> Self,
+    {
+        let yaml: Yaml<R> = match self.yaml() {
+            Yaml::Seq(v) if deep => v
+                .iter()
+                .map(|n| n.map_keys_inner(deep, f))
+                .collect::<Seq<R>>()
+                .into(),
+            Yaml::Map(m) => m
+                .iter()
+                .map(|(k, v)| {
+                    let v = if deep {
+                        v.map_keys_inner(deep, f)
+                    } else {
+                        v.clone()
+                    };
+                    (f(k), v)
+                })
+                .collect::<Map<R>>()
+                .into(),
+            _ => return self.clone(),
+        };
+        Self::new(yaml, self.pos, &self.tag).with_meta(self.meta.clone())
+    }
+
+    /// Deep copy the node tree into another representation.
+    ///
+    /// The position and tag of each node are kept.
+    pub fn to_repr<R2: Repr>(&self) -> Node<R2> {
+        let yaml = match self.yaml() {
+            Yaml::Null => Yaml::Null,
+            Yaml::Bool(b) => Yaml::Bool(*b),
+            Yaml::Int(s) => Yaml::Int(s.clone()),
+            Yaml::Float(s) => Yaml::Float(s.clone()),
+            Yaml::Str(s) => Yaml::Str(s.clone()),
+            Yaml::Seq(v) => v.iter().map(Self::to_repr).collect(),
+            Yaml::Map(m) => m.iter().map(|(k, v)| (k.to_repr(), v.to_repr())).collect(),
+            Yaml::Alias(a) => Yaml::Alias(a.clone()),
+        };
+        Node::new(yaml, self.pos, &self.tag)
+    }
+}
+
+impl NodeRc {
+    /// Convert the single-thread tree into the multiple-thread tree.
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeArc};
+    ///
+    /// let n = node!({"a" => node!([1, 2])});
+    /// let n: NodeArc = n.into_arc();
+    /// assert_eq!(node!(arc{"a" => node!(arc[1, 2])}), n);
+    /// ```
+    pub fn into_arc(self) -> NodeArc {
+        self.to_repr()
+    }
+}
+
+impl NodeArc {
+    /// Convert the multiple-thread tree into the single-thread tree.
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc};
+    ///
+    /// let n = node!(arc{"a" => node!(arc[1, 2])});
+    /// let n: NodeRc = n.into_rc();
+    /// assert_eq!(node!({"a" => node!([1, 2])}), n);
+    /// ```
+    pub fn into_rc(self) -> NodeRc {
+        self.to_repr()
+    }
 }
 
 impl<R: Repr> Debug for Node<R> {
@@ -450,16 +1778,87 @@ impl<R: Repr> Debug for Node<R> {
     }
 }
 
+/// Dump the node with the default options, see [`dump`].
+///
+/// ```
+/// use yaml_peg::node;
+///
+/// let n = node!({"a" => node!([1, 2])});
+/// assert_eq!(n.to_string(), "a:\n  - 1\n  - 2".replace('\n', yaml_peg::dumper::NL));
+/// ```
+impl<R: Repr> Display for Node<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let anchors = Anchors::new();
+        f.write_str(dumper::Dumper::new(self, &anchors).dump().trim_start())
+    }
+}
+
+/// Parse a single document, see [`parse`].
+///
+/// The empty document will be parsed as null,
+/// and the multiple documents are not allowed.
+///
+/// ```
+/// use yaml_peg::{node, NodeRc};
+///
+/// let n = "a: 1".parse::<NodeRc>().unwrap();
+/// assert_eq!(n, node!({"a" => 1}));
+/// assert!("a\n---\nb".parse::<NodeRc>().is_err());
+/// ```
+impl<R: Repr> FromStr for Node<R> {
+    type Err = parser::PError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut docs = parse(s)?;
+        if docs.len() > 1 {
+            let pos = docs[1].pos();
+            return Err(parser::PError::terminate(
+                parser::ErrorKind::MultipleDocuments,
+                s.as_bytes(),
+                pos,
+            ));
+        }
+        Ok(docs.pop().unwrap_or_else(|| Self::from(())))
+    }
+}
+
 impl<R: Repr> Clone for Node<R> {
     fn clone(&self) -> Self {
         Self {
             tag: self.tag.clone(),
             yaml: self.clone_yaml(),
+            meta: self.meta.clone(),
             ..*self
         }
     }
 }
 
+/// The memoized digests of the node data, with the alias paths of the
+/// cyclic ones.
+type Digests<'a, R> = BTreeMap<*const Yaml<R>, Vec<(Option<Vec<&'a str>>, u64)>>;
+
+/// The 64-bit FNV-1a hasher for [`Node::digest`].
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
 impl<R: Repr> Hash for Node<R> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.yaml.hash(state)
@@ -474,6 +1873,19 @@ impl<R: Repr> PartialEq for Node<R> {
 
 impl<R: Repr> Eq for Node<R> {}
 
+/// The total ordering of the YAML data, see [`Yaml`].
+impl<R: Repr> Ord for Node<R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.yaml.cmp(&other.yaml)
+    }
+}
+
+impl<R: Repr> PartialOrd for Node<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 /// Indicator of the node use to index the sequence position.
 pub struct Ind(pub usize);
 
@@ -0,0 +1,307 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// The newtype name of [`BigInt`] in serialization.
+#[cfg(feature = "serde")]
+pub(crate) const BIG_INT_TOKEN: &str = "$yaml_peg::BigInt";
+/// The newtype name of [`Decimal`] in serialization.
+#[cfg(feature = "serde")]
+pub(crate) const DECIMAL_TOKEN: &str = "$yaml_peg::Decimal";
+
+/// The error of parsing [`BigInt`] and [`Decimal`] from string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NumberError(String);
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid number `{}`", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NumberError {}
+
+/// An integer without precision limit, see [`Node::as_big_int`](crate::Node::as_big_int).
+///
+/// The value is stored as the decimal digits,
+/// the radix prefixes `0x` and `0o` are converted.
+///
+/// ```
+/// use yaml_peg::{BigInt, NodeRc};
+///
+/// let doc = "a: 0x100000000000000000000000000000000\nb: -0012";
+/// let root: NodeRc = doc.parse().unwrap();
+/// assert!(root["a"].as_int().is_err());
+/// let a = root["a"].as_big_int().unwrap();
+/// assert_eq!(a.as_str(), "340282366920938463463374607431768211456");
+/// assert_eq!(a.to_i128(), None);
+/// assert_eq!(root["b"].as_big_int().unwrap(), "-12".parse::<BigInt>().unwrap());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt(String);
+
+impl BigInt {
+    /// The decimal digits, with `-` sign if negative.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Return true if the integer is negative.
+    pub fn is_negative(&self) -> bool {
+        self.0.starts_with('-')
+    }
+
+    /// Convert to primitive integer if it is in range.
+    pub fn to_i128(&self) -> Option<i128> {
+        self.0.parse().ok()
+    }
+
+    /// Convert to primitive unsigned integer if it is in range.
+    pub fn to_u128(&self) -> Option<u128> {
+        self.0.parse().ok()
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = NumberError;
+
+    /// Parse the YAML integer, includes the radix prefixes and the
+    /// underscores.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || NumberError(s.to_string());
+        let t = s.replace('_', "");
+        let (neg, t) = match t.as_bytes().first() {
+            Some(b'-') => (true, &t[1..]),
+            Some(b'+') => (false, &t[1..]),
+            _ => (false, &t[..]),
+        };
+        let (radix, t) = if let Some(t) = t.strip_prefix("0x") {
+            (16, t)
+        } else if let Some(t) = t.strip_prefix("0o") {
+            (8, t)
+        } else {
+            (10, t)
+        };
+        if t.is_empty() {
+            return Err(err());
+        }
+        // Little-endian decimal digits
+        let mut digits = Vec::new();
+        for c in t.chars() {
+            let mut carry = c.to_digit(radix).ok_or_else(err)?;
+            for d in digits.iter_mut() {
+                let n = *d * radix + carry;
+                *d = n % 10;
+                carry = n / 10;
+            }
+            while carry > 0 {
+                digits.push(carry % 10);
+                carry /= 10;
+            }
+        }
+        let mut v = String::with_capacity(digits.len() + 1);
+        if neg && !digits.is_empty() {
+            v.push('-');
+        }
+        if digits.is_empty() {
+            v.push('0');
+        }
+        v.extend(digits.iter().rev().map(|d| char::from(b'0' + *d as u8)));
+        Ok(Self(v))
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    /// Compare by the values, the digits have no leading zeros.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let a = self.0.trim_start_matches('-');
+        let b = other.0.trim_start_matches('-');
+        let abs = a.len().cmp(&b.len()).then_with(|| a.cmp(b));
+        match (self.is_negative(), other.is_negative()) {
+            (false, false) => abs,
+            (true, true) => abs.reverse(),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+        }
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A decimal number without precision limit, see
+/// [`Node::as_decimal`](crate::Node::as_decimal).
+///
+/// The value is stored as its YAML text without the underscores,
+/// the infinity and NaN are not allowed.
+///
+/// ```
+/// use yaml_peg::{node, NodeRc};
+///
+/// let n: NodeRc = "3.14159265358979323846264338".parse().unwrap();
+/// let d = n.as_decimal().unwrap();
+/// assert_eq!(d.as_str(), "3.14159265358979323846264338");
+/// assert_eq!(d.to_f64(), std::f64::consts::PI);
+/// assert!(node!(f64::INFINITY).as_decimal().is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Decimal(String);
+
+impl Decimal {
+    /// The decimal text.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Convert to the nearest float.
+    pub fn to_f64(&self) -> f64 {
+        self.0.parse().unwrap()
+    }
+
+    /// Return true if the value can be represented by [`f64`] without loss.
+    #[cfg(feature = "serde")]
+    pub(crate) fn is_f64(&self) -> bool {
+        significand(&self.0) == significand(&alloc::format!("{:e}", self.to_f64()))
+    }
+}
+
+/// Return the sign, the significant digits and the exponent of the
+/// validated decimal text.
+#[cfg(feature = "serde")]
+fn significand(s: &str) -> (bool, String, i64) {
+    let (neg, s) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let (m, e) = s.split_once(['e', 'E']).unwrap_or((s, "0"));
+    let (int, frac) = m.split_once('.').unwrap_or((m, ""));
+    let digits = alloc::format!("{int}{frac}");
+    let lead = digits.len() - digits.trim_start_matches('0').len();
+    let digits = digits.trim_matches('0');
+    if digits.is_empty() {
+        return (false, String::new(), 0);
+    }
+    let e = e.parse::<i64>().unwrap_or_default() + int.len() as i64 - lead as i64;
+    (neg, digits.to_string(), e)
+}
+
+impl FromStr for Decimal {
+    type Err = NumberError;
+
+    /// Parse the YAML float or integer in decimal.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let t = s.replace('_', "");
+        let b = t.trim_start_matches(['-', '+']);
+        let (m, e) = b.split_once(['e', 'E']).unwrap_or((b, "0"));
+        let valid = t.len() - b.len() <= 1
+            && m.chars().any(|c| c.is_ascii_digit())
+            && m.chars().all(|c| c.is_ascii_digit() || c == '.')
+            && m.matches('.').count() <= 1
+            && e.parse::<i64>().is_ok();
+        if valid {
+            Ok(Self(t))
+        } else {
+            Err(NumberError(s.to_string()))
+        }
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use serde::{
+        de::{Error, Unexpected, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    macro_rules! impl_serde {
+        ($($ty:ident($token:ident, $exp:literal))+) => {$(
+            impl Serialize for $ty {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: Serializer,
+                {
+                    serializer.serialize_newtype_struct($token, self.as_str())
+                }
+            }
+
+            impl<'a> Deserialize<'a> for $ty {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: Deserializer<'a>,
+                {
+                    struct V;
+
+                    impl<'a> Visitor<'a> for V {
+                        type Value = $ty;
+
+                        fn expecting(&self, fmt: &mut Formatter) -> core::fmt::Result {
+                            fmt.write_str($exp)
+                        }
+
+                        fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+                            self.visit_str(&v.to_string())
+                        }
+
+                        fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+                            self.visit_str(&v.to_string())
+                        }
+
+                        fn visit_i128<E: Error>(self, v: i128) -> Result<Self::Value, E> {
+                            self.visit_str(&v.to_string())
+                        }
+
+                        fn visit_u128<E: Error>(self, v: u128) -> Result<Self::Value, E> {
+                            self.visit_str(&v.to_string())
+                        }
+
+                        fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+                            self.visit_str(&v.to_string())
+                        }
+
+                        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+                            v.parse().map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+                        }
+
+                        fn visit_newtype_struct<D>(self, d: D) -> Result<Self::Value, D::Error>
+                        where
+                            D: Deserializer<'a>,
+                        {
+                            d.deserialize_any(self)
+                        }
+                    }
+
+                    deserializer.deserialize_newtype_struct($token, V)
+                }
+            }
+        )+};
+    }
+
+    impl_serde! {
+        BigInt(BIG_INT_TOKEN, "integer")
+        Decimal(DECIMAL_TOKEN, "decimal number")
+    }
+}
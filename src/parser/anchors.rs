@@ -0,0 +1,324 @@
+use crate::{repr::Repr, *};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    fmt::{Debug, Display, Formatter},
+    ops::{Deref, DerefMut},
+};
+
+/// The anchor recorder in the [`Loader`](super::Loader), maps anchor name to
+/// its node.
+///
+/// This type can be used as a [`BTreeMap`] by dereference.
+///
+/// ```
+/// use yaml_peg::{node, parse_cyclic};
+///
+/// let (root, mut anchors) = parse_cyclic("a: &x [1, *y]\nb: &y 2\nc: *x").unwrap();
+/// let anchors = anchors.remove(0);
+/// let names = anchors.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+/// assert_eq!(names, ["x", "y"]);
+/// assert_eq!(
+///     anchors.resolve_all(&root[0]).unwrap(),
+///     node!({"a" => node!([1, 2]), "b" => 2, "c" => node!([1, 2])})
+/// );
+/// ```
+pub struct Anchors<R: Repr>(BTreeMap<String, Node<R>>);
+
+impl<R: Repr> Anchors<R> {
+    /// Create an empty anchor set.
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Iterate over the anchor names and their nodes.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Node<R>)> {
+        self.0.iter()
+    }
+
+    /// Merge another anchor set, the anchors of `other` will replace the
+    /// existing anchors with the same name.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parse_cyclic};
+    ///
+    /// let (_, mut docs) = parse_cyclic("- &a 1\n- &b 2\n---\n- &a 3").unwrap();
+    /// let mut anchors = docs.remove(0);
+    /// anchors.merge(docs.remove(0));
+    /// assert_eq!(anchors["a"], node!(3));
+    /// assert_eq!(anchors["b"], node!(2));
+    /// ```
+    pub fn merge(&mut self, other: Self) {
+        self.0.extend(other.0);
+    }
+
+    /// Expand all aliases of the tree with this anchor set.
+    ///
+    /// The aliases in the anchor nodes are also expanded.
+    /// Return `Err` with the alias position if the anchor is undefined or the
+    /// alias is cyclic.
+    pub fn resolve_all(&self, node: &Node<R>) -> Result<Node<R>, u64> {
+        self.resolve_inner(node, &mut Vec::new())
+    }
+
+    fn resolve_inner<'a>(
+        &'a self,
+        node: &'a Node<R>,
+        visited: &mut Vec<&'a str>,
+    ) -> Result<Node<R>, u64> {
+        let yaml: Yaml<R> = match node.yaml() {
+            Yaml::Alias(a) => {
+                if visited.contains(&a.as_str()) {
+                    return Err(node.pos());
+                }
+                let anchor = self.0.get(a).ok_or_else(|| node.pos())?;
+                visited.push(a);
+                let ret = self.resolve_inner(anchor, visited);
+                visited.pop();
+                return ret;
+            }
+            Yaml::Seq(v) => v
+                .iter()
+                .map(|n| self.resolve_inner(n, visited))
+                .collect::<Result<Seq<R>, _>>()?
+                .into(),
+            Yaml::Map(m) => m
+                .iter()
+                .map(|(k, v)| {
+                    Ok((
+                        self.resolve_inner(k, visited)?,
+                        self.resolve_inner(v, visited)?,
+                    ))
+                })
+                .collect::<Result<Map<R>, u64>>()?
+                .into(),
+            _ => return Ok(node.clone()),
+        };
+        Ok(Node::new(yaml, node.pos(), node.tag()).with_meta(node.meta().clone()))
+    }
+
+    /// Expand all aliases of the tree like [`Anchors::resolve_all`], but the
+    /// number of the copied nodes is limited by `max_nodes`.
+    ///
+    /// The copied nodes are counted for every alias, includes the map keys,
+    /// so the tree is safe to be exported to the formats without references
+    /// (e.g., JSON), even if the anchors are nested for many times.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parse_cyclic, parser::ExpandError, Ind};
+    ///
+    /// let doc = "\
+    /// a: &a [x, x]
+    /// b: &b [*a, *a]
+    /// c: &c [*b, *b]
+    /// ";
+    /// let (root, mut anchors) = parse_cyclic(doc).unwrap();
+    /// let anchors = anchors.remove(0);
+    /// let n = anchors.expand(&root[0], 20).unwrap();
+    /// assert_eq!(n["c"][Ind(0)][Ind(1)], node!(["x", "x"]));
+    /// let e = anchors.expand(&root[0], 19).unwrap_err();
+    /// assert_eq!(e, ExpandError::TooLarge { name: "b".to_string(), pos: 39 });
+    /// ```
+    pub fn expand(&self, node: &Node<R>, max_nodes: usize) -> Result<Node<R>, ExpandError> {
+        let mut budget = max_nodes;
+        self.expand_inner(node, &mut Vec::new(), &mut budget)
+    }
+
+    fn expand_inner<'a>(
+        &'a self,
+        node: &'a Node<R>,
+        visited: &mut Vec<(&'a str, u64)>,
+        budget: &mut usize,
+    ) -> Result<Node<R>, ExpandError> {
+        if let Some((name, pos)) = visited.first() {
+            if *budget == 0 {
+                return Err(ExpandError::TooLarge { name: name.to_string(), pos: *pos });
+            }
+            *budget -= 1;
+        }
+        let yaml: Yaml<R> = match node.yaml() {
+            Yaml::Alias(a) => {
+                let err = |f: fn(String, u64) -> ExpandError| f(a.clone(), node.pos());
+                if visited.iter().any(|(name, _)| name == a) {
+                    return Err(err(|name, pos| ExpandError::Cyclic { name, pos }));
+                }
+                let anchor = match self.0.get(a) {
+                    Some(anchor) => anchor,
+                    None => return Err(err(|name, pos| ExpandError::Undefined { name, pos })),
+                };
+                // The alias itself is not a copied node
+                if !visited.is_empty() {
+                    *budget += 1;
+                }
+                visited.push((a, node.pos()));
+                let ret = self.expand_inner(anchor, visited, budget);
+                visited.pop();
+                return ret;
+            }
+            Yaml::Seq(v) => v
+                .iter()
+                .map(|n| self.expand_inner(n, visited, budget))
+                .collect::<Result<Seq<R>, _>>()?
+                .into(),
+            Yaml::Map(m) => m
+                .iter()
+                .map(|(k, v)| {
+                    Ok((
+                        self.expand_inner(k, visited, budget)?,
+                        self.expand_inner(v, visited, budget)?,
+                    ))
+                })
+                .collect::<Result<Map<R>, _>>()?
+                .into(),
+            _ => return Ok(node.clone()),
+        };
+        Ok(Node::new(yaml, node.pos(), node.tag()).with_meta(node.meta().clone()))
+    }
+}
+
+/// The error of [`Anchors::expand`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExpandError {
+    /// The anchor of the alias is undefined.
+    Undefined {
+        /// Anchor name.
+        name: String,
+        /// The alias position.
+        pos: u64,
+    },
+    /// The alias is expanded inside itself.
+    Cyclic {
+        /// Anchor name.
+        name: String,
+        /// The alias position.
+        pos: u64,
+    },
+    /// The copied nodes exceed the limit, the alias is the outermost one
+    /// being expanded.
+    TooLarge {
+        /// Anchor name.
+        name: String,
+        /// The alias position.
+        pos: u64,
+    },
+}
+
+impl Display for ExpandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Undefined { name, pos } => write!(f, "undefined anchor `{}` at {}", name, pos),
+            Self::Cyclic { name, pos } => write!(f, "cyclic alias `{}` at {}", name, pos),
+            Self::TooLarge { name, pos } => {
+                write!(
+                    f,
+                    "too many nodes expanded from alias `{}` at {}",
+                    name, pos
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExpandError {}
+
+impl<R: Repr> Default for Anchors<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Repr> Debug for Anchors<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<R: Repr> Clone for Anchors<R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<R: Repr> PartialEq for Anchors<R> {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 == rhs.0
+    }
+}
+
+impl<R: Repr> Eq for Anchors<R> {}
+
+impl<R: Repr> Deref for Anchors<R> {
+    type Target = BTreeMap<String, Node<R>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<R: Repr> DerefMut for Anchors<R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<R: Repr> From<BTreeMap<String, Node<R>>> for Anchors<R> {
+    fn from(m: BTreeMap<String, Node<R>>) -> Self {
+        Self(m)
+    }
+}
+
+impl<R: Repr> FromIterator<(String, Node<R>)> for Anchors<R> {
+    fn from_iter<T: IntoIterator<Item = (String, Node<R>)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<R: Repr> Extend<(String, Node<R>)> for Anchors<R> {
+    fn extend<T: IntoIterator<Item = (String, Node<R>)>>(&mut self, iter: T) {
+        self.0.extend(iter)
+    }
+}
+
+impl<R: Repr> IntoIterator for Anchors<R> {
+    type Item = (String, Node<R>);
+    type IntoIter = alloc::collections::btree_map::IntoIter<String, Node<R>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, R: Repr> IntoIterator for &'a Anchors<R> {
+    type Item = (&'a String, &'a Node<R>);
+    type IntoIter = alloc::collections::btree_map::Iter<'a, String, Node<R>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Serialized as a map of the anchor names and their nodes.
+#[cfg(feature = "serde")]
+impl<R: Repr> ::serde::Serialize for Anchors<R> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, R: Repr> ::serde::Deserialize<'a> for Anchors<R> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'a>,
+    {
+        BTreeMap::deserialize(deserializer).map(Self)
+    }
+}
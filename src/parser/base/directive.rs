@@ -1,18 +1,50 @@
 use super::*;
 
+/// The policy of the `%YAML` directive, see [`Loader::version_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VersionPolicy {
+    /// Accept version `1.1` and `1.2`, both are resolved with 1.2 schema.
+    /// Other versions are invalid.
+    #[default]
+    Compatible,
+    /// Accept version `1.1` and `1.2`, version `1.1` is resolved with 1.1
+    /// schema, e.g., `yes`/`no`/`on`/`off` booleans and `0`-prefixed octal
+    /// integers.
+    Resolve,
+    /// Accept version `1.2` only.
+    Strict,
+    /// Accept any version number and continue, unsupported versions are
+    /// resolved with 1.2 schema.
+    Ignore,
+}
+
 /// The implementation of the directives.
 impl Parser<'_> {
     /// Match directives.
     pub fn directive(&mut self) -> PResult<()> {
         self.sym(b'%')?;
+        let pos = self.indicator();
         self.context(|p| {
-            if p.sym_seq(b"YAML").is_ok() {
-                p.directive_yaml()
-            } else if p.sym_seq(b"TAG").is_ok() {
-                p.directive_tag()
-            } else {
-                // Unknown - ignore
-                p.take_while(Self::not_in(b"\n\r"), TakeOpt::More(0))
+            p.take_while(Self::not_in(b" \t\n\r"), TakeOpt::More(0))?;
+            match p.text().as_str() {
+                "YAML" => p.directive_yaml(),
+                "TAG" => {
+                    let (tag_pos, eaten) = (p.pos, p.eaten);
+                    p.directive_tag().or_else(|e| {
+                        e.or(|| {
+                            // Invalid - ignore
+                            p.pos = tag_pos;
+                            p.eaten = eaten;
+                            p.warn(WarningKind::InvalidTagDirective, pos);
+                            p.take_while(Self::not_in(b"\n\r"), TakeOpt::More(0))
+                        })
+                    })
+                }
+                _ => {
+                    // Unknown (reserved) - ignore
+                    p.warn(WarningKind::UnknownDirective, pos);
+                    p.take_while(Self::not_in(b"\n\r"), TakeOpt::More(0))
+                }
             }
         })?;
         self.gap(true).map(|_| ())
@@ -20,21 +52,45 @@ impl Parser<'_> {
 
     fn directive_yaml(&mut self) -> PResult<()> {
         self.ws(TakeOpt::More(1))?;
-        if self.version_checked {
-            self.err("checked version")
-        } else if !self.context(|p| p.sym_seq(b"1.1").is_ok() || p.sym_seq(b"1.2").is_ok()) {
-            self.err("invalid version")
-        } else {
-            self.version_checked = true;
+        if self.version.is_some() {
+            return self.err(ErrorKind::DuplicatedVersion);
+        }
+        let version = self.context(|p| {
+            p.take_while(u8::is_ascii_digit, TakeOpt::More(1))?;
+            p.sym(b'.')?;
+            p.take_while(u8::is_ascii_digit, TakeOpt::More(1))?;
+            Ok(p.text())
+        })?;
+        let pos = self.indicator() - version.len() as u64;
+        let supported = match version.as_str() {
+            "1.2" => true,
+            "1.1" => {
+                if self.version_policy == VersionPolicy::Compatible {
+                    self.warn(WarningKind::Yaml11Version, pos);
+                }
+                self.version_policy != VersionPolicy::Strict
+            }
+            _ => {
+                if self.version_policy == VersionPolicy::Ignore {
+                    self.warn(WarningKind::UnsupportedVersion, pos);
+                }
+                self.version_policy == VersionPolicy::Ignore
+            }
+        };
+        if supported {
+            self.version = Some(version);
             Ok(())
+        } else {
+            self.err(ErrorKind::UnknownVersion)
         }
     }
 
     fn directive_tag(&mut self) -> PResult<()> {
         self.ws(TakeOpt::More(1))?;
+        let pos = self.indicator();
         self.sym(b'!')?;
         self.context(|p| {
-            let tag = if p.identifier().is_ok() {
+            let tag = if p.tag_word().is_ok() {
                 let tag = p.text();
                 p.sym(b'!')?;
                 tag
@@ -44,10 +100,15 @@ impl Parser<'_> {
                 "!".to_string()
             };
             p.ws(TakeOpt::More(1))?;
-            let doc = p.context(|p| {
-                p.take_while(Self::not_in(b" \n\r"), TakeOpt::More(1))?;
-                Ok(p.text())
-            })?;
+            // A local prefix or a global prefix
+            let doc = p.tag_uri(true)?;
+            let valid = doc.starts_with('!') || doc.bytes().next().is_some_and(is_tag_char);
+            if !valid || !matches!(p.food(), [] | [b' ' | b'\t' | b'\n' | b'\r', ..]) {
+                return Err(PError::Mismatch);
+            }
+            if !p.tag_declared.insert(tag.clone()) {
+                return p.err_at(ErrorKind::DuplicatedTagHandle, pos);
+            }
             p.tag.insert(tag, doc);
             Ok(())
         })
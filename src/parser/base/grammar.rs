@@ -1,5 +1,4 @@
 use super::*;
-use alloc::format;
 use core::cmp::Ordering;
 
 /// The low level grammar implementation for YAML.
@@ -8,12 +7,32 @@ use core::cmp::Ordering;
 /// [`Parser::backward`] if mismatched.
 impl Parser<'_> {
     /// Match invisible boundaries and keep the gaps. (must matched once)
+    ///
+    /// The end of document is also a boundary.
     pub fn bound(&mut self) -> PResult<()> {
+        if self.food().is_empty() {
+            return Ok(());
+        }
         self.sym_set(b":{}[] ,\n\r")?;
         self.back(1);
         self.ws(TakeOpt::More(0))
     }
 
+    /// Match the document marker `---` or `...`, which is followed by a white
+    /// space or the line end, so `...[1]` is a plain scalar.
+    pub fn doc_marker(&mut self, marker: &[u8]) -> PResult<()> {
+        self.sym_seq(marker)?;
+        if matches!(
+            self.food().first(),
+            None | Some(b' ' | b'\t' | b'\n' | b'\r')
+        ) {
+            Ok(())
+        } else {
+            self.back(marker.len());
+            Err(PError::Mismatch)
+        }
+    }
+
     /// Match complex mapping indicator (`?`).
     pub fn complex_mapping(&mut self) -> PResult<()> {
         self.sym(b'?')?;
@@ -47,8 +66,8 @@ impl Parser<'_> {
         self.take_while(Self::ascii_digit(16), TakeOpt::More(1))
     }
 
-    fn ascii_digit(i: u8) -> impl Fn(&u8) -> bool + 'static {
-        move |c| c.is_ascii_digit() || (*c > b'a' && *c < b'a' + i) || (*c > b'A' && *c < b'A' + i)
+    fn ascii_digit(i: u32) -> impl Fn(&u8) -> bool + 'static {
+        move |c| char::from(*c).is_digit(i)
     }
 
     /// Match float.
@@ -61,12 +80,16 @@ impl Parser<'_> {
             self.back(1);
         }
         self.bound()?;
-        Ok(s.trim_end_matches(|c| ".0".contains(c)).to_string())
+        Ok(s)
     }
 
     /// Match float with scientific notation.
     pub fn sci_float(&mut self) -> PResult<String> {
         self.num_prefix()?;
+        if self.food().starts_with(b".") {
+            self.sym(b'.')?;
+            self.take_while(u8::is_ascii_digit, TakeOpt::More(0))?;
+        }
         self.sym_set(b"eE")?;
         self.take_while(Self::is_in(b"+-"), TakeOpt::Range(0, 1))?;
         self.take_while(u8::is_ascii_digit, TakeOpt::More(1))?;
@@ -91,6 +114,9 @@ impl Parser<'_> {
                 p.take_while(Self::not_in(&[b'\n', b'\r', b'\\', sym]), TakeOpt::More(0))?;
                 v.push_str(&p.text());
                 p.forward();
+                if p.food().is_empty() {
+                    return p.err(ErrorKind::UnclosedQuote);
+                }
                 if p.sym_seq(ignore).is_ok() {
                     v.push(char::from(sym));
                 } else if let Ok(mut t) = p.gap(false) {
@@ -124,6 +150,67 @@ impl Parser<'_> {
         })
     }
 
+    /// Match double quoted string, the escape characters are resolved.
+    ///
+    /// The invalid escape characters will raise an error.
+    pub fn string_double_quoted(&mut self) -> PResult<String> {
+        self.sym(b'"')?;
+        let food = self.food();
+        let mut v = String::new();
+        // The escaped characters are kept when trimming the line end
+        let mut escaped = 0;
+        let mut escaped_nl = false;
+        let mut i = 0;
+        loop {
+            match food.get(i) {
+                None => return self.err(ErrorKind::UnclosedDoubleQuote),
+                Some(b'"') => {
+                    i += 1;
+                    break;
+                }
+                Some(b'\\') if matches!(food.get(i + 1), Some(b'\n' | b'\r')) => {
+                    // Escaped line break
+                    i += 1;
+                    let t = skip_lines(food, &mut i);
+                    v += &"\n".repeat(t - 1);
+                    escaped = v.len();
+                    escaped_nl = false;
+                }
+                Some(b'\\') => match escape_char(&food[i + 1..]) {
+                    Some((c, n)) => {
+                        v.push(c);
+                        escaped = v.len();
+                        escaped_nl = c == '\n';
+                        i += n + 1;
+                    }
+                    None => {
+                        self.pos += i;
+                        return self.err(ErrorKind::BadEscape);
+                    }
+                },
+                Some(b'\n' | b'\r') => {
+                    v.truncate(escaped.max(v.trim_end_matches([' ', '\t']).len()));
+                    match skip_lines(food, &mut i) {
+                        // Manual wrapping
+                        1 if escaped_nl && escaped == v.len() => {}
+                        1 => v.push(' '),
+                        t => v += &"\n".repeat(t - 1),
+                    }
+                }
+                Some(_) => {
+                    let n = food[i..]
+                        .iter()
+                        .take_while(|c| !matches!(c, b'"' | b'\\' | b'\n' | b'\r'))
+                        .count();
+                    v += &String::from_utf8_lossy(&food[i..i + n]);
+                    i += n;
+                }
+            }
+        }
+        self.pos += i;
+        Ok(v)
+    }
+
     /// Match plain string.
     pub fn string_plain(&mut self, level: usize, inner: bool) -> PResult<String> {
         let mut patt = b"[]{}: \n\r".to_vec();
@@ -134,6 +221,7 @@ impl Parser<'_> {
             let mut v = String::new();
             let mut is_leading = false;
             loop {
+                let pos = p.pos;
                 p.forward();
                 p.take_while(Self::not_in(&patt), TakeOpt::More(0))?;
                 v.push_str(&p.text());
@@ -172,6 +260,9 @@ impl Parser<'_> {
                     }
                     is_leading = true;
                 }
+                if p.pos == pos {
+                    break;
+                }
             }
             v.truncate(v.trim_end().len());
             if v.is_empty() {
@@ -185,22 +276,167 @@ impl Parser<'_> {
     /// Match literal string.
     pub fn string_literal(&mut self, level: usize) -> PResult<String> {
         self.sym(b'|')?;
-        let chomp = self.chomp();
-        self.ws(TakeOpt::More(0))?;
-        let s = self.string_wrapped(level, b'\n', true)?;
-        Ok(chomp(s))
+        self.string_block(level, false)
     }
 
     /// Match folded string.
     pub fn string_folded(&mut self, level: usize) -> PResult<String> {
         self.sym(b'>')?;
-        let chomp = self.chomp();
-        self.ws(TakeOpt::More(0))?;
-        let s = self.string_wrapped(level, b' ', false)?;
-        Ok(chomp(s))
+        self.string_block(level, true)
+    }
+
+    /// Match the block scalar after the `|` or `>` indicator.
+    ///
+    /// The header may contain the chomping indicator (`-` or `+`) and the
+    /// indentation indicator (`1` to `9`) in any order.
+    /// The indentation is detected by the first non-empty line if the
+    /// indentation indicator is not given.
+    // `Option::is_none_or` is not available in the older compilers
+    #[allow(clippy::unnecessary_map_or)]
+    fn string_block(&mut self, level: usize, folded: bool) -> PResult<String> {
+        let food = self.food();
+        let mut chomp = None;
+        let mut explicit = None;
+        let mut i = 0;
+        while let Some(c) = food.get(i) {
+            match c {
+                b'-' | b'+' if chomp.is_none() => chomp = Some(*c),
+                b'1'..=b'9' if explicit.is_none() => explicit = Some(usize::from(c - b'0')),
+                _ => break,
+            }
+            i += 1;
+        }
+        let header = i;
+        while let Some(b' ' | b'\t') = food.get(i) {
+            i += 1;
+        }
+        if food.get(i) == Some(&b'#') && i > header {
+            while !matches!(food.get(i), None | Some(b'\n' | b'\r')) {
+                i += 1;
+            }
+        }
+        if !matches!(food.get(i), None | Some(b'\n' | b'\r')) {
+            self.backward();
+            return Err(PError::Mismatch);
+        }
+        // Indent of the parent node, the root node has no parent
+        let parent = (level > 0).then(|| {
+            (0..level)
+                .map(|i| self.indent.get(i).copied().unwrap_or(2))
+                .sum::<usize>()
+        });
+        // Split the lines, each line is (start, end) without line break
+        let mut lines = Vec::new();
+        let mut end = i;
+        while end < food.len() {
+            let start = end
+                + if food[end..].starts_with(b"\r\n") {
+                    2
+                } else {
+                    1
+                };
+            let mut j = start;
+            while !matches!(food.get(j), None | Some(b'\n' | b'\r')) {
+                j += 1;
+            }
+            lines.push((start, j));
+            end = j;
+        }
+        let spaces = |(start, end): (usize, usize)| {
+            food[start..end].iter().take_while(|c| **c == b' ').count()
+        };
+        let is_blank = |(start, end): (usize, usize)| {
+            food[start..end].iter().all(|c| *c == b' ' || *c == b'\t')
+        };
+        let ind = match explicit {
+            Some(m) => parent.map_or(m - 1, |n| n + m),
+            None => {
+                let ind = lines
+                    .iter()
+                    .find(|line| !is_blank(**line))
+                    .map_or(0, |line| spaces(*line));
+                let leading = lines
+                    .iter()
+                    .take_while(|line| is_blank(**line))
+                    .map(|line| spaces(*line))
+                    .max()
+                    .unwrap_or_default();
+                if parent.map_or(true, |n| ind > n) && leading > ind {
+                    return self.err(ErrorKind::BadIndent);
+                }
+                ind
+            }
+        };
+        let mut v = String::new();
+        // The end of the last content line
+        let mut last = None;
+        let mut more = false;
+        let mut breaks = 0;
+        for &(start, end) in lines.iter() {
+            let n = spaces((start, end));
+            if n < ind || parent.is_some_and(|p| n <= p) {
+                if is_blank((start, end)) && end < food.len() {
+                    breaks += 1;
+                    continue;
+                }
+                break;
+            }
+            let text = &food[start + ind..end];
+            if ind == 0
+                && (text.starts_with(b"---") || text.starts_with(b"..."))
+                && matches!(text.get(3), None | Some(b' ' | b'\t'))
+            {
+                break;
+            }
+            if end == start + ind && end == food.len() {
+                // Empty line without line break
+                break;
+            }
+            if text.is_empty() {
+                breaks += 1;
+                continue;
+            }
+            let is_more = text.starts_with(b" ") || text.starts_with(b"\t");
+            if last.is_none() {
+                v += &"\n".repeat(breaks);
+            } else if folded && !more && !is_more {
+                if breaks == 0 {
+                    v.push(' ');
+                } else {
+                    v += &"\n".repeat(breaks);
+                }
+            } else {
+                v += &"\n".repeat(breaks + 1);
+            }
+            v.push_str(&String::from_utf8_lossy(text));
+            last = Some(end);
+            more = is_more;
+            breaks = 0;
+        }
+        let lb = last.is_some_and(|end| end < food.len());
+        match chomp {
+            Some(b'-') => {}
+            Some(b'+') => {
+                if lb {
+                    v.push('\n');
+                }
+                v += &"\n".repeat(breaks);
+            }
+            _ => {
+                if lb {
+                    v.push('\n');
+                }
+            }
+        }
+        // Keep the last wrap
+        self.pos += last.unwrap_or(i);
+        Ok(v)
     }
 
     /// Match string chomping option.
+    #[deprecated(
+        note = "the chomping indicator is matched by `string_literal` and `string_folded`"
+    )]
     pub fn chomp(&mut self) -> impl Fn(String) -> String {
         self.context(|p| {
             if p.sym(b'-').is_ok() {
@@ -214,6 +450,7 @@ impl Parser<'_> {
     }
 
     /// Match wrapped string.
+    #[deprecated(note = "use `string_literal` or `string_folded` to match the block scalars")]
     pub fn string_wrapped(&mut self, level: usize, sep: u8, leading: bool) -> PResult<String> {
         self.context(|p| {
             let mut v = String::new();
@@ -255,26 +492,25 @@ impl Parser<'_> {
     }
 
     /// String escaping, return a new string.
+    ///
+    /// The invalid escape characters are kept.
     pub fn escape(doc: &str) -> String {
         let mut s = String::new();
-        let mut b = false;
-        for c in doc.chars() {
-            if c == '\\' && !b {
-                b = true;
-                continue;
+        let mut doc = doc;
+        while let Some(i) = doc.find('\\') {
+            s.push_str(&doc[..i]);
+            match escape_char(&doc.as_bytes()[i + 1..]) {
+                Some((c, n)) => {
+                    s.push(c);
+                    doc = &doc[i + 1 + n..];
+                }
+                None => {
+                    s.push('\\');
+                    doc = &doc[i + 1..];
+                }
             }
-            s.push(match c {
-                '\\' if b => '\\',
-                'n' if b => '\n',
-                'r' if b => '\r',
-                't' if b => '\t',
-                'b' if b => '\x08',
-                'f' if b => '\x0C',
-                c => c,
-            });
-            b = false;
         }
-        s
+        s + doc
     }
 
     /// Match valid YAML identifier.
@@ -286,57 +522,94 @@ impl Parser<'_> {
         )
     }
 
-    /// Match tags.
+    /// Match the word characters of the tag handles.
+    pub fn tag_word(&mut self) -> PResult<()> {
+        self.take_while(
+            |c| c.is_ascii_alphanumeric() || *c == b'-',
+            TakeOpt::More(1),
+        )
+    }
+
+    /// Match tags, the shorthands are resolved by the tag handles, and the
+    /// `%` escapes are decoded.
     pub fn tag(&mut self) -> PResult<String> {
+        let start = self.indicator();
         self.sym(b'!')?;
         self.context(|p| {
-            p.identifier().unwrap_or_default();
-            let tag = p.text();
-            let prefix = if !tag.is_empty() {
-                if p.sym(b'!').is_ok() {
-                    // Tag prefix variable
-                    p.tag[&tag].clone()
-                } else {
-                    String::new()
+            if p.sym(b'<').is_ok() {
+                // Verbatim tag, a local tag or a global tag
+                let tag = p.tag_uri(true)?;
+                let valid = match tag.strip_prefix('!') {
+                    Some(name) => !name.is_empty(),
+                    None => is_global_tag(&tag),
+                };
+                if !valid || p.sym(b'>').is_err() {
+                    return p.err_at(ErrorKind::InvalidTag, start);
                 }
-            } else if p.sym(b'<').is_ok() {
-                // Full tag
-                let tag = p.context(|p| {
-                    p.take_while(Self::not_in(b" <>\n\r"), TakeOpt::More(1))?;
-                    Ok(p.text())
-                })?;
-                p.sym(b'>')?;
-                tag
-            } else if p.sym(b'!').is_ok() {
+                return Ok(tag);
+            }
+            let prefix = if p.sym(b'!').is_ok() {
                 p.tag["!!"].clone()
+            } else if let Some(handle) = p.opt(|p| {
+                p.tag_word()?;
+                let handle = p.text();
+                p.sym(b'!')?;
+                Ok(handle)
+            })? {
+                // Tag prefix variable
+                match p.tag.get(&handle) {
+                    Some(prefix) => prefix.clone(),
+                    None => return p.err(ErrorKind::UndeclaredTagHandle),
+                }
             } else {
                 p.tag["!"].clone()
             };
-            let doc = p.context(|p| {
-                if p.identifier().is_ok() {
-                    p.text()
-                } else {
-                    String::new()
-                }
-            });
-            Ok(format!("{}{}", prefix, doc))
+            Ok(prefix + &p.tag_uri(false)?)
         })
     }
 
+    /// Match the URI characters of the tags and decode the `%` escapes, the
+    /// `!` and the flow indicators are only allowed in the verbatim tags.
+    pub(crate) fn tag_uri(&mut self, verbatim: bool) -> PResult<String> {
+        let start = self.indicator();
+        let mut s = Vec::new();
+        loop {
+            match self.food() {
+                [b'%', a, b, ..] if a.is_ascii_hexdigit() && b.is_ascii_hexdigit() => {
+                    let hex = |c: u8| (c as char).to_digit(16).unwrap() as u8;
+                    s.push(hex(*a) << 4 | hex(*b));
+                    self.pos += 3;
+                }
+                [b'%', ..] => return self.err(ErrorKind::InvalidTag),
+                [c, ..] if is_uri_char(*c) && (verbatim || is_tag_char(*c)) => {
+                    s.push(*c);
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        String::from_utf8(s).or_else(|_| self.err_at(ErrorKind::InvalidTag, start))
+    }
+
     /// Match anchor definition.
     pub fn anchor(&mut self) -> PResult<String> {
         self.sym(b'&')?;
-        self.context(|p| {
-            p.identifier()?;
-            Ok(p.text())
-        })
+        self.anchor_name()
     }
 
     /// Match anchor used.
     pub fn anchor_use(&mut self) -> PResult<String> {
         self.sym(b'*')?;
+        self.anchor_name()
+    }
+
+    /// Match anchor name, the non-ASCII alphanumeric characters are allowed.
+    pub fn anchor_name(&mut self) -> PResult<String> {
         self.context(|p| {
-            p.identifier()?;
+            p.take_while_char(
+                |c| c.is_alphanumeric() || c == '-' || c == '_',
+                TakeOpt::More(1),
+            )?;
             Ok(p.text())
         })
     }
@@ -352,12 +625,9 @@ impl Parser<'_> {
     /// Match newline characters.
     pub fn nl(&mut self) -> PResult<()> {
         self.context(|p| {
-            (p.sym_seq(b"\r\n").is_ok()
-                || p.sym_seq(b"\n\r").is_ok()
-                || p.sym(b'\n').is_ok()
-                || p.sym(b'\r').is_ok())
-            .then(|| ())
-            .ok_or(PError::Mismatch)
+            (p.sym_seq(b"\r\n").is_ok() || p.sym(b'\n').is_ok() || p.sym(b'\r').is_ok())
+                .then_some(())
+                .ok_or(PError::Mismatch)
         })
     }
 
@@ -372,6 +642,7 @@ impl Parser<'_> {
             self.ind(level - 1)?;
         }
         let ind = self.count(|p| p.take_while(|c| c.is_ascii_whitespace(), TakeOpt::More(0)))?;
+        self.check_indent(level, ind)?;
         if level == self.indent.len() {
             self.indent.push(ind);
         } else {
@@ -380,6 +651,32 @@ impl Parser<'_> {
         Ok(())
     }
 
+    /// Define new indent size by the current position, for the collection
+    /// which starts at the same line as its parent, such as `- - a` and
+    /// `- k: v`.
+    ///
+    /// The root collection takes the indentation of the line instead, since
+    /// it may follow the document marker `---`.
+    pub fn ind_compact(&mut self, level: usize) {
+        let ind = if level == 0 {
+            let start = self.pos - self.column();
+            self.doc[start..self.pos]
+                .iter()
+                .take_while(|c| **c == b' ')
+                .count()
+        } else {
+            let base = self.indent.iter().take(level).sum::<usize>();
+            self.column().saturating_sub(base)
+        };
+        if level >= self.indent.len() {
+            self.indent.resize(level, 2);
+            self.indent.push(ind);
+        } else {
+            self.indent.truncate(level + 1);
+            self.indent[level] = ind;
+        }
+    }
+
     /// Match any optional invisible characters between two lines.
     ///
     /// Set `cmt` to `true` to ignore comments at the line end.
@@ -395,7 +692,7 @@ impl Parser<'_> {
                 p.forward();
                 p.ws(TakeOpt::More(0))?;
                 if cmt {
-                    p.comment().unwrap_or_default();
+                    p.context(|p| p.comment().unwrap_or_default());
                 }
                 if p.nl().is_err() {
                     p.backward();
@@ -406,6 +703,39 @@ impl Parser<'_> {
         })
     }
 
+    /// Match the separator in the flow collections, includes the line breaks
+    /// and the comments.
+    ///
+    /// The lines must be indented at least `min` spaces, except the lines
+    /// start with a closing bracket.
+    pub fn flow_gap(&mut self, min: usize) -> PResult<()> {
+        let mut line_start = None;
+        loop {
+            // The comment must be separated by white spaces
+            if self.food().first() == Some(&b'#')
+                && (self.pos == 0 || self.doc[self.pos - 1].is_ascii_whitespace())
+            {
+                self.take_while(Self::not_in(b"\n\r"), TakeOpt::More(0))?;
+            }
+            if self.nl().is_ok() {
+                line_start = Some(self.pos);
+                continue;
+            }
+            let pos = self.pos;
+            self.ws(TakeOpt::More(0))?;
+            if pos == self.pos {
+                break;
+            }
+        }
+        if let Some(start) = line_start {
+            let ind = self.doc[start..].iter().take_while(|c| **c == b' ').count();
+            if ind < min && !matches!(self.food().first(), None | Some(b']' | b'}')) {
+                return self.err(ErrorKind::FlowIndent);
+            }
+        }
+        Ok(())
+    }
+
     /// Match comment.
     pub fn comment(&mut self) -> PResult<()> {
         self.ws(TakeOpt::More(0))?;
@@ -413,3 +743,59 @@ impl Parser<'_> {
         self.take_while(Self::not_in(b"\n\r"), TakeOpt::More(0))
     }
 }
+
+/// Resolve the escape character after the backslash,
+/// return the character and the length of the escape sequence.
+fn escape_char(doc: &[u8]) -> Option<(char, usize)> {
+    let c = match doc.first()? {
+        b'0' => '\0',
+        b'a' => '\x07',
+        b'b' => '\x08',
+        b't' | b'\t' => '\t',
+        b'n' => '\n',
+        b'v' => '\x0B',
+        b'f' => '\x0C',
+        b'r' => '\r',
+        b'e' => '\x1B',
+        b' ' => ' ',
+        b'"' => '"',
+        b'/' => '/',
+        b'\\' => '\\',
+        b'N' => '\u{85}',
+        b'_' => '\u{A0}',
+        b'L' => '\u{2028}',
+        b'P' => '\u{2029}',
+        c @ (b'x' | b'u' | b'U') => {
+            let n = match c {
+                b'x' => 2,
+                b'u' => 4,
+                _ => 8,
+            };
+            let hex = doc.get(1..=n)?;
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            let code = u32::from_str_radix(core::str::from_utf8(hex).ok()?, 16).ok()?;
+            return Some((char::from_u32(code)?, n + 1));
+        }
+        _ => return None,
+    };
+    Some((c, 1))
+}
+
+/// Skip the line breaks and the leading white spaces of the next lines,
+/// return the number of the line breaks.
+fn skip_lines(doc: &[u8], i: &mut usize) -> usize {
+    let mut t = 0;
+    loop {
+        match doc.get(*i..) {
+            Some([b'\r', b'\n', ..]) => *i += 2,
+            Some([b'\n' | b'\r', ..]) => *i += 1,
+            _ => return t,
+        }
+        t += 1;
+        while let Some(b' ' | b'\t') = doc.get(*i) {
+            *i += 1;
+        }
+    }
+}
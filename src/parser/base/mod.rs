@@ -1,5 +1,7 @@
 use super::*;
-use alloc::borrow::Cow;
+use alloc::{borrow::Cow, format};
+
+pub use self::directive::VersionPolicy;
 
 mod directive;
 mod grammar;
@@ -16,6 +18,65 @@ pub enum TakeOpt {
     More(usize),
 }
 
+/// The saved cursor of the [`Parser`], created by [`Parser::checkpoint`].
+///
+/// The guard dereferences to the parser. It rewinds the parser to the saved
+/// position when dropped, unless [`Checkpoint::commit`] is called, so the
+/// mismatched branch of a custom grammar can simply return early.
+///
+/// ```
+/// use yaml_peg::parser::{Parser, TakeOpt};
+///
+/// let mut p = Parser::new(b"abc");
+/// {
+///     let mut cp = p.checkpoint();
+///     cp.sym_seq(b"ab").unwrap();
+///     assert!(cp.sym(b'x').is_err());
+/// }
+/// assert_eq!(p.food(), b"abc");
+/// let mut cp = p.checkpoint();
+/// cp.sym_seq(b"ab").unwrap();
+/// cp.commit();
+/// assert_eq!(p.food(), b"c");
+/// ```
+pub struct Checkpoint<'p, 'a> {
+    parser: &'p mut Parser<'a>,
+    saved: (usize, usize),
+    commit: bool,
+}
+
+impl Checkpoint<'_, '_> {
+    /// Keep the current position, and restore the read position of the
+    /// outer rule.
+    pub fn commit(mut self) {
+        self.commit = true;
+    }
+}
+
+impl<'a> core::ops::Deref for Checkpoint<'_, 'a> {
+    type Target = Parser<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        self.parser
+    }
+}
+
+impl core::ops::DerefMut for Checkpoint<'_, '_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.parser
+    }
+}
+
+impl Drop for Checkpoint<'_, '_> {
+    fn drop(&mut self) {
+        let (pos, eaten) = self.saved;
+        if !self.commit {
+            self.parser.pos = pos;
+        }
+        self.parser.eaten = eaten;
+    }
+}
+
 /// Basic greedy parser with YAML syntax.
 ///
 /// Its methods are actually the sub-parser of the syntax.
@@ -23,25 +84,57 @@ pub struct Parser<'a> {
     doc: &'a [u8],
     indent: Vec<usize>,
     consumed: u64,
-    pub(crate) version_checked: bool,
+    pub(crate) version: Option<String>,
+    pub(crate) version_policy: VersionPolicy,
     pub(crate) tag: BTreeMap<String, String>,
+    /// The handles declared by the `%TAG` directives of the current document.
+    tag_declared: BTreeSet<String>,
+    warnings: Vec<Warning>,
+    /// The recorded warnings, and the line index for their messages.
+    warned: BTreeSet<(u64, WarningKind)>,
+    lines: Option<LineIndex>,
+    pub(crate) strict_indent: bool,
+    indent_width: Option<usize>,
+    #[cfg(feature = "trace")]
+    pub(crate) tracer: Option<super::trace::Tracer<'a>>,
+    #[cfg(feature = "trace")]
+    trace_depth: usize,
+    #[cfg(feature = "profile")]
+    pub(crate) profiler: super::profile::Profiler,
     /// Current position.
     pub pos: usize,
     /// Read position.
     pub eaten: usize,
 }
 
+fn default_tag() -> BTreeMap<String, String> {
+    let mut tag = BTreeMap::new();
+    tag.insert("!".to_string(), String::new());
+    tag.insert("!!".to_string(), tag_prefix!().to_string());
+    tag
+}
+
 impl Default for Parser<'_> {
     fn default() -> Self {
-        let mut tag = BTreeMap::new();
-        tag.insert("!".to_string(), String::new());
-        tag.insert("!!".to_string(), tag_prefix!().to_string());
         Self {
             doc: b"",
             indent: vec![0],
             consumed: 0,
-            version_checked: false,
-            tag,
+            version: None,
+            version_policy: VersionPolicy::default(),
+            tag: default_tag(),
+            tag_declared: BTreeSet::new(),
+            warnings: Vec::new(),
+            warned: BTreeSet::new(),
+            lines: None,
+            strict_indent: false,
+            indent_width: None,
+            #[cfg(feature = "trace")]
+            tracer: None,
+            #[cfg(feature = "trace")]
+            trace_depth: 0,
+            #[cfg(feature = "profile")]
+            profiler: Default::default(),
             pos: 0,
             eaten: 0,
         }
@@ -60,16 +153,103 @@ impl<'a> Parser<'a> {
         Self { doc, ..self }
     }
 
+    /// Save the cursor and start a new read position, the returned guard
+    /// rewinds the parser when dropped, see [`Checkpoint`].
+    pub fn checkpoint(&mut self) -> Checkpoint<'_, 'a> {
+        let saved = (self.pos, self.eaten);
+        self.forward();
+        Checkpoint { parser: self, saved, commit: false }
+    }
+
     /// Show the right hand side string after the current cursor.
     pub fn food(&self) -> &'a [u8] {
         &self.doc[self.pos..]
     }
 
     /// Encoded version of the left characters.
-    pub fn food_str(&self) -> Cow<str> {
+    pub fn food_str(&self) -> Cow<'_, str> {
         String::from_utf8_lossy(&self.doc[self.pos..])
     }
 
+    /// The declared tag handles, excludes the default handles `!` and `!!`,
+    /// unless they are overridden.
+    ///
+    /// The named handles are returned in the full form, e.g., `!x!`.
+    pub fn tag_handles(&self) -> BTreeMap<String, String> {
+        let default = default_tag();
+        self.tag
+            .iter()
+            .filter(|(k, v)| default.get(*k) != Some(*v))
+            .map(|(k, v)| match k.as_str() {
+                "!" | "!!" => (k.clone(), v.clone()),
+                _ => (format!("!{}!", k), v.clone()),
+            })
+            .collect()
+    }
+
+    /// Return true if the YAML 1.1 schema is used in the current document.
+    pub fn is_yaml11(&self) -> bool {
+        self.version_policy == VersionPolicy::Resolve && self.version.as_deref() == Some("1.1")
+    }
+
+    /// Clear the directive settings for the next document.
+    pub(crate) fn reset_directives(&mut self) {
+        self.version = None;
+        self.tag = default_tag();
+        self.tag_declared.clear();
+    }
+
+    /// The position of the line break before the first line after `start`
+    /// which is indented less than or equal to `ind` spaces, or the end of
+    /// the document. The empty lines and the comment lines are skipped.
+    pub(crate) fn skip_indented(&self, start: usize, ind: usize) -> usize {
+        let mut i = start.min(self.doc.len());
+        loop {
+            let Some(brk) = self.doc[i..]
+                .iter()
+                .position(|c| matches!(c, b'\n' | b'\r'))
+            else {
+                return self.doc.len();
+            };
+            let brk = i + brk;
+            i = brk + 1;
+            if self.doc[brk] == b'\r' && self.doc.get(i) == Some(&b'\n') {
+                i += 1;
+            }
+            let n = self.doc[i..].iter().take_while(|c| **c == b' ').count();
+            match self.doc.get(i + n) {
+                None | Some(b'\n' | b'\r' | b'#') => (),
+                Some(_) if n > ind => (),
+                Some(_) => return brk,
+            }
+        }
+    }
+
+    /// The start of the first line after the error position which begins
+    /// with the document marker `---` or a directive, or the end of the
+    /// document.
+    pub(crate) fn skip_document(&self, pos: u64) -> usize {
+        let mut i = usize::try_from(pos.saturating_sub(self.consumed))
+            .unwrap_or(usize::MAX)
+            .min(self.doc.len());
+        loop {
+            let Some(brk) = self.doc[i..]
+                .iter()
+                .position(|c| matches!(c, b'\n' | b'\r'))
+            else {
+                return self.doc.len();
+            };
+            i += brk + 1;
+            let line = &self.doc[i..];
+            if line.starts_with(b"%")
+                || line.starts_with(b"---")
+                    && matches!(line.get(3), None | Some(b' ' | b'\t' | b'\n' | b'\r'))
+            {
+                return i;
+            }
+        }
+    }
+
     /// Get the text from the eaten cursor to the current position.
     pub fn text(&mut self) -> String {
         if self.eaten < self.pos {
@@ -85,24 +265,135 @@ impl<'a> Parser<'a> {
 /// These sub-parser returns `Result<(), PError>`, and calling
 /// [`Parser::backward`] if mismatched.
 impl Parser<'_> {
-    /// Set the starting point if character boundary is valid.
+    /// Set the starting point.
+    ///
+    /// The position is limited to the end of the document, and moved back to
+    /// the character boundary if it lands inside a UTF-8 sequence.
     pub fn pos(self, pos: usize) -> Self {
+        let mut pos = pos.min(self.doc.len());
+        while pos > 0 && pos < self.doc.len() && self.doc[pos] & 0xc0 == 0x80 {
+            pos -= 1;
+        }
         Self { pos, eaten: pos, ..self }
     }
 
+    /// Shift the document positions by the offset, to make the positions of
+    /// several documents unique, see [`Sources`](super::Sources).
+    pub fn offset(self, offset: u64) -> Self {
+        Self { consumed: offset, ..self }
+    }
+
     /// Get the indicator.
     pub fn indicator(&self) -> u64 {
         self.consumed + self.pos as u64
     }
 
     /// A short function to raise error.
-    pub fn err<R>(&self, name: &'static str) -> PResult<R> {
+    pub fn err<R>(&self, kind: ErrorKind) -> PResult<R> {
+        self.err_at(kind, self.indicator())
+    }
+
+    /// Raise error at the document position.
+    pub fn err_at<R>(&self, kind: ErrorKind, pos: u64) -> PResult<R> {
+        let food = usize::try_from(pos - pos.min(self.consumed))
+            .ok()
+            .and_then(|pos| self.doc.get(pos..))
+            .unwrap_or_default();
         Err(PError::Terminate {
-            name,
-            msg: indicated_msg(self.doc, self.indicator()),
+            kind,
+            found: String::from_utf8_lossy(&food[..food.len().min(4)])
+                .chars()
+                .next(),
+            pos,
+            msg: self.indicated_msg(pos),
         })
     }
 
+    /// The indicated message of the document position, see
+    /// [`indicated_msg`].
+    pub fn indicated_msg(&self, pos: u64) -> String {
+        indicated_msg(self.doc, pos - pos.min(self.consumed))
+    }
+
+    /// Record a non-fatal diagnostic at the position, see
+    /// [`Parser::warnings`].
+    ///
+    /// The warning is recorded once if the position is parsed again.
+    pub fn warn(&mut self, kind: WarningKind, pos: u64) {
+        if !self.warned.insert((pos, kind)) {
+            return;
+        }
+        // The lines are indexed at the first warning
+        let doc = self.doc;
+        let lines = self.lines.get_or_insert_with(|| LineIndex::new(doc));
+        let msg = lines.indicated_msg(doc, pos - pos.min(self.consumed));
+        self.warnings.push(Warning { kind, pos, msg });
+    }
+
+    /// Report the rule event to the tracer.
+    #[cfg(feature = "trace")]
+    pub(crate) fn trace(&mut self, rule: &'static str, event: TraceEvent) {
+        let Some(tracer) = &mut self.tracer else {
+            return;
+        };
+        if event != TraceEvent::Enter {
+            self.trace_depth -= 1;
+        }
+        let pos = self.consumed + self.pos as u64;
+        tracer(&Trace { rule, event, pos, depth: self.trace_depth });
+        if event == TraceEvent::Enter {
+            self.trace_depth += 1;
+        }
+    }
+
+    /// The non-fatal diagnostics recorded in parsing.
+    ///
+    /// ```
+    /// use yaml_peg::parser::{Loader, WarningKind};
+    ///
+    /// let mut loader = Loader::<yaml_peg::repr::RcRepr>::new(b"%FOO bar\n---\n[yes, 010]");
+    /// loader.parse().unwrap();
+    /// let kinds = loader.warnings().iter().map(|w| w.kind).collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     kinds,
+    ///     [WarningKind::UnknownDirective, WarningKind::Yaml11Scalar, WarningKind::Yaml11Scalar]
+    /// );
+    /// assert_eq!(loader.warnings()[1].pos, 14);
+    /// ```
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Take out the recorded warnings.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        self.warned.clear();
+        core::mem::take(&mut self.warnings)
+    }
+
+    /// The statistics of the grammar rules in parsing, the rules are the
+    /// same as [`Loader::trace`](super::Loader::trace).
+    ///
+    /// The statistics are accumulated across the parses of the same parser.
+    ///
+    /// ```
+    /// use yaml_peg::parser::Loader;
+    ///
+    /// let mut loader = Loader::<yaml_peg::repr::RcRepr>::new(b"a: [1, 2]\nb: {c: d}\n");
+    /// loader.parse().unwrap();
+    /// let profile = loader.profile();
+    /// assert_eq!(profile["doc"].calls, 1);
+    /// assert_eq!(profile["seq_flow"].matched, 1);
+    /// let mut rules = profile.iter().collect::<Vec<_>>();
+    /// rules.sort_by_key(|(_, s)| std::cmp::Reverse(s.self_time));
+    /// let (slowest, _) = rules[0];
+    /// assert!(profile.contains_key(slowest));
+    /// ```
+    #[cfg(feature = "profile")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "profile")))]
+    pub fn profile(&self) -> &BTreeMap<&'static str, super::RuleStats> {
+        &self.profiler.stats
+    }
+
     /// Consume and move the pointer.
     pub fn consume(&mut self) {
         self.forward();
@@ -121,9 +412,13 @@ impl Parser<'_> {
         self.pos = self.eaten;
     }
 
-    /// Move back current cursor.
+    /// Move back current cursor by `n` bytes, stop at the beginning of the
+    /// document.
+    ///
+    /// This is used to un-match the bytes just matched, so it never
+    /// underflows. To rewind a rule, prefer [`Parser::checkpoint`].
     pub fn back(&mut self, n: usize) {
-        self.pos -= n;
+        self.pos = self.pos.saturating_sub(n);
     }
 
     /// Match symbol.
@@ -187,6 +482,65 @@ impl Parser<'_> {
         }
     }
 
+    /// Same as [`Parser::take_while`], but match the UTF-8 characters.
+    ///
+    /// The matching is stopped at the invalid UTF-8 sequence.
+    ///
+    /// ```
+    /// use yaml_peg::parser::{Parser, TakeOpt};
+    ///
+    /// let mut p = Parser::new("中文 abc".as_bytes());
+    /// p.take_while_char(char::is_alphanumeric, TakeOpt::More(1)).unwrap();
+    /// assert_eq!(p.text(), "中文");
+    /// p.forward();
+    /// assert!(p.take_while_char(Parser::is_in_char("中"), TakeOpt::One).is_err());
+    /// assert_eq!(p.food(), " abc".as_bytes());
+    /// ```
+    pub fn take_while_char<F>(&mut self, f: F, opt: TakeOpt) -> PResult<()>
+    where
+        F: Fn(char) -> bool,
+    {
+        let pos = self.pos;
+        let mut counter = 0;
+        let food = self.food();
+        let s = match core::str::from_utf8(food) {
+            Ok(s) => s,
+            Err(e) => core::str::from_utf8(&food[..e.valid_up_to()]).unwrap(),
+        };
+        for c in s.chars() {
+            if !f(c) {
+                break;
+            }
+            self.pos += c.len_utf8();
+            counter += 1;
+            if let TakeOpt::One = opt {
+                break;
+            }
+            if let TakeOpt::Range(_, c) = opt {
+                if counter == c {
+                    break;
+                }
+            }
+        }
+        if pos == self.pos {
+            if let TakeOpt::More(c) | TakeOpt::Range(c, _) = opt {
+                if c == 0 {
+                    return Ok(());
+                }
+            }
+            self.backward();
+            Err(PError::Mismatch)
+        } else {
+            if let TakeOpt::More(c) | TakeOpt::Range(c, _) = opt {
+                if counter < c {
+                    self.backward();
+                    return Err(PError::Mismatch);
+                }
+            }
+            Ok(())
+        }
+    }
+
     /// Count the position that parser goes, expect error.
     pub fn count<F, R>(&mut self, f: F) -> PResult<usize>
     where
@@ -198,17 +552,124 @@ impl Parser<'_> {
     }
 
     /// A wrapper for saving checkpoint locally.
+    ///
+    /// The read position is restored after the function, but the current
+    /// position is kept, same as a committed [`Checkpoint`].
     pub fn context<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut Self) -> R,
     {
-        let eaten = self.eaten;
-        self.forward();
-        let r = f(self);
-        self.eaten = eaten;
+        let mut cp = self.checkpoint();
+        let r = f(&mut cp);
+        cp.commit();
         r
     }
 
+    /// Match the rule optionally, return `None` without moving the cursor
+    /// if mismatched. The [`PError::Terminate`] error is still returned.
+    ///
+    /// ```
+    /// use yaml_peg::parser::Parser;
+    ///
+    /// let mut p = Parser::new(b"-a");
+    /// assert_eq!(p.opt(|p| p.sym_seq(b"--")).unwrap(), None);
+    /// assert_eq!(p.opt(|p| p.sym(b'-')).unwrap(), Some(()));
+    /// assert_eq!(p.food(), b"a");
+    /// ```
+    pub fn opt<F, R>(&mut self, f: F) -> PResult<Option<R>>
+    where
+        F: FnOnce(&mut Self) -> PResult<R>,
+    {
+        let mut cp = self.checkpoint();
+        match f(&mut cp) {
+            Ok(r) => {
+                cp.commit();
+                Ok(Some(r))
+            }
+            Err(PError::Mismatch) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Match the first rule, or the second rule if the first one is
+    /// mismatched. Nest this method for more choices.
+    pub fn alt<F1, F2, R>(&mut self, f1: F1, f2: F2) -> PResult<R>
+    where
+        F1: FnOnce(&mut Self) -> PResult<R>,
+        F2: FnOnce(&mut Self) -> PResult<R>,
+    {
+        if let Some(r) = self.opt(f1)? {
+            return Ok(r);
+        }
+        self.opt(f2)?.ok_or(PError::Mismatch)
+    }
+
+    /// Match the rule repeatedly, at least `min` times, and collect the
+    /// results. The cursor is not moved if mismatched.
+    ///
+    /// The repetition stops if the rule matches nothing.
+    ///
+    /// ```
+    /// use yaml_peg::parser::Parser;
+    ///
+    /// let mut p = Parser::new(b"aab");
+    /// assert!(p.many(|p| p.sym(b'a'), 3).is_err());
+    /// assert_eq!(p.many(|p| p.sym(b'a'), 1).unwrap().len(), 2);
+    /// assert_eq!(p.food(), b"b");
+    /// ```
+    pub fn many<F, R>(&mut self, mut f: F, min: usize) -> PResult<Vec<R>>
+    where
+        F: FnMut(&mut Self) -> PResult<R>,
+    {
+        let mut cp = self.checkpoint();
+        let mut v = Vec::new();
+        loop {
+            let pos = cp.pos;
+            match cp.opt(&mut f)? {
+                Some(r) => v.push(r),
+                None => break,
+            }
+            if cp.pos == pos {
+                break;
+            }
+        }
+        if v.len() < min {
+            return Err(PError::Mismatch);
+        }
+        cp.commit();
+        Ok(v)
+    }
+
+    /// Match the rule without moving the cursor.
+    pub fn peek<F, R>(&mut self, f: F) -> PResult<R>
+    where
+        F: FnOnce(&mut Self) -> PResult<R>,
+    {
+        let mut cp = self.checkpoint();
+        f(&mut cp)
+    }
+
+    /// Succeed if the rule is mismatched, without moving the cursor.
+    ///
+    /// ```
+    /// use yaml_peg::parser::Parser;
+    ///
+    /// let mut p = Parser::new(b"...");
+    /// assert!(p.not(|p| p.sym_seq(b"---")).is_ok());
+    /// assert!(p.not(|p| p.sym_seq(b"...")).is_err());
+    /// assert_eq!(p.food(), b"...");
+    /// ```
+    pub fn not<F, R>(&mut self, f: F) -> PResult<()>
+    where
+        F: FnOnce(&mut Self) -> PResult<R>,
+    {
+        match self.peek(f) {
+            Ok(_) => Err(PError::Mismatch),
+            Err(PError::Mismatch) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
     /// A SET detector.
     pub fn is_in(s: &[u8]) -> impl Fn(&u8) -> bool + '_ {
         move |c| !Self::not_in(s)(c)
@@ -226,12 +687,54 @@ impl Parser<'_> {
         }
     }
 
+    /// A SET detector of the characters.
+    pub fn is_in_char(s: &str) -> impl Fn(char) -> bool + '_ {
+        move |c| s.contains(c)
+    }
+
+    /// A NOT detector of the characters.
+    pub fn not_in_char(s: &str) -> impl Fn(char) -> bool + '_ {
+        move |c| !s.contains(c)
+    }
+
+    /// The column of the current position, counted in bytes.
+    pub(crate) fn column(&self) -> usize {
+        self.doc[..self.pos]
+            .iter()
+            .rev()
+            .take_while(|c| !matches!(c, b'\n' | b'\r'))
+            .count()
+    }
+
+    /// Check the indent size of the nested level in the strict mode, see
+    /// [`Loader::strict_indent`].
+    pub(crate) fn check_indent(&mut self, level: usize, ind: usize) -> PResult<()> {
+        if !self.strict_indent || level == 0 || ind == 0 {
+            return Ok(());
+        }
+        match self.indent_width {
+            None => self.indent_width = Some(ind),
+            Some(width) if width != ind => return self.err(ErrorKind::InconsistentIndent),
+            Some(_) => {}
+        }
+        Ok(())
+    }
+
     /// Match indent.
+    ///
+    /// The indent size of the new level is detected from the document, which
+    /// must be indented more than the parent level.
     pub fn ind(&mut self, level: usize) -> PResult<()> {
         if level >= self.indent.len() {
-            for _ in 0..level - self.indent.len() + 1 {
-                self.indent.push(2);
+            self.indent.resize(level, 2);
+            let base = self.indent.iter().sum::<usize>();
+            let ind = self.food().iter().take_while(|c| **c == b' ').count();
+            if level > 0 && ind <= base {
+                self.backward();
+                return Err(PError::Mismatch);
             }
+            self.check_indent(level, ind - base)?;
+            self.indent.push(ind - base);
         } else {
             // Clear the old indent settings
             self.indent.drain(level + 1..);
@@ -4,23 +4,211 @@ use core::fmt::{Display, Error, Formatter};
 /// Type of the parser result.
 pub type PResult<T> = Result<T, PError>;
 
+macro_rules! impl_kind {
+    ($($(#[$meta:meta])* $kind:ident => $name:literal, $expected:literal)+) => {
+        /// The kind of [`PError::Terminate`] error.
+        ///
+        /// Please see [module level document](super) for the error groups.
+        ///
+        /// ```
+        /// use yaml_peg::{parse, parser::{ErrorKind, PError}, repr::RcRepr};
+        ///
+        /// match parse::<RcRepr>("a: \"b\\qc\"") {
+        ///     Err(PError::Terminate { kind, found, pos, .. }) => {
+        ///         assert_eq!(kind, ErrorKind::BadEscape);
+        ///         assert_eq!(kind.expected(), "escape character");
+        ///         assert_eq!(found, Some('\\'));
+        ///         assert_eq!(pos, 5);
+        ///     }
+        ///     _ => unreachable!(),
+        /// }
+        /// ```
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum ErrorKind {
+            $($(#[$meta])* $kind,)+
+            /// The error raised by the custom sub-parser.
+            ///
+            /// ```
+            /// use yaml_peg::parser::{ErrorKind, Parser};
+            ///
+            /// let kind = ErrorKind::Custom { name: "version", expected: "`v` prefix" };
+            /// let e = Parser::new(b"1.0").err::<()>(kind).unwrap_err();
+            /// assert_eq!(e.kind().map(|kind| kind.expected()), Some("`v` prefix"));
+            /// assert_eq!(e.to_string(), "invalid version at byte 0: \n\n1:1\n1.0\n^");
+            /// ```
+            Custom {
+                /// The name of the sub-parser, see [`ErrorKind::name`].
+                name: &'static str,
+                /// The description of the expected syntax, see
+                /// [`ErrorKind::expected`].
+                expected: &'static str,
+            },
+        }
+
+        impl ErrorKind {
+            /// The name of the sub-parser group, which is shown in the
+            /// error message.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$kind => $name,)+
+                    Self::Custom { name, .. } => name,
+                }
+            }
+
+            /// The description of the expected syntax.
+            pub fn expected(&self) -> &'static str {
+                match self {
+                    $(Self::$kind => $expected,)+
+                    Self::Custom { expected, .. } => expected,
+                }
+            }
+        }
+    };
+}
+
+impl_kind! {
+    /// Error about the document splitter `---` / `...`.
+    DocumentSplitter => "document splitter", "document marker or end of document"
+    /// Version directive `%YAML 1.2` is used again.
+    DuplicatedVersion => "checked version", "single version directive"
+    /// Version directive is wrong or unsupported, see
+    /// [`VersionPolicy`](super::VersionPolicy).
+    UnknownVersion => "invalid version", "supported version"
+    /// The tag handle is declared again by the `%TAG` directive of the same
+    /// document.
+    DuplicatedTagHandle => "tag directive", "single tag directive for the handle"
+    /// The tag handle is not declared by the `%TAG` directive.
+    UndeclaredTagHandle => "tag handle", "declared tag handle"
+    /// The tag contains an invalid `%` escape, or the verbatim tag `!<...>`
+    /// is not a local tag or a URI.
+    InvalidTag => "tag URI", "valid tag URI"
+    /// Multiple documents are parsed as a single node, see the
+    /// [`FromStr`](core::str::FromStr) implementation of
+    /// [`Node`](crate::Node).
+    MultipleDocuments => "single document", "single document"
+    /// Item in `[]` bracket is invalid.
+    FlowSeqItem => "flow sequence item", "sequence item"
+    /// The `[]` or `{}` bracket is not closed, or the items are not split by
+    /// `,`.
+    UnclosedFlow => "flow collection terminator", "`,` or closing bracket"
+    /// The line in `[]` or `{}` bracket is not indented more than the parent
+    /// block collection.
+    FlowIndent => "flow indentation", "indented line of flow collection"
+    /// Key of map item in `{}` bracket is invalid.
+    FlowMapKey => "flow map key", "map key"
+    /// Value of map item in `{}` bracket is invalid.
+    FlowMapValue => "flow map value", "map value"
+    /// Splitter `:` of map item in `{}` bracket is invalid.
+    FlowMapSplitter => "flow map splitter", "`:`"
+    /// Item behind `-` indicator is invalid.
+    SeqItem => "sequence item", "sequence item"
+    /// The end of sequence is invalid, may caused by the last item (like
+    /// wrapped string).
+    SeqTerminator => "sequence terminator", "new line of sequence item"
+    /// The nested level is indented by a different size in the strict mode,
+    /// see [`Loader::strict_indent`](super::Loader::strict_indent).
+    InconsistentIndent => "indentation", "consistent indent size"
+    /// Key of map item is invalid.
+    MapKey => "map key", "map key"
+    /// Value of map item is invalid.
+    MapValue => "map value", "map value"
+    /// Splitter `:` of map item is invalid.
+    MapSplitter => "map splitter", "`:`"
+    /// The key is defined again in the same map, see
+    /// [`Loader::key_policy`](super::Loader::key_policy).
+    DuplicatedKey => "duplicated map key", "unique map key"
+    /// The end of map is invalid, may caused by the last value (like wrapped
+    /// string).
+    MapTerminator => "map terminator", "new line of map item"
+    /// The single quoted string is not closed.
+    UnclosedQuote => "quoted string", "`'`"
+    /// The double quoted string is not closed.
+    UnclosedDoubleQuote => "double quoted string", "`\"`"
+    /// The escape character in double quoted string is invalid.
+    BadEscape => "escape character", "escape character"
+    /// The leading empty lines of the block scalar are more indented than the
+    /// first non-empty line.
+    BadIndent => "block scalar indentation", "less indented leading empty lines"
+    /// The anchor is defined twice on the same node.
+    DuplicatedAnchor => "duplicated anchor definition", "single anchor"
+    /// The anchor is defined again, see
+    /// [`AnchorPolicy::Error`](super::AnchorPolicy::Error).
+    RedefinedAnchor => "anchor redefinition", "unique anchor name"
+    /// The alias is used before the anchor definition.
+    UndefinedAnchor => "anchor referenced before definition", "defined anchor"
+    /// The aliases in a document are more than the limit, see
+    /// [`Loader::max_aliases`](super::Loader::max_aliases).
+    TooManyAliases => "alias limit", "fewer aliases"
+    /// The documents in a stream are more than the limit, see
+    /// [`ParseLimits`](super::ParseLimits).
+    TooManyDocuments => "document limit", "fewer documents"
+    /// The items in a map are more than the limit, see
+    /// [`ParseLimits`](super::ParseLimits).
+    TooManyKeys => "map key limit", "fewer map keys"
+    /// The collections are nested deeper than the limit, see
+    /// [`ParseLimits`](super::ParseLimits).
+    TooDeep => "nesting depth limit", "shallower nesting"
+    /// The scalar contains a non-printable character, see
+    /// [`Loader::allow_non_printable`](super::Loader::allow_non_printable).
+    NonPrintable => "non-printable character", "printable character or escape"
+    /// The tagged scalar is rejected by the handler of the tag, see
+    /// [`Loader::tag_handler`](super::Loader::tag_handler).
+    TagValue => "tagged value", "value accepted by the tag handler"
+}
+
 /// The error of parser handling, returned by [`Parser`](super::Parser).
 ///
 /// Please see [module level document](super) for more error information.
+///
+/// The enum and the [`PError::Terminate`] fields are non-exhaustive, use
+/// [`PError::terminate`] to create the errors.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum PError {
     /// If parser mismatched, just choose another one.
     Mismatch,
     /// The parser is the only one can be matched.
+    #[non_exhaustive]
     Terminate {
-        /// Name of sub-parser group.
-        name: &'static str,
+        /// Kind of the error.
+        kind: ErrorKind,
+        /// The character at the error position, `None` for the end of
+        /// document.
+        found: Option<char>,
         /// Document position.
+        pos: u64,
+        /// Indicated message of the position, see
+        /// [`indicated_msg`](crate::indicated_msg).
         msg: String,
     },
 }
 
 impl PError {
+    /// Create a [`PError::Terminate`] error at the position of the document.
+    ///
+    /// ```
+    /// use yaml_peg::parser::{ErrorKind, PError};
+    ///
+    /// let e = PError::terminate(ErrorKind::MapKey, b"a: b\n? c", 5);
+    /// assert_eq!(e.kind(), Some(ErrorKind::MapKey));
+    /// assert_eq!(e.to_string(), "invalid map key at byte 5: \n\n2:1\n? c\n^");
+    /// ```
+    pub fn terminate(kind: ErrorKind, doc: &[u8], pos: u64) -> Self {
+        let food = usize::try_from(pos)
+            .ok()
+            .and_then(|pos| doc.get(pos..))
+            .unwrap_or_default();
+        Self::Terminate {
+            kind,
+            found: String::from_utf8_lossy(&food[..food.len().min(4)])
+                .chars()
+                .next(),
+            pos,
+            msg: crate::indicated_msg(doc, pos),
+        }
+    }
+
     /// A "than" function for the error handling, execute the given function
     /// when mismatched.
     pub fn or<R, F>(self, f: F) -> Result<R, Self>
@@ -32,14 +220,30 @@ impl PError {
             Self::Terminate { .. } => Err(self),
         }
     }
+
+    /// Return the error kind, `None` if mismatched.
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            Self::Mismatch => None,
+            Self::Terminate { kind, .. } => Some(*kind),
+        }
+    }
 }
 
+/// The error kind and the byte position, then the indicated message.
+///
+/// ```
+/// use yaml_peg::{parse, repr::RcRepr};
+///
+/// let e = parse::<RcRepr>("a: [b").unwrap_err();
+/// assert_eq!(e.to_string(), "invalid flow collection terminator at byte 5: \n\n1:6\na: [b\n     ^");
+/// ```
 impl Display for PError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         match self {
             Self::Mismatch => write!(f, "not matched"),
-            Self::Terminate { name, msg } => {
-                write!(f, "invalid {}: \n\n{}", name, msg)
+            Self::Terminate { kind, pos, msg, .. } => {
+                write!(f, "invalid {} at byte {}: \n\n{}", kind.name(), pos, msg)
             }
         }
     }
@@ -47,3 +251,54 @@ impl Display for PError {
 
 #[cfg(feature = "std")]
 impl std::error::Error for PError {}
+
+/// The error of [`load_layered`](super::load_layered), the parsing error of
+/// a source.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct LayerError {
+    /// The index of the source.
+    pub index: usize,
+    /// The parsing error.
+    pub error: PError,
+}
+
+impl LayerError {
+    /// Create the error.
+    pub fn new(index: usize, error: PError) -> Self {
+        Self { index, error }
+    }
+}
+
+impl Display for LayerError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "source {}: {}", self.index, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LayerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Converted as [`InvalidData`](std::io::ErrorKind::InvalidData) error.
+///
+/// ```
+/// use yaml_peg::{parse, repr::RcRepr};
+///
+/// fn load(doc: &str) -> std::io::Result<usize> {
+///     Ok(parse::<RcRepr>(doc)?.len())
+/// }
+///
+/// assert_eq!(load("a: b").unwrap(), 1);
+/// let e = load("a: [b").unwrap_err();
+/// assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+/// ```
+#[cfg(feature = "std")]
+impl From<PError> for std::io::Error {
+    fn from(e: PError) -> Self {
+        Self::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
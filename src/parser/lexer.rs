@@ -0,0 +1,340 @@
+use super::*;
+
+/// The kind of [`Token`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TokenKind {
+    /// The leading white spaces of a line.
+    Indent,
+    /// The white spaces between the tokens.
+    Whitespace,
+    /// The line break.
+    Newline,
+    /// The comment from `#` to the line end.
+    Comment,
+    /// The directive line, such as `%YAML 1.2`.
+    Directive,
+    /// The document start marker `---`.
+    DocStart,
+    /// The document end marker `...`.
+    DocEnd,
+    /// The sequence entry indicator `-`.
+    Dash,
+    /// The complex mapping key indicator `?`.
+    Question,
+    /// The mapping value indicator `:`.
+    Colon,
+    /// The flow collection separator `,`.
+    Comma,
+    /// `[`
+    SeqStart,
+    /// `]`
+    SeqEnd,
+    /// `{`
+    MapStart,
+    /// `}`
+    MapEnd,
+    /// The anchor definition, such as `&a`.
+    Anchor,
+    /// The alias, such as `*a`.
+    Alias,
+    /// The tag, such as `!!str` and `!<tag:x>`.
+    Tag,
+    /// The plain scalar, a multi-line plain scalar is split by lines.
+    Plain,
+    /// The single quoted scalar.
+    SingleQuoted,
+    /// The double quoted scalar.
+    DoubleQuoted,
+    /// The literal block scalar, includes its header and content lines.
+    Literal,
+    /// The folded block scalar, includes its header and content lines.
+    Folded,
+    /// The unknown character or the unclosed quoted scalar and verbatim
+    /// tag, the scanning is continued at the next character or the line end.
+    Error,
+}
+
+/// A token of the [`Lexer`], with its source range in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Token {
+    /// Kind of the token.
+    pub kind: TokenKind,
+    /// The start position.
+    pub start: u64,
+    /// The end position.
+    pub end: u64,
+}
+
+/// The scanner of the YAML tokens, without building the nodes.
+///
+/// The tokens cover the whole document, includes the white spaces and the
+/// comments, so syntax highlighters and formatters can rebuild the text by
+/// the token ranges. The errors of the structures are not checked.
+///
+/// ```
+/// use yaml_peg::parser::{Lexer, TokenKind::*};
+///
+/// let doc = "- a: &x [1, \"b\"] # c\n";
+/// let tokens = Lexer::new(doc.as_bytes())
+///     .filter(|t| t.kind != Whitespace)
+///     .map(|t| (t.kind, &doc[t.start as usize..t.end as usize]))
+///     .collect::<Vec<_>>();
+/// assert_eq!(
+///     tokens,
+///     [
+///         (Dash, "-"),
+///         (Plain, "a"),
+///         (Colon, ":"),
+///         (Anchor, "&x"),
+///         (SeqStart, "["),
+///         (Plain, "1"),
+///         (Comma, ","),
+///         (DoubleQuoted, "\"b\""),
+///         (SeqEnd, "]"),
+///         (Comment, "# c"),
+///         (Newline, "\n"),
+///     ]
+/// );
+/// ```
+pub struct Lexer<'a> {
+    parser: Parser<'a>,
+    line_start: bool,
+    indent: usize,
+    flow: usize,
+    adjacent: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Create the lexer of the document.
+    pub fn new(doc: &'a [u8]) -> Self {
+        Self {
+            parser: Parser::new(doc),
+            line_start: true,
+            indent: 0,
+            flow: 0,
+            adjacent: false,
+        }
+    }
+
+    fn is_bound(food: &[u8], i: usize) -> bool {
+        matches!(food.get(i), None | Some(b' ' | b'\t' | b'\n' | b'\r'))
+    }
+
+    fn is_flow_indicator(&self, c: Option<&u8>) -> bool {
+        self.flow > 0 && matches!(c, Some(b',' | b'[' | b']' | b'{' | b'}'))
+    }
+
+    /// Move to the end of line, for the error recovery.
+    fn skip_line(&mut self) {
+        self.parser.backward();
+        self.parser
+            .take_while(Parser::not_in(b"\n\r"), TakeOpt::More(1))
+            .unwrap_or_default();
+    }
+
+    fn scan(&mut self) -> TokenKind {
+        let line_start = core::mem::replace(&mut self.line_start, false);
+        let adjacent = core::mem::replace(&mut self.adjacent, false);
+        if self.parser.nl().is_ok() {
+            self.line_start = true;
+            self.indent = 0;
+            return TokenKind::Newline;
+        }
+        if self.parser.ws(TakeOpt::More(1)).is_ok() {
+            if line_start {
+                self.indent = self.parser.pos - self.parser.eaten;
+                return TokenKind::Indent;
+            }
+            return TokenKind::Whitespace;
+        }
+        let food = self.parser.food();
+        let col0 = line_start && self.flow == 0;
+        match food[0] {
+            b'#' => {
+                self.parser.comment().unwrap_or_default();
+                TokenKind::Comment
+            }
+            b'%' if col0 => {
+                self.parser
+                    .take_while(Parser::not_in(b"\n\r"), TakeOpt::More(1))
+                    .unwrap_or_default();
+                TokenKind::Directive
+            }
+            b'-' if col0 && food.starts_with(b"---") && Self::is_bound(food, 3) => {
+                self.parser.pos += 3;
+                TokenKind::DocStart
+            }
+            b'.' if col0 && food.starts_with(b"...") && Self::is_bound(food, 3) => {
+                self.parser.pos += 3;
+                TokenKind::DocEnd
+            }
+            b'-' if Self::is_bound(food, 1) => {
+                self.parser.pos += 1;
+                TokenKind::Dash
+            }
+            b'?' if Self::is_bound(food, 1) => {
+                self.parser.pos += 1;
+                TokenKind::Question
+            }
+            b':' if Self::is_bound(food, 1)
+                || self.flow > 0 && (adjacent || self.is_flow_indicator(food.get(1))) =>
+            {
+                self.parser.pos += 1;
+                TokenKind::Colon
+            }
+            b',' if self.flow > 0 => {
+                self.parser.pos += 1;
+                TokenKind::Comma
+            }
+            b'[' | b'{' => {
+                self.parser.pos += 1;
+                self.flow += 1;
+                if food[0] == b'[' {
+                    TokenKind::SeqStart
+                } else {
+                    TokenKind::MapStart
+                }
+            }
+            b']' | b'}' if self.flow > 0 => {
+                self.parser.pos += 1;
+                self.flow -= 1;
+                self.adjacent = true;
+                if food[0] == b']' {
+                    TokenKind::SeqEnd
+                } else {
+                    TokenKind::MapEnd
+                }
+            }
+            // Rewind if the name is missing, then it is a plain scalar
+            b'&' if matches!(self.parser.opt(Parser::anchor), Ok(Some(_))) => TokenKind::Anchor,
+            b'*' if matches!(self.parser.opt(Parser::anchor_use), Ok(Some(_))) => TokenKind::Alias,
+            b'!' => {
+                if food.get(1) == Some(&b'<') {
+                    self.parser
+                        .take_while(Parser::not_in(b">\n\r"), TakeOpt::More(1))
+                        .unwrap_or_default();
+                    // The unclosed verbatim tag runs to the line end
+                    if self.parser.food().first() != Some(&b'>') {
+                        return TokenKind::Error;
+                    }
+                    self.parser.pos += 1;
+                } else {
+                    let flow = self.flow > 0;
+                    self.parser
+                        .take_while(
+                            |c| !(c.is_ascii_whitespace() || flow && b",[]{}".contains(c)),
+                            TakeOpt::More(1),
+                        )
+                        .unwrap_or_default();
+                }
+                TokenKind::Tag
+            }
+            b'\'' | b'"' => {
+                let r = if food[0] == b'"' {
+                    self.parser.string_double_quoted()
+                } else {
+                    self.parser.string_quoted(b'\'', b"''")
+                };
+                if r.is_err() {
+                    self.skip_line();
+                    return TokenKind::Error;
+                }
+                self.adjacent = true;
+                if food[0] == b'"' {
+                    TokenKind::DoubleQuoted
+                } else {
+                    TokenKind::SingleQuoted
+                }
+            }
+            b'|' | b'>' if self.flow == 0 => {
+                self.parser.pos += self.block_len(food);
+                if food[0] == b'|' {
+                    TokenKind::Literal
+                } else {
+                    TokenKind::Folded
+                }
+            }
+            b']' | b'}' | b'@' | b'`' => {
+                self.parser
+                    .take_while_char(|_| true, TakeOpt::One)
+                    .unwrap_or_default();
+                TokenKind::Error
+            }
+            _ => {
+                self.parser.pos += self.plain_len(food);
+                TokenKind::Plain
+            }
+        }
+    }
+
+    /// The length of the plain scalar in the current line.
+    fn plain_len(&self, food: &[u8]) -> usize {
+        let mut i = 1;
+        let mut end = 1;
+        while let Some(c) = food.get(i) {
+            match c {
+                b'\n' | b'\r' => break,
+                b':' if Self::is_bound(food, i + 1) || self.is_flow_indicator(food.get(i + 1)) => {
+                    break
+                }
+                b' ' | b'\t' if food.get(i + 1) == Some(&b'#') => break,
+                b' ' | b'\t' => {}
+                _ if self.is_flow_indicator(Some(c)) => break,
+                _ => end = i + 1,
+            }
+            i += 1;
+        }
+        end
+    }
+
+    /// The length of the block scalar, the content lines are indented more
+    /// than the line of the header.
+    fn block_len(&self, food: &[u8]) -> usize {
+        let line_end = |i: usize| {
+            food[i..]
+                .iter()
+                .position(|c| matches!(c, b'\n' | b'\r'))
+                .map_or(food.len(), |n| i + n)
+        };
+        let mut end = line_end(0);
+        let mut i = end;
+        while i < food.len() {
+            // Skip the line break
+            i += if food[i..].starts_with(b"\r\n") { 2 } else { 1 };
+            let ind = food[i..].iter().take_while(|c| **c == b' ').count();
+            let next = line_end(i);
+            if i + ind == next {
+                // Empty line
+            } else if ind > self.indent {
+                end = next;
+            } else {
+                break;
+            }
+            i = next;
+        }
+        end
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.forward();
+        if self.parser.food().is_empty() {
+            return None;
+        }
+        let start = self.parser.indicator();
+        let mut kind = self.scan();
+        // Skip a character if nothing is scanned, so the scanning never stalls
+        if self.parser.pos == self.parser.eaten {
+            kind = TokenKind::Error;
+            if self.parser.take_while_char(|_| true, TakeOpt::One).is_err() {
+                self.parser.pos += 1;
+            }
+        }
+        self.parser.forward();
+        Some(Token { kind, start, end: self.parser.indicator() })
+    }
+}
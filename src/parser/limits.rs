@@ -0,0 +1,79 @@
+/// The sanity limits of the parsed stream, see [`Loader::limits`].
+///
+/// The untrusted documents may abuse the consumers with a huge number of the
+/// documents, the map keys (hash flooding) or the aliases, the limits abort
+/// the parsing early. The default is no limit, except the nesting depth of
+/// the collections, which is limited to [`ParseLimits::DEFAULT_MAX_DEPTH`]
+/// so the deeply nested documents cannot overflow the stack.
+///
+/// ```
+/// use yaml_peg::parser::{ErrorKind, Loader, ParseLimits};
+///
+/// let limits = ParseLimits::new().max_documents(1).max_keys(2);
+/// let mut loader = Loader::<yaml_peg::repr::RcRepr>::new(b"{a: 1, b: 2, c: 3}").limits(limits);
+/// assert_eq!(loader.parse().unwrap_err().kind(), Some(ErrorKind::TooManyKeys));
+/// let mut loader = Loader::<yaml_peg::repr::RcRepr>::new(b"a\n---\nb").limits(limits);
+/// assert_eq!(loader.parse().unwrap_err().kind(), Some(ErrorKind::TooManyDocuments));
+/// ```
+///
+/// [`Loader::limits`]: super::Loader::limits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseLimits {
+    /// The maximum number of the documents in the stream, raise
+    /// [`ErrorKind::TooManyDocuments`](super::ErrorKind::TooManyDocuments).
+    pub max_documents: Option<usize>,
+    /// The maximum number of the items in each map, raise
+    /// [`ErrorKind::TooManyKeys`](super::ErrorKind::TooManyKeys).
+    pub max_keys: Option<usize>,
+    /// The maximum number of the aliases in each document, raise
+    /// [`ErrorKind::TooManyAliases`](super::ErrorKind::TooManyAliases), see
+    /// [`Loader::max_aliases`](super::Loader::max_aliases).
+    pub max_aliases: Option<usize>,
+    /// The maximum nesting depth of the collections, the root collection is
+    /// at depth 1, raise
+    /// [`ErrorKind::TooDeep`](super::ErrorKind::TooDeep). Setting it to
+    /// `None` may overflow the stack with the untrusted documents.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParseLimits {
+    /// The default nesting depth limit.
+    pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+    /// No limit except the default nesting depth limit.
+    pub const fn new() -> Self {
+        Self {
+            max_documents: None,
+            max_keys: None,
+            max_aliases: None,
+            max_depth: Some(Self::DEFAULT_MAX_DEPTH),
+        }
+    }
+
+    /// Limit the number of the documents in the stream.
+    pub const fn max_documents(self, max_documents: usize) -> Self {
+        Self { max_documents: Some(max_documents), ..self }
+    }
+
+    /// Limit the number of the items in each map.
+    pub const fn max_keys(self, max_keys: usize) -> Self {
+        Self { max_keys: Some(max_keys), ..self }
+    }
+
+    /// Limit the number of the aliases in each document.
+    pub const fn max_aliases(self, max_aliases: usize) -> Self {
+        Self { max_aliases: Some(max_aliases), ..self }
+    }
+
+    /// Limit the nesting depth of the collections.
+    pub const fn max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth: Some(max_depth), ..self }
+    }
+}
@@ -4,60 +4,171 @@
 //! matched successfully; try next or return error when mismatched.
 //!
 //! The [`Loader`] type can simply convert string into [`Node`] type.
+//! The [`Lexer`] type scans the spanned tokens without building the nodes,
+//! for the syntax highlighters and the formatters. The [`StreamLoader`] type
+//! parses a stream fed by the chunks, document by document, and the
+//! [`split_documents`] / [`join_documents`] functions split and join the
+//! streams without parsing.
 //!
 //! Each pattern (the method of [`Parser`] type) is called "sub-parser",
 //! which returns a `Result<T, PError>` type, where `T` is the return type.
+//! The sub-parsers can be composed by the combinators [`Parser::opt`],
+//! [`Parser::alt`], [`Parser::many`], [`Parser::peek`] and [`Parser::not`]
+//! to extend the grammar.
 //!
 //! # Errors
 //!
-//! ## Document
+//! The [`PError::Terminate`] error carries an [`ErrorKind`] with the
+//! position and the character found there, so the caller can react on it
+//! without matching the message.
+//! The error message shows the name of the kind, e.g.
+//! "invalid map value".
+//! Use [`Loader::parse_recover`] to collect the errors of the whole stream.
 //!
-//! **WRONG**: Invalid tag directive will be ignored.
+//! ## Document
 //!
-//! + document splitter: Error about the document splitter `---` / `...`.
-//! + checked version: Version directive `%YAML 1.2` is used again.
-//! + version: Version directive is wrong, must be `1.2`.
+//! + [`ErrorKind::DocumentSplitter`] (document splitter)
+//! + [`ErrorKind::DuplicatedVersion`] (checked version)
+//! + [`ErrorKind::UnknownVersion`] (invalid version)
+//! + [`ErrorKind::DuplicatedTagHandle`] (tag directive)
+//! + [`ErrorKind::UndeclaredTagHandle`] (tag handle)
+//! + [`ErrorKind::InvalidTag`] (tag URI)
+//! + [`ErrorKind::MultipleDocuments`] (single document)
 //!
 //! ## Structure
 //!
 //! ### Flow Array
 //!
-//! + flow sequence item: Item in `[]` bracket is invalid.
+//! + [`ErrorKind::FlowSeqItem`] (flow sequence item)
+//! + [`ErrorKind::UnclosedFlow`] (flow collection terminator)
+//! + [`ErrorKind::FlowIndent`] (flow indentation)
 //!
 //! ### Flow Map
 //!
-//! + flow map key: Key of map item in `{}` bracket is invalid.
-//! + flow map value: Value of map item in `{}` bracket is invalid.
-//! + flow map splitter: Splitter `:` of map item in `{}` bracket is invalid.
+//! + [`ErrorKind::FlowMapKey`] (flow map key)
+//! + [`ErrorKind::FlowMapValue`] (flow map value)
+//! + [`ErrorKind::FlowMapSplitter`] (flow map splitter)
+//! + [`ErrorKind::UnclosedFlow`] (flow collection terminator)
+//! + [`ErrorKind::FlowIndent`] (flow indentation)
 //!
 //! ### Array
 //!
-//! + sequence item: Item behind `-` indicator is invalid.
-//! + sequence terminator: The end of sequence is invalid, may caused by the
-//! last item   (like wrapped string).
+//! + [`ErrorKind::SeqItem`] (sequence item)
+//! + [`ErrorKind::SeqTerminator`] (sequence terminator)
 //!
 //! ### Map
 //!
-//! + map key: Key of map item is invalid.
-//! + map value: Value of map item is invalid.
-//! + map splitter: Splitter `:` of map item is invalid.
-//! + map terminator: The end of map is invalid, may caused by the last value
-//!   (like wrapped string).
+//! + [`ErrorKind::MapKey`] (map key)
+//! + [`ErrorKind::MapValue`] (map value)
+//! + [`ErrorKind::MapSplitter`] (map splitter)
+//! + [`ErrorKind::DuplicatedKey`] (duplicated map key)
+//! + [`ErrorKind::MapTerminator`] (map terminator)
+//! + [`ErrorKind::InconsistentIndent`] (indentation)
+//!
+//! ## Scalar
+//!
+//! + [`ErrorKind::UnclosedQuote`] (quoted string)
+//! + [`ErrorKind::UnclosedDoubleQuote`] (double quoted string)
+//! + [`ErrorKind::BadEscape`] (escape character)
+//! + [`ErrorKind::BadIndent`] (block scalar indentation)
+//! + [`ErrorKind::NonPrintable`] (non-printable character)
+//! + [`ErrorKind::TagValue`] (tagged value)
+//!
+//! ## Anchor
+//!
+//! + [`ErrorKind::DuplicatedAnchor`] (duplicated anchor definition)
+//! + [`ErrorKind::UndefinedAnchor`] (anchor referenced before definition)
+//! + [`ErrorKind::RedefinedAnchor`] (anchor redefinition)
+//! + [`ErrorKind::TooManyAliases`] (alias limit)
+//!
+//! ## Limit
+//!
+//! + [`ErrorKind::TooManyDocuments`] (document limit)
+//! + [`ErrorKind::TooManyKeys`] (map key limit)
+//! + [`ErrorKind::TooDeep`] (nesting depth limit)
+//!
+//! ## Extension
+//!
+//! + [`ErrorKind::Custom`] (the name given by the custom sub-parser)
+//!
+//! # Warnings
+//!
+//! The non-fatal diagnostics are recorded as [`Warning`] instead of
+//! stopping the parser, see [`Parser::warnings`].
+//!
+//! + [`WarningKind::UnknownDirective`] and
+//!   [`WarningKind::InvalidTagDirective`]: The directive is ignored. The
+//!   directives other than `%YAML` and `%TAG` are reserved, e.g., `%FOO bar`.
+//! + [`WarningKind::UnsupportedVersion`] and [`WarningKind::Yaml11Version`]:
+//!   The version is accepted by [`VersionPolicy`] but resolved with 1.2
+//!   schema.
+//! + [`WarningKind::Yaml11Scalar`]: The plain scalar like `yes` or `010` has
+//!   a different meaning in YAML 1.1.
+//! + [`WarningKind::RedefinedAnchor`]: The anchor is defined again, see
+//!   [`AnchorPolicy`].
+//! + [`WarningKind::NonPrintable`]: The scalar contains a non-printable
+//!   character, see [`Loader::allow_non_printable`].
+//!
+//! The flow collections can be wrapped into multiple lines with comments,
+//! but the lines must be indented more than the parent block collection,
+//! otherwise [`ErrorKind::FlowIndent`] is raised. The trailing comma is
+//! accepted, see [`Loader::allow_trailing_comma`].
+//!
+//! # Tracing
+//!
+//! Enable `trace` feature to report the grammar rules entered and exited
+//! to a callback, see [`Loader::trace`]. Enable `profile` feature to count
+//! and time the rules, see [`Parser::profile`].
+//!
+//! # Panics
+//!
+//! The parser never panics on arbitrary bytes, the invalid documents are
+//! always returned as [`PError`]. The deeply nested documents are rejected by
+//! the nesting depth limit before they overflow the stack, see
+//! [`ParseLimits::max_depth`].
+//! This is checked by the fuzz target in the `fuzz` directory of the
+//! repository, run it with `cargo fuzz run parse`.
+#[cfg(feature = "profile")]
+pub use self::profile::RuleStats;
+#[cfg(feature = "trace")]
+pub use self::trace::{Trace, TraceEvent};
 pub use self::{
-    base::{Parser, TakeOpt},
-    error::{PError, PResult},
+    anchors::{Anchors, ExpandError},
+    base::{Checkpoint, Parser, TakeOpt, VersionPolicy},
+    error::{ErrorKind, LayerError, PError, PResult},
+    lexer::{Lexer, Token, TokenKind},
+    limits::ParseLimits,
+    schema::{BoolSchema, NullSchema},
+    source_map::{SourceMap, Span},
+    sources::{SourceId, Sources},
+    stream::{join_documents, split_documents, StreamLoader},
+    warning::{Warning, WarningKind},
 };
 use crate::{repr::Repr, *};
 use alloc::{
-    collections::BTreeMap,
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    format,
     string::{String, ToString},
     vec,
     vec::Vec,
 };
 use core::ops::{Deref, DerefMut};
 
+mod anchors;
 mod base;
 mod error;
+mod lexer;
+mod limits;
+#[cfg(feature = "profile")]
+mod profile;
+mod schema;
+mod source_map;
+mod sources;
+mod stream;
+#[cfg(feature = "trace")]
+mod trace;
+mod warning;
 
 macro_rules! tag_prefix {
     () => {
@@ -67,8 +178,86 @@ macro_rules! tag_prefix {
 
 pub(crate) use tag_prefix;
 
-/// The type of anchor recorder in the [`Loader`].
-pub type Anchors<R> = BTreeMap<String, Node<R>>;
+/// The policy of the anchor defined twice in a document, see
+/// [`Loader::anchor_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnchorPolicy {
+    /// The aliases refer to the latest definition, the YAML standard
+    /// behavior.
+    #[default]
+    LastWins,
+    /// The aliases refer to the first definition, the redefinitions are
+    /// ignored.
+    FirstWins,
+    /// Raise [`ErrorKind::RedefinedAnchor`] error, the message shows the
+    /// positions of both definitions.
+    Error,
+}
+
+/// The policy of the duplicated map keys, see [`Loader::key_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// The value of the later key replaces the former one.
+    #[default]
+    LastWins,
+    /// Raise [`ErrorKind::DuplicatedKey`] error if the keys have the same
+    /// resolved value, so `1`, `01` and `0x1` are the same integer but `"1"`
+    /// is a string. The values are resolved by the schema of the document.
+    Value,
+    /// Raise [`ErrorKind::DuplicatedKey`] error if the keys have the same
+    /// text, like the failsafe schema, so `1` and `"1"` are the same key but
+    /// `01` is another one. The other keys are compared by value.
+    Text,
+}
+
+/// Return true if the character is allowed in the YAML stream, the
+/// `c-printable` production.
+fn is_printable(c: char) -> bool {
+    matches!(c,
+        '\t' | '\n' | '\r' | ' '..='~' | '\u{85}' | '\u{A0}'..='\u{D7FF}'
+        | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// Return true if the character is allowed in the tag URIs, the `ns-uri-char`
+/// production without the `%` escapes.
+pub(crate) fn is_uri_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"-#;/?:@&=+$,_.!~*'()[]".contains(&c)
+}
+
+/// Return true if the character is allowed in the tag shorthands, the
+/// `ns-tag-char` production without the `%` escapes.
+pub(crate) fn is_tag_char(c: u8) -> bool {
+    is_uri_char(c) && !b"!,[]".contains(&c)
+}
+
+/// Return true if the tag begins with a URI scheme, such as `tag:`.
+pub(crate) fn is_global_tag(tag: &str) -> bool {
+    let Some((scheme, _)) = tag.split_once(':') else {
+        return false;
+    };
+    scheme
+        .bytes()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || b"+-.".contains(&c))
+}
+
+/// Write the characters of the tag with the `%` escapes, the reverse of the
+/// tag parsing.
+pub(crate) fn escape_tag(tag: &str, verbatim: bool) -> String {
+    let mut s = String::with_capacity(tag.len());
+    for c in tag.bytes() {
+        if is_uri_char(c) && (verbatim || is_tag_char(c)) {
+            s.push(c as char);
+        } else {
+            s += &format!("%{c:02X}");
+        }
+    }
+    s
+}
+
 /// The default prefix of the YAML sub tag.
 pub const DEFAULT_PREFIX: &str = tag_prefix!();
 
@@ -92,8 +281,10 @@ pub const DEFAULT_PREFIX: &str = tag_prefix!();
 /// + They will move the current cursor if matched.
 /// + Returned value:
 ///     + `Result<(), PError>` represents the sub-parser can be matched and
-/// mismatched.     + [`PError`] represents the sub-parser can be totally
-/// breaked when mismatched. + Use `?` to match a condition.
+///       mismatched.
+///     + [`PError`] represents the sub-parser can be totally breaked when
+///       mismatched.
+/// + Use `?` to match a condition.
 /// + Use [`Result::unwrap_or_default`] to match an optional condition.
 /// + Method [`Parser::forward`] is used to move on.
 /// + Method [`Parser::text`] is used to get the matched string.
@@ -102,20 +293,140 @@ pub struct Loader<'a, R: Repr> {
     /// Parser base.
     pub parser: Parser<'a>,
     cyclic_mode: bool,
+    bool_schema: BoolSchema,
+    null_schema: NullSchema,
+    anchor_policy: AnchorPolicy,
+    key_policy: KeyPolicy,
+    global_anchors: bool,
     anchors: Vec<Anchors<R>>,
+    limits: ParseLimits,
+    aliases: BTreeSet<u64>,
+    allow_non_printable: bool,
+    allow_trailing_comma: bool,
+    flow_min: Option<usize>,
+    flow_lens: BTreeMap<usize, usize>,
+    block_ind: Vec<usize>,
+    depth: usize,
     doc_ind: usize,
+    source_map: Option<SourceMap<R>>,
+    meta: Option<MetaFn<'a, R>>,
+    tag_handlers: BTreeMap<String, TagFn<'a, R>>,
+    errors: Option<Vec<PError>>,
 }
 
+/// The callback of [`Loader::meta`].
+type MetaFn<'a, R> = Box<dyn FnMut(&Node<R>) -> <R as Repr>::Meta + 'a>;
+/// The callback of [`Loader::tag_handler`].
+type TagFn<'a, R> = Box<dyn FnMut(&str) -> Result<Yaml<R>, String> + 'a>;
+
 impl<'a, R: Repr> Loader<'a, R> {
     /// Create YAML loader includes a parser.
     pub fn new(doc: &'a [u8]) -> Self {
         Self {
             parser: Parser::new(doc),
             cyclic_mode: false,
+            bool_schema: BoolSchema::default(),
+            null_schema: NullSchema::default(),
+            anchor_policy: AnchorPolicy::default(),
+            key_policy: KeyPolicy::default(),
+            global_anchors: false,
             anchors: Vec::new(),
+            limits: ParseLimits::new(),
+            aliases: BTreeSet::new(),
+            allow_non_printable: false,
+            allow_trailing_comma: true,
+            flow_min: None,
+            flow_lens: BTreeMap::new(),
+            block_ind: Vec::new(),
+            depth: 0,
             doc_ind: 0,
+            source_map: None,
+            meta: None,
+            tag_handlers: BTreeMap::new(),
+            errors: None,
         }
     }
+
+    /// Create the metadata of each parsed node by the callback, see
+    /// [`Repr::Meta`]. The callback is called before the anchor is recorded,
+    /// so the aliases share the metadata.
+    ///
+    /// The nodes have the default metadata if the callback is not set.
+    pub fn meta<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&Node<R>) -> R::Meta + 'a,
+    {
+        self.meta = Some(Box::new(f));
+        self
+    }
+
+    /// Convert the scalars with the tag by the callback, for the schema
+    /// annotations embedded in the document, such as `!u32 12`.
+    ///
+    /// The tag is the resolved name as [`Node::tag`], e.g., `u32` for
+    /// `!u32`. The callback receives the scalar value and returns the
+    /// replaced value, or the reason that raises [`ErrorKind::TagValue`]
+    /// error. The tag is kept on the node. The callback may be called again
+    /// for the same scalar when the parser backtracks.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{ErrorKind, Loader}, repr::RcRepr, Yaml};
+    ///
+    /// let u32_handler = |s: &str| match s.parse::<u32>() {
+    ///     Ok(n) => Ok(Yaml::Int(n.to_string())),
+    ///     Err(e) => Err(e.to_string()),
+    /// };
+    /// let n = Loader::<RcRepr>::new(b"a: !u32 '12'")
+    ///     .tag_handler("u32", u32_handler)
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(n, [node!({"a" => 12})]);
+    /// assert_eq!(n[0]["a"].tag(), "u32");
+    /// let e = Loader::<RcRepr>::new(b"a: !u32 -1")
+    ///     .tag_handler("u32", u32_handler)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), Some(ErrorKind::TagValue));
+    /// ```
+    pub fn tag_handler<F>(mut self, tag: &str, f: F) -> Self
+    where
+        F: FnMut(&str) -> Result<Yaml<R>, String> + 'a,
+    {
+        self.tag_handlers.insert(tag.to_string(), Box::new(f));
+        self
+    }
+
+    /// Report the grammar rules entered and exited to the callback, for
+    /// debugging why a document fails to parse.
+    ///
+    /// The rules are the sub-parsers of the nodes: `doc`, `scalar`,
+    /// `scalar_term`, `seq`, `map`, `seq_flow` and `map_flow`. The callback
+    /// can forward the records to a logger.
+    ///
+    /// ```
+    /// use yaml_peg::parser::{Loader, TraceEvent};
+    ///
+    /// let mut rules = Vec::new();
+    /// Loader::<yaml_peg::repr::RcRepr>::new(b"[a]")
+    ///     .trace(|t| {
+    ///         if t.event == TraceEvent::Matched {
+    ///             rules.push((t.rule, t.depth, t.pos));
+    ///         }
+    ///     })
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(rules.contains(&("seq_flow", 4, 3)));
+    /// assert_eq!(rules.last(), Some(&("doc", 0, 3)));
+    /// ```
+    #[cfg(feature = "trace")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "trace")))]
+    pub fn trace<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&Trace) + 'a,
+    {
+        self.parser.tracer = Some(Box::new(f));
+        self
+    }
 }
 
 /// The basic implementation.
@@ -146,47 +457,481 @@ impl<R: Repr> Loader<'_, R> {
         Self { cyclic_mode, ..self }
     }
 
+    /// Set the policy of the `%YAML` directive.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{Loader, VersionPolicy}};
+    ///
+    /// let doc = b"%YAML 1.1\n---\n[yes, 010]";
+    /// let n = Loader::new(doc).parse().unwrap();
+    /// assert_eq!(n, vec![node!(["yes", 10])]);
+    /// let n = Loader::new(doc)
+    ///     .version_policy(VersionPolicy::Resolve)
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(n, vec![node!([true, 8])]);
+    /// assert!(Loader::<yaml_peg::repr::RcRepr>::new(doc)
+    ///     .version_policy(VersionPolicy::Strict)
+    ///     .parse()
+    ///     .is_err());
+    /// ```
+    pub fn version_policy(mut self, policy: VersionPolicy) -> Self {
+        self.parser.version_policy = policy;
+        self
+    }
+
+    /// Shift the positions of the nodes and the errors by the offset, see
+    /// [`Parser::offset`].
+    pub fn offset(self, offset: u64) -> Self {
+        Self { parser: self.parser.offset(offset), ..self }
+    }
+
+    /// Set the boolean resolution of plain scalars.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{BoolSchema, Loader}};
+    ///
+    /// let doc = b"[no, NO, true]";
+    /// let n = Loader::new(doc).parse().unwrap();
+    /// assert_eq!(n, vec![node!(["no", "NO", true])]);
+    /// let n = Loader::new(doc).bool_schema(BoolSchema::Yaml11).parse().unwrap();
+    /// assert_eq!(n, vec![node!([false, false, true])]);
+    /// ```
+    pub fn bool_schema(self, bool_schema: BoolSchema) -> Self {
+        Self { bool_schema, ..self }
+    }
+
+    /// Set the null resolution of scalars.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{Loader, NullSchema}};
+    ///
+    /// let doc = b"{a: ~, b: null, c: NULL, d: }";
+    /// let n = Loader::new(doc).parse().unwrap();
+    /// assert_eq!(n, vec![node!({"a" => (), "b" => (), "c" => (), "d" => ()})]);
+    /// let n = Loader::new(doc).null_schema(NullSchema::JSON).parse().unwrap();
+    /// assert_eq!(n, vec![node!({"a" => "~", "b" => (), "c" => "NULL", "d" => ""})]);
+    /// ```
+    pub fn null_schema(self, null_schema: NullSchema) -> Self {
+        Self { null_schema, ..self }
+    }
+
+    /// Set the policy of the anchor defined twice in a document.
+    ///
+    /// A warning [`WarningKind::RedefinedAnchor`] is recorded for the
+    /// redefinition if the error is not raised.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{AnchorPolicy, ErrorKind, Loader}};
+    ///
+    /// let doc = b"- &a 1\n- &a 2\n- *a";
+    /// let n = Loader::new(doc).parse().unwrap();
+    /// assert_eq!(n, vec![node!([1, 2, 2])]);
+    /// let n = Loader::new(doc)
+    ///     .anchor_policy(AnchorPolicy::FirstWins)
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(n, vec![node!([1, 2, 1])]);
+    /// let e = Loader::<yaml_peg::repr::RcRepr>::new(doc)
+    ///     .anchor_policy(AnchorPolicy::Error)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), Some(ErrorKind::RedefinedAnchor));
+    /// assert!(e.to_string().ends_with("first defined at 1:6\n- &a 1\n     ^"));
+    /// ```
+    pub fn anchor_policy(self, anchor_policy: AnchorPolicy) -> Self {
+        Self { anchor_policy, ..self }
+    }
+
+    /// Set the policy of the duplicated map keys, the keys are compared by
+    /// the resolved value or the text.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{ErrorKind, KeyPolicy, Loader}};
+    ///
+    /// let doc = b"1: a\n\"1\": b\n01: c";
+    /// let n = Loader::new(doc).parse().unwrap();
+    /// assert_eq!(n, vec![node!({"1" => "b", 1 => "c"})]);
+    /// let e = Loader::<yaml_peg::repr::RcRepr>::new(doc)
+    ///     .key_policy(KeyPolicy::Value)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), Some(ErrorKind::DuplicatedKey));
+    /// assert!(e.to_string().ends_with("first defined at 1:1\n1: a\n^"));
+    /// let e = Loader::<yaml_peg::repr::RcRepr>::new(doc)
+    ///     .key_policy(KeyPolicy::Text)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert!(e.to_string().starts_with("invalid duplicated map key at byte 5"));
+    /// ```
+    pub fn key_policy(self, key_policy: KeyPolicy) -> Self {
+        Self { key_policy, ..self }
+    }
+
+    /// Let the anchors defined in a document be visible in the later
+    /// documents of the same stream.
+    ///
+    /// **This is a deviation from the YAML standard**, where each document
+    /// has its own anchors. Use it only for the tools that expect this
+    /// behavior. The recorded anchors of each document include the anchors
+    /// of the previous documents, and the redefinitions in the later
+    /// documents follow the [`Loader::anchor_policy`].
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::Loader};
+    ///
+    /// let doc = b"a: &x 1\n---\nb: *x";
+    /// assert!(Loader::<yaml_peg::repr::RcRepr>::new(doc).parse().is_err());
+    /// let n = Loader::new(doc).global_anchors(true).parse().unwrap();
+    /// assert_eq!(n, vec![node!({"a" => 1}), node!({"b" => 1})]);
+    /// ```
+    pub fn global_anchors(self, global_anchors: bool) -> Self {
+        Self { global_anchors, ..self }
+    }
+
+    /// Limit the number of the aliases in each document, raise
+    /// [`ErrorKind::TooManyAliases`] error at the first alias over the limit.
+    ///
+    /// The aliases of the untrusted documents may be expanded into a huge
+    /// tree by the consumers (the "billion laughs" attack), this limit
+    /// aborts the parsing early. The default is no limit. See also
+    /// [`Anchors::expand`] to limit the expanded size.
+    ///
+    /// ```
+    /// use yaml_peg::parser::{ErrorKind, Loader};
+    ///
+    /// let doc = b"a: &a [x]\nb: [*a, *a]\nc: [*a, *a]\n";
+    /// assert!(Loader::<yaml_peg::repr::RcRepr>::new(doc).max_aliases(4).parse().is_ok());
+    /// let e = Loader::<yaml_peg::repr::RcRepr>::new(doc)
+    ///     .max_aliases(3)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), Some(ErrorKind::TooManyAliases));
+    /// if let yaml_peg::parser::PError::Terminate { pos, .. } = e {
+    ///     assert_eq!(pos, 30);
+    /// }
+    /// ```
+    pub fn max_aliases(self, max_aliases: usize) -> Self {
+        Self {
+            limits: self.limits.max_aliases(max_aliases),
+            ..self
+        }
+    }
+
+    /// Set the sanity limits of the stream, see [`ParseLimits`]. The default
+    /// is no limit.
+    pub fn limits(self, limits: ParseLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// Accept the non-printable characters in the scalars, such as the C0
+    /// control characters other than tab and line breaks, default to false.
+    ///
+    /// The YAML standard only allows them in the escaped form of the double
+    /// quoted strings, so they raise [`ErrorKind::NonPrintable`] error by
+    /// default. The lenient mode keeps them in the strings for the legacy
+    /// files, and records a [`WarningKind::NonPrintable`] warning.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{ErrorKind, Loader, WarningKind}};
+    ///
+    /// let doc = b"a: b\x07c\nd: \"\\a\"\n";
+    /// let e = Loader::<yaml_peg::repr::RcRepr>::new(doc).parse().unwrap_err();
+    /// assert_eq!(e.kind(), Some(ErrorKind::NonPrintable));
+    /// assert!(e.to_string().starts_with("invalid non-printable character at byte 4"));
+    /// let mut loader = Loader::new(doc).allow_non_printable(true);
+    /// assert_eq!(loader.parse().unwrap(), [node!({"a" => "b\x07c", "d" => "\x07"})]);
+    /// assert_eq!(loader.warnings()[0].kind, WarningKind::NonPrintable);
+    /// ```
+    pub fn allow_non_printable(self, allow_non_printable: bool) -> Self {
+        Self { allow_non_printable, ..self }
+    }
+
+    /// Enforce the consistent indent size of the nested block collections,
+    /// default to false.
+    ///
+    /// The indent size of each level is detected from the document, so the
+    /// levels can be indented by different sizes. In the strict mode, the
+    /// first nested level decides the size of the whole stream, and the
+    /// other sizes raise [`ErrorKind::InconsistentIndent`] error. The
+    /// zero-indented sequences and the compact collections like `- - a` are
+    /// not counted.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{ErrorKind, Loader}};
+    ///
+    /// let doc = b"a:\n  b:\n      c: d\n";
+    /// assert_eq!(Loader::new(doc).parse().unwrap(), [node!({"a" => node!({"b" => node!({"c" => "d"})})})]);
+    /// let e = Loader::<yaml_peg::repr::RcRepr>::new(doc)
+    ///     .strict_indent(true)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), Some(ErrorKind::InconsistentIndent));
+    /// ```
+    pub fn strict_indent(mut self, strict_indent: bool) -> Self {
+        self.parser.strict_indent = strict_indent;
+        self
+    }
+
+    /// Accept the trailing comma in the flow collections, such as `[a, b,]`,
+    /// default to true.
+    ///
+    /// Set to false to raise [`ErrorKind::FlowSeqItem`] or
+    /// [`ErrorKind::FlowMapKey`] error at the trailing comma. The empty
+    /// entries like `[a,,b]` are always rejected.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{ErrorKind, Loader}};
+    ///
+    /// let doc = b"[a, b,]";
+    /// assert_eq!(Loader::new(doc).parse().unwrap(), [node!(["a", "b"])]);
+    /// let e = Loader::<yaml_peg::repr::RcRepr>::new(doc)
+    ///     .allow_trailing_comma(false)
+    ///     .parse()
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), Some(ErrorKind::FlowSeqItem));
+    /// ```
+    pub fn allow_trailing_comma(self, allow_trailing_comma: bool) -> Self {
+        Self { allow_trailing_comma, ..self }
+    }
+
+    /// Record the source ranges of the nodes, for re-serializing the mutated
+    /// tree with the untouched text, see [`Loader::get_source_map`] and
+    /// [`dumper::dump_preserving`].
+    pub fn round_trip(self, round_trip: bool) -> Self {
+        Self {
+            source_map: round_trip.then(SourceMap::new),
+            ..self
+        }
+    }
+
+    fn empty(&self) -> Yaml<R> {
+        if self.null_schema.empty {
+            Yaml::Null
+        } else {
+            Yaml::Str(String::new())
+        }
+    }
+
+    fn is_bool11(&self) -> bool {
+        match self.bool_schema {
+            BoolSchema::Auto => self.is_yaml11(),
+            BoolSchema::Core => false,
+            BoolSchema::Yaml11 => true,
+        }
+    }
+
     /// Consume this loader and return the recorded anchors.
     pub fn get_anchors(self) -> Vec<Anchors<R>> {
         self.anchors
     }
 
+    /// Consume this loader and return the recorded source ranges, it is
+    /// empty if [`Loader::round_trip`] is not enabled.
+    pub fn get_source_map(self) -> SourceMap<R> {
+        self.source_map.unwrap_or_default()
+    }
+
+    /// Create the parsed node, then fill the metadata and record the source
+    /// range.
+    fn new_node(&mut self, yaml: R::Rc, pos: u64, tag: &str, start: u64) -> Node<R> {
+        let mut node = Node::new_repr(yaml, pos, tag);
+        if let Some(f) = &mut self.meta {
+            *node.meta_mut() = f(&node);
+        }
+        let end = self.indicator();
+        if let Some(map) = &mut self.source_map {
+            map.insert(&node, start, end);
+        }
+        node
+    }
+
     /// YAML entry point, return entire doc if exist.
     pub fn parse(&mut self) -> PResult<Vec<Node<R>>> {
+        let docs = self.parse_documents()?;
+        Ok(docs.into_iter().map(|doc| doc.root).collect())
+    }
+
+    /// YAML entry point, return entire doc with their directives and anchors.
+    ///
+    /// The directives after the document end marker `...` will replace the
+    /// previous settings.
+    pub fn parse_documents(&mut self) -> PResult<Vec<Document<R>>> {
+        let mut v = Vec::new();
         loop {
-            match self.context(Parser::directive) {
-                Ok(()) => (),
-                Err(PError::Mismatch) => break,
-                Err(e) => return Err(e),
+            v.push(self.next_document(v.is_empty())?);
+            self.gap(true).unwrap_or_default();
+            if self.food().is_empty() {
+                break;
             }
         }
-        self.gap(true).unwrap_or_default();
-        self.sym_seq(b"---").unwrap_or_default();
-        let mut v = vec![self.doc()?];
+        Ok(v)
+    }
+
+    /// YAML entry point that collects the errors instead of failing at the
+    /// first one, for the linters and the editors.
+    ///
+    /// The broken map items are skipped until the next key at the same or
+    /// lower indentation, and their values are null. The broken documents
+    /// are skipped until the next document marker `---`, and they are not
+    /// returned.
+    ///
+    /// ```
+    /// use yaml_peg::{node, parser::{ErrorKind, Loader}};
+    ///
+    /// let doc = b"a: 1\nb: [x\nc: 'y\n  z'\nd: 'w\n---\n[e\n---\nf\n";
+    /// let (nodes, errors) = Loader::<yaml_peg::repr::RcRepr>::new(doc).parse_recover();
+    /// assert_eq!(nodes, [node!({"a" => 1, "b" => (), "c" => "y z", "d" => ()}), node!("f")]);
+    /// let kinds = errors.iter().map(|e| e.kind().unwrap()).collect::<Vec<_>>();
+    /// assert_eq!(kinds, [ErrorKind::FlowIndent, ErrorKind::UnclosedQuote, ErrorKind::UnclosedFlow]);
+    /// ```
+    pub fn parse_recover(&mut self) -> (Vec<Node<R>>, Vec<PError>) {
+        self.errors = Some(Vec::new());
+        let mut v = Vec::new();
+        let mut first = true;
         loop {
+            match self.next_document(first) {
+                Ok(doc) => v.push(doc.root),
+                Err(e) if e.kind() == Some(ErrorKind::TooManyDocuments) => {
+                    self.record(e);
+                    break;
+                }
+                Err(e) => {
+                    // Drop the anchors of the broken document
+                    self.anchors.truncate(self.doc_ind);
+                    let pos = match &e {
+                        PError::Terminate { pos, .. } => *pos,
+                        PError::Mismatch => self.indicator(),
+                    };
+                    self.record(e);
+                    self.pos = self.skip_document(pos);
+                    self.forward();
+                }
+            }
+            first = false;
             self.gap(true).unwrap_or_default();
             if self.food().is_empty() {
                 break;
             }
-            if self.sym_seq(b"---").is_err() {
-                return self.err("document splitter");
+        }
+        (v, self.errors.take().unwrap_or_default())
+    }
+
+    fn next_document(&mut self, first: bool) -> PResult<Document<R>> {
+        if first || self.food().starts_with(b"%") {
+            if !first {
+                self.reset_directives();
+            }
+            self.directives()?;
+        }
+        self.gap(true).unwrap_or_default();
+        if self.limits.max_documents.is_some_and(|n| self.doc_ind >= n) {
+            return self.err(ErrorKind::TooManyDocuments);
+        }
+        let explicit_start = self.doc_marker(b"---").is_ok();
+        if !first && !explicit_start {
+            return self.err(ErrorKind::DocumentSplitter);
+        }
+        let (root, explicit_end) = self.doc_body()?;
+        Ok(Document {
+            root,
+            anchors: self.anchors[self.doc_ind - 1].clone(),
+            version: self.version.clone(),
+            tags: self.tag_handles(),
+            explicit_start,
+            explicit_end,
+        })
+    }
+
+    /// Record the error of [`Loader::parse_recover`], the error at the same
+    /// position is recorded once since the nodes may be parsed again.
+    fn record(&mut self, e: PError) {
+        let Some(errors) = &mut self.errors else {
+            return;
+        };
+        let PError::Terminate { kind, pos, .. } = &e else {
+            return;
+        };
+        let recorded = errors.iter().any(|old| match old {
+            PError::Terminate { kind: k, pos: p, .. } => k == kind && p == pos,
+            PError::Mismatch => false,
+        });
+        if !recorded {
+            errors.push(e);
+        }
+    }
+
+    /// Skip the broken map item which starts at the position in the recovery
+    /// mode, otherwise return the error.
+    fn recover(&mut self, e: PError, start: usize) -> PResult<()> {
+        if self.errors.is_none() || matches!(e, PError::Mismatch) {
+            return Err(e);
+        }
+        self.record(e);
+        let ind = self.block_ind.last().copied().unwrap_or_default();
+        self.pos = self.skip_indented(start, ind);
+        self.forward();
+        Ok(())
+    }
+
+    fn directives(&mut self) -> PResult<()> {
+        loop {
+            match self.context(Parser::directive) {
+                Ok(()) => (),
+                Err(PError::Mismatch) => break Ok(()),
+                Err(e) => break Err(e),
             }
-            v.push(self.doc()?);
         }
-        Ok(v)
     }
 
     /// Match one doc block.
     pub fn doc(&mut self) -> PResult<Node<R>> {
+        self.doc_body().map(|(root, _)| root)
+    }
+
+    fn doc_body(&mut self) -> PResult<(Node<R>, bool)> {
+        self.rule("doc", Self::doc_inner)
+    }
+
+    fn doc_inner(&mut self) -> PResult<(Node<R>, bool)> {
+        self.aliases.clear();
         self.context(|p| p.bound().unwrap_or_default());
         self.forward();
-        self.anchors.push(Anchors::new());
+        // The properties of the root node may start at the next line
+        let (pos, eaten) = (self.pos, self.eaten);
+        if self.gap(true).is_ok() {
+            self.ws(TakeOpt::More(0)).unwrap_or_default();
+            if !matches!(self.food().first(), Some(b'!' | b'&')) {
+                self.pos = pos;
+                self.eaten = eaten;
+            }
+        }
+        self.forward();
+        let anchors = match self.anchors.last() {
+            Some(anchors) if self.global_anchors => anchors.clone(),
+            _ => Anchors::new(),
+        };
+        self.anchors.push(anchors);
         let ret = self.scalar(0, false, false)?;
+        if let Some(map) = &mut self.source_map {
+            map.insert_root(&ret);
+        }
         self.gap(true).unwrap_or_default();
-        self.sym_seq(b"...").unwrap_or_default();
+        let explicit_end = self.doc_marker(b"...").is_ok();
         self.forward();
         self.doc_ind += 1;
-        Ok(ret)
+        Ok((ret, explicit_end))
+    }
+
+    /// Return true if only white spaces and comment are left.
+    fn is_end(&mut self) -> bool {
+        self.context(|p| {
+            p.comment().unwrap_or_default();
+            p.ws(TakeOpt::More(0)).unwrap_or_default();
+            let end = p.food().is_empty();
+            p.backward();
+            end
+        })
     }
 
     /// Match doc end.
@@ -195,7 +940,7 @@ impl<R: Repr> Loader<'_, R> {
             true
         } else {
             self.context(|p| {
-                let b = p.sym_seq(b"---").is_ok() || p.sym_seq(b"...").is_ok();
+                let b = p.doc_marker(b"---").is_ok() || p.doc_marker(b"...").is_ok();
                 if b {
                     p.backward();
                 }
@@ -206,17 +951,59 @@ impl<R: Repr> Loader<'_, R> {
 
     /// Match scalar.
     pub fn scalar(&mut self, level: usize, map: bool, flow: bool) -> PResult<Node<R>> {
+        self.rule("scalar", |p| p.scalar_inner(level, map, flow))
+    }
+
+    fn scalar_inner(&mut self, level: usize, map: bool, flow: bool) -> PResult<Node<R>> {
+        if !map && !flow && self.key_properties(level) {
+            // The properties belong to the first key of the map
+            let pos = self.indicator();
+            let yaml = self.map(level, map, flow)?;
+            return Ok(self.new_node(yaml, pos, "", pos));
+        }
         self.scalar_node(|p| {
-            if let Ok(s) = p.string_literal(level) {
-                Ok(R::new_rc(Yaml::Str(s)))
-            } else if let Ok(s) = p.string_folded(level) {
-                Ok(R::new_rc(Yaml::Str(s)))
+            p.string_literal(level)
+                .or_else(|e| e.or(|| p.string_folded(level)))
+                .map(|s| R::new_rc(Yaml::Str(s)))
+                .or_else(|e| e.or(|| p.seq(level, map)))
+                .or_else(|e| e.or(|| p.map(level, map, flow)))
+                .or_else(|e| e.or(|| p.scalar_term(level, flow)))
+        })
+    }
+
+    /// Return true if the properties are followed by an implicit key at the
+    /// same line, such as `&a k: v`.
+    fn key_properties(&mut self, level: usize) -> bool {
+        let (pos, eaten) = (self.pos, self.eaten);
+        let mut props = false;
+        while self.anchor().is_ok() || self.tag().is_ok() {
+            props = true;
+            self.forward();
+            if self.bound().is_err() {
+                break;
+            }
+            self.forward();
+        }
+        self.forward();
+        let food = self.food();
+        let b = props
+            && !matches!(food.first(), None | Some(b'\n' | b'\r' | b'#'))
+            && if matches!(food.first(), Some(b'[' | b'{')) {
+                // The plain scalar cannot start with the flow indicators
+                self.seq_flow(level + 1)
+                    .or_else(|e| e.or(|| self.map_flow(level + 1)))
+                    .is_ok()
             } else {
-                p.seq(level, map)
-                    .or_else(|e| e.or(|| p.map(level, map, flow)))
-                    .or_else(|e| e.or(|| p.scalar_term(level, flow)))
+                self.scalar_term(level + 1, false).is_ok()
             }
-        })
+            && !food[..food.len() - self.food().len()]
+                .iter()
+                .any(|c| matches!(c, b'\n' | b'\r'))
+            && self.sym(b':').is_ok()
+            && self.bound().is_ok();
+        self.pos = pos;
+        self.eaten = eaten;
+        b
     }
 
     /// Match flow scalar.
@@ -228,12 +1015,13 @@ impl<R: Repr> Loader<'_, R> {
     where
         F: FnOnce(&mut Self) -> PResult<R::Rc>,
     {
+        let start = self.indicator();
         let mut anchor = self.anchor().unwrap_or_default();
         if !anchor.is_empty() {
             self.bound()?;
         }
         self.forward();
-        let tag = self.tag().unwrap_or_default();
+        let tag = self.tag().or_else(|e| e.or(|| Ok(String::new())))?;
         if !tag.is_empty() {
             self.bound()?;
         }
@@ -244,82 +1032,311 @@ impl<R: Repr> Loader<'_, R> {
                 self.bound()?;
                 anchor = anchor2;
             } else {
-                return self.err("duplicated anchor definition");
+                return self.err(ErrorKind::DuplicatedAnchor);
             }
         }
         self.forward();
         let pos = self.indicator();
+        let food = self.food();
         let yaml = f(self)?;
+        let text = &food[..food.len() - self.food().len()];
+        if let Yaml::Str(_) = &*yaml {
+            self.check_printable(text, pos)?;
+        }
+        let yaml = self.core_tag(yaml, &tag, text);
+        let yaml = self.tag_handle(yaml, &tag, text, pos)?;
         self.forward();
-        let node = Node::new_repr(yaml, pos, &tag);
-        if !anchor.is_empty()
-            && self.anchors[self.doc_ind]
-                .insert(anchor, node.clone())
-                .is_some()
-        {
-            self.err("duplicated anchor definition")
+        let node = self.new_node(yaml, pos, &tag, start);
+        if !anchor.is_empty() {
+            self.insert_anchor(anchor, &node)?;
+        }
+        Ok(node)
+    }
+
+    /// Check the raw text of the scalar at the position.
+    fn check_printable(&mut self, text: &[u8], pos: u64) -> PResult<()> {
+        let s = match core::str::from_utf8(text) {
+            Ok(s) => s,
+            Err(e) => core::str::from_utf8(&text[..e.valid_up_to()]).unwrap_or_default(),
+        };
+        let Some((i, _)) = s.char_indices().find(|(_, c)| !is_printable(*c)) else {
+            return Ok(());
+        };
+        let pos = pos + i as u64;
+        if self.allow_non_printable {
+            self.warn(WarningKind::NonPrintable, pos);
+            Ok(())
         } else {
-            Ok(node)
+            self.err_at(ErrorKind::NonPrintable, pos)
+        }
+    }
+
+    /// Resolve the scalar by the explicit core tag, such as `!!int "1"` and
+    /// `!!str 1`. The other values are kept if they are not matched.
+    fn core_tag(&self, yaml: R::Rc, tag: &str, text: &[u8]) -> R::Rc {
+        let Some(name) = tag.strip_prefix(tag_prefix!()) else {
+            return yaml;
+        };
+        let resolved = match (name, &*yaml) {
+            ("str", Yaml::Null | Yaml::Bool(_) | Yaml::Int(_) | Yaml::Float(_)) => {
+                Yaml::Str(String::from_utf8_lossy(text).trim_end().into())
+            }
+            ("null", Yaml::Str(s)) if s.is_empty() || self.null_schema.is_null(s) => Yaml::Null,
+            ("bool", Yaml::Str(s)) => match s.as_str() {
+                "true" | "True" | "TRUE" => Yaml::Bool(true),
+                "false" | "False" | "FALSE" => Yaml::Bool(false),
+                _ => return yaml,
+            },
+            ("int", Yaml::Str(s)) if crate::yaml::to_i128(s).is_ok() => Yaml::Int(s.clone()),
+            ("float", Yaml::Str(s) | Yaml::Int(s)) if crate::yaml::to_f64(s).is_ok() => {
+                Yaml::Float(s.clone())
+            }
+            _ => return yaml,
+        };
+        R::new_rc(resolved)
+    }
+
+    /// Convert the tagged scalar by the handler of [`Loader::tag_handler`].
+    fn tag_handle(&mut self, yaml: R::Rc, tag: &str, text: &[u8], pos: u64) -> PResult<R::Rc> {
+        let Some(f) = self.tag_handlers.get_mut(tag) else {
+            return Ok(yaml);
+        };
+        let s = match &*yaml {
+            Yaml::Str(s) | Yaml::Int(s) | Yaml::Float(s) => s.clone(),
+            Yaml::Null | Yaml::Bool(_) => String::from_utf8_lossy(text).trim_end().into(),
+            Yaml::Seq(_) | Yaml::Map(_) | Yaml::Alias(_) => return Ok(yaml),
+        };
+        match f(&s) {
+            Ok(yaml) => Ok(R::new_rc(yaml)),
+            Err(reason) => self.err_at(ErrorKind::TagValue, pos).map_err(|e| match e {
+                PError::Terminate { kind, found, pos, msg } => PError::Terminate {
+                    kind,
+                    found,
+                    pos,
+                    msg: format!("{}\n\n{}: {}", msg, tag, reason),
+                },
+                e => e,
+            }),
         }
     }
 
+    fn insert_anchor(&mut self, anchor: String, node: &Node<R>) -> PResult<()> {
+        let pos = node.pos();
+        let anchors = &mut self.anchors[self.doc_ind];
+        // The node may be parsed again in the same position
+        let first = match anchors.get(&anchor) {
+            Some(old) if old.pos() != pos => old.pos(),
+            _ => {
+                anchors.insert(anchor, node.clone());
+                return Ok(());
+            }
+        };
+        match self.anchor_policy {
+            AnchorPolicy::LastWins => {
+                anchors.insert(anchor, node.clone());
+            }
+            AnchorPolicy::FirstWins => (),
+            AnchorPolicy::Error => {
+                return self
+                    .err_at(ErrorKind::RedefinedAnchor, pos)
+                    .map_err(|e| match e {
+                        PError::Terminate { kind, found, pos, msg } => PError::Terminate {
+                            kind,
+                            found,
+                            pos,
+                            msg: format!(
+                                "{}\n\nfirst defined at {}",
+                                msg,
+                                self.indicated_msg(first)
+                            ),
+                        },
+                        e => e,
+                    });
+            }
+        }
+        self.warn(WarningKind::RedefinedAnchor, pos);
+        Ok(())
+    }
+
+    /// Check the duplicated map key by the key policy, the keys of the map
+    /// are recorded with their positions.
+    fn unique_key(&self, keys: &mut BTreeMap<Node<R>, u64>, k: &Node<R>) -> PResult<()> {
+        let key = match (self.key_policy, k.yaml()) {
+            (KeyPolicy::LastWins, _) => return Ok(()),
+            (KeyPolicy::Text, Yaml::Int(s) | Yaml::Float(s)) => Node::from(s.as_str()),
+            _ => k.clone(),
+        };
+        let Some(first) = keys.insert(key, k.pos()) else {
+            return Ok(());
+        };
+        self.err_at(ErrorKind::DuplicatedKey, k.pos())
+            .map_err(|e| match e {
+                PError::Terminate { kind, found, pos, msg } => PError::Terminate {
+                    kind,
+                    found,
+                    pos,
+                    msg: format!("{}\n\nfirst defined at {}", msg, self.indicated_msg(first)),
+                },
+                e => e,
+            })
+    }
+
     /// Match flow scalar terminal.
     pub fn scalar_term(&mut self, level: usize, flow: bool) -> PResult<R::Rc> {
+        self.rule("scalar_term", |p| p.scalar_term_inner(level, flow))
+    }
+
+    fn scalar_term_inner(&mut self, level: usize, flow: bool) -> PResult<R::Rc> {
+        let pos = self.indicator();
         let yaml = if let Ok(s) = self.float() {
             R::new_rc(Yaml::Float(s))
         } else if let Ok(s) = self.sci_float() {
             R::new_rc(Yaml::Float(s))
         } else if let Ok(s) = self.int() {
-            R::new_rc(Yaml::Int(s))
+            R::new_rc(Yaml::Int(if self.is_yaml11() {
+                Self::octal11(s)
+            } else {
+                if Self::octal11(s.clone()) != s {
+                    self.warn(WarningKind::Yaml11Scalar, pos);
+                }
+                s
+            }))
         } else if let Ok(s) = self.anchor_use() {
+            // The alias may be parsed again in the same position
+            self.aliases.insert(pos);
+            if self
+                .limits
+                .max_aliases
+                .is_some_and(|n| self.aliases.len() > n)
+            {
+                return self.err_at(ErrorKind::TooManyAliases, pos);
+            }
             if self.cyclic_mode {
                 R::new_rc(Yaml::Alias(s))
             } else if let Some(node) = self.anchors[self.doc_ind].get(&s) {
                 node.clone_yaml()
             } else {
-                return self.err("anchor referenced before definition");
+                return self.err(ErrorKind::UndefinedAnchor);
             }
-        } else if let Ok(s) = self.string_quoted(b'\'', b"''") {
+        } else if let Some(s) = self
+            .string_quoted(b'\'', b"''")
+            .map(Some)
+            .or_else(|e| e.or(|| Ok(None)))?
+        {
+            R::new_rc(Yaml::Str(s))
+        } else if let Some(s) = self
+            .string_double_quoted()
+            .map(Some)
+            .or_else(|e| e.or(|| Ok(None)))?
+        {
             R::new_rc(Yaml::Str(s))
-        } else if let Ok(s) = self.string_quoted(b'"', b"\\\"") {
-            R::new_rc(Yaml::Str(Parser::escape(&s)))
         } else if let Ok(s) = self.string_plain(level, flow) {
+            let yaml11 = self.is_yaml11();
+            let bool11 = self.is_bool11();
+            if !bool11 && Self::is_bool11_str(&s) || !yaml11 && Self::is_int11(&s) {
+                self.warn(WarningKind::Yaml11Scalar, pos);
+            }
             R::new_rc(match s.as_str() {
-                "~" | "null" | "Null" | "NULL" => Yaml::Null,
+                _ if self.null_schema.is_null(&s) => Yaml::Null,
                 "true" | "True" | "TRUE" => Yaml::Bool(true),
                 "false" | "False" | "FALSE" => Yaml::Bool(false),
-                ".nan" | ".NaN" | ".NAN" => Yaml::Float("NaN".to_string()),
-                ".inf" | ".Inf" | ".INF" => Yaml::Float("inf".to_string()),
-                "-.inf" | "-.Inf" | "-.INF" => Yaml::Float("-inf".to_string()),
+                "y" | "Y" | "yes" | "Yes" | "YES" | "on" | "On" | "ON" if bool11 => {
+                    Yaml::Bool(true)
+                }
+                "n" | "N" | "no" | "No" | "NO" | "off" | "Off" | "OFF" if bool11 => {
+                    Yaml::Bool(false)
+                }
+                ".nan" | ".NaN" | ".NAN" | ".inf" | ".Inf" | ".INF" | "-.inf" | "-.Inf"
+                | "-.INF" => Yaml::Float(s),
+                _ if yaml11 && Self::is_int11(&s) => Yaml::Int(s),
                 _ => Yaml::Str(s),
             })
         } else {
             self.seq_flow(level)
                 .or_else(|e| e.or(|| self.map_flow(level)))
-                .or_else(|e| e.or(|| Ok(R::new_rc(Yaml::Null))))?
+                .or_else(|e| e.or(|| Ok(R::new_rc(self.empty()))))?
         };
         Ok(yaml)
     }
 
+    /// YAML 1.1 octal integer starts with `0`, convert it into `0o` prefix.
+    fn octal11(s: String) -> String {
+        let (sign, digits) = match s.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", s.as_str()),
+        };
+        match digits.strip_prefix('0') {
+            Some(n) if !n.is_empty() && n.bytes().all(|c| (b'0'..b'8').contains(&c)) => {
+                format!("{}0o{}", sign, n)
+            }
+            _ => s,
+        }
+    }
+
+    /// YAML 1.1 boolean which is a string in YAML 1.2.
+    pub(crate) fn is_bool11_str(s: &str) -> bool {
+        matches!(
+            s,
+            "y" | "Y"
+                | "yes"
+                | "Yes"
+                | "YES"
+                | "on"
+                | "On"
+                | "ON"
+                | "n"
+                | "N"
+                | "no"
+                | "No"
+                | "NO"
+                | "off"
+                | "Off"
+                | "OFF"
+        )
+    }
+
+    /// YAML 1.1 integer allows underscores.
+    pub(crate) fn is_int11(s: &str) -> bool {
+        let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+        s.starts_with(|c: char| c.is_ascii_digit())
+            && s.bytes().all(|c| c.is_ascii_digit() || c == b'_')
+    }
+
     /// Match flow sequence.
     pub fn seq_flow(&mut self, level: usize) -> PResult<R::Rc> {
+        self.rule("seq_flow", |p| p.flow(level, Self::seq_flow_inner))
+    }
+
+    fn seq_flow_inner(&mut self, level: usize) -> PResult<R::Rc> {
+        let cap = self.flow_lens.get(&self.pos).copied().unwrap_or_default();
         self.sym(b'[')?;
-        let mut v = vec![];
+        self.nest()?;
+        let min = self.flow_min.unwrap_or_default();
+        let mut v = Vec::with_capacity(cap);
         loop {
-            self.inv(TakeOpt::More(0))?;
+            self.flow_gap(min)?;
             self.forward();
             if self.sym(b']').is_ok() {
+                if !v.is_empty() && !self.allow_trailing_comma {
+                    return self.err_at(ErrorKind::FlowSeqItem, self.indicator() - 1);
+                }
                 break;
             }
+            if self.food().starts_with(b",") {
+                // Empty entry
+                return self.err(ErrorKind::FlowSeqItem);
+            }
             self.forward();
             let n = self
-                .scalar(level + 1, false, true)
-                .or_else(|e| e.or(|| self.err("flow sequence item")))?;
+                .flow_item(level + 1)
+                .or_else(|e| e.or(|| self.err(ErrorKind::FlowSeqItem)))?;
             v.push(n);
-            self.inv(TakeOpt::More(0))?;
+            let end = self.indicator();
+            self.flow_gap(min)?;
+            self.forward();
             if self.sym(b',').is_err() {
-                self.inv(TakeOpt::More(0))?;
-                self.sym(b']')?;
+                self.sym(b']')
+                    .or_else(|e| e.or(|| self.err_at(ErrorKind::UnclosedFlow, end)))?;
                 break;
             }
         }
@@ -329,40 +1346,58 @@ impl<R: Repr> Loader<'_, R> {
 
     /// Match flow map.
     pub fn map_flow(&mut self, level: usize) -> PResult<R::Rc> {
+        self.rule("map_flow", |p| p.flow(level, Self::map_flow_inner))
+    }
+
+    fn map_flow_inner(&mut self, level: usize) -> PResult<R::Rc> {
+        let cap = self.flow_lens.get(&self.pos).copied().unwrap_or_default();
         self.sym(b'{')?;
-        let mut m = vec![];
+        self.nest()?;
+        let min = self.flow_min.unwrap_or_default();
+        let mut m = Vec::with_capacity(cap);
+        let mut keys = BTreeMap::new();
         loop {
-            self.inv(TakeOpt::More(0))?;
+            self.flow_gap(min)?;
             self.forward();
             if self.sym(b'}').is_ok() {
+                if !m.is_empty() && !self.allow_trailing_comma {
+                    return self.err_at(ErrorKind::FlowMapKey, self.indicator() - 1);
+                }
                 break;
             }
+            if self.food().starts_with(b",") {
+                // Empty entry
+                return self.err(ErrorKind::FlowMapKey);
+            }
             self.forward();
-            let k = if self.complex_mapping().is_ok() {
+            self.complex_mapping().unwrap_or_default();
+            self.forward();
+            let k = self
+                .scalar_flow(level + 1, true)
+                .or_else(|e| e.or(|| self.err(ErrorKind::FlowMapKey)))?;
+            if self.limits.max_keys.is_some_and(|n| m.len() >= n) {
+                return self.err_at(ErrorKind::TooManyKeys, k.pos());
+            }
+            self.unique_key(&mut keys, &k)?;
+            self.flow_gap(min)?;
+            self.forward();
+            let v = if self.sym(b':').is_ok() {
+                self.context(|p| p.bound().unwrap_or_default());
                 self.forward();
-                let k = self
-                    .scalar(level + 1, false, true)
-                    .or_else(|e| e.or(|| self.err("flow map key")))?;
-                if self.gap(true).is_ok() {
-                    self.ind(level)?;
-                }
-                k
+                self.scalar(level + 1, false, true)
+                    .or_else(|e| e.or(|| self.err(ErrorKind::FlowMapValue)))?
+            } else if self.food().starts_with(b",") || self.food().starts_with(b"}") {
+                self.null_node()
             } else {
-                self.scalar_flow(level + 1, true)
-                    .or_else(|e| e.or(|| self.err("flow map key")))?
+                return self.err(ErrorKind::FlowMapSplitter);
             };
-            if self.sym(b':').is_err() {
-                return self.err("flow map splitter");
-            }
-            self.context(|p| p.bound().unwrap_or_default());
-            self.forward();
-            let v = self
-                .scalar(level + 1, false, true)
-                .or_else(|e| e.or(|| self.err("flow map value")))?;
             m.push((k, v));
+            let end = self.indicator();
+            self.flow_gap(min)?;
+            self.forward();
             if self.sym(b',').is_err() {
-                self.inv(TakeOpt::More(0))?;
-                self.sym(b'}')?;
+                self.sym(b'}')
+                    .or_else(|e| e.or(|| self.err_at(ErrorKind::UnclosedFlow, end)))?;
                 break;
             }
         }
@@ -370,8 +1405,128 @@ impl<R: Repr> Loader<'_, R> {
         Ok(R::new_rc(m.into_iter().collect()))
     }
 
+    /// Run the flow collection rule, the outermost collection decides the
+    /// minimum indentation of the lines, which must be indented more than
+    /// the parent block collection.
+    fn flow<F>(&mut self, level: usize, f: F) -> PResult<R::Rc>
+    where
+        F: FnOnce(&mut Self, usize) -> PResult<R::Rc>,
+    {
+        let outermost = self.flow_min.is_none();
+        if outermost {
+            self.flow_min = Some(self.block_ind.last().map_or(0, |ind| ind + 1));
+            self.flow_lens = self.count_flow();
+        }
+        let depth = self.depth;
+        let r = f(self, level);
+        self.depth = depth;
+        if outermost {
+            self.flow_min = None;
+            self.flow_lens.clear();
+        }
+        r
+    }
+
+    /// Count the items of the flow collections from the opening bracket by
+    /// the commas, as the capacity hints of the collections at the
+    /// positions. The quoted strings and the comments are skipped.
+    fn count_flow(&self) -> BTreeMap<usize, usize> {
+        let food = self.food();
+        let mut lens = BTreeMap::new();
+        // The position, the number of the commas, and the last item is not empty
+        let mut stack = Vec::<(usize, usize, bool)>::new();
+        let mut i = 0;
+        while i < food.len() {
+            let start = i == 0
+                || matches!(
+                    food[i - 1],
+                    b' ' | b'\t' | b'\n' | b'\r' | b'[' | b'{' | b',' | b':'
+                );
+            match food[i] {
+                b'[' | b'{' => {
+                    if let Some(top) = stack.last_mut() {
+                        top.2 = true;
+                    }
+                    stack.push((self.pos + i, 0, false));
+                }
+                b']' | b'}' => {
+                    let Some((pos, commas, item)) = stack.pop() else {
+                        break;
+                    };
+                    lens.insert(pos, commas + usize::from(item));
+                    if stack.is_empty() {
+                        break;
+                    }
+                }
+                b',' => match stack.last_mut() {
+                    Some(top) => {
+                        top.1 += 1;
+                        top.2 = false;
+                    }
+                    None => break,
+                },
+                b'#' if start => {
+                    i += food[i..]
+                        .iter()
+                        .position(|c| *c == b'\n')
+                        .unwrap_or(food.len() - i);
+                    continue;
+                }
+                q @ (b'"' | b'\'') if start => {
+                    i += 1;
+                    while i < food.len() {
+                        match food[i] {
+                            b'\\' if q == b'"' => i += 1,
+                            b'\'' if q == b'\'' && food.get(i + 1) == Some(&b'\'') => i += 1,
+                            c if c == q => break,
+                            _ => (),
+                        }
+                        i += 1;
+                    }
+                    if let Some(top) = stack.last_mut() {
+                        top.2 = true;
+                    }
+                }
+                b' ' | b'\t' | b'\n' | b'\r' => (),
+                _ => match stack.last_mut() {
+                    Some(top) => top.2 = true,
+                    None => break,
+                },
+            }
+            i += 1;
+        }
+        lens
+    }
+
+    /// Enter the collection after matching its first indicator, raise the
+    /// error if it is nested too deep, see [`ParseLimits::max_depth`].
+    fn nest(&mut self) -> PResult<()> {
+        self.depth += 1;
+        if self.limits.max_depth.is_some_and(|n| self.depth > n) {
+            return self.err(ErrorKind::TooDeep);
+        }
+        Ok(())
+    }
+
+    /// Run the block collection rule, the indentation pushed by the rule
+    /// and the nesting depth are restored after the rule exits.
+    fn block<F>(&mut self, f: F) -> PResult<R::Rc>
+    where
+        F: FnOnce(&mut Self) -> PResult<R::Rc>,
+    {
+        let (len, depth) = (self.block_ind.len(), self.depth);
+        let r = f(self);
+        self.block_ind.truncate(len);
+        self.depth = depth;
+        r
+    }
+
     /// Match sequence.
     pub fn seq(&mut self, level: usize, map: bool) -> PResult<R::Rc> {
+        self.rule("seq", |p| p.block(|p| p.seq_inner(level, map)))
+    }
+
+    fn seq_inner(&mut self, level: usize, map: bool) -> PResult<R::Rc> {
         let mut v = vec![];
         loop {
             self.forward();
@@ -383,12 +1538,20 @@ impl<R: Repr> Loader<'_, R> {
                 } else if self.gap(true).is_ok() {
                     // Root
                     self.ind(level)?;
+                } else {
+                    // Compact
+                    self.ind_compact(level);
                 }
+                self.block_ind.push(self.column());
                 self.sym(b'-')?;
                 self.bound()?;
+                self.nest()?;
             } else {
                 if self.gap(true).is_err() {
-                    return self.err("sequence terminator");
+                    if self.is_end() {
+                        break;
+                    }
+                    return self.err(ErrorKind::SeqTerminator);
                 }
                 if self.doc_end() || self.ind(level).is_err() {
                     break;
@@ -401,7 +1564,7 @@ impl<R: Repr> Loader<'_, R> {
             self.forward();
             let n = self
                 .scalar(level + 1, false, false)
-                .or_else(|e| e.or(|| self.err("sequence item")))?;
+                .or_else(|e| e.or(|| self.err(ErrorKind::SeqItem)))?;
             v.push(n);
         }
         // Keep last wrapping
@@ -411,10 +1574,15 @@ impl<R: Repr> Loader<'_, R> {
 
     /// Match map.
     pub fn map(&mut self, level: usize, map: bool, flow: bool) -> PResult<R::Rc> {
+        self.rule("map", |p| p.block(|p| p.map_inner(level, map, flow)))
+    }
+
+    fn map_inner(&mut self, level: usize, map: bool, flow: bool) -> PResult<R::Rc> {
         let mut m = vec![];
+        let mut keys = BTreeMap::new();
         loop {
             self.forward();
-            let k = if m.is_empty() {
+            let ((k, value, complex), start) = if m.is_empty() {
                 // First item
                 if map {
                     self.gap(true)?;
@@ -422,61 +1590,202 @@ impl<R: Repr> Loader<'_, R> {
                 } else if self.gap(true).is_ok() {
                     // Root
                     self.ind(level)?;
+                } else {
+                    // Compact
+                    self.ind_compact(level);
                 }
                 self.forward();
-                let k = if self.complex_mapping().is_ok() {
-                    self.forward();
-                    let k = self
-                        .scalar(level + 1, true, flow)
-                        .or_else(|e| e.or(|| self.err("map key")))?;
-                    if self.gap(true).is_ok() {
-                        self.ind(level)?;
-                    }
-                    k
+                let start = self.pos;
+                let col = self.column();
+                let item = if let Some((k, value)) = self.complex_key(level, flow)? {
+                    (k, value, true)
                 } else {
-                    self.scalar_flow(level + 1, flow)?
+                    // The plain string of compact node may be wrapped if it is not a key
+                    let k_level = if map || level == 0 { level + 1 } else { level };
+                    let food = self.food();
+                    let k = self.scalar_flow(k_level, flow)?;
+                    // Implicit key is restricted to a single line
+                    let wrapped = food[..food.len() - self.food().len()]
+                        .iter()
+                        .any(|c| matches!(c, b'\n' | b'\r'));
+                    if wrapped || self.sym(b':').is_err() || self.bound().is_err() {
+                        // Return key
+                        return Ok(k.clone_yaml());
+                    }
+                    (k, true, false)
                 };
-                if self.sym(b':').is_err() || self.bound().is_err() {
-                    // Return key
-                    return Ok(k.clone_yaml());
-                }
-                k
+                self.nest()?;
+                self.block_ind.push(col);
+                (item, start)
             } else {
                 if self.gap(true).is_err() {
-                    return self.err("map terminator");
+                    if self.is_end() {
+                        break;
+                    }
+                    let e = self.err::<()>(ErrorKind::MapTerminator).unwrap_err();
+                    self.recover(e, self.pos)?;
+                    continue;
                 }
                 if self.doc_end() || self.ind(level).is_err() {
                     break;
                 }
                 self.forward();
-                let k = if self.complex_mapping().is_ok() {
-                    self.forward();
-                    let k = self
-                        .scalar(level + 1, true, flow)
-                        .or_else(|e| e.or(|| self.err("map key")))?;
-                    if self.gap(true).is_ok() {
-                        self.ind(level)?;
+                let start = self.pos;
+                match self.map_key(level, flow) {
+                    Ok(item) => (item, start),
+                    Err(e) => {
+                        self.recover(e, start)?;
+                        continue;
                     }
-                    k
-                } else {
-                    self.scalar_flow(level + 1, flow)
-                        .or_else(|e| e.or(|| self.err("map key")))?
-                };
-                if self.sym(b':').is_err() || self.bound().is_err() {
-                    return self.err("map splitter");
                 }
-                k
             };
+            if self.limits.max_keys.is_some_and(|n| m.len() >= n) {
+                return self.err_at(ErrorKind::TooManyKeys, k.pos());
+            }
+            self.unique_key(&mut keys, &k)?;
             self.forward();
-            let v = self
-                .scalar(level + 1, true, false)
-                .or_else(|e| e.or(|| self.err("map value")))?;
+            let v = if !value {
+                Ok(self.null_node())
+            } else if complex {
+                self.scalar_compact(level + 1, false)
+                    .or_else(|e| e.or(|| self.err(ErrorKind::MapValue)))
+            } else {
+                self.scalar(level + 1, true, false)
+                    .or_else(|e| e.or(|| self.err(ErrorKind::MapValue)))
+            };
+            let v = match v {
+                Ok(v) => v,
+                Err(e) => {
+                    self.recover(e, start)?;
+                    self.null_node()
+                }
+            };
             m.push((k, v));
         }
         // Keep last wrapping
         self.backward();
         Ok(R::new_rc(m.into_iter().collect()))
     }
+
+    /// Match the key of the map item after the first item, return the key
+    /// and the flags of the value and the complex key.
+    fn map_key(&mut self, level: usize, flow: bool) -> PResult<(Node<R>, bool, bool)> {
+        if let Some((k, value)) = self.complex_key(level, flow)? {
+            return Ok((k, value, true));
+        }
+        let k = self
+            .scalar_flow(level + 1, flow)
+            .or_else(|e| e.or(|| self.err(ErrorKind::MapKey)))?;
+        if self.sym(b':').is_err() || self.bound().is_err() {
+            return self.err(ErrorKind::MapSplitter);
+        }
+        Ok((k, true, false))
+    }
+
+    /// Match the complex key behind `?` indicator, return the key and
+    /// a flag that indicates the value is defined by `:` indicator.
+    fn complex_key(&mut self, level: usize, flow: bool) -> PResult<Option<(Node<R>, bool)>> {
+        if self.complex_mapping().is_err() {
+            return Ok(None);
+        }
+        self.forward();
+        if self.complex_value(level) {
+            // Empty key
+            return Ok(Some((self.null_node(), true)));
+        }
+        let k = self
+            .scalar_compact(level + 1, flow)
+            .or_else(|e| e.or(|| self.err(ErrorKind::MapKey)))?;
+        Ok(Some((k, self.complex_value(level))))
+    }
+
+    /// Match the `:` indicator of the complex mapping.
+    fn complex_value(&mut self, level: usize) -> bool {
+        self.context(|p| {
+            let b = (p.gap(true).is_err() || p.ind(level).is_ok())
+                && p.sym(b':').is_ok()
+                && p.bound().is_ok();
+            if !b {
+                p.backward();
+            }
+            b
+        })
+    }
+
+    /// Match the flow sequence item, which may be a single pair map.
+    fn flow_item(&mut self, level: usize) -> PResult<Node<R>> {
+        let pos = self.indicator();
+        let complex = self.complex_mapping().is_ok();
+        self.forward();
+        let k = self.scalar_flow(level, true)?;
+        let value = self.context(|p| {
+            p.inv(TakeOpt::More(0)).unwrap_or_default();
+            let b = p.sym(b':').is_ok();
+            if !b {
+                p.backward();
+            }
+            b
+        });
+        let v = if value {
+            self.context(|p| p.bound().unwrap_or_default());
+            self.forward();
+            self.scalar_flow(level, true)
+                .or_else(|e| e.or(|| self.err(ErrorKind::FlowSeqItem)))?
+        } else if complex {
+            self.null_node()
+        } else {
+            return Ok(k);
+        };
+        let yaml = R::new_rc([(k, v)].into_iter().collect());
+        Ok(self.new_node(yaml, pos, "", pos))
+    }
+
+    /// Match the node behind the indicators of complex mapping,
+    /// the compact sequence and map can be started at the same line.
+    fn scalar_compact(&mut self, level: usize, flow: bool) -> PResult<Node<R>> {
+        let map = self.context(|p| {
+            let b = p.gap(true).is_ok();
+            p.backward();
+            b
+        });
+        self.scalar(level, map, flow)
+    }
+
+    /// Run the grammar rule, the rule name is used by the tracing and the
+    /// profiling.
+    #[cfg_attr(
+        not(any(feature = "trace", feature = "profile")),
+        allow(unused_variables)
+    )]
+    fn rule<T, F>(&mut self, name: &'static str, f: F) -> PResult<T>
+    where
+        F: FnOnce(&mut Self) -> PResult<T>,
+    {
+        #[cfg(feature = "trace")]
+        self.parser.trace(name, TraceEvent::Enter);
+        #[cfg(feature = "profile")]
+        self.parser.profiler.enter(name);
+        let r = f(self);
+        #[cfg(feature = "profile")]
+        self.parser.profiler.exit(name, r.is_ok());
+        #[cfg(feature = "trace")]
+        self.parser.trace(
+            name,
+            match &r {
+                Ok(_) => TraceEvent::Matched,
+                Err(PError::Mismatch) => TraceEvent::Mismatched,
+                Err(_) => TraceEvent::Failed,
+            },
+        );
+        r
+    }
+
+    /// Create the null node of the omitted value.
+    fn null_node(&mut self) -> Node<R> {
+        let pos = self.indicator();
+        let yaml = R::new_rc(self.empty());
+        self.new_node(yaml, pos, "", pos)
+    }
 }
 
 impl<'a, R: Repr> Deref for Loader<'a, R> {
@@ -497,6 +1806,8 @@ impl<R: Repr> DerefMut for Loader<'_, R> {
 /// [`alloc::sync::Arc`] data holder. Return an sequence of nodes and insert the
 /// anchors automatically.
 ///
+/// This function never panics, see [the module level document](crate::parser#panics).
+///
 /// ```
 /// use yaml_peg::{parse, node};
 ///
@@ -525,6 +1836,27 @@ pub fn parse<R: Repr>(doc: &str) -> Result<Seq<R>, PError> {
     Loader::new(doc.as_bytes()).parse()
 }
 
+/// Parse non-cyclic YAML document into [`alloc::rc::Rc`] or
+/// [`alloc::sync::Arc`] data holder. Return an sequence of documents with their
+/// directives and anchors.
+///
+/// ```
+/// use yaml_peg::{node, parse_documents};
+///
+/// let doc = "
+/// --- &a 1
+/// --- [2]
+/// ";
+/// let docs = parse_documents(doc).unwrap();
+/// assert_eq!(docs.len(), 2);
+/// assert_eq!(docs[0].anchors["a"], node!(1));
+/// assert_eq!(docs[1].root, node!([2]));
+/// assert!(docs[1].anchors.is_empty());
+/// ```
+pub fn parse_documents<R: Repr>(doc: &str) -> Result<Vec<Document<R>>, PError> {
+    Loader::new(doc.as_bytes()).parse_documents()
+}
+
 /// Parse cyclic YAML document into [`alloc::rc::Rc`] or [`alloc::sync::Arc`]
 /// data holder. Return an sequence of nodes and keep the anchors placeholder.
 ///
@@ -543,3 +1875,46 @@ pub fn parse_cyclic<R: Repr>(doc: &str) -> Result<(Seq<R>, Vec<Anchors<R>>), PEr
     let mut loader = Loader::new(doc.as_bytes()).cyclic_mode(true);
     loader.parse().map(|root| (root, loader.get_anchors()))
 }
+
+/// Parse YAML document in round-trip mode, return an sequence of nodes and
+/// the source ranges of them, see [`Loader::round_trip`].
+pub fn parse_round_trip<R: Repr>(doc: &str) -> Result<(Seq<R>, SourceMap<R>), PError> {
+    let mut loader = Loader::new(doc.as_bytes()).round_trip(true);
+    loader.parse().map(|root| (root, loader.get_source_map()))
+}
+
+/// Parse the sources and deep merge them in order into one tree, for the
+/// configs of the layered sources, e.g., the defaults, the files, then the
+/// overrides from the environment.
+///
+/// The documents are merged by [`Node::merge_patch`], so the maps are
+/// merged recursively, the null values remove the keys, and the other
+/// values replace the earlier layers. The sources may contain multiple
+/// documents which are merged in order as well, and the empty sources are
+/// skipped. Return null if there is no document.
+///
+/// Return [`LayerError`] with the index of the invalid source.
+///
+/// ```
+/// use yaml_peg::{load_layered, node, repr::RcRepr};
+///
+/// let defaults = "server: {host: localhost, port: 80}\nlog: info\n";
+/// let file = "server:\n  port: 8080\n";
+/// let env = "log: ~\ndebug: true\n";
+/// let n = load_layered::<RcRepr>(&[defaults, file, env]).unwrap();
+/// assert_eq!(
+///     n,
+///     node!({"server" => node!({"host" => "localhost", "port" => 8080}), "debug" => true})
+/// );
+/// let e = load_layered::<RcRepr>(&[defaults, "a: [b"]).unwrap_err();
+/// assert_eq!(e.index, 1);
+/// ```
+pub fn load_layered<R: Repr>(sources: &[&str]) -> Result<Node<R>, LayerError> {
+    let mut root = Node::from(());
+    for (index, doc) in sources.iter().enumerate() {
+        for layer in parse(doc).map_err(|error| LayerError::new(index, error))? {
+            root.merge_patch(&layer);
+        }
+    }
+    Ok(root)
+}
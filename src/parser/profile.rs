@@ -0,0 +1,50 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use std::time::{Duration, Instant};
+
+/// The statistics of a grammar rule, see [`Parser::profile`].
+///
+/// [`Parser::profile`]: super::Parser::profile
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RuleStats {
+    /// The number of the calls.
+    pub calls: usize,
+    /// The number of the matched calls, the others are mismatched or failed.
+    pub matched: usize,
+    /// The time spent in the rule, includes the nested rules, and the
+    /// recursive calls are counted once.
+    pub total: Duration,
+    /// The time spent in the rule itself, excludes the nested rules.
+    pub self_time: Duration,
+}
+
+/// The timer of the rules.
+#[derive(Default)]
+pub(crate) struct Profiler {
+    pub(crate) stats: BTreeMap<&'static str, RuleStats>,
+    /// The rule, the entrance time and the time of the nested rules.
+    stack: Vec<(&'static str, Instant, Duration)>,
+}
+
+impl Profiler {
+    pub(crate) fn enter(&mut self, rule: &'static str) {
+        self.stack.push((rule, Instant::now(), Duration::ZERO));
+    }
+
+    pub(crate) fn exit(&mut self, rule: &'static str, matched: bool) {
+        let Some((_, start, nested)) = self.stack.pop() else {
+            return;
+        };
+        let elapsed = start.elapsed();
+        if let Some((_, _, parent)) = self.stack.last_mut() {
+            *parent += elapsed;
+        }
+        let stats = self.stats.entry(rule).or_default();
+        stats.calls += 1;
+        stats.matched += matched as usize;
+        if self.stack.iter().all(|(r, _, _)| *r != rule) {
+            stats.total += elapsed;
+        }
+        stats.self_time += elapsed.saturating_sub(nested);
+    }
+}
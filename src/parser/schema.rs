@@ -0,0 +1,107 @@
+/// The boolean resolution of plain scalars, see [`Loader::bool_schema`].
+///
+/// [`Loader::bool_schema`]: super::Loader::bool_schema
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoolSchema {
+    /// Follow the schema of the document version,
+    /// see [`VersionPolicy::Resolve`](super::VersionPolicy::Resolve).
+    #[default]
+    Auto,
+    /// YAML 1.2 booleans only, `true` and `false`.
+    ///
+    /// The YAML 1.1 booleans such as `no` are treated as strings.
+    Core,
+    /// YAML 1.1 booleans are also accepted, `y`, `n`, `yes`, `no`, `on` and
+    /// `off` in lowercase, title case or uppercase.
+    Yaml11,
+}
+
+/// The null resolution of scalars, see [`Loader::null_schema`].
+///
+/// The disabled forms are treated as strings.
+///
+/// ```
+/// use yaml_peg::parser::NullSchema;
+///
+/// let schema = NullSchema::CORE.empty(false).tilde(false);
+/// assert!(schema.is_null("null"));
+/// assert!(!schema.is_null("~"));
+/// ```
+///
+/// [`Loader::null_schema`]: super::Loader::null_schema
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NullSchema {
+    /// `~`
+    pub tilde: bool,
+    /// Empty value.
+    pub empty: bool,
+    /// `null`
+    pub lower: bool,
+    /// `Null`
+    pub title: bool,
+    /// `NULL`
+    pub upper: bool,
+}
+
+impl NullSchema {
+    /// All forms of the YAML 1.2 core schema.
+    pub const CORE: Self = Self {
+        tilde: true,
+        empty: true,
+        lower: true,
+        title: true,
+        upper: true,
+    };
+    /// The JSON form `null` only.
+    pub const JSON: Self = Self {
+        tilde: false,
+        empty: false,
+        lower: true,
+        title: false,
+        upper: false,
+    };
+
+    /// Accept `~`.
+    pub const fn tilde(self, tilde: bool) -> Self {
+        Self { tilde, ..self }
+    }
+
+    /// Accept the empty value.
+    pub const fn empty(self, empty: bool) -> Self {
+        Self { empty, ..self }
+    }
+
+    /// Accept `null`.
+    pub const fn lower(self, lower: bool) -> Self {
+        Self { lower, ..self }
+    }
+
+    /// Accept `Null`.
+    pub const fn title(self, title: bool) -> Self {
+        Self { title, ..self }
+    }
+
+    /// Accept `NULL`.
+    pub const fn upper(self, upper: bool) -> Self {
+        Self { upper, ..self }
+    }
+
+    /// Return true if the plain scalar is null.
+    pub fn is_null(&self, s: &str) -> bool {
+        match s {
+            "~" => self.tilde,
+            "" => self.empty,
+            "null" => self.lower,
+            "Null" => self.title,
+            "NULL" => self.upper,
+            _ => false,
+        }
+    }
+}
+
+impl Default for NullSchema {
+    fn default() -> Self {
+        Self::CORE
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{repr::Repr, *};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt::{Debug, Formatter};
+
+/// The source range of a node, in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// The start of the properties (anchor and tag), same as `pos` if there
+    /// is no property.
+    pub start: u64,
+    /// The start of the content, same as [`Node::pos`].
+    pub pos: u64,
+    /// The end of the content, the trailing white spaces of the plain
+    /// scalars are included.
+    pub end: u64,
+}
+
+/// The source ranges of the parsed nodes, recorded by
+/// [`Loader::round_trip`](super::Loader::round_trip).
+///
+/// The nodes are identified by their position and their data holder, so the
+/// copies of the parsed nodes are found, but the modified nodes are not.
+///
+/// ```
+/// use yaml_peg::parser::{parse_round_trip, Span};
+///
+/// let doc = "a: &x [1, 2]\n";
+/// let (root, map) = parse_round_trip::<yaml_peg::repr::RcRepr>(doc).unwrap();
+/// let span = map.span(&root[0]["a"]).unwrap();
+/// assert_eq!(span, Span { start: 3, pos: 6, end: 12 });
+/// assert_eq!(&doc[span.pos as usize..span.end as usize], "[1, 2]");
+/// assert_eq!(map.span(&yaml_peg::node!([1, 2])), None);
+/// ```
+pub struct SourceMap<R: Repr> {
+    spans: BTreeMap<u64, Vec<(Node<R>, Span)>>,
+    roots: Vec<(Node<R>, Span)>,
+}
+
+impl<R: Repr> SourceMap<R> {
+    /// Create an empty source map.
+    pub fn new() -> Self {
+        Self { spans: BTreeMap::new(), roots: Vec::new() }
+    }
+
+    /// Get the source range of the parsed node.
+    pub fn span(&self, node: &Node<R>) -> Option<Span> {
+        self.spans
+            .get(&node.pos())?
+            .iter()
+            .find(|(n, _)| core::ptr::eq::<Yaml<R>>(n.yaml(), node.yaml()))
+            .map(|(_, span)| *span)
+    }
+
+    /// Iterate over the parsed document roots and their source ranges.
+    pub fn roots(&self) -> impl Iterator<Item = (&Node<R>, Span)> {
+        self.roots.iter().map(|(n, span)| (n, *span))
+    }
+
+    pub(crate) fn insert(&mut self, node: &Node<R>, start: u64, end: u64) {
+        let span = Span { start, pos: node.pos(), end };
+        let v = self.spans.entry(node.pos()).or_default();
+        // The wrapper of the same data holder has the outer range
+        match v
+            .iter_mut()
+            .find(|(n, _)| core::ptr::eq::<Yaml<R>>(n.yaml(), node.yaml()))
+        {
+            Some((_, old)) => *old = span,
+            None => v.push((node.clone(), span)),
+        }
+    }
+
+    pub(crate) fn insert_root(&mut self, node: &Node<R>) {
+        if let Some(span) = self.span(node) {
+            self.roots.push((node.clone(), span));
+        }
+    }
+}
+
+impl<R: Repr> Default for SourceMap<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Repr> Debug for SourceMap<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_list()
+            .entries(self.spans.values().flatten())
+            .finish()
+    }
+}
+
+impl<R: Repr> Clone for SourceMap<R> {
+    fn clone(&self) -> Self {
+        Self {
+            spans: self.spans.clone(),
+            roots: self.roots.clone(),
+        }
+    }
+}
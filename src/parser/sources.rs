@@ -0,0 +1,147 @@
+use super::*;
+use crate::indicated_msg_file;
+
+/// The source ID of [`Sources`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceId(usize);
+
+impl SourceId {
+    /// The index of the source, in the adding order.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// The registry of the source files, for parsing several files into one
+/// logical tree.
+///
+/// Each source has a unique range of the document positions, so the nodes
+/// can still report which file they came from after merging.
+///
+/// ```
+/// use yaml_peg::{parser::Sources, repr::RcRepr};
+///
+/// let mut sources = Sources::new();
+/// let base = sources.add("base.yaml", "host: localhost\nport: 80\n");
+/// let local = sources.add("local.yaml", "port: 8080\n");
+/// let n = sources.load_layered::<RcRepr>().unwrap();
+/// let port = n.get("port").unwrap();
+/// assert_eq!(sources.locate(port.pos()), Some((local, 6)));
+/// let host = n.get("host").unwrap();
+/// assert_eq!(sources.locate(host.pos()), Some((base, 6)));
+/// assert_eq!(sources.indicated_msg(port.pos()).unwrap(), "local.yaml:1:7\nport: 8080\n      ^");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Sources {
+    files: Vec<Source>,
+}
+
+#[derive(Clone, Debug)]
+struct Source {
+    name: String,
+    doc: String,
+    offset: u64,
+}
+
+impl Sources {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source with its name, such as the file path.
+    pub fn add(&mut self, name: impl ToString, doc: impl ToString) -> SourceId {
+        let offset = match self.files.last() {
+            // Keep a gap for the end position of the previous source
+            Some(f) => f.offset + f.doc.len() as u64 + 1,
+            None => 0,
+        };
+        self.files.push(Source {
+            name: name.to_string(),
+            doc: doc.to_string(),
+            offset,
+        });
+        SourceId(self.files.len() - 1)
+    }
+
+    /// The number of the sources.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Return true if there is no source.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The source IDs in the adding order.
+    pub fn ids(&self) -> impl Iterator<Item = SourceId> {
+        (0..self.files.len()).map(SourceId)
+    }
+
+    /// The name of the source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID is not from this registry.
+    pub fn name(&self, id: SourceId) -> &str {
+        &self.files[id.0].name
+    }
+
+    /// The document of the source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID is not from this registry.
+    pub fn doc(&self, id: SourceId) -> &str {
+        &self.files[id.0].doc
+    }
+
+    /// Find the source of the document position, return the source ID and
+    /// the position in the source.
+    pub fn locate(&self, pos: u64) -> Option<(SourceId, u64)> {
+        let i = self
+            .files
+            .partition_point(|f| f.offset <= pos)
+            .checked_sub(1)?;
+        let f = &self.files[i];
+        let local = pos - f.offset;
+        (local <= f.doc.len() as u64).then_some((SourceId(i), local))
+    }
+
+    /// The indicated message of the document position with the source name,
+    /// see [`indicated_msg_file`].
+    pub fn indicated_msg(&self, pos: u64) -> Option<String> {
+        let (id, pos) = self.locate(pos)?;
+        let f = &self.files[id.0];
+        Some(indicated_msg_file(&f.name, f.doc.as_bytes(), pos))
+    }
+
+    /// Parse the source, the positions of the nodes and the error are in the
+    /// range of the source, see [`Sources::locate`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID is not from this registry.
+    pub fn parse<R: Repr>(&self, id: SourceId) -> Result<Seq<R>, PError> {
+        let f = &self.files[id.0];
+        Loader::new(f.doc.as_bytes()).offset(f.offset).parse()
+    }
+
+    /// Merge all documents of the sources in the adding order, see
+    /// [`load_layered`].
+    ///
+    /// The [`LayerError::index`] is the index of the [`SourceId`].
+    pub fn load_layered<R: Repr>(&self) -> Result<Node<R>, LayerError> {
+        let mut root = Node::from(());
+        for id in self.ids() {
+            for layer in self
+                .parse(id)
+                .map_err(|error| LayerError::new(id.0, error))?
+            {
+                root.merge_patch(&layer);
+            }
+        }
+        Ok(root)
+    }
+}
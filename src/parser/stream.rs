@@ -0,0 +1,290 @@
+use super::*;
+use alloc::collections::VecDeque;
+
+/// The incremental loader of a YAML stream, fed by the chunks.
+///
+/// The documents are split by the document markers `---` and `...` at the
+/// line start, a document is parsed once the next marker (or the end of the
+/// stream) is fed, so the memory usage is limited to the largest document.
+/// The readers, includes the asynchronous readers, can feed the chunks in
+/// a loop and take the documents between the reads.
+///
+/// ```
+/// use std::io::Read;
+/// use yaml_peg::{node, parser::StreamLoader, repr::RcRepr};
+///
+/// let mut reader = "a: 1\n---\nb: 2\n...\n--- [3]\n".as_bytes();
+/// let mut loader = StreamLoader::<RcRepr>::new();
+/// let mut docs = Vec::new();
+/// let mut buf = [0; 4];
+/// loop {
+///     let n = reader.read(&mut buf).unwrap();
+///     if n == 0 {
+///         loader.finish();
+///     } else {
+///         loader.feed(&buf[..n]);
+///     }
+///     while let Some(doc) = loader.next_document() {
+///         docs.push((loader.pos(), doc.unwrap()));
+///     }
+///     if n == 0 {
+///         break;
+///     }
+/// }
+/// assert_eq!(
+///     docs,
+///     [(0, node!({"a" => 1})), (5, node!({"b" => 2})), (18, node!([3]))]
+/// );
+/// ```
+///
+/// The node positions are relative to the document, see
+/// [`StreamLoader::pos`] for the document position in the stream.
+pub struct StreamLoader<R: Repr> {
+    buf: Vec<u8>,
+    consumed: u64,
+    scanned: usize,
+    started: bool,
+    finished: bool,
+    docs: VecDeque<(u64, Result<Node<R>, PError>)>,
+    pos: u64,
+}
+
+impl<R: Repr> StreamLoader<R> {
+    /// Create an empty loader.
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            consumed: 0,
+            scanned: 0,
+            started: false,
+            finished: false,
+            docs: VecDeque::new(),
+            pos: 0,
+        }
+    }
+
+    /// Feed a chunk of the stream, the chunks after [`StreamLoader::finish`]
+    /// are ignored.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        if self.finished {
+            return;
+        }
+        self.buf.extend_from_slice(chunk);
+        while let Some(n) = self.buf[self.scanned..].iter().position(|c| *c == b'\n') {
+            self.line(self.scanned + n + 1);
+        }
+    }
+
+    /// Mark the end of the stream, then the last document is parsed.
+    pub fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        if self.scanned < self.buf.len() {
+            self.line(self.buf.len());
+        }
+        let end = self.buf.len();
+        if self.started {
+            self.emit(end);
+        } else {
+            self.drop_until(end);
+        }
+    }
+
+    /// Take the next parsed document, return `None` if there is no complete
+    /// document yet.
+    ///
+    /// The parsing error of a document does not stop the loader, the next
+    /// documents are still available.
+    pub fn next_document(&mut self) -> Option<Result<Node<R>, PError>> {
+        let (pos, doc) = self.docs.pop_front()?;
+        self.pos = pos;
+        Some(doc)
+    }
+
+    /// The stream position of the last document taken by
+    /// [`StreamLoader::next_document`].
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+
+    /// Return true if the stream is finished and all the documents are
+    /// taken.
+    pub fn is_done(&self) -> bool {
+        self.finished && self.docs.is_empty()
+    }
+
+    /// Check the line from the scanned position to the end.
+    fn line(&mut self, end: usize) {
+        let start = self.scanned;
+        let line = &self.buf[start..end];
+        let marker = |m: &[u8]| is_marker(line, m);
+        if marker(b"---") {
+            if self.started {
+                self.emit(start);
+            }
+            self.started = true;
+        } else if marker(b"...") {
+            self.scanned = end;
+            if self.started {
+                self.emit(end);
+            } else {
+                self.drop_until(end);
+            }
+            return;
+        } else if !self.started {
+            self.started = is_content(line);
+        }
+        // The buffer may be drained before the line
+        let drained = start - self.scanned;
+        self.scanned = end - drained;
+    }
+
+    /// Parse the buffer until the position as a document.
+    fn emit(&mut self, until: usize) {
+        let pos = self.consumed;
+        let seg = self.drop_until(until);
+        match Loader::<R>::new(&seg).parse() {
+            Ok(v) => self.docs.extend(v.into_iter().map(|n| (pos, Ok(n)))),
+            Err(e) => self.docs.push_back((pos, Err(e))),
+        };
+    }
+
+    fn drop_until(&mut self, until: usize) -> Vec<u8> {
+        self.started = false;
+        self.consumed += until as u64;
+        self.scanned -= until.min(self.scanned);
+        self.buf.drain(..until).collect()
+    }
+}
+
+impl<R: Repr> Default for StreamLoader<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Return true if the line starts with the document marker.
+fn is_marker(line: &[u8], m: &[u8]) -> bool {
+    line.starts_with(m) && matches!(line.get(3), None | Some(b' ' | b'\t' | b'\n' | b'\r'))
+}
+
+/// Return true if the line is not empty, comment or directive.
+fn is_content(line: &[u8]) -> bool {
+    let s = line
+        .iter()
+        .position(|c| !matches!(c, b' ' | b'\t' | b'\r' | b'\n'))
+        .map_or(&[][..], |i| &line[i..]);
+    !(s.is_empty() || s.starts_with(b"#") || line.starts_with(b"%"))
+}
+
+/// Split a YAML stream into the documents without parsing them.
+///
+/// The documents are split by the document markers `---` and `...` at the
+/// line start, like [`StreamLoader`], so the markers in the scalars (which
+/// must be indented) and the `---` prefix of the text like `---x` are not
+/// splitters. Each document keeps its directives, comments and markers, so
+/// it is a valid stream by itself. The trailing comments after the last
+/// `...` marker are kept in the last document, and the stream without any
+/// document returns an empty list.
+///
+/// ```
+/// use yaml_peg::parser::split_documents;
+///
+/// let doc = "\
+/// ## first
+/// a: |
+///   ---
+/// ...
+/// %YAML 1.2
+/// ---
+/// b: ---x
+/// --- [c]
+/// ";
+/// let docs = split_documents(doc);
+/// assert_eq!(docs, ["# first\na: |\n  ---\n...\n", "%YAML 1.2\n---\nb: ---x\n", "--- [c]\n"]);
+/// assert_eq!(docs.concat(), doc);
+/// ```
+pub fn split_documents(doc: &str) -> Vec<&str> {
+    let mut docs = Vec::new();
+    let mut begin = 0;
+    let mut start = 0;
+    let mut started = false;
+    for line in doc.split_inclusive('\n') {
+        let end = start + line.len();
+        let line = line.as_bytes();
+        if is_marker(line, b"---") {
+            if started {
+                docs.push(begin..start);
+                begin = start;
+            }
+            started = true;
+        } else if is_marker(line, b"...") {
+            if started {
+                docs.push(begin..end);
+                begin = end;
+            }
+            started = false;
+        } else if !started {
+            started = is_content(line);
+        }
+        start = end;
+    }
+    if started {
+        docs.push(begin..doc.len());
+    } else if let Some(last) = docs.last_mut() {
+        last.end = doc.len();
+    }
+    docs.into_iter().map(|r| &doc[r]).collect()
+}
+
+/// Join the documents into a YAML stream, the reverse of
+/// [`split_documents`].
+///
+/// The `---` marker is inserted before the documents without the start
+/// marker, and the `...` marker is inserted before the directives if the
+/// previous document is not ended, so the directives are not mixed into the
+/// previous document. The empty documents (or only the comments) are
+/// written as null.
+///
+/// ```
+/// use yaml_peg::{node, parse, parser::join_documents, repr::RcRepr};
+///
+/// let docs = ["a: 1".to_string(), "%YAML 1.2\n---\nb: 2\n".to_string(), "- c".to_string()];
+/// let doc = join_documents(&docs);
+/// assert_eq!(doc, "a: 1\n...\n%YAML 1.2\n---\nb: 2\n---\n- c\n");
+/// assert_eq!(
+///     parse::<RcRepr>(&doc).unwrap(),
+///     [node!({"a" => 1}), node!({"b" => 2}), node!(["c"])]
+/// );
+/// ```
+pub fn join_documents(docs: &[String]) -> String {
+    let mut s = String::new();
+    let mut ended = true;
+    for (i, doc) in docs.iter().enumerate() {
+        let first = doc.lines().find(|line| {
+            let line = line.as_bytes();
+            is_content(line) || line.starts_with(b"%") || is_marker(line, b"---")
+        });
+        let directive = first.is_some_and(|line| line.starts_with('%'));
+        let marker = first.is_some_and(|line| is_marker(line.as_bytes(), b"---"));
+        if directive && !ended {
+            s.push_str("...\n");
+        } else if first.is_none() {
+            s.push_str("--- null\n");
+        } else if !directive && !marker && i > 0 {
+            s.push_str("---\n");
+        }
+        s.push_str(doc);
+        if !doc.is_empty() && !doc.ends_with('\n') {
+            s.push('\n');
+        }
+        ended = doc
+            .lines()
+            .rev()
+            .find(|line| is_content(line.as_bytes()) || is_marker(line.as_bytes(), b"---"))
+            .is_some_and(|line| is_marker(line.as_bytes(), b"..."));
+    }
+    s
+}
@@ -0,0 +1,35 @@
+use alloc::boxed::Box;
+
+/// The callback of [`Loader::trace`](super::Loader::trace).
+pub(crate) type Tracer<'a> = Box<dyn FnMut(&Trace) + 'a>;
+
+/// The event of [`Trace`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TraceEvent {
+    /// The rule is entered.
+    Enter,
+    /// The rule is matched.
+    Matched,
+    /// The rule is mismatched, the parser will try another rule.
+    Mismatched,
+    /// The rule raised a [`PError::Terminate`](super::PError::Terminate)
+    /// error.
+    Failed,
+}
+
+/// A grammar rule entered or exited, reported by
+/// [`Loader::trace`](super::Loader::trace).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Trace {
+    /// The rule name, the method name of [`Loader`](super::Loader), e.g.
+    /// `seq` and `scalar_term`.
+    pub rule: &'static str,
+    /// The event.
+    pub event: TraceEvent,
+    /// Document position of the entrance or the exit.
+    pub pos: u64,
+    /// The nesting depth of the rule, the outermost rule is 0.
+    pub depth: usize,
+}
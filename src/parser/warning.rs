@@ -0,0 +1,65 @@
+use alloc::string::String;
+use core::fmt::{Display, Error, Formatter};
+
+/// The kind of [`Warning`].
+///
+/// Please see [module level document](super) for the warning list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// The directive is not `%YAML` or `%TAG`, and it is ignored.
+    UnknownDirective,
+    /// The `%TAG` directive is invalid, and it is ignored.
+    InvalidTagDirective,
+    /// The version is not supported but accepted by
+    /// [`VersionPolicy::Ignore`](super::VersionPolicy::Ignore).
+    UnsupportedVersion,
+    /// The `%YAML 1.1` directive is accepted but resolved with 1.2 schema,
+    /// see [`VersionPolicy::Compatible`](super::VersionPolicy::Compatible).
+    Yaml11Version,
+    /// The plain scalar has a different meaning in YAML 1.1, such as `yes`
+    /// and `010`, but it is resolved with 1.2 schema.
+    Yaml11Scalar,
+    /// The anchor is defined again, the aliases after it will refer to the
+    /// new node.
+    RedefinedAnchor,
+    /// The scalar contains a non-printable character, accepted by
+    /// [`Loader::allow_non_printable`](super::Loader::allow_non_printable).
+    NonPrintable,
+}
+
+impl WarningKind {
+    /// The description of the warning, which is shown in the message.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UnknownDirective => "unknown directive",
+            Self::InvalidTagDirective => "invalid tag directive",
+            Self::UnsupportedVersion => "unsupported version",
+            Self::Yaml11Version => "version 1.1 resolved with 1.2 schema",
+            Self::Yaml11Scalar => "scalar resolved differently in YAML 1.1",
+            Self::RedefinedAnchor => "redefined anchor",
+            Self::NonPrintable => "non-printable character",
+        }
+    }
+}
+
+/// The non-fatal diagnostic of the parser, see [`Parser::warnings`].
+///
+/// [`Parser::warnings`]: super::Parser::warnings
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Warning {
+    /// Kind of the warning.
+    pub kind: WarningKind,
+    /// Document position.
+    pub pos: u64,
+    /// Indicated message of the position, see
+    /// [`indicated_msg`](crate::indicated_msg).
+    pub msg: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}: \n\n{}", self.kind.name(), self.msg)
+    }
+}
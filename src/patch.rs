@@ -0,0 +1,403 @@
+//! Patch the node trees in place, includes [JSON Patch (RFC 6902)] and
+//! [JSON Merge Patch (RFC 7386)] semantics.
+//!
+//! The patch can be built directly or read from a YAML / JSON document, see
+//! [`Patch::from_node`].
+//!
+//! ```
+//! use yaml_peg::{node, patch::Patch, NodeRc};
+//!
+//! let mut root: NodeRc = "spec:\n  replicas: 1\n  ports: [80]".parse().unwrap();
+//! let patch: NodeRc = "\
+//! - {op: replace, path: /spec/replicas, value: 3}
+//! - {op: add, path: /spec/ports/-, value: 443}
+//! - {op: test, path: /spec/ports/0, value: 80}
+//! "
+//! .parse()
+//! .unwrap();
+//! let patch = Patch::from_node(&patch).unwrap();
+//! root.apply_patch(&patch).unwrap();
+//! assert_eq!(root, node!({"spec" => node!({"replicas" => 3, "ports" => node!([80, 443])})}));
+//! ```
+//!
+//! The paths are [JSON Pointer (RFC 6901)], the tokens are matched to the
+//! string keys or the scalar keys with the same text, and the indices of the
+//! sequences.
+//!
+//! [JSON Patch (RFC 6902)]: https://www.rfc-editor.org/rfc/rfc6902
+//! [JSON Merge Patch (RFC 7386)]: https://www.rfc-editor.org/rfc/rfc7386
+//! [JSON Pointer (RFC 6901)]: https://www.rfc-editor.org/rfc/rfc6901
+use crate::{repr::Repr, *};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Formatter};
+
+/// The error of the patch operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatchError {
+    /// The patch document is invalid.
+    InvalidPatch(String),
+    /// The path is not a valid JSON Pointer.
+    InvalidPointer(String),
+    /// The path is not existing in the tree.
+    NotFound(String),
+    /// The `test` operation is failed at the path.
+    TestFailed(String),
+    /// The `move` operation moves a node into its children.
+    MoveIntoChild(String),
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidPatch(s) => write!(f, "invalid patch: {}", s),
+            Self::InvalidPointer(s) => write!(f, "invalid pointer: `{}`", s),
+            Self::NotFound(s) => write!(f, "path not found: `{}`", s),
+            Self::TestFailed(s) => write!(f, "test failed: `{}`", s),
+            Self::MoveIntoChild(s) => write!(f, "cannot move into child: `{}`", s),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PatchError {}
+
+/// A JSON Patch operation.
+#[derive(Debug)]
+pub enum PatchOp<R: Repr> {
+    /// Add the value to the map, or insert the value into the sequence.
+    Add {
+        /// The target path.
+        path: String,
+        /// The new value.
+        value: Node<R>,
+    },
+    /// Remove the value.
+    Remove {
+        /// The target path.
+        path: String,
+    },
+    /// Replace the existing value.
+    Replace {
+        /// The target path.
+        path: String,
+        /// The new value.
+        value: Node<R>,
+    },
+    /// Remove the value and add it to the target.
+    Move {
+        /// The source path.
+        from: String,
+        /// The target path.
+        path: String,
+    },
+    /// Copy the value to the target.
+    Copy {
+        /// The source path.
+        from: String,
+        /// The target path.
+        path: String,
+    },
+    /// Test the value is equal.
+    Test {
+        /// The target path.
+        path: String,
+        /// The expected value.
+        value: Node<R>,
+    },
+}
+
+impl<R: Repr> Clone for PatchOp<R> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Add { path, value } => Self::Add { path: path.clone(), value: value.clone() },
+            Self::Remove { path } => Self::Remove { path: path.clone() },
+            Self::Replace { path, value } => {
+                Self::Replace { path: path.clone(), value: value.clone() }
+            }
+            Self::Move { from, path } => Self::Move { from: from.clone(), path: path.clone() },
+            Self::Copy { from, path } => Self::Copy { from: from.clone(), path: path.clone() },
+            Self::Test { path, value } => Self::Test { path: path.clone(), value: value.clone() },
+        }
+    }
+}
+
+/// A JSON Patch document, the list of [`PatchOp`].
+///
+/// See [`Node::apply_patch`].
+#[derive(Debug)]
+pub struct Patch<R: Repr>(pub Vec<PatchOp<R>>);
+
+impl<R: Repr> Clone for Patch<R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<R: Repr> Patch<R> {
+    /// Read the patch from its document, a sequence of the operation maps.
+    ///
+    /// ```
+    /// use yaml_peg::{node, patch::{Patch, PatchError}};
+    ///
+    /// let patch = node!([node!({"op" => "remove", "path" => "/a"})]);
+    /// assert!(Patch::from_node(&patch).is_ok());
+    /// let patch = node!([node!({"op" => "remove"})]);
+    /// assert!(matches!(Patch::from_node(&patch), Err(PatchError::InvalidPatch(_))));
+    /// ```
+    pub fn from_node(node: &Node<R>) -> Result<Self, PatchError> {
+        let err = |s: &str| PatchError::InvalidPatch(s.to_string());
+        let Yaml::Seq(v) = node.yaml() else {
+            return Err(err("expect a sequence"));
+        };
+        v.iter()
+            .map(|n| {
+                let field = |key: &str| {
+                    n.get(key)
+                        .map_err(|_| err(&format!("missing field `{}`", key)))
+                };
+                let string = |key: &str| {
+                    field(key)?
+                        .as_str()
+                        .map(String::from)
+                        .map_err(|_| err(&format!("field `{}` should be a string", key)))
+                };
+                let path = string("path")?;
+                Ok(match string("op")?.as_str() {
+                    "add" => PatchOp::Add { path, value: field("value")?.clone() },
+                    "remove" => PatchOp::Remove { path },
+                    "replace" => PatchOp::Replace { path, value: field("value")?.clone() },
+                    "move" => PatchOp::Move { from: string("from")?, path },
+                    "copy" => PatchOp::Copy { from: string("from")?, path },
+                    "test" => PatchOp::Test { path, value: field("value")?.clone() },
+                    op => return Err(err(&format!("unknown operation `{}`", op))),
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl<R: Repr> FromIterator<PatchOp<R>> for Patch<R> {
+    fn from_iter<T: IntoIterator<Item = PatchOp<R>>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Split the JSON Pointer into tokens.
+fn tokens(path: &str) -> Result<Vec<String>, PatchError> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(path) = path.strip_prefix('/') else {
+        return Err(PatchError::InvalidPointer(path.to_string()));
+    };
+    Ok(path
+        .split('/')
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+pub(crate) fn is_key<R: Repr>(key: &Node<R>, token: &str) -> bool {
+    matches!(key.yaml(), Yaml::Str(s) | Yaml::Int(s) | Yaml::Float(s) if s == token)
+        || matches!(key.yaml(), Yaml::Bool(b) if b.to_string() == token)
+}
+
+pub(crate) fn index(token: &str, len: usize) -> Option<usize> {
+    if token == "0" || !token.starts_with('0') && token.bytes().all(|c| c.is_ascii_digit()) {
+        token.parse().ok().filter(|i| *i < len)
+    } else {
+        None
+    }
+}
+
+fn get<'a, R: Repr>(node: &'a Node<R>, tokens: &[String]) -> Option<&'a Node<R>> {
+    tokens
+        .iter()
+        .try_fold(node, |node, token| match node.yaml() {
+            Yaml::Seq(v) => v.get(index(token, v.len())?),
+            Yaml::Map(m) => m.iter().find(|(k, _)| is_key(k, token)).map(|(_, v)| v),
+            _ => None,
+        })
+}
+
+/// Modify the parent of the last token, the copy-on-write is applied to the
+/// ancestors.
+fn modify<R, F>(node: &mut Node<R>, tokens: &[String], f: F) -> Option<()>
+where
+    R: Repr,
+    F: FnOnce(&mut Yaml<R>, &str) -> Option<()>,
+{
+    let mut yaml = node.yaml().clone();
+    match tokens {
+        [] => return None,
+        [last] => f(&mut yaml, last)?,
+        [token, tokens @ ..] => {
+            let child = match &mut yaml {
+                Yaml::Seq(v) => {
+                    let i = index(token, v.len())?;
+                    &mut v[i]
+                }
+                Yaml::Map(m) => m.iter_mut().find(|(k, _)| is_key(k, token))?.1,
+                _ => return None,
+            };
+            modify(child, tokens, f)?;
+        }
+    }
+    node.set_yaml(yaml);
+    Some(())
+}
+
+fn add<R: Repr>(node: &mut Node<R>, path: &str, value: Node<R>) -> Result<(), PatchError> {
+    let tokens = tokens(path)?;
+    if tokens.is_empty() {
+        *node = value;
+        return Ok(());
+    }
+    modify(node, &tokens, |yaml, last| match yaml {
+        Yaml::Seq(v) => {
+            let i = if last == "-" {
+                v.len()
+            } else {
+                index(last, v.len() + 1)?
+            };
+            v.insert(i, value);
+            Some(())
+        }
+        Yaml::Map(m) => {
+            match m.iter_mut().find(|(k, _)| is_key(k, last)) {
+                Some((_, v)) => *v = value,
+                None => {
+                    m.insert(Node::from(last), value);
+                }
+            }
+            Some(())
+        }
+        _ => None,
+    })
+    .ok_or_else(|| PatchError::NotFound(path.to_string()))
+}
+
+fn remove<R: Repr>(node: &mut Node<R>, path: &str) -> Result<Node<R>, PatchError> {
+    let tokens = tokens(path)?;
+    let mut ret = None;
+    modify(node, &tokens, |yaml, last| {
+        ret = Some(match yaml {
+            Yaml::Seq(v) => v.remove(index(last, v.len())?),
+            Yaml::Map(m) => {
+                let k = m.keys().find(|k| is_key(k, last))?.clone();
+                m.remove(&k)?
+            }
+            _ => return None,
+        });
+        Some(())
+    });
+    ret.ok_or_else(|| PatchError::NotFound(path.to_string()))
+}
+
+impl<R: Repr> Node<R> {
+    /// Apply the JSON Patch in place, see [`patch`](crate::patch) module.
+    ///
+    /// The operations are applied in order, and the node is unchanged if
+    /// any operation is failed.
+    ///
+    /// ```
+    /// use yaml_peg::{node, patch::{Patch, PatchError, PatchOp}};
+    ///
+    /// let mut n = node!({"a" => node!([1, 2]), "b" => 3});
+    /// let patch = Patch(vec![
+    ///     PatchOp::Move { from: "/b".into(), path: "/a/0".into() },
+    ///     PatchOp::Copy { from: "/a".into(), path: "/c".into() },
+    ///     PatchOp::Remove { path: "/a/2".into() },
+    /// ]);
+    /// n.apply_patch(&patch).unwrap();
+    /// assert_eq!(n, node!({"a" => node!([3, 1]), "c" => node!([3, 1, 2])}));
+    /// let patch = Patch(vec![
+    ///     PatchOp::Remove { path: "/a".into() },
+    ///     PatchOp::Test { path: "/c/0".into(), value: node!(4) },
+    /// ]);
+    /// assert_eq!(n.apply_patch(&patch), Err(PatchError::TestFailed("/c/0".into())));
+    /// assert_eq!(n, node!({"a" => node!([3, 1]), "c" => node!([3, 1, 2])}));
+    /// ```
+    pub fn apply_patch(&mut self, patch: &Patch<R>) -> Result<(), PatchError> {
+        let mut node = self.clone();
+        for op in &patch.0 {
+            match op {
+                PatchOp::Add { path, value } => add(&mut node, path, value.clone())?,
+                PatchOp::Remove { path } => {
+                    remove(&mut node, path)?;
+                }
+                PatchOp::Replace { path, value } => {
+                    if get(&node, &tokens(path)?).is_none() {
+                        return Err(PatchError::NotFound(path.clone()));
+                    }
+                    add(&mut node, path, value.clone())?;
+                }
+                PatchOp::Move { from, path } => {
+                    if path.starts_with(from.as_str())
+                        && path.as_bytes().get(from.len()) == Some(&b'/')
+                    {
+                        return Err(PatchError::MoveIntoChild(path.clone()));
+                    }
+                    if from != path {
+                        let value = remove(&mut node, from)?;
+                        add(&mut node, path, value)?;
+                    }
+                }
+                PatchOp::Copy { from, path } => {
+                    let value = get(&node, &tokens(from)?)
+                        .ok_or_else(|| PatchError::NotFound(from.clone()))?
+                        .clone();
+                    add(&mut node, path, value)?;
+                }
+                PatchOp::Test { path, value } => {
+                    if get(&node, &tokens(path)?) != Some(value) {
+                        return Err(PatchError::TestFailed(path.clone()));
+                    }
+                }
+            }
+        }
+        *self = node;
+        Ok(())
+    }
+
+    /// Apply the JSON Merge Patch in place.
+    ///
+    /// The maps are merged recursively, the null values remove the keys, and
+    /// the other values replace the target.
+    ///
+    /// ```
+    /// use yaml_peg::{node, NodeRc};
+    ///
+    /// let mut n: NodeRc = "a: b\nc: {d: e, f: g}".parse().unwrap();
+    /// let patch: NodeRc = "a: z\nc: {f: null}\nh: [1]".parse().unwrap();
+    /// n.merge_patch(&patch);
+    /// assert_eq!(n, node!({"a" => "z", "c" => node!({"d" => "e"}), "h" => node!([1])}));
+    /// ```
+    pub fn merge_patch(&mut self, patch: &Self) {
+        let Yaml::Map(patch) = patch.yaml() else {
+            *self = patch.clone();
+            return;
+        };
+        let mut m = match self.yaml() {
+            Yaml::Map(m) => m.clone(),
+            _ => Map::new(),
+        };
+        for (k, v) in patch.iter() {
+            if v.is_null() {
+                m.remove(k);
+            } else if let Some(target) = m.get_mut(k) {
+                target.merge_patch(v);
+            } else {
+                let mut target = Node::from(());
+                target.merge_patch(v);
+                m.insert(k.clone(), target);
+            }
+        }
+        self.set_yaml(m);
+    }
+}
@@ -0,0 +1,401 @@
+//! Query language, a subset of the JSONPath and `yq` expressions.
+//!
+//! The expression is compiled into [`Query`] and evaluated against a node
+//! tree, returns the matched nodes with their key paths.
+//!
+//! ```
+//! use yaml_peg::{node, query::query, NodeRc};
+//!
+//! let doc = "\
+//! items:
+//! - kind: Pod
+//!   name: a
+//! - kind: Service
+//!   name: b
+//! - kind: Pod
+//!   name: c
+//! ";
+//! let root: NodeRc = doc.parse().unwrap();
+//! let m = query(&root, ".items[?(@.kind == \"Pod\")].name").unwrap();
+//! let names = m.iter().map(|m| m.node.clone()).collect::<Vec<_>>();
+//! assert_eq!(names, [node!("a"), node!("c")]);
+//! assert_eq!(m[1].path, "items[2].name");
+//! ```
+//!
+//! # Syntax
+//!
+//! The expression is a chain of selectors, the leading `$` refers to the root
+//! node and can be omitted.
+//!
+//! + `.key` / `["key"]` / `['key']`: The value of the map key. The quoted
+//!   forms allow the special characters.
+//! + `[0]` / `[-1]`: The item of the sequence, the negative index counts from
+//!   the end.
+//! + `.*` / `[*]`: All the values of the map or the items of the sequence.
+//! + `..key` / `..*` / `..[0]`: The selector applied to the node and all of
+//!   its descendants.
+//! + `[?(@.key)]`: The values or items that the sub-path `@.key` is
+//!   existing.
+//! + `[?(@.key == literal)]`: The values or items that the sub-path is
+//!   compared to a literal, the operators are `==`, `!=`, `<`, `<=`, `>` and
+//!   `>=`. The literal is a quoted string or a YAML plain scalar like `1`,
+//!   `true` and `null`. The ordering is only defined between the numbers or
+//!   the strings.
+//!
+//! The aliases are not resolved, please use [`parse`](crate::parse) or
+//! [`Anchors::resolve_all`](crate::parser::Anchors::resolve_all) first.
+use crate::{
+    parser::{PError, Parser, TakeOpt},
+    repr::Repr,
+    *,
+};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// The error of parsing [`Query`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct QueryError {
+    /// Message.
+    pub msg: String,
+    /// The position of the expression.
+    pub pos: usize,
+}
+
+impl QueryError {
+    /// Create the error.
+    pub fn new(msg: impl ToString, pos: usize) -> Self {
+        Self { msg: msg.to_string(), pos }
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at {}", self.msg, self.pos)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QueryError {}
+
+/// A matched node of [`Query`].
+#[derive(Debug)]
+pub struct Match<'a, R: Repr> {
+    /// The key path from the root, see [`Node::path_of`].
+    pub path: String,
+    /// The matched node.
+    pub node: &'a Node<R>,
+}
+
+impl<R: Repr> Clone for Match<'_, R> {
+    fn clone(&self) -> Self {
+        Self { path: self.path.clone(), node: self.node }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Selector {
+    Key(String),
+    Index(isize),
+    Wildcard,
+    Descendant(Box<Selector>),
+    Filter(Vec<Selector>, Option<(Cmp, NodeRc)>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// The compiled query expression, see [module level document](self).
+///
+/// ```
+/// use yaml_peg::{node, query::Query};
+///
+/// let q = "$.a[*]".parse::<Query>().unwrap();
+/// let n = node!({"a" => node!([1, 2])});
+/// let m = q.eval(&n);
+/// assert_eq!(m.len(), 2);
+/// assert_eq!(m[0].path, "a[0]");
+/// assert_eq!(*m[1].node, node!(2));
+/// assert!("a[".parse::<Query>().is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Query(Vec<Selector>);
+
+impl Query {
+    /// Compile the query expression.
+    pub fn new(expr: &str) -> Result<Self, QueryError> {
+        let mut p = Parser::new(expr.as_bytes());
+        p.sym(b'$').unwrap_or_default();
+        let v = selectors(&mut p, true)?;
+        if p.food().is_empty() {
+            Ok(Self(v))
+        } else {
+            Err(error(&p, "unexpected character"))
+        }
+    }
+
+    /// Evaluate the query against the root node.
+    pub fn eval<'a, R: Repr>(&self, root: &'a Node<R>) -> Vec<Match<'a, R>> {
+        let mut v = vec![Match { path: String::new(), node: root }];
+        for s in &self.0 {
+            v = v.iter().flat_map(|m| select(s, m)).collect();
+        }
+        v
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// Compile and evaluate the query expression against the root node.
+pub fn query<'a, R: Repr>(root: &'a Node<R>, expr: &str) -> Result<Vec<Match<'a, R>>, QueryError> {
+    Query::new(expr).map(|q| q.eval(root))
+}
+
+fn error(p: &Parser, msg: &str) -> QueryError {
+    QueryError::new(msg, p.pos)
+}
+
+fn key_name(p: &mut Parser) -> Option<String> {
+    p.context(|p| {
+        p.take_while_char(
+            |c| c.is_alphanumeric() || c == '-' || c == '_',
+            TakeOpt::More(1),
+        )
+        .ok()?;
+        Some(p.text())
+    })
+}
+
+fn quoted(p: &mut Parser) -> Result<Option<String>, QueryError> {
+    let s = p
+        .string_double_quoted()
+        .or_else(|e| e.or(|| p.string_quoted(b'\'', b"''")));
+    match s {
+        Ok(s) => Ok(Some(s)),
+        Err(PError::Mismatch) => Ok(None),
+        Err(PError::Terminate { .. }) => Err(error(p, "invalid string")),
+    }
+}
+
+/// Match the selectors until mismatched.
+///
+/// The leading key without the dot is allowed if `first` is true.
+fn selectors(p: &mut Parser, first: bool) -> Result<Vec<Selector>, QueryError> {
+    let mut v = Vec::new();
+    if first {
+        if let Some(k) = key_name(p) {
+            v.push(Selector::Key(k));
+        }
+    }
+    loop {
+        p.forward();
+        let s = if p.sym_seq(b"..").is_ok() {
+            p.forward();
+            Selector::Descendant(Box::new(child(p, true)?))
+        } else if p.sym(b'.').is_ok() {
+            p.forward();
+            child(p, false)?
+        } else if p.sym(b'[').is_ok() {
+            bracket(p)?
+        } else {
+            break;
+        };
+        v.push(s);
+    }
+    Ok(v)
+}
+
+/// Match the selector after the dot, the bracket is allowed after `..`.
+fn child(p: &mut Parser, bracket_ok: bool) -> Result<Selector, QueryError> {
+    if p.sym(b'*').is_ok() {
+        Ok(Selector::Wildcard)
+    } else if let Some(k) = key_name(p) {
+        Ok(Selector::Key(k))
+    } else if bracket_ok && p.sym(b'[').is_ok() {
+        bracket(p)
+    } else {
+        Err(error(p, "expect key or `*`"))
+    }
+}
+
+/// Match the selector in the bracket, the leading `[` is matched.
+fn bracket(p: &mut Parser) -> Result<Selector, QueryError> {
+    p.forward();
+    let s = if p.sym(b'*').is_ok() {
+        Selector::Wildcard
+    } else if p.sym_seq(b"?(").is_ok() {
+        filter(p)?
+    } else if let Some(s) = quoted(p)? {
+        Selector::Key(s)
+    } else {
+        p.sym(b'-').unwrap_or_default();
+        p.take_while(u8::is_ascii_digit, TakeOpt::More(1))
+            .map_err(|_| error(p, "expect index, key, `*` or filter"))?;
+        Selector::Index(
+            p.text()
+                .parse()
+                .map_err(|_| error(p, "index out of range"))?,
+        )
+    };
+    p.forward();
+    p.sym(b']').map_err(|_| error(p, "expect `]`"))?;
+    Ok(s)
+}
+
+/// Match the filter, the leading `?(` is matched.
+fn filter(p: &mut Parser) -> Result<Selector, QueryError> {
+    p.ws(TakeOpt::More(0)).unwrap_or_default();
+    p.forward();
+    p.sym(b'@').map_err(|_| error(p, "expect `@`"))?;
+    let path = selectors(p, false)?;
+    p.ws(TakeOpt::More(0)).unwrap_or_default();
+    p.forward();
+    let cmp = [
+        (&b"=="[..], Cmp::Eq),
+        (b"!=", Cmp::Ne),
+        (b"<=", Cmp::Le),
+        (b">=", Cmp::Ge),
+        (b"<", Cmp::Lt),
+        (b">", Cmp::Gt),
+    ]
+    .into_iter()
+    .find(|(s, _)| p.context(|p| p.sym_seq(s).map(|_| p.forward()).is_ok()));
+    let cmp = match cmp {
+        Some((_, cmp)) => {
+            p.ws(TakeOpt::More(0)).unwrap_or_default();
+            p.forward();
+            let lit = match quoted(p)? {
+                Some(s) => NodeRc::from(s),
+                None => {
+                    p.take_while_char(|c| c != ')' && !c.is_whitespace(), TakeOpt::More(1))
+                        .map_err(|_| error(p, "expect literal"))?;
+                    let s = p.text();
+                    s.parse::<NodeRc>()
+                        .map_err(|_| error(p, &format!("invalid literal `{}`", s)))?
+                }
+            };
+            p.ws(TakeOpt::More(0)).unwrap_or_default();
+            Some((cmp, lit))
+        }
+        None => None,
+    };
+    p.forward();
+    p.sym(b')').map_err(|_| error(p, "expect `)`"))?;
+    Ok(Selector::Filter(path, cmp))
+}
+
+/// Return the children of the node.
+fn children<'a, R: Repr>(m: &Match<'a, R>) -> Vec<Match<'a, R>> {
+    match m.node.yaml() {
+        Yaml::Seq(v) => v
+            .iter()
+            .enumerate()
+            .map(|(i, node)| Match { path: format!("{}[{}]", m.path, i), node })
+            .collect(),
+        Yaml::Map(map) => map
+            .iter()
+            .map(|(k, node)| {
+                let mut path = m.path.clone();
+                k.push_path(&mut path);
+                Match { path, node }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn select<'a, R: Repr>(s: &Selector, m: &Match<'a, R>) -> Vec<Match<'a, R>> {
+    match s {
+        Selector::Key(k) => match m.node.yaml() {
+            Yaml::Map(map) => map
+                .iter()
+                .filter(|(key, _)| matches!(key.yaml(), Yaml::Str(s) if s == k))
+                .map(|(key, node)| {
+                    let mut path = m.path.clone();
+                    key.push_path(&mut path);
+                    Match { path, node }
+                })
+                .collect(),
+            _ => Vec::new(),
+        },
+        Selector::Index(i) => match m.node.yaml() {
+            Yaml::Seq(v) => {
+                let i = if *i < 0 {
+                    v.len().checked_sub(i.unsigned_abs())
+                } else {
+                    Some(*i as usize)
+                };
+                i.and_then(|i| v.get(i).map(|node| (i, node)))
+                    .map(|(i, node)| Match { path: format!("{}[{}]", m.path, i), node })
+                    .into_iter()
+                    .collect()
+            }
+            _ => Vec::new(),
+        },
+        Selector::Wildcard => children(m),
+        Selector::Descendant(s) => {
+            let mut v = Vec::new();
+            let mut stack = vec![m.clone()];
+            while let Some(m) = stack.pop() {
+                v.extend(select(s, &m));
+                stack.extend(children(&m).into_iter().rev());
+            }
+            v
+        }
+        Selector::Filter(path, cmp) => children(m)
+            .into_iter()
+            .filter(|m| {
+                let q = Query(path.clone());
+                let found = q.eval(m.node);
+                match cmp {
+                    None => !found.is_empty(),
+                    Some((cmp, lit)) => {
+                        let lit = lit.to_repr::<R>();
+                        found.iter().any(|m| compare(*cmp, m.node, &lit))
+                    }
+                }
+            })
+            .collect(),
+    }
+}
+
+fn compare<R: Repr>(cmp: Cmp, lhs: &Node<R>, rhs: &Node<R>) -> bool {
+    let ord = match (lhs.as_number(), rhs.as_number()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b),
+        _ => match (lhs.as_str(), rhs.as_str()) {
+            (Ok(a), Ok(b)) => Some(a.cmp(b)),
+            _ => None,
+        },
+    };
+    match cmp {
+        Cmp::Eq => lhs == rhs,
+        Cmp::Ne => lhs != rhs,
+        Cmp::Lt => ord == Some(Ordering::Less),
+        Cmp::Le => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+        Cmp::Gt => ord == Some(Ordering::Greater),
+        Cmp::Ge => matches!(ord, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
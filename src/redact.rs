@@ -0,0 +1,167 @@
+//! Mask the sensitive values of the node trees, for safely logging the
+//! configs.
+//!
+//! ```
+//! use yaml_peg::{node, redact::{redact, Matcher}, NodeRc};
+//!
+//! let root: NodeRc = "\
+//! db:
+//!   user: admin
+//!   password: 1234
+//! servers:
+//! - {host: a, token: x}
+//! - {host: b, token: y}
+//! "
+//! .parse()
+//! .unwrap();
+//! let matcher = Matcher::new().key("password").path("servers.*.token");
+//! assert_eq!(
+//!     redact(&root, &matcher, "***"),
+//!     node!({
+//!         "db" => node!({"user" => "admin", "password" => "***"}),
+//!         "servers" => node!([
+//!             node!({"host" => "a", "token" => "***"}),
+//!             node!({"host" => "b", "token" => "***"}),
+//!         ]),
+//!     })
+//! );
+//! ```
+//!
+//! The aliases are not followed, please resolve them before redacting, see
+//! [`Anchors::resolve_all`](crate::parser::Anchors::resolve_all).
+use crate::{repr::Repr, *};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The set of the key names and the path patterns to be redacted.
+///
+/// A path pattern is a list of segments split by dots, such as
+/// `servers.*.token`. The segments are matched to the map keys or the
+/// sequence indices, where `*` matches any single segment and `**` matches
+/// any number of segments.
+///
+/// If a node is matched, all the scalar values inside it are replaced, so
+/// the pattern `secrets` redacts the whole `secrets` map.
+#[derive(Clone, Debug, Default)]
+pub struct Matcher {
+    keys: Vec<String>,
+    paths: Vec<Vec<String>>,
+    ignore_case: bool,
+}
+
+impl Matcher {
+    /// Create an empty matcher.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match the map values of the key name at any depth.
+    pub fn key(mut self, name: impl ToString) -> Self {
+        self.keys.push(name.to_string());
+        self
+    }
+
+    /// Match the nodes of the path pattern from the root.
+    pub fn path(mut self, pattern: &str) -> Self {
+        self.paths
+            .push(pattern.split('.').map(str::to_string).collect());
+        self
+    }
+
+    /// Compare the key names and the segments in ASCII case-insensitive
+    /// manner, default to false.
+    ///
+    /// ```
+    /// use yaml_peg::{node, redact::{redact, Matcher}};
+    ///
+    /// let n = node!({"Password" => "1234"});
+    /// assert_eq!(redact(&n, &Matcher::new().key("password"), "***"), n);
+    /// let matcher = Matcher::new().key("password").ignore_case(true);
+    /// assert_eq!(redact(&n, &matcher, "***"), node!({"Password" => "***"}));
+    /// ```
+    pub fn ignore_case(self, ignore_case: bool) -> Self {
+        Self { ignore_case, ..self }
+    }
+
+    /// Return true if the path of segments is matched.
+    pub fn is_match(&self, path: &[String]) -> bool {
+        let last = match path.last() {
+            Some(last) => last,
+            None => return false,
+        };
+        self.keys.iter().any(|k| self.eq(k, last))
+            || self.paths.iter().any(|pat| self.glob(pat, path))
+    }
+
+    fn eq(&self, a: &str, b: &str) -> bool {
+        if self.ignore_case {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    }
+
+    fn glob(&self, pat: &[String], path: &[String]) -> bool {
+        match pat.split_first() {
+            None => path.is_empty(),
+            Some((s, rest)) if s == "**" => (0..=path.len()).any(|i| self.glob(rest, &path[i..])),
+            Some((s, rest)) => path
+                .split_first()
+                .is_some_and(|(p, path)| (s == "*" || self.eq(s, p)) && self.glob(rest, path)),
+        }
+    }
+}
+
+/// Return a copy of the tree with the matched scalar values replaced by the
+/// replacement.
+///
+/// The keys are kept, and the replaced nodes keep the positions of the
+/// original values.
+pub fn redact<R: Repr>(
+    node: &Node<R>,
+    matcher: &Matcher,
+    replacement: impl Into<Node<R>>,
+) -> Node<R> {
+    redact_inner(node, matcher, &replacement.into(), &mut Vec::new())
+}
+
+fn redact_inner<R: Repr>(
+    node: &Node<R>,
+    matcher: &Matcher,
+    replacement: &Node<R>,
+    path: &mut Vec<String>,
+) -> Node<R> {
+    if matcher.is_match(path) {
+        return node.map_values_deep(|_, n| Node::new_repr(replacement.clone_yaml(), n.pos(), ""));
+    }
+    let yaml: Yaml<R> = match node.yaml() {
+        Yaml::Seq(v) => v
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                path.push(i.to_string());
+                let n = redact_inner(n, matcher, replacement, path);
+                path.pop();
+                n
+            })
+            .collect::<Seq<R>>()
+            .into(),
+        Yaml::Map(m) => m
+            .iter()
+            .map(|(k, v)| {
+                path.push(match k.yaml() {
+                    Yaml::Str(s) => s.clone(),
+                    _ => k.to_string(),
+                });
+                let v = redact_inner(v, matcher, replacement, path);
+                path.pop();
+                (k.clone(), v)
+            })
+            .collect::<Map<R>>()
+            .into(),
+        _ => return node.clone(),
+    };
+    Node::new(yaml, node.pos(), node.tag()).with_meta(node.meta().clone())
+}
@@ -0,0 +1,147 @@
+use super::Repr;
+use crate::Yaml;
+use alloc::{boxed::Box, rc::Rc};
+use core::{
+    cell::{Cell, RefCell, UnsafeCell},
+    fmt::{Debug, Formatter},
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+/// The capacity of the first chunk, the next chunks are doubled.
+const FIRST_CHUNK: usize = 64;
+/// The maximum capacity of the chunks.
+const MAX_CHUNK: usize = 1 << 16;
+
+std::thread_local! {
+    static CURRENT: RefCell<Rc<Chunk>> = RefCell::new(Rc::new(Chunk::new(FIRST_CHUNK)));
+}
+
+/// The representation symbol for [`ArenaRc`], the nodes are allocated in
+/// the chunks of the current thread.
+///
+/// The chunks are allocated at once for many nodes, so parsing the large
+/// documents puts less pressure on the allocator, and the nodes are close to
+/// each other in memory. The slots of the dropped nodes are not reused, a
+/// chunk is freed when all of its nodes are dropped, so it is suitable for
+/// the documents which are dropped all at once. The node is not [`Send`],
+/// like [`RcRepr`](super::RcRepr).
+///
+/// ```
+/// use yaml_peg::{parser::Loader, repr::ArenaRepr, Ind};
+///
+/// let root = Loader::<ArenaRepr>::new(b"a: [1, 2]").parse().unwrap().remove(0);
+/// assert_eq!(root["a"][Ind(1)].as_int(), Ok(2));
+/// ```
+pub struct ArenaRepr;
+
+impl Repr for ArenaRepr {
+    type Rc = ArenaRc;
+    type Meta = ();
+
+    fn new_rc(yaml: Yaml<Self>) -> Self::Rc {
+        CURRENT.with(|current| {
+            let mut current = current.borrow_mut();
+            let cap = current.slots.len();
+            if current.len.get() == cap {
+                *current = Rc::new(Chunk::new((cap * 2).min(MAX_CHUNK)));
+            }
+            let index = current.len.get();
+            current.len.set(index + 1);
+            let slot = &current.slots[index];
+            slot.count.set(1);
+            // SAFETY: The slot is never used before
+            unsafe { *slot.yaml.get() = Some(yaml) };
+            ArenaRc { chunk: Rc::into_raw(Rc::clone(&current)), index }
+        })
+    }
+}
+
+/// A chunk of the nodes, the slots are never moved.
+struct Chunk {
+    slots: Box<[Slot]>,
+    len: Cell<usize>,
+}
+
+impl Chunk {
+    fn new(cap: usize) -> Self {
+        let slots = (0..cap)
+            .map(|_| Slot { count: Cell::new(0), yaml: UnsafeCell::new(None) })
+            .collect();
+        Self { slots, len: Cell::new(0) }
+    }
+}
+
+/// The node and its reference count.
+struct Slot {
+    count: Cell<usize>,
+    yaml: UnsafeCell<Option<Yaml<ArenaRepr>>>,
+}
+
+/// The node handle of [`ArenaRepr`], a chunk and the index of the node.
+///
+/// The nodes are counted like [`Rc`], and each living node keeps its chunk
+/// alive.
+pub struct ArenaRc {
+    chunk: *const Chunk,
+    index: usize,
+}
+
+impl ArenaRc {
+    fn slot(&self) -> &Slot {
+        // SAFETY: The chunk is counted by the living node
+        unsafe { &(*self.chunk).slots[self.index] }
+    }
+}
+
+impl Deref for ArenaRc {
+    type Target = Yaml<ArenaRepr>;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The node is only written before and after it is counted
+        unsafe { (*self.slot().yaml.get()).as_ref().unwrap() }
+    }
+}
+
+impl Clone for ArenaRc {
+    fn clone(&self) -> Self {
+        let count = &self.slot().count;
+        count.set(count.get() + 1);
+        Self { chunk: self.chunk, index: self.index }
+    }
+}
+
+impl Drop for ArenaRc {
+    fn drop(&mut self) {
+        let slot = self.slot();
+        slot.count.set(slot.count.get() - 1);
+        if slot.count.get() == 0 {
+            // SAFETY: No handle refers to the node, then the node is dropped
+            // before releasing the chunk
+            unsafe {
+                drop((*slot.yaml.get()).take());
+                Rc::decrement_strong_count(self.chunk);
+            }
+        }
+    }
+}
+
+impl Debug for ArenaRc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl Hash for ArenaRc {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl PartialEq for ArenaRc {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for ArenaRc {}
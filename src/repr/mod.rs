@@ -0,0 +1,83 @@
+//! This module contains the representation holders, the underlay type of
+//! [`Yaml`] type.
+//!
+//! [`Rc`] is the single thread reference counter,
+//! and [`Arc`] is the multiple thread reference counter.
+//! Enable `arena` feature to allocate the nodes in chunks, see
+//! [`ArenaRepr`].
+//!
+//! Implement [`Repr`] for a custom symbol to attach the metadata to each
+//! node, such as the file ID. The parser fills the metadata with
+//! [`Loader::meta`](crate::parser::Loader::meta).
+//!
+//! ```
+//! use std::rc::Rc;
+//! use yaml_peg::{parser::Loader, repr::Repr, Ind, Yaml};
+//!
+//! struct FileRepr;
+//!
+//! impl Repr for FileRepr {
+//!     type Rc = Rc<Yaml<Self>>;
+//!     type Meta = u32;
+//!
+//!     fn new_rc(yaml: Yaml<Self>) -> Self::Rc {
+//!         Rc::new(yaml)
+//!     }
+//! }
+//!
+//! let root = Loader::<FileRepr>::new(b"a: [1, 2]")
+//!     .meta(|_| 7)
+//!     .parse()
+//!     .unwrap()
+//!     .remove(0);
+//! assert_eq!(*root.meta(), 7);
+//! assert_eq!(*root["a"][Ind(1)].meta(), 7);
+//! ```
+#[cfg(feature = "arena")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "arena")))]
+pub use self::arena::{ArenaRc, ArenaRepr};
+use crate::Yaml;
+use alloc::{rc::Rc, sync::Arc};
+use core::{fmt::Debug, hash::Hash, ops::Deref};
+
+#[cfg(feature = "arena")]
+mod arena;
+
+/// The representation symbol for [`Rc`].
+pub struct RcRepr;
+/// The representation symbol for [`Arc`].
+pub struct ArcRepr;
+
+/// The generic representation holder for [`Yaml`].
+///
+/// See the implementor list for the choose.
+pub trait Repr: Sized {
+    /// Type of the representation, e.g., the reference counter type.
+    type Rc: Deref<Target = Yaml<Self>> + Hash + Eq + Clone + Debug;
+    /// Type of the metadata of each node, use `()` if not needed.
+    ///
+    /// The metadata is ignored by the comparison and the hashing of the
+    /// nodes.
+    type Meta: Clone + Debug + Default;
+
+    /// The creation function of this type.
+    fn new_rc(yaml: Yaml<Self>) -> Self::Rc;
+}
+
+impl Repr for RcRepr {
+    type Rc = Rc<Yaml<Self>>;
+    type Meta = ();
+
+    fn new_rc(yaml: Yaml<Self>) -> Self::Rc {
+        Rc::new(yaml)
+    }
+}
+
+impl Repr for ArcRepr {
+    type Rc = Arc<Yaml<Self>>;
+    type Meta = ();
+
+    fn new_rc(yaml: Yaml<Self>) -> Self::Rc {
+        Arc::new(yaml)
+    }
+}
@@ -1,17 +1,22 @@
 use super::SerdeError;
+#[cfg(feature = "arbitrary-precision")]
+use crate::{
+    number::{BIG_INT_TOKEN, DECIMAL_TOKEN},
+    BigInt, Decimal,
+};
 use crate::{
     parse,
     repr::{RcRepr, Repr},
     to_f64, to_i64, Map, Node, Seq, Yaml,
 };
 use alloc::{format, string::ToString, vec::Vec};
-use core::marker::PhantomData;
+use core::{marker::PhantomData, ops::Deref};
 use serde::{
     de::{
         DeserializeOwned, DeserializeSeed, EnumAccess, Error, Expected, MapAccess, SeqAccess,
         Unexpected, VariantAccess, Visitor,
     },
-    serde_if_integer128, Deserialize, Deserializer,
+    Deserialize, Deserializer,
 };
 
 macro_rules! impl_visitor {
@@ -41,6 +46,96 @@ macro_rules! impl_deserializer {
     };
 }
 
+macro_rules! forward_deserializer {
+    ($(fn $method:ident($($arg:ident: $ty:ty),*))+) => {
+        $(fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'a>,
+        {
+            NodeDe(self, DeOptions::new()).$method($($arg,)* visitor)
+        })+
+    };
+}
+
+/// The options of the deserializer, see [`from_str_with`].
+///
+/// ```
+/// use serde::Deserialize;
+/// use yaml_peg::serde::{from_str, from_str_with, DeOptions};
+///
+/// #[derive(Deserialize)]
+/// struct Member {
+///     name: String,
+/// }
+///
+/// let doc = "name: Bob\nage: 46\n";
+/// assert!(from_str::<Member>(doc).is_ok());
+/// let err = from_str_with::<Member>(doc, DeOptions::new().deny_unknown(true))
+///     .err()
+///     .unwrap();
+/// assert_eq!(err.pos, 10);
+/// assert_eq!(err.path.as_deref(), Some("age"));
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct DeOptions {
+    deny_unknown: bool,
+    recursion_limit: Option<usize>,
+}
+
+impl DeOptions {
+    /// Create the default options.
+    pub const fn new() -> Self {
+        Self { deny_unknown: false, recursion_limit: Some(128) }
+    }
+
+    /// Report the unknown keys of the structs and struct variants as errors,
+    /// like `#[serde(deny_unknown_fields)]`, default to false.
+    ///
+    /// The key names are compared to the field names after renaming,
+    /// so the aliases and the keys of `#[serde(flatten)]` fields are reported
+    /// as unknown too.
+    pub fn deny_unknown(self, deny_unknown: bool) -> Self {
+        Self { deny_unknown, ..self }
+    }
+
+    /// The maximum nesting depth of the sequences and maps, default to 128.
+    ///
+    /// Deserializing the deeply nested nodes into recursive types may
+    /// overflow the stack, so the limit is exceeded as an error.
+    /// Set to `None` to disable the limit.
+    ///
+    /// ```
+    /// use yaml_peg::{serde::{from_node_with, DeOptions}, NodeRc};
+    ///
+    /// let n = "[[[[1]]]]".parse::<NodeRc>().unwrap();
+    /// let opts = DeOptions::new().recursion_limit(Some(3));
+    /// let err = from_node_with::<NodeRc, _>(n.clone(), opts).unwrap_err();
+    /// assert_eq!(err.msg, "recursion limit exceeded");
+    /// assert_eq!(err.pos, 3);
+    /// let opts = DeOptions::new().recursion_limit(Some(4));
+    /// assert_eq!(from_node_with::<NodeRc, _>(n.clone(), opts).unwrap(), n);
+    /// ```
+    pub fn recursion_limit(self, recursion_limit: Option<usize>) -> Self {
+        Self { recursion_limit, ..self }
+    }
+
+    /// Consume a level of the recursion limit.
+    fn enter(self, pos: u64) -> Result<Self, SerdeError> {
+        match self.recursion_limit {
+            Some(0) => Err(SerdeError::from("recursion limit exceeded".to_string()).pos(pos)),
+            Some(n) => Ok(Self { recursion_limit: Some(n - 1), ..self }),
+            None => Ok(self),
+        }
+    }
+}
+
+impl Default for DeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Parse the document and deserialize nodes to a specific type.
 ///
 /// Since the document can be split into multiple parts,
@@ -73,8 +168,74 @@ pub fn from_str<D>(doc: &str) -> Result<Vec<D>, SerdeError>
 where
     D: DeserializeOwned,
 {
-    let root = parse::<RcRepr>(doc).map_err(|e| e.to_string())?;
-    root.into_iter().map(D::deserialize).collect()
+    let root = parse::<RcRepr>(doc)?;
+    root.into_iter()
+        .map(|n| D::deserialize(n.clone()).map_err(|e| e.with_path(&n)))
+        .collect()
+}
+
+/// Parse the document and deserialize nodes to a specific type with options.
+///
+/// Please see [`DeOptions`] for the example.
+pub fn from_str_with<D>(doc: &str, opts: DeOptions) -> Result<Vec<D>, SerdeError>
+where
+    D: DeserializeOwned,
+{
+    let root = parse::<RcRepr>(doc)?;
+    root.into_iter()
+        .map(|n| from_node_with(n.clone(), opts).map_err(|e| e.with_path(&n)))
+        .collect()
+}
+
+/// Deserialize the node to a specific type with options.
+///
+/// The [`Node`] itself is a deserializer with the default options.
+pub fn from_node_with<D, R>(node: Node<R>, opts: DeOptions) -> Result<D, SerdeError>
+where
+    D: DeserializeOwned,
+    R: Repr,
+{
+    D::deserialize(NodeDe(node, opts))
+}
+
+/// Parse the single document and deserialize it into an existing value.
+///
+/// The value is updated by [`Deserialize::deserialize_in_place`], so the
+/// buffers of the strings and the vectors are reused when reloading the
+/// documents, and the custom types can implement it as well (enable the
+/// `deserialize_in_place` feature of `serde_derive` for the derived
+/// structs). The value may be partially updated if an error is returned.
+///
+/// ```
+/// use yaml_peg::serde::from_str_in_place;
+///
+/// let mut v = Vec::<String>::with_capacity(8);
+/// from_str_in_place("[a, b]", &mut v).unwrap();
+/// let ptr = v.as_ptr();
+/// from_str_in_place("[c, d, e]", &mut v).unwrap();
+/// assert_eq!(v, ["c", "d", "e"]);
+/// assert_eq!(v.as_ptr(), ptr);
+/// ```
+pub fn from_str_in_place<D>(doc: &str, place: &mut D) -> Result<(), SerdeError>
+where
+    D: DeserializeOwned,
+{
+    let n = doc.parse::<Node<RcRepr>>()?;
+    from_node_in_place_with(n.clone(), place, DeOptions::new()).map_err(|e| e.with_path(&n))
+}
+
+/// Deserialize the node into an existing value with options, see
+/// [`from_str_in_place`].
+pub fn from_node_in_place_with<D, R>(
+    node: Node<R>,
+    place: &mut D,
+    opts: DeOptions,
+) -> Result<(), SerdeError>
+where
+    D: DeserializeOwned,
+    R: Repr,
+{
+    D::deserialize_in_place(NodeDe(node, opts), place)
 }
 
 struct NodeVisitor<R: Repr>(PhantomData<R>);
@@ -90,6 +251,8 @@ impl<'a, R: Repr> Visitor<'a> for NodeVisitor<R> {
         fn visit_bool(bool)
         fn visit_i64(i64)
         fn visit_u64(u64)
+        fn visit_i128(i128)
+        fn visit_u128(u128)
         fn visit_f64(f64)
         fn visit_str(&str)
         fn visit_none
@@ -122,15 +285,19 @@ impl<'a, R: Repr> Visitor<'a> for NodeVisitor<R> {
         while let Some((k, v)) = map.next_entry()? {
             m.insert(k, v);
         }
+        #[cfg(feature = "arbitrary-precision")]
+        if let Some(n) = from_token(&m) {
+            return Ok(n);
+        }
         Ok(m.into_iter().collect())
     }
 }
 
-struct SeqVisitor<R: Repr>(<Seq<R> as IntoIterator>::IntoIter);
+struct SeqVisitor<R: Repr>(<Seq<R> as IntoIterator>::IntoIter, DeOptions);
 
-impl<R: Repr> From<Seq<R>> for SeqVisitor<R> {
-    fn from(v: Seq<R>) -> Self {
-        Self(v.into_iter())
+impl<R: Repr> SeqVisitor<R> {
+    fn new(v: &Seq<R>, opts: DeOptions) -> Self {
+        Self(v.clone().into_iter(), opts)
     }
 }
 
@@ -142,17 +309,30 @@ impl<'a, R: Repr> SeqAccess<'a> for SeqVisitor<R> {
         T: DeserializeSeed<'a>,
     {
         match self.0.next() {
-            Some(e) => seed.deserialize(e).map(Some),
+            Some(e) => {
+                let pos = e.pos();
+                seed.deserialize(NodeDe(e, self.1))
+                    .map(Some)
+                    .map_err(|e| e.or_pos(pos))
+            }
             None => Ok(None),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
 }
 
-struct MapVisitor<R: Repr>(<Map<R> as IntoIterator>::IntoIter, Option<Node<R>>);
+struct MapVisitor<R: Repr>(
+    <Map<R> as IntoIterator>::IntoIter,
+    Option<Node<R>>,
+    DeOptions,
+);
 
-impl<R: Repr> From<Map<R>> for MapVisitor<R> {
-    fn from(m: Map<R>) -> Self {
-        Self(m.into_iter(), None)
+impl<R: Repr> MapVisitor<R> {
+    fn new(m: &Map<R>, opts: DeOptions) -> Self {
+        Self(m.clone().into_iter(), None, opts)
     }
 }
 
@@ -166,7 +346,10 @@ impl<'a, R: Repr> MapAccess<'a> for MapVisitor<R> {
         match self.0.next() {
             Some((k, v)) => {
                 self.1 = Some(v);
-                seed.deserialize(k).map(Some)
+                let pos = k.pos();
+                seed.deserialize(NodeDe(k, self.2))
+                    .map(Some)
+                    .map_err(|e| e.or_pos(pos))
             }
             None => Ok(None),
         }
@@ -177,13 +360,21 @@ impl<'a, R: Repr> MapAccess<'a> for MapVisitor<R> {
         V: DeserializeSeed<'a>,
     {
         match self.1.take() {
-            Some(v) => seed.deserialize(v),
+            Some(v) => {
+                let pos = v.pos();
+                seed.deserialize(NodeDe(v, self.2))
+                    .map_err(|e| e.or_pos(pos))
+            }
             None => unreachable!("visit_value called before visit_key"),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
 }
 
-struct EnumVisitor<R: Repr>(Node<R>, Option<Node<R>>);
+struct EnumVisitor<R: Repr>(Node<R>, Option<Node<R>>, DeOptions);
 
 impl<'a, R: Repr> EnumAccess<'a> for EnumVisitor<R> {
     type Error = SerdeError;
@@ -193,19 +384,20 @@ impl<'a, R: Repr> EnumAccess<'a> for EnumVisitor<R> {
     where
         V: DeserializeSeed<'a>,
     {
-        let visitor = VariantVisitor(self.1);
-        seed.deserialize(self.0).map(|v| (v, visitor))
+        let visitor = VariantVisitor(self.1, self.2);
+        seed.deserialize(NodeDe(self.0, self.2))
+            .map(|v| (v, visitor))
     }
 }
 
-struct VariantVisitor<R: Repr>(Option<Node<R>>);
+struct VariantVisitor<R: Repr>(Option<Node<R>>, DeOptions);
 
 impl<'a, R: Repr> VariantAccess<'a> for VariantVisitor<R> {
     type Error = SerdeError;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
         match self.0 {
-            Some(v) => Deserialize::deserialize(v),
+            Some(v) => Deserialize::deserialize(NodeDe(v, self.1)),
             None => Ok(()),
         }
     }
@@ -215,7 +407,7 @@ impl<'a, R: Repr> VariantAccess<'a> for VariantVisitor<R> {
         T: DeserializeSeed<'a>,
     {
         match self.0 {
-            Some(v) => seed.deserialize(v),
+            Some(v) => seed.deserialize(NodeDe(v, self.1)),
             None => Err(Error::invalid_type(
                 Unexpected::UnitVariant,
                 &"new type variant",
@@ -229,7 +421,7 @@ impl<'a, R: Repr> VariantAccess<'a> for VariantVisitor<R> {
     {
         match self.0 {
             Some(node) => match node.yaml() {
-                Yaml::Seq(v) => visitor.visit_seq(SeqVisitor::from(v.clone())),
+                Yaml::Seq(v) => visitor.visit_seq(SeqVisitor::new(v, self.1.enter(node.pos())?)),
                 _ => Err(unexpected(&node, "tuple variant")),
             },
             None => Err(Error::invalid_type(
@@ -241,7 +433,7 @@ impl<'a, R: Repr> VariantAccess<'a> for VariantVisitor<R> {
 
     fn struct_variant<V>(
         self,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
@@ -249,7 +441,10 @@ impl<'a, R: Repr> VariantAccess<'a> for VariantVisitor<R> {
     {
         match self.0 {
             Some(node) => match node.yaml() {
-                Yaml::Map(m) => visitor.visit_map(MapVisitor::from(m.clone())),
+                Yaml::Map(m) => {
+                    check_fields(m, fields, self.1)?;
+                    visitor.visit_map(MapVisitor::new(m, self.1.enter(node.pos())?))
+                }
                 _ => Err(unexpected(&node, "struct variant")),
             },
             None => Err(Error::invalid_type(
@@ -272,6 +467,65 @@ impl<'a, R: Repr> Deserialize<'a> for Node<R> {
 impl<'a, R: Repr> Deserializer<'a> for Node<R> {
     type Error = SerdeError;
 
+    forward_deserializer! {
+        fn deserialize_any()
+        fn deserialize_bool()
+        fn deserialize_i8()
+        fn deserialize_i16()
+        fn deserialize_i32()
+        fn deserialize_i64()
+        fn deserialize_i128()
+        fn deserialize_u8()
+        fn deserialize_u16()
+        fn deserialize_u32()
+        fn deserialize_u64()
+        fn deserialize_u128()
+        fn deserialize_f32()
+        fn deserialize_f64()
+        fn deserialize_char()
+        fn deserialize_str()
+        fn deserialize_string()
+        fn deserialize_bytes()
+        fn deserialize_byte_buf()
+        fn deserialize_option()
+        fn deserialize_unit()
+        fn deserialize_unit_struct(name: &'static str)
+        fn deserialize_newtype_struct(name: &'static str)
+        fn deserialize_seq()
+        fn deserialize_tuple(len: usize)
+        fn deserialize_tuple_struct(name: &'static str, len: usize)
+        fn deserialize_map()
+        fn deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        fn deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        fn deserialize_identifier()
+        fn deserialize_ignored_any()
+    }
+}
+
+/// The node deserializer with options.
+struct NodeDe<R: Repr>(Node<R>, DeOptions);
+
+impl<R: Repr> Deref for NodeDe<R> {
+    type Target = Node<R>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<R: Repr> NodeDe<R> {
+    fn seq(&self, v: &Seq<R>) -> Result<SeqVisitor<R>, SerdeError> {
+        Ok(SeqVisitor::new(v, self.1.enter(self.pos())?))
+    }
+
+    fn map(&self, m: &Map<R>) -> Result<MapVisitor<R>, SerdeError> {
+        Ok(MapVisitor::new(m, self.1.enter(self.pos())?))
+    }
+}
+
+impl<'a, R: Repr> Deserializer<'a> for NodeDe<R> {
+    type Error = SerdeError;
+
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'a>,
@@ -279,11 +533,23 @@ impl<'a, R: Repr> Deserializer<'a> for Node<R> {
         match self.yaml() {
             Yaml::Null => visitor.visit_unit(),
             Yaml::Bool(b) => visitor.visit_bool(*b),
+            #[cfg(feature = "arbitrary-precision")]
+            Yaml::Int(n) => match to_i64(n) {
+                Ok(n) => visitor.visit_i64(n),
+                Err(_) => visit_big_int(&self, n, visitor),
+            },
+            #[cfg(feature = "arbitrary-precision")]
+            Yaml::Float(n) => match n.parse::<Decimal>() {
+                Ok(d) if !d.is_f64() => visitor.visit_map(to_token::<R>(DECIMAL_TOKEN, d.as_str())),
+                _ => visitor.visit_f64(to_f64(n).unwrap()),
+            },
+            #[cfg(not(feature = "arbitrary-precision"))]
             Yaml::Int(n) => visitor.visit_i64(to_i64(n).unwrap()),
+            #[cfg(not(feature = "arbitrary-precision"))]
             Yaml::Float(n) => visitor.visit_f64(to_f64(n).unwrap()),
             Yaml::Str(s) => visitor.visit_str(s),
-            Yaml::Seq(v) => visitor.visit_seq(SeqVisitor::from(v.clone())),
-            Yaml::Map(m) => visitor.visit_map(MapVisitor::from(m.clone())),
+            Yaml::Seq(v) => visitor.visit_seq(self.seq(v)?),
+            Yaml::Map(m) => visitor.visit_map(self.map(m)?),
             Yaml::Alias(a) => Err(SerdeError::from(format!("anchor {}", a)).pos(self.pos())),
         }
     }
@@ -303,15 +569,31 @@ impl<'a, R: Repr> Deserializer<'a> for Node<R> {
         fn deserialize_str(Str) => visit_str(s => s)
         fn deserialize_string(Str) => visit_str(s => s)
         fn deserialize_char(Str) => visit_str(s => s)
-        fn deserialize_seq(Seq) => visit_seq(a => SeqVisitor::from(a.clone()))
-        fn deserialize_map(Map) => visit_map(m => MapVisitor::from(m.clone()))
         fn deserialize_identifier(Str) => visit_str(s => s)
     }
 
-    serde_if_integer128! {
-        impl_deserializer! {
-            fn deserialize_i128(Int) => visit_i128(n => to_i64(n).unwrap() as i128)
-            fn deserialize_u128(Int) => visit_u128(n => to_i64(n).unwrap() as u128)
+    impl_deserializer! {
+        fn deserialize_i128(Int) => visit_i128(n => to_i64(n).unwrap() as i128)
+        fn deserialize_u128(Int) => visit_u128(n => to_i64(n).unwrap() as u128)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'a>,
+    {
+        match self.yaml() {
+            Yaml::Seq(v) => visitor.visit_seq(self.seq(v)?),
+            _ => Err(unexpected(&self, visitor)),
+        }
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'a>,
+    {
+        match self.yaml() {
+            Yaml::Map(m) => visitor.visit_map(self.map(m)?),
+            _ => Err(unexpected(&self, visitor)),
         }
     }
 
@@ -328,7 +610,7 @@ impl<'a, R: Repr> Deserializer<'a> for Node<R> {
     {
         match self.yaml() {
             Yaml::Str(s) => visitor.visit_str(s),
-            Yaml::Seq(v) => visitor.visit_seq(&mut SeqVisitor::from(v.clone())),
+            Yaml::Seq(v) => visitor.visit_seq(&mut self.seq(v)?),
             _ => Err(unexpected(&self, visitor)),
         }
     }
@@ -373,6 +655,11 @@ impl<'a, R: Repr> Deserializer<'a> for Node<R> {
     where
         V: Visitor<'a>,
     {
+        #[cfg(feature = "arbitrary-precision")]
+        if let (BIG_INT_TOKEN | DECIMAL_TOKEN, Yaml::Int(n) | Yaml::Float(n)) = (_name, self.yaml())
+        {
+            return visitor.visit_str(n);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -398,17 +685,20 @@ impl<'a, R: Repr> Deserializer<'a> for Node<R> {
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'a>,
     {
         match self.yaml() {
-            Yaml::Seq(v) => visitor.visit_seq(SeqVisitor::from(v.clone())),
-            Yaml::Map(m) => visitor.visit_map(MapVisitor::from(m.clone())),
+            Yaml::Seq(v) => visitor.visit_seq(self.seq(v)?),
+            Yaml::Map(m) => {
+                check_fields(m, fields, self.1).and_then(|_| visitor.visit_map(self.map(m)?))
+            }
             _ => Err(unexpected(&self, visitor)),
         }
+        .map_err(|e| e.or_pos(self.pos()))
     }
 
     fn deserialize_enum<V>(
@@ -420,8 +710,10 @@ impl<'a, R: Repr> Deserializer<'a> for Node<R> {
     where
         V: Visitor<'a>,
     {
+        let mut opts = self.1;
         let (k, v) = match self.yaml() {
             Yaml::Map(m) => {
+                opts = opts.enter(self.pos())?;
                 if m.len() != 1 {
                     return Err(unexpected(&self, "map with single pair"));
                 }
@@ -431,10 +723,10 @@ impl<'a, R: Repr> Deserializer<'a> for Node<R> {
                     unreachable!()
                 }
             }
-            Yaml::Str(_) => (self.clone(), None),
+            Yaml::Str(_) => (self.0.clone(), None),
             _ => return Err(unexpected(&self, visitor)),
         };
-        visitor.visit_enum(EnumVisitor(k, v))
+        visitor.visit_enum(EnumVisitor(k, v, opts))
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -445,6 +737,71 @@ impl<'a, R: Repr> Deserializer<'a> for Node<R> {
     }
 }
 
+/// Visit the integer in the smallest primitive type,
+/// or the token map if it is out of range.
+#[cfg(feature = "arbitrary-precision")]
+fn visit_big_int<'a, R, V>(node: &Node<R>, n: &str, visitor: V) -> Result<V::Value, SerdeError>
+where
+    R: Repr,
+    V: Visitor<'a>,
+{
+    let n = n
+        .parse::<BigInt>()
+        .map_err(|e| SerdeError::from(e.to_string()).pos(node.pos()))?;
+    if let Ok(n) = n.as_str().parse() {
+        visitor.visit_u64(n)
+    } else if let Some(n) = n.to_i128() {
+        visitor.visit_i128(n)
+    } else if let Some(n) = n.to_u128() {
+        visitor.visit_u128(n)
+    } else {
+        visitor.visit_map(to_token::<R>(BIG_INT_TOKEN, n.as_str()))
+    }
+}
+
+/// The map that carries the lossless number, see [`from_token`].
+#[cfg(feature = "arbitrary-precision")]
+fn to_token<R: Repr>(token: &'static str, n: &str) -> MapVisitor<R> {
+    let mut m = Map::new();
+    m.insert(Node::from(token), Node::from(n));
+    MapVisitor::new(&m, DeOptions::new())
+}
+
+/// Convert the token map back to the number.
+#[cfg(feature = "arbitrary-precision")]
+fn from_token<R: Repr>(m: &Map<R>) -> Option<Node<R>> {
+    let (k, v) = m.iter().next().filter(|_| m.len() == 1)?;
+    let (Yaml::Str(k), Yaml::Str(n)) = (k.yaml(), v.yaml()) else {
+        return None;
+    };
+    match k.as_str() {
+        BIG_INT_TOKEN => Some(Node::from(Yaml::Int(n.clone()))),
+        DECIMAL_TOKEN => Some(Node::from(Yaml::Float(n.clone()))),
+        _ => None,
+    }
+}
+
+/// Check the unknown keys if [`DeOptions::deny_unknown`] is enabled.
+fn check_fields<R: Repr>(
+    m: &Map<R>,
+    fields: &'static [&'static str],
+    opts: DeOptions,
+) -> Result<(), SerdeError> {
+    if !opts.deny_unknown {
+        return Ok(());
+    }
+    for k in m.keys() {
+        let name = match k.yaml() {
+            Yaml::Str(s) => s.to_string(),
+            _ => k.to_string(),
+        };
+        if !fields.contains(&name.as_str()) {
+            return Err(SerdeError::unknown_field(&name, fields).pos(k.pos()));
+        }
+    }
+    Ok(())
+}
+
 #[cold]
 fn unexpected<R: Repr>(node: &Node<R>, exp: impl Expected) -> SerdeError {
     let ty = match node.yaml() {
@@ -1,11 +1,16 @@
-use alloc::string::{String, ToString};
+use crate::{parser::PError, repr::Repr, Node};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
 use core::fmt::{Debug, Display, Formatter, Result};
 
 /// The error type for the serialization.
 ///
 /// If the error is used at deserializing to a custom data,
 /// the field [`SerdeError.pos`] will provide the position of the original YAML
-/// document.
+/// document, and the field [`SerdeError.path`] will provide the key path if
+/// the root node is known, see [`SerdeError::with_path`].
 #[derive(Debug)]
 pub struct SerdeError {
     /// Message.
@@ -14,6 +19,9 @@ pub struct SerdeError {
     ///
     /// If not provided, this field becomes zero.
     pub pos: u64,
+    /// The key path of the node from the root, see [`Node::path_of`].
+    pub path: Option<String>,
+    source: Option<Box<PError>>,
 }
 
 impl SerdeError {
@@ -21,11 +29,61 @@ impl SerdeError {
         self.pos = pos;
         self
     }
+
+    /// Set the position if it is not provided.
+    pub(crate) fn or_pos(self, pos: u64) -> Self {
+        if self.pos == 0 {
+            self.pos(pos)
+        } else {
+            self
+        }
+    }
+
+    /// Find the key path of the error position from the root node.
+    ///
+    /// The [`from_str`](super::from_str) function will call this method
+    /// automatically.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use yaml_peg::NodeRc;
+    ///
+    /// let root: NodeRc = "a: [1, b]".parse().unwrap();
+    /// let e = Vec::<u8>::deserialize(root["a"].clone()).unwrap_err();
+    /// assert_eq!(e.path, None);
+    /// assert_eq!(e.with_path(&root).path.unwrap(), "a[1]");
+    /// ```
+    pub fn with_path<R: Repr>(mut self, root: &Node<R>) -> Self {
+        self.path = root.path_of(self.pos);
+        self
+    }
+
+    /// The parser error if the error is caused by parsing the document,
+    /// which is also the [source](std::error::Error::source) of the error.
+    pub fn parse_error(&self) -> Option<&PError> {
+        self.source.as_deref()
+    }
 }
 
 impl From<String> for SerdeError {
     fn from(msg: String) -> Self {
-        Self { msg, pos: 0 }
+        Self { msg, pos: 0, path: None, source: None }
+    }
+}
+
+/// The parser error is kept as the [source](std::error::Error::source).
+impl From<PError> for SerdeError {
+    fn from(e: PError) -> Self {
+        let pos = match e {
+            PError::Terminate { pos, .. } => pos,
+            PError::Mismatch => 0,
+        };
+        Self {
+            msg: e.to_string(),
+            pos,
+            path: None,
+            source: Some(Box::new(e)),
+        }
     }
 }
 
@@ -36,7 +94,19 @@ impl Display for SerdeError {
 }
 
 #[cfg(feature = "std")]
-impl std::error::Error for SerdeError {}
+impl std::error::Error for SerdeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as _)
+    }
+}
+
+/// Converted as [`InvalidData`](std::io::ErrorKind::InvalidData) error.
+#[cfg(feature = "std")]
+impl From<SerdeError> for std::io::Error {
+    fn from(e: SerdeError) -> Self {
+        Self::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
 
 impl serde::ser::Error for SerdeError {
     fn custom<T: Display>(msg: T) -> Self {
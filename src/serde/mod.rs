@@ -31,7 +31,10 @@
 //!
 //! For converting custom data into YAML data, please see [`to_node`] and
 //! [`to_arc_node`], and if you went to parse / dump YAML document, use
-//! [`from_str`] and [`to_string`].
+//! [`from_str`] and [`to_string`]. The fields can be skipped or renamed by
+//! [`SerOptions`] without the attributes, see [`to_node_with`]. The reloaded
+//! documents can be deserialized into the existing values to reuse their
+//! buffers, see [`from_str_in_place`].
 //!
 //! # Anchors
 //!
@@ -41,6 +44,35 @@
 //!
 //! Cyclic data should be handled manually.
 //!
+//! # Tags
+//!
+//! The YAML tags can be attached by the [`Tagged`] wrapper. The anchors
+//! cannot be attached since they are not stored in the nodes.
+//!
+//! # Key Order
+//!
+//! The [`Map`](crate::Map) keeps the insertion order, so the key order of the
+//! documents is preserved through the parsing, the deserialization into
+//! [`Node`](crate::Node) (includes the `#[serde(flatten)]` fields), the
+//! serialization and the dumping. The diffs of the generated documents only
+//! show the changed items.
+//!
+//! ```
+//! use yaml_peg::{
+//!     dumper::NL,
+//!     serde::{from_str, to_string},
+//!     NodeRc,
+//! };
+//!
+//! let doc = "zone: b\nname: a\nmeta:\n  v: 1\n  u: 2\n";
+//! let n = from_str::<NodeRc>(doc).unwrap().remove(0);
+//! assert_eq!(to_string(&n).unwrap(), doc.replace('\n', NL));
+//! ```
+//!
+//! The struct fields are serialized in the declaration order, and the
+//! ordered collections like [`BTreeMap`](alloc::collections::BTreeMap) are
+//! sorted by themselves.
+//!
 //! # Mixed String Type
 //!
 //! If the data needs to deserialized from any type into string, please see
@@ -51,9 +83,14 @@
 //! If the data supports listed items but allows single mapped item, please see
 //! [`InlineList`] type.
 //!
+//! # Helpers
+//!
+//! The helpers for the `#[serde(with = "...")]` attribute are in the [`with`]
+//! module.
+//!
 //! # Error
 //!
-//! The error message will provide the position of the node.
+//! The error message will provide the position and the key path of the node.
 //!
 //! Please see [`SerdeError`] for more information.
 //!
@@ -76,8 +113,9 @@
 //! let err = from_str::<Member>(yaml).err().unwrap();
 //! assert_eq!("invalid type: integer `84`, expected a boolean", err.msg);
 //! assert_eq!(20, err.pos);
+//! assert_eq!(Some("married"), err.path.as_deref());
 //! ```
-pub use self::{de::*, error::*, inline_list::*, optional::*, ser::*, stringify::*};
+pub use self::{de::*, error::*, inline_list::*, optional::*, ser::*, stringify::*, tagged::*};
 
 mod de;
 mod error;
@@ -86,3 +124,5 @@ mod optional;
 mod ser;
 mod ser_node;
 mod stringify;
+mod tagged;
+pub mod with;
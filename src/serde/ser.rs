@@ -1,13 +1,23 @@
-use super::SerdeError;
-use crate::{dump, node, repr::Repr, Map, Node, NodeArc, NodeRc, Seq};
-use alloc::string::String;
+use super::{
+    tagged::{tag_name, TAGGED_TOKEN},
+    SerdeError,
+};
+#[cfg(feature = "arbitrary-precision")]
+use crate::number::{BIG_INT_TOKEN, DECIMAL_TOKEN};
+use crate::{
+    dump,
+    dumper::{dump_with, DumpOptions},
+    repr::Repr,
+    Map, Node, NodeArc, NodeRc, Seq, Yaml,
+};
+use alloc::string::{String, ToString};
 use core::marker::PhantomData;
 use serde::{
     ser::{
         SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
         SerializeTupleStruct, SerializeTupleVariant,
     },
-    serde_if_integer128, Serialize, Serializer,
+    Serialize, Serializer,
 };
 
 macro_rules! impl_serializer {
@@ -22,10 +32,10 @@ macro_rules! impl_serializer {
 
 macro_rules! impl_end {
     (@ $self:ident) => {
-        $self.0.into()
+        $self.1.into()
     };
     (@map $self:ident) => {
-        node!(@{$self.1 => $self.0})
+        node!(@{$self.2 => $self.1})
     };
 }
 
@@ -39,7 +49,7 @@ macro_rules! impl_seq_serializer {
             where
                 T: Serialize + ?Sized,
             {
-                self.0.push(value.serialize(NodeSerializer(PhantomData))?);
+                self.1.push(value.serialize(NodeSerializer(self.0, PhantomData))?);
                 Ok(())
             }
 
@@ -60,10 +70,12 @@ macro_rules! impl_map_serializer {
             where
                 T: Serialize + ?Sized,
             {
-                self.0.insert(
-                    key.serialize(NodeSerializer(PhantomData))?,
-                    value.serialize(NodeSerializer(PhantomData))?,
-                );
+                let opts = self.0;
+                let v = value.serialize(NodeSerializer(opts, PhantomData))?;
+                if opts.skip(&v) {
+                    return Ok(());
+                }
+                self.1.insert(Node::from(opts.rename.apply(key)), v);
                 Ok(())
             }
 
@@ -74,6 +86,128 @@ macro_rules! impl_map_serializer {
     };
 }
 
+/// The options of the serializer, see [`to_node_with`].
+///
+/// The options apply to the fields of the structs and the struct variants,
+/// so the internal types can be written as clean documents without the
+/// `#[serde(...)]` attributes on every struct.
+///
+/// ```
+/// use serde::Serialize;
+/// use yaml_peg::{node, serde::{to_node_with, RenamePolicy, SerOptions}};
+///
+/// #[derive(Serialize)]
+/// struct Container<'a> {
+///     image_name: &'a str,
+///     pull_policy: Option<&'a str>,
+///     extra_args: Vec<&'a str>,
+/// }
+///
+/// let c = Container { image_name: "nginx", pull_policy: None, extra_args: vec![] };
+/// let opts = SerOptions::new()
+///     .skip_none(true)
+///     .skip_empty(true)
+///     .rename(RenamePolicy::CamelCase);
+/// assert_eq!(to_node_with(&c, opts).unwrap(), node!({"imageName" => "nginx"}));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct SerOptions {
+    skip_none: bool,
+    skip_empty: bool,
+    rename: RenamePolicy,
+}
+
+impl SerOptions {
+    /// Create the default options.
+    pub const fn new() -> Self {
+        Self {
+            skip_none: false,
+            skip_empty: false,
+            rename: RenamePolicy::Keep,
+        }
+    }
+
+    /// Skip the null fields, such as `None` and `()`, default to false.
+    pub fn skip_none(self, skip_none: bool) -> Self {
+        Self { skip_none, ..self }
+    }
+
+    /// Skip the empty sequences and maps of the fields, default to false.
+    pub fn skip_empty(self, skip_empty: bool) -> Self {
+        Self { skip_empty, ..self }
+    }
+
+    /// Rename the fields by the policy, default to [`RenamePolicy::Keep`].
+    ///
+    /// The renamed fields of `#[serde(rename = "...")]` are converted too.
+    pub fn rename(self, rename: RenamePolicy) -> Self {
+        Self { rename, ..self }
+    }
+
+    fn skip<R: Repr>(&self, n: &Node<R>) -> bool {
+        match n.yaml() {
+            Yaml::Null => self.skip_none,
+            Yaml::Seq(v) => self.skip_empty && v.is_empty(),
+            Yaml::Map(m) => self.skip_empty && m.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+/// The rename policy of the field names, see [`SerOptions::rename`].
+///
+/// The field names are assumed to be `snake_case` like the Rust fields.
+///
+/// ```
+/// use yaml_peg::serde::RenamePolicy;
+///
+/// assert_eq!(RenamePolicy::CamelCase.apply("max_retry_count"), "maxRetryCount");
+/// assert_eq!(RenamePolicy::PascalCase.apply("max_retry_count"), "MaxRetryCount");
+/// assert_eq!(RenamePolicy::KebabCase.apply("max_retry_count"), "max-retry-count");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RenamePolicy {
+    /// Keep the field names.
+    #[default]
+    Keep,
+    /// The `camelCase` names.
+    CamelCase,
+    /// The `PascalCase` names.
+    PascalCase,
+    /// The `kebab-case` names.
+    KebabCase,
+    /// The `SCREAMING_SNAKE_CASE` names.
+    ScreamingSnakeCase,
+}
+
+impl RenamePolicy {
+    /// Convert the field name.
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            Self::Keep => name.to_string(),
+            Self::CamelCase | Self::PascalCase => {
+                let mut s = String::with_capacity(name.len());
+                let mut upper = *self == Self::PascalCase;
+                for c in name.chars() {
+                    if c == '_' {
+                        upper = !s.is_empty();
+                    } else if upper {
+                        s.extend(c.to_uppercase());
+                        upper = false;
+                    } else {
+                        s.push(c);
+                    }
+                }
+                s
+            }
+            Self::KebabCase => name.replace('_', "-"),
+            Self::ScreamingSnakeCase => name.to_uppercase(),
+        }
+    }
+}
+
 /// Serialize data into [`Node`].
 ///
 /// If a serializable data is provide,
@@ -102,7 +236,12 @@ macro_rules! impl_map_serializer {
 /// There is another version for multi-thread reference counter:
 /// [`to_arc_node`].
 pub fn to_node(any: &impl Serialize) -> Result<NodeRc, SerdeError> {
-    any.serialize(NodeSerializer(PhantomData))
+    to_node_with(any, SerOptions::new())
+}
+
+/// Same as [`to_node`], but serialize with the options, see [`SerOptions`].
+pub fn to_node_with(any: &impl Serialize, opts: SerOptions) -> Result<NodeRc, SerdeError> {
+    any.serialize(NodeSerializer(opts, PhantomData))
 }
 
 /// Serialize data into [`NodeArc`].
@@ -129,7 +268,13 @@ pub fn to_node(any: &impl Serialize) -> Result<NodeRc, SerdeError> {
 ///
 /// There is another version for single-thread reference counter: [`to_node`].
 pub fn to_arc_node(any: impl Serialize) -> Result<NodeArc, SerdeError> {
-    any.serialize(NodeSerializer(PhantomData))
+    to_arc_node_with(any, SerOptions::new())
+}
+
+/// Same as [`to_arc_node`], but serialize with the options, see
+/// [`SerOptions`].
+pub fn to_arc_node_with(any: impl Serialize, opts: SerOptions) -> Result<NodeArc, SerdeError> {
+    any.serialize(NodeSerializer(opts, PhantomData))
 }
 
 /// Serialize data into [`Node`] then dump into string.
@@ -157,7 +302,50 @@ pub fn to_string(any: &impl Serialize) -> Result<String, SerdeError> {
     Ok(dump(&[to_node(any)?], &[]))
 }
 
-struct NodeSerializer<R: Repr>(PhantomData<R>);
+/// Same as [`to_string`], but dump with the style options.
+///
+/// ```
+/// use serde::Serialize;
+/// use yaml_peg::{
+///     dumper::{DumpOptions, NullStyle, NL},
+///     serde::{from_str, to_string_with},
+/// };
+///
+/// #[derive(Serialize)]
+/// struct Member<'a> {
+///     name: &'a str,
+///     spouse: Option<&'a str>,
+///     pets: Vec<Pet<'a>>,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Pet<'a> {
+///     name: &'a str,
+///     age: u8,
+/// }
+///
+/// let officer = Member {
+///     name: "Bob",
+///     spouse: None,
+///     pets: vec![Pet { name: "Tom", age: 3 }],
+/// };
+/// let opts = DumpOptions::new().indent(4).null(NullStyle::Tilde);
+/// let officer_doc = "\
+/// name: Bob
+/// spouse: ~
+/// pets:
+///     - name: Tom
+///       age: 3
+/// ";
+/// let doc = to_string_with(&officer, opts).unwrap();
+/// assert_eq!(officer_doc.replace('\n', NL), doc);
+/// assert!(from_str::<yaml_peg::NodeRc>(&doc).is_ok());
+/// ```
+pub fn to_string_with(any: &impl Serialize, opts: DumpOptions) -> Result<String, SerdeError> {
+    Ok(dump_with(&[to_node(any)?], &[], opts))
+}
+
+struct NodeSerializer<R: Repr>(SerOptions, PhantomData<R>);
 
 impl<R: Repr> Serializer for NodeSerializer<R> {
     type Ok = Node<R>;
@@ -188,11 +376,9 @@ impl<R: Repr> Serializer for NodeSerializer<R> {
         fn serialize_unit
     }
 
-    serde_if_integer128! {
-        impl_serializer! {
-            fn serialize_i128(i128)
-            fn serialize_u128(u128)
-        }
+    impl_serializer! {
+        fn serialize_i128(i128)
+        fn serialize_u128(u128)
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
@@ -221,13 +407,30 @@ impl<R: Repr> Serializer for NodeSerializer<R> {
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize + ?Sized,
     {
-        value.serialize(self)
+        let n = value.serialize(self)?;
+        if name == TAGGED_TOKEN {
+            if let Yaml::Seq(v) = n.yaml() {
+                if let [tag, v] = v.as_slice() {
+                    let tag = tag_name(tag.as_str().unwrap_or_default());
+                    return Ok(Node::new_repr(v.clone_yaml(), v.pos(), tag));
+                }
+            }
+        }
+        #[cfg(feature = "arbitrary-precision")]
+        if let Yaml::Str(s) = n.yaml() {
+            match name {
+                BIG_INT_TOKEN => return Ok(Node::from(Yaml::Int(s.clone()))),
+                DECIMAL_TOKEN => return Ok(Node::from(Yaml::Float(s.clone()))),
+                _ => {}
+            }
+        }
+        Ok(n)
     }
 
     fn serialize_newtype_variant<T>(
@@ -240,7 +443,7 @@ impl<R: Repr> Serializer for NodeSerializer<R> {
     where
         T: Serialize + ?Sized,
     {
-        Ok(node!(@{variant => value.serialize(NodeSerializer(PhantomData))?}))
+        Ok(node!(@{variant => value.serialize(self)?}))
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
@@ -248,7 +451,7 @@ impl<R: Repr> Serializer for NodeSerializer<R> {
             Some(n) => Seq::with_capacity(n),
             None => Seq::new(),
         };
-        Ok(SeqSerializer(seq))
+        Ok(SeqSerializer(self.0, seq))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
@@ -270,11 +473,12 @@ impl<R: Repr> Serializer for NodeSerializer<R> {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Ok(TupleVariant(Seq::with_capacity(len), variant))
+        Ok(TupleVariant(self.0, Seq::with_capacity(len), variant))
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         Ok(MapSerializer(
+            self.0,
             match len {
                 Some(n) => Map::with_capacity(n),
                 None => Map::new(),
@@ -288,7 +492,7 @@ impl<R: Repr> Serializer for NodeSerializer<R> {
         _name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(StructSerializer(Map::with_capacity(len)))
+        Ok(StructSerializer(self.0, Map::with_capacity(len)))
     }
 
     fn serialize_struct_variant(
@@ -298,15 +502,15 @@ impl<R: Repr> Serializer for NodeSerializer<R> {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        Ok(StructVariant(Map::with_capacity(len), variant))
+        Ok(StructVariant(self.0, Map::with_capacity(len), variant))
     }
 }
 
-struct SeqSerializer<R: Repr>(Seq<R>);
-struct TupleVariant<R: Repr>(Seq<R>, &'static str);
-struct MapSerializer<R: Repr>(Map<R>, Option<Node<R>>);
-struct StructSerializer<R: Repr>(Map<R>);
-struct StructVariant<R: Repr>(Map<R>, &'static str);
+struct SeqSerializer<R: Repr>(SerOptions, Seq<R>);
+struct TupleVariant<R: Repr>(SerOptions, Seq<R>, &'static str);
+struct MapSerializer<R: Repr>(SerOptions, Map<R>, Option<Node<R>>);
+struct StructSerializer<R: Repr>(SerOptions, Map<R>);
+struct StructVariant<R: Repr>(SerOptions, Map<R>, &'static str);
 
 impl_seq_serializer! {
     impl SerializeSeq for SeqSerializer => serialize_element
@@ -328,7 +532,7 @@ impl<R: Repr> SerializeMap for MapSerializer<R> {
     where
         T: Serialize + ?Sized,
     {
-        self.1 = Some(key.serialize(NodeSerializer(PhantomData))?);
+        self.2 = Some(key.serialize(NodeSerializer(self.0, PhantomData))?);
         Ok(())
     }
 
@@ -336,10 +540,10 @@ impl<R: Repr> SerializeMap for MapSerializer<R> {
     where
         T: Serialize + ?Sized,
     {
-        match self.1.take() {
+        match self.2.take() {
             Some(k) => self
-                .0
-                .insert(k, value.serialize(NodeSerializer(PhantomData))?),
+                .1
+                .insert(k, value.serialize(NodeSerializer(self.0, PhantomData))?),
             None => unreachable!("serialize_value called before serialize_key"),
         };
         Ok(())
@@ -350,13 +554,13 @@ impl<R: Repr> SerializeMap for MapSerializer<R> {
         K: Serialize + ?Sized,
         V: Serialize + ?Sized,
     {
-        let k = key.serialize(NodeSerializer(PhantomData))?;
-        let v = value.serialize(NodeSerializer(PhantomData))?;
-        self.0.insert(k, v);
+        let k = key.serialize(NodeSerializer(self.0, PhantomData))?;
+        let v = value.serialize(NodeSerializer(self.0, PhantomData))?;
+        self.1.insert(k, v);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0.into())
+        Ok(self.1.into())
     }
 }
@@ -1,4 +1,6 @@
 use crate::{repr::Repr, to_f64, to_i64, Node, Yaml};
+#[cfg(feature = "arbitrary-precision")]
+use crate::{BigInt, Decimal};
 use alloc::format;
 use serde::{
     ser::{Error as _, SerializeMap as _},
@@ -13,7 +15,25 @@ impl<R: Repr> Serialize for Node<R> {
         match self.yaml() {
             Yaml::Null => serializer.serialize_unit(),
             Yaml::Bool(b) => serializer.serialize_bool(*b),
+            #[cfg(feature = "arbitrary-precision")]
+            Yaml::Int(n) => match (to_i64(n), n.parse::<BigInt>()) {
+                (Ok(n), _) => serializer.serialize_i64(n),
+                (_, Ok(n)) => match (n.as_str().parse(), n.to_i128(), n.to_u128()) {
+                    (Ok(n), _, _) => serializer.serialize_u64(n),
+                    (_, Some(n), _) => serializer.serialize_i128(n),
+                    (_, _, Some(n)) => serializer.serialize_u128(n),
+                    _ => n.serialize(serializer),
+                },
+                (_, Err(e)) => Err(S::Error::custom(e)),
+            },
+            #[cfg(feature = "arbitrary-precision")]
+            Yaml::Float(n) => match n.parse::<Decimal>() {
+                Ok(d) if !d.is_f64() => d.serialize(serializer),
+                _ => serializer.serialize_f64(to_f64(n).unwrap()),
+            },
+            #[cfg(not(feature = "arbitrary-precision"))]
             Yaml::Int(n) => serializer.serialize_i64(to_i64(n).unwrap()),
+            #[cfg(not(feature = "arbitrary-precision"))]
             Yaml::Float(n) => serializer.serialize_f64(to_f64(n).unwrap()),
             Yaml::Str(s) => serializer.serialize_str(s),
             Yaml::Seq(v) => v.serialize(serializer),
@@ -0,0 +1,60 @@
+use alloc::{format, string::String};
+use serde::{Serialize, Serializer};
+
+pub(crate) const TAGGED_TOKEN: &str = "$yaml_peg::Tagged";
+
+/// A wrapper that attaches a YAML tag on the serialized value.
+///
+/// The tag is written in the YAML notation: `!name` is a local tag, `!!name`
+/// is a tag of the core schema, and `!<uri>` or a bare URI is a verbatim
+/// tag.
+///
+/// ```
+/// use serde::Serialize;
+/// use yaml_peg::{dumper::NL, serde::{to_node, to_string, Tagged}};
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     home: Tagged<&'static str>,
+///     ports: Tagged<Vec<u16>>,
+/// }
+///
+/// let config = Config {
+///     home: Tagged("!env", "HOME"),
+///     ports: Tagged("!!set", vec![80, 443]),
+/// };
+/// let n = to_node(&config).unwrap();
+/// assert_eq!(n["home"].tag(), "env");
+/// assert_eq!(n["ports"].tag(), "tag:yaml.org,2002:set");
+/// let doc = "\
+/// home: !env HOME
+/// ports: !!set
+///   - 80
+///   - 443
+/// ";
+/// assert_eq!(to_string(&config).unwrap(), doc.replace('\n', NL));
+/// ```
+///
+/// The other serializers see a tuple of the tag and the value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tagged<T>(pub &'static str, pub T);
+
+impl<T: Serialize> Serialize for Tagged<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TAGGED_TOKEN, &(self.0, &self.1))
+    }
+}
+
+/// Convert the tag notation to the tag name of the nodes.
+pub(crate) fn tag_name(tag: &str) -> String {
+    if let Some(uri) = tag.strip_prefix("!<").and_then(|s| s.strip_suffix('>')) {
+        uri.into()
+    } else if let Some(name) = tag.strip_prefix("!!") {
+        format!(concat!(crate::parser::tag_prefix!(), "{}"), name)
+    } else {
+        tag.strip_prefix('!').unwrap_or(tag).into()
+    }
+}
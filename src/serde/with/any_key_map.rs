@@ -0,0 +1,81 @@
+//! A string-keyed map that accepts any YAML keys, such as `80: http` and
+//! `true: yes`.
+//!
+//! The keys are converted into strings:
+//! the strings are unchanged, the other scalars use their YAML text,
+//! and the collections are dumped in block format, see [`crate::dump`].
+//! The map type can be any type that implements
+//! `FromIterator<(String, V)>`, such as [`alloc::collections::BTreeMap`].
+//!
+//! ```
+//! use serde::Deserialize;
+//! use std::collections::BTreeMap;
+//! use yaml_peg::{node, serde::with};
+//!
+//! #[derive(Deserialize)]
+//! struct Ports {
+//!     #[serde(with = "with::any_key_map")]
+//!     ports: BTreeMap<String, String>,
+//! }
+//!
+//! let n = node!({"ports" => node!({80 => "http", "ssh" => "22", () => "none"})});
+//! let p = Ports::deserialize(n).unwrap();
+//! assert_eq!(p.ports["80"], "http");
+//! assert_eq!(p.ports["ssh"], "22");
+//! assert_eq!(p.ports["null"], "none");
+//! ```
+use crate::{NodeRc, Yaml};
+use alloc::string::{String, ToString};
+use core::{iter::FromIterator, marker::PhantomData};
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+struct AnyKeyMap<M, V>(PhantomData<(M, V)>);
+
+impl<'a, M, V> Visitor<'a> for AnyKeyMap<M, V>
+where
+    M: FromIterator<(String, V)>,
+    V: Deserialize<'a>,
+{
+    type Value = M;
+
+    fn expecting(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.write_str("map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'a>,
+    {
+        let mut v = alloc::vec::Vec::new();
+        while let Some((k, value)) = map.next_entry::<NodeRc, V>()? {
+            let k = match k.yaml() {
+                Yaml::Str(s) => s.clone(),
+                _ => k.to_string(),
+            };
+            v.push((k, value));
+        }
+        Ok(v.into_iter().collect())
+    }
+}
+
+/// Serialize the map as usual.
+pub fn serialize<M, S>(m: &M, serializer: S) -> Result<S::Ok, S::Error>
+where
+    M: Serialize,
+    S: Serializer,
+{
+    m.serialize(serializer)
+}
+
+/// Deserialize the map with any keys.
+pub fn deserialize<'a, M, V, D>(deserializer: D) -> Result<M, D::Error>
+where
+    M: FromIterator<(String, V)>,
+    V: Deserialize<'a>,
+    D: Deserializer<'a>,
+{
+    deserializer.deserialize_map(AnyKeyMap(PhantomData))
+}
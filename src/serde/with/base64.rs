@@ -0,0 +1,108 @@
+//! Binary data in base64 string, like the `!!binary` tag.
+//!
+//! The field type can be any owned bytes that implements
+//! `From<Vec<u8>>` and `AsRef<[u8]>`, such as [`Vec<u8>`].
+//! The white spaces and line breaks are ignored at deserializing,
+//! so the wrapped block scalars are allowed.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use yaml_peg::{node, serde::{to_string, with}};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Secret {
+//!     #[serde(with = "with::base64")]
+//!     data: Vec<u8>,
+//! }
+//!
+//! let s = Secret::deserialize(node!({"data" => "aGVs\nbG8="})).unwrap();
+//! assert_eq!(s.data, b"hello");
+//! let doc = to_string(&s).unwrap();
+//! assert_eq!(doc.trim_end(), "data: aGVsbG8=");
+//! ```
+use alloc::{string::String, vec::Vec};
+use core::marker::PhantomData;
+use serde::{
+    de::{Error, Visitor},
+    Deserializer, Serializer,
+};
+
+const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode the bytes into base64 string with padding.
+fn encode(b: &[u8]) -> String {
+    let mut s = String::with_capacity(b.len().div_ceil(3) * 4);
+    for chunk in b.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |n, (i, c)| n | u32::from(*c) << (16 - i * 8));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(char::from(TABLE[(n >> (18 - i * 6)) as usize & 0x3F]));
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+/// Decode the base64 string, return `None` if the string is invalid.
+fn decode(s: &str) -> Option<Vec<u8>> {
+    let s = s
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    if s.len() % 4 != 0 {
+        return None;
+    }
+    let mut v = Vec::with_capacity(s.len() / 4 * 3);
+    for (i, chunk) in s.chunks(4).enumerate() {
+        let pad = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if pad > 2 || (pad > 0 && i != s.len() / 4 - 1) {
+            return None;
+        }
+        let mut n = 0;
+        for c in &chunk[..4 - pad] {
+            n = n << 6 | TABLE.iter().position(|t| t == c)? as u32;
+        }
+        n <<= pad * 6;
+        v.extend_from_slice(&n.to_be_bytes()[1..4 - pad]);
+    }
+    Some(v)
+}
+
+struct Base64<T>(PhantomData<T>);
+
+impl<T: From<Vec<u8>>> Visitor<'_> for Base64<T> {
+    type Value = T;
+
+    fn expecting(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.write_str("base64 string")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        decode(v)
+            .map(T::from)
+            .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+    }
+}
+
+/// Serialize the bytes as base64 string.
+pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]>,
+    S: Serializer,
+{
+    serializer.serialize_str(&encode(v.as_ref()))
+}
+
+/// Deserialize the bytes from base64 string.
+pub fn deserialize<'a, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<Vec<u8>>,
+    D: Deserializer<'a>,
+{
+    deserializer.deserialize_str(Base64(PhantomData))
+}
@@ -0,0 +1,26 @@
+//! Helper modules for the `#[serde(with = "...")]` attribute.
+//!
+//! Each module provides the `serialize` and `deserialize` functions,
+//! which handle the common patterns of YAML configurations.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use yaml_peg::{node, serde::with};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Container {
+//!     #[serde(with = "with::one_or_many")]
+//!     command: Vec<String>,
+//!     #[serde(with = "with::string_or_number")]
+//!     port: u16,
+//! }
+//!
+//! let n = node!({"command" => "run", "port" => "8080"});
+//! let c = Container::deserialize(n).unwrap();
+//! assert_eq!(c.command, ["run"]);
+//! assert_eq!(c.port, 8080);
+//! ```
+pub mod any_key_map;
+pub mod base64;
+pub mod one_or_many;
+pub mod string_or_number;
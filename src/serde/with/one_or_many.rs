@@ -0,0 +1,41 @@
+//! A list that allows a single item to be written without the sequence.
+//!
+//! This is the `with` form of [`InlineList`],
+//! the field type is a [`Vec`] and it is always serialized as a sequence.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use yaml_peg::{node, serde::with};
+//!
+//! #[derive(Deserialize)]
+//! struct Job {
+//!     #[serde(with = "with::one_or_many")]
+//!     needs: Vec<String>,
+//! }
+//!
+//! let single = Job::deserialize(node!({"needs" => "build"})).unwrap();
+//! let listed = Job::deserialize(node!({"needs" => node!(["build", "test"])})).unwrap();
+//! assert_eq!(single.needs, ["build"]);
+//! assert_eq!(listed.needs, ["build", "test"]);
+//! ```
+use crate::serde::InlineList;
+use alloc::vec::Vec;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serialize the items as a sequence.
+pub fn serialize<T, S>(v: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    v.serialize(serializer)
+}
+
+/// Deserialize a sequence or a single item.
+pub fn deserialize<'a, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'a>,
+    D: Deserializer<'a>,
+{
+    Ok(InlineList::deserialize(deserializer)?.into_iter().collect())
+}
@@ -0,0 +1,87 @@
+//! A value that can be written as a string or a number,
+//! such as `port: 8080` and `port: "8080"`.
+//!
+//! The field type is parsed by [`FromStr`] and serialized as a string by
+//! [`Display`], so [`alloc::string::String`] fields can accept numbers as
+//! well.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use yaml_peg::{node, serde::with};
+//!
+//! #[derive(Deserialize)]
+//! struct Service {
+//!     #[serde(with = "with::string_or_number")]
+//!     port: u16,
+//!     #[serde(with = "with::string_or_number")]
+//!     version: String,
+//! }
+//!
+//! let s = Service::deserialize(node!({"port" => "80", "version" => 1.2})).unwrap();
+//! assert_eq!(s.port, 80);
+//! assert_eq!(s.version, "1.2");
+//! assert!(Service::deserialize(node!({"port" => "http", "version" => 1})).is_err());
+//! ```
+use alloc::string::{String, ToString};
+use core::{
+    fmt::{Display, Formatter},
+    marker::PhantomData,
+    str::FromStr,
+};
+use serde::{
+    de::{Error, Visitor},
+    Deserializer, Serializer,
+};
+
+struct StringOrNumber<T>(PhantomData<T>);
+
+impl<T> Visitor<'_> for StringOrNumber<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    type Value = T;
+
+    fn expecting(&self, fmt: &mut Formatter) -> core::fmt::Result {
+        fmt.write_str("string or number")
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_string<E: Error>(self, v: String) -> Result<Self::Value, E> {
+        self.visit_str(&v)
+    }
+
+    fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+}
+
+/// Serialize the value as a string.
+pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.collect_str(v)
+}
+
+/// Deserialize the value from a string or a number.
+pub fn deserialize<'a, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'a>,
+{
+    deserializer.deserialize_any(StringOrNumber(PhantomData))
+}
@@ -0,0 +1,207 @@
+use crate::{repr::Repr, *};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// The statistics of a tree, see [`analyze`].
+///
+/// The counts are the sizes of the expanded tree, the shared nodes (the
+/// resolved aliases) are counted at each reference, and they are saturated
+/// at [`usize::MAX`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The number of the null nodes.
+    pub nulls: usize,
+    /// The number of the boolean nodes.
+    pub bools: usize,
+    /// The number of the integer nodes.
+    pub ints: usize,
+    /// The number of the float nodes.
+    pub floats: usize,
+    /// The number of the string nodes.
+    pub strs: usize,
+    /// The number of the sequences.
+    pub seqs: usize,
+    /// The number of the maps.
+    pub maps: usize,
+    /// The maximum depth, the root is at depth 1.
+    pub max_depth: usize,
+    /// The total bytes of the string and the number texts.
+    pub scalar_bytes: usize,
+    /// The number of the anchors, which are the nodes shared by multiple
+    /// parents and the names of the unresolved aliases.
+    pub anchors: usize,
+    /// The number of the aliases, which are the references to the shared
+    /// nodes except the first one and the unresolved aliases
+    /// [`Yaml::Alias`].
+    pub aliases: usize,
+    /// The number of the collections which are the same as a previous one
+    /// but not shared with it, the nested ones are not counted. The
+    /// collections are the same if they have the same tags, scalar
+    /// notations and map orders.
+    pub duplicates: usize,
+    /// The total number of the nodes in the duplicated collections.
+    pub duplicate_nodes: usize,
+}
+
+impl Stats {
+    /// The total number of the nodes, the unresolved aliases are excluded.
+    pub fn nodes(&self) -> usize {
+        [
+            self.nulls,
+            self.bools,
+            self.ints,
+            self.floats,
+            self.strs,
+            self.seqs,
+            self.maps,
+        ]
+        .into_iter()
+        .fold(0, usize::saturating_add)
+    }
+
+    /// Add the sizes of the child subtree.
+    fn push(&mut self, child: &Self) {
+        let add = |a: &mut usize, b| *a = a.saturating_add(b);
+        add(&mut self.nulls, child.nulls);
+        add(&mut self.bools, child.bools);
+        add(&mut self.ints, child.ints);
+        add(&mut self.floats, child.floats);
+        add(&mut self.strs, child.strs);
+        add(&mut self.seqs, child.seqs);
+        add(&mut self.maps, child.maps);
+        add(&mut self.scalar_bytes, child.scalar_bytes);
+        self.max_depth = self.max_depth.max(child.max_depth.saturating_add(1));
+    }
+}
+
+/// The interned subtree: tag, kind, scalar text and the children.
+type Key<'a> = (&'a str, u8, &'a str, Vec<usize>);
+
+struct Walker<'a, R: Repr> {
+    stats: Stats,
+    /// The subtree sizes and the subtree IDs of the visited nodes, and
+    /// whether the nodes are shared.
+    seen: BTreeMap<*const Yaml<R>, (Stats, usize, bool)>,
+    ids: BTreeMap<Key<'a>, usize>,
+    names: BTreeSet<&'a str>,
+}
+
+impl<'a, R: Repr> Walker<'a, R> {
+    /// Return the subtree sizes and the subtree ID.
+    fn walk(&mut self, node: &'a Node<R>) -> (Stats, usize) {
+        let ptr = node.yaml() as *const _;
+        if let Some((sub, id, shared)) = self.seen.get_mut(&ptr) {
+            if !*shared {
+                *shared = true;
+                self.stats.anchors += 1;
+            }
+            self.stats.aliases += 1;
+            return (sub.clone(), *id);
+        }
+        let (duplicates, duplicate_nodes) = (self.stats.duplicates, self.stats.duplicate_nodes);
+        let mut sub = Stats { max_depth: 1, ..Stats::default() };
+        let mut children = Vec::new();
+        let (kind, text) = match node.yaml() {
+            Yaml::Null => {
+                sub.nulls = 1;
+                (0, "")
+            }
+            Yaml::Bool(b) => {
+                sub.bools = 1;
+                (1, if *b { "true" } else { "false" })
+            }
+            Yaml::Int(n) => {
+                sub.ints = 1;
+                sub.scalar_bytes = n.len();
+                (2, n.as_str())
+            }
+            Yaml::Float(n) => {
+                sub.floats = 1;
+                sub.scalar_bytes = n.len();
+                (3, n.as_str())
+            }
+            Yaml::Str(s) => {
+                sub.strs = 1;
+                sub.scalar_bytes = s.len();
+                (4, s.as_str())
+            }
+            Yaml::Seq(v) => {
+                sub.seqs = 1;
+                for n in v {
+                    let (child, id) = self.walk(n);
+                    sub.push(&child);
+                    children.push(id);
+                }
+                (5, "")
+            }
+            Yaml::Map(m) => {
+                sub.maps = 1;
+                for (k, v) in m.iter() {
+                    for n in [k, v] {
+                        let (child, id) = self.walk(n);
+                        sub.push(&child);
+                        children.push(id);
+                    }
+                }
+                (6, "")
+            }
+            Yaml::Alias(a) => {
+                if self.names.insert(a) {
+                    self.stats.anchors += 1;
+                }
+                self.stats.aliases += 1;
+                (7, a.as_str())
+            }
+        };
+        let len = self.ids.len();
+        let key = (node.tag(), kind, text, children);
+        let is_collection = matches!(kind, 5 | 6);
+        let id = match self.ids.get(&key) {
+            Some(id) if is_collection => {
+                // The nested duplicates are replaced by the outer one
+                self.stats.duplicates = duplicates + 1;
+                self.stats.duplicate_nodes = duplicate_nodes.saturating_add(sub.nodes());
+                *id
+            }
+            Some(id) => *id,
+            None => *self.ids.entry(key).or_insert(len),
+        };
+        self.seen.insert(ptr, (sub.clone(), id, false));
+        (sub, id)
+    }
+}
+
+/// Analyze the tree, for planning the capacity or rejecting the oversized
+/// documents before using them.
+///
+/// The tree is walked once, the shared nodes are not walked again, so the
+/// aliases of the untrusted documents cannot slow it down, see [`Stats`].
+///
+/// ```
+/// use yaml_peg::{analyze, parse, repr::RcRepr};
+///
+/// let doc = "a: &x [1, 2]\nb: *x\nc: [1, 2]\nd: longer text\n";
+/// let stats = analyze(&parse::<RcRepr>(doc).unwrap()[0]);
+/// assert_eq!(stats.nodes(), 15);
+/// assert_eq!((stats.seqs, stats.ints, stats.strs), (3, 6, 5));
+/// assert_eq!(stats.max_depth, 3);
+/// assert_eq!(stats.scalar_bytes, 21);
+/// assert_eq!((stats.anchors, stats.aliases), (1, 1));
+/// assert_eq!((stats.duplicates, stats.duplicate_nodes), (1, 3));
+/// ```
+pub fn analyze<R: Repr>(node: &Node<R>) -> Stats {
+    let mut walker = Walker {
+        stats: Stats::default(),
+        seen: BTreeMap::new(),
+        ids: BTreeMap::new(),
+        names: BTreeSet::new(),
+    };
+    let (sub, _) = walker.walk(node);
+    let mut stats = walker.stats;
+    stats.push(&sub);
+    stats.max_depth = sub.max_depth;
+    stats
+}
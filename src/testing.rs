@@ -0,0 +1,256 @@
+//! Helpers for the golden-file tests, which parse the fixtures and compare
+//! them against the expected trees or the dumped canonical output.
+//!
+//! The assertions panic with the differences instead of the whole trees, see
+//! [`diff`].
+//!
+//! ```
+//! use yaml_peg::{node, testing::assert_tree_eq, NodeRc};
+//!
+//! let found: NodeRc = "spec: {replicas: 3, ports: [80]}".parse().unwrap();
+//! assert_tree_eq(&found, &node!({"spec" => node!({"replicas" => 3, "ports" => node!([80])})}));
+//! ```
+//!
+//! The golden files are the canonical dumps, see [`assert_golden`]. Set the
+//! `YAML_PEG_BLESS` environment variable to `1` to write the files instead of
+//! comparing them.
+use crate::{
+    dumper::{dump_with, DumpOptions},
+    json::to_json,
+    repr::Repr,
+    *,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// A difference of two trees, see [`diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Difference {
+    /// The key path, in the form of [`Node::path_of`].
+    pub path: String,
+    /// The expected node, `None` if it is unexpected.
+    pub expected: Option<String>,
+    /// The found node, `None` if it is missing.
+    pub found: Option<String>,
+}
+
+impl Difference {
+    fn new<R: Repr>(path: &str, expected: Option<&Node<R>>, found: Option<&Node<R>>) -> Self {
+        let (mut e, mut n) = (expected.map(show), found.map(show));
+        // Show the tags if the notations are the same, such as `1` and `1.0`
+        if let (Some(e), Some(n), Some(expected), Some(found)) = (&mut e, &mut n, expected, found) {
+            if e == n {
+                *e = format!("{} {e}", dumper::tag_notation(expected.tag()));
+                *n = format!("{} {n}", dumper::tag_notation(found.tag()));
+            }
+        }
+        Self { path: path.to_string(), expected: e, found: n }
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let path = if self.path.is_empty() {
+            "<root>"
+        } else {
+            &self.path
+        };
+        match (&self.expected, &self.found) {
+            (Some(e), Some(n)) => write!(f, "{path}: expected {e}, found {n}"),
+            (Some(e), None) => write!(f, "{path}: missing {e}"),
+            (None, Some(n)) => write!(f, "{path}: unexpected {n}"),
+            (None, None) => f.write_str(path),
+        }
+    }
+}
+
+/// The node in a single line.
+fn show<R: Repr>(node: &Node<R>) -> String {
+    let s = node.to_string();
+    if s.contains('\n') {
+        to_json(node)
+    } else {
+        s
+    }
+}
+
+/// Find the differences of two trees in the document order, empty if they
+/// are the same by [`semantic_eq`].
+///
+/// The sequence items are compared by their indices, and the map items are
+/// compared by their keys. If the collections only differ in their tags or
+/// their orders, the collections themselves are reported.
+///
+/// ```
+/// use yaml_peg::{node, testing::diff, NodeRc, SemanticOptions};
+///
+/// let found: NodeRc = "a: [1, 2, 3]\nb: x\n".parse().unwrap();
+/// let expected = node!({"a" => node!([1, 5]), "c" => "x"});
+/// let v = diff(&found, &expected, SemanticOptions::new())
+///     .into_iter()
+///     .map(|d| d.to_string())
+///     .collect::<Vec<_>>();
+/// assert_eq!(v, ["a[1]: expected 5, found 2", "a[2]: unexpected 3", "c: missing x", "b: unexpected x"]);
+/// ```
+pub fn diff<R: Repr>(
+    found: &Node<R>,
+    expected: &Node<R>,
+    opts: SemanticOptions,
+) -> Vec<Difference> {
+    fn walk<R: Repr>(
+        found: &Node<R>,
+        expected: &Node<R>,
+        opts: SemanticOptions,
+        path: &mut String,
+        v: &mut Vec<Difference>,
+    ) {
+        if semantic_eq(found, expected, opts) {
+            return;
+        }
+        let (len, start) = (path.len(), v.len());
+        match (found.yaml(), expected.yaml()) {
+            (Yaml::Seq(a), Yaml::Seq(b)) => {
+                for i in 0..a.len().max(b.len()) {
+                    path.push_str(&format!("[{i}]"));
+                    match (a.get(i), b.get(i)) {
+                        (Some(a), Some(b)) => walk(a, b, opts, path, v),
+                        (a, b) => v.push(Difference::new(path, b, a)),
+                    }
+                    path.truncate(len);
+                }
+            }
+            (Yaml::Map(a), Yaml::Map(b)) => {
+                for (k, e) in b.iter() {
+                    k.push_path(path);
+                    match a.get(k) {
+                        Some(n) => walk(n, e, opts, path, v),
+                        None => v.push(Difference::new(path, Some(e), None)),
+                    }
+                    path.truncate(len);
+                }
+                for (k, n) in a.iter().filter(|(k, _)| !b.contains_key(k)) {
+                    k.push_path(path);
+                    v.push(Difference::new(path, None, Some(n)));
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+        if v.len() == start {
+            v.push(Difference::new(path, Some(expected), Some(found)));
+        }
+    }
+    let mut v = Vec::new();
+    walk(found, expected, opts, &mut String::new(), &mut v);
+    v
+}
+
+/// Parse the fixture file, panic with the file name if the file cannot be
+/// read or parsed.
+///
+/// ```no_run
+/// use yaml_peg::{repr::RcRepr, testing::load_fixture};
+///
+/// let docs = load_fixture::<RcRepr>("tests/fixtures/config.yaml");
+/// ```
+#[track_caller]
+pub fn load_fixture<R: Repr>(path: impl AsRef<Path>) -> Vec<Node<R>> {
+    let path = path.as_ref();
+    let doc = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {e}", path.display()));
+    parse(&doc).unwrap_or_else(|e| panic!("cannot parse {}: {e}", path.display()))
+}
+
+/// Assert the trees are the same by [`semantic_eq`] with the default
+/// options, panic with the differences.
+#[track_caller]
+pub fn assert_tree_eq<R: Repr>(found: &Node<R>, expected: &Node<R>) {
+    let v = diff(found, expected, SemanticOptions::new());
+    if !v.is_empty() {
+        let v = v.iter().map(|d| format!("  {d}\n")).collect::<String>();
+        panic!("the trees are different:\n{v}");
+    }
+}
+
+/// The canonical dump of the documents, the line endings are `\n`.
+fn canonical<R: Repr>(nodes: &[Node<R>]) -> String {
+    dump_with(nodes, &[], DumpOptions::new().canonical(true)).replace("\r\n", "\n")
+}
+
+/// The lines of the expected text and the found text, the removed lines are
+/// marked with `-` and the added lines are marked with `+`. The lines are
+/// matched by a bounded table, so the large changes are shown as a whole.
+fn line_diff(expected: &str, found: &str) -> String {
+    let a = expected.lines().collect::<Vec<_>>();
+    let b = found.lines().collect::<Vec<_>>();
+    let mut pairs = dumper::common_pairs(&a, &b, |x, y| x == y);
+    pairs.push((a.len(), b.len()));
+    let (mut i, mut j) = (0, 0);
+    let mut doc = String::new();
+    for (pi, pj) in pairs {
+        a[i..pi].iter().for_each(|s| doc += &format!("- {s}\n"));
+        b[j..pj].iter().for_each(|s| doc += &format!("+ {s}\n"));
+        if let Some(s) = a.get(pi) {
+            doc += &format!("  {s}\n");
+        }
+        (i, j) = (pi + 1, pj + 1);
+    }
+    doc
+}
+
+/// Assert the canonical dump of the documents is the expected text, panic
+/// with the line differences. The line endings are ignored.
+///
+/// ```
+/// use yaml_peg::{node, testing::assert_canonical};
+///
+/// assert_canonical(&[node!({"a" => 1})], "%YAML 1.2\n--- !!map {\n  ? !!str \"a\"\n  : !!int \"1\",\n}\n");
+/// ```
+#[track_caller]
+pub fn assert_canonical<R: Repr>(nodes: &[Node<R>], expected: &str) {
+    let found = canonical(nodes);
+    let expected = expected.replace("\r\n", "\n");
+    if found != expected {
+        panic!(
+            "the canonical dump is different:\n{}",
+            line_diff(&expected, &found)
+        );
+    }
+}
+
+/// Whether the `YAML_PEG_BLESS` environment variable is set to a true value.
+fn bless() -> bool {
+    std::env::var_os("YAML_PEG_BLESS")
+        .is_some_and(|v| !matches!(v.to_str(), Some("" | "0" | "false")))
+}
+
+/// Assert the canonical dump of the documents is the content of the golden
+/// file, see [`assert_canonical`].
+///
+/// If the `YAML_PEG_BLESS` environment variable is set and not empty, `0` or
+/// `false`, the golden file is written instead.
+///
+/// ```no_run
+/// use yaml_peg::{repr::RcRepr, testing::{assert_golden, load_fixture}};
+///
+/// let docs = load_fixture::<RcRepr>("tests/fixtures/config.yaml");
+/// assert_golden(&docs, "tests/golden/config.yaml");
+/// ```
+#[track_caller]
+pub fn assert_golden<R: Repr>(nodes: &[Node<R>], path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    if bless() {
+        std::fs::write(path, canonical(nodes))
+            .unwrap_or_else(|e| panic!("cannot write {}: {e}", path.display()));
+        return;
+    }
+    let expected = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {e}", path.display()));
+    assert_canonical(nodes, &expected);
+}
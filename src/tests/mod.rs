@@ -1,4 +1,10 @@
 use crate::{parser::PError, *};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
 fn show_err<E>(e: PError) -> E {
     panic!("{}", e)
@@ -56,10 +62,10 @@ fn test_yaml() {
                 (),
             ]),
             node!({"a4" => ()}) => -30,
-            node!({"a4" => ()}) => "b3, b4",
+            node!({node!({"a4" => ()}) => "b3, b4"}) => (),
             "test multiline" => node!([
                 node!({
-                    "folded" => "aaa{}[] bbb ccc\nddd\n# eee\n",
+                    "folded" => "aaa{}[] bbb\n  ccc\n\n  ddd\n\n# eee\n",
                     "literal" => "aaa{}[]\nbbb\n  ccc\n\n  ddd\n\n# eee\n",
                 }),
                 node!({
@@ -117,6 +123,11 @@ fn test_dump() {
     ];
     let doc = dump(&nodes, &[]);
     assert_eq!(doc.replace("\r\n", "\n"), DOC.replace("\r\n", "\n"));
+    // The empty collections
+    let n = node!({"a" => node!([]), "b" => node!({}), "c" => node!([node!([])])});
+    let doc = dump(core::slice::from_ref(&n), &[]);
+    assert_eq!(doc.replace("\r\n", "\n"), "a: []\nb: {}\nc:\n  - []\n");
+    assert_eq!(parse(&doc).unwrap(), [n]);
 }
 
 #[test]
@@ -157,6 +168,2854 @@ fn test_anchor() {
             node!({"a" => "b"}),
             node!([node!({"a" => "b"}), node!({"a" => "b"})]),
             node!([node!({"a" => "b"}), node!({"a" => "b"})]),
+            "c",
+            "c",
         ])
     );
 }
+
+#[test]
+fn test_anchor_collection() {
+    const DOC: &str = "\
+x-common: &common
+  image: nginx
+  ports: &ports [80, 443]
+services:
+  web:
+    <<: *common
+    expose: *ports
+  api: *common
+  tags:
+    - &tag k: v
+    - *tag
+";
+    let common = node!({"image" => "nginx", "ports" => node!([80, 443])});
+    let ans = node!({
+        "x-common" => common.clone(),
+        "services" => node!({
+            "web" => node!({"<<" => common.clone(), "expose" => node!([80, 443])}),
+            "api" => common.clone(),
+            "tags" => node!([node!({"k" => "v"}), "k"]),
+        }),
+    });
+    assert_eq!(parse(DOC).unwrap_or_else(show_err), [ans]);
+    let mut loader = parser::Loader::<repr::RcRepr>::new(DOC.as_bytes()).cyclic_mode(true);
+    let root = loader.parse().unwrap_or_else(show_err).remove(0);
+    assert_eq!(
+        root.get("services").unwrap().get("api").unwrap(),
+        &node!(*"common")
+    );
+    let anchors = loader.get_anchors().remove(0);
+    assert_eq!(anchors["common"], common);
+    assert_eq!(anchors["ports"], node!([80, 443]));
+    assert_eq!(anchors["tag"], node!("k"));
+}
+
+#[test]
+fn test_documents() {
+    use crate::dumper::{
+        dump_documents, dump_documents_with, DumpOptions, LineEnding, QuotePolicy,
+    };
+    const DOC: &str = "\
+%YAML 1.2
+%TAG !x! tag:x.prefix:
+--- !x!a
+a
+...
+%TAG !y! tag:y.prefix:
+--- !y!b
+b
+---
+c
+";
+    let docs = parse_documents::<repr::RcRepr>(DOC).unwrap_or_else(show_err);
+    assert_eq!(docs.len(), 3);
+    assert_eq!(docs[0].root.tag(), "tag:x.prefix:a");
+    assert_eq!(docs[0].version.as_deref(), Some("1.2"));
+    assert!(docs[0].explicit_end);
+    assert_eq!(docs[1].root.tag(), "tag:y.prefix:b");
+    assert_eq!(docs[1].version, None);
+    assert!(!docs[1].tags.contains_key("!x!"));
+    assert!(!docs[1].explicit_end);
+    assert_eq!(docs[2].tags, docs[1].tags);
+    assert!(docs[2].explicit_start);
+    // The line endings and the styles of the options
+    let opts = DumpOptions::new().line_ending(LineEnding::Lf);
+    let doc = dump_documents_with(&docs, opts.clone());
+    assert_eq!(dump_documents(&docs), doc.replace('\n', dumper::NL));
+    assert!(doc.ends_with("---\n!y!b b\n...\n%TAG !y! tag:y.prefix:\n---\nc\n"));
+    let opts = opts
+        .line_ending(LineEnding::CrLf)
+        .quote(QuotePolicy::Always);
+    let doc = doc.replace('\n', "\r\n").replace(" a\r", " \"a\"\r");
+    let doc = doc
+        .replace(" b\r", " \"b\"\r")
+        .replace("\nc\r", "\n\"c\"\r");
+    assert_eq!(dump_documents_with(&docs, opts), doc);
+}
+
+#[test]
+fn test_number_format() {
+    const DOC: &str = "[10.0, 1.50, 4., 0xab, 0o17, -2e-4, .inf, -.inf, .nan]";
+    let root = parse::<repr::RcRepr>(DOC).unwrap_or_else(show_err);
+    assert_eq!(
+        root[0],
+        node!([
+            10.,
+            1.5,
+            4.,
+            0xab,
+            0o17,
+            -2e-4,
+            f64::INFINITY,
+            -f64::INFINITY,
+            f64::NAN
+        ])
+    );
+    let doc = dump(&root, &[]);
+    let ans = "
+- 10.0
+- 1.50
+- 4.
+- 0xab
+- 0o17
+- -2e-4
+- .inf
+- -.inf
+- .nan
+";
+    assert_eq!(doc.replace("\r\n", "\n"), ans);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_dump_sink() {
+    use crate::dumper::{dump_fmt, dump_to, dump_with, DumpOptions};
+    use std::io::{Error, ErrorKind, Write};
+    /// Record the largest write.
+    #[derive(Default)]
+    struct Sink(String, usize);
+    impl core::fmt::Write for Sink {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.0 += s;
+            self.1 = self.1.max(s.len());
+            Ok(())
+        }
+    }
+    let item = |i: i32| node!({"a" => i, "b" => node!([node!({"c" => i})])});
+    let root = node!((0..1000).map(item).collect::<Seq<_>>());
+    let nodes = [root.clone(), root];
+    for opts in [DumpOptions::new(), DumpOptions::new().canonical(true)] {
+        let mut sink = Sink::default();
+        dump_fmt(&mut sink, &nodes, &[], opts.clone()).unwrap();
+        assert_eq!(sink.0, dump_with(&nodes, &[], opts));
+        assert!(sink.1 < 20, "{}", sink.1);
+    }
+    // The I/O error is returned
+    struct Fail;
+    impl Write for Fail {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(Error::new(ErrorKind::BrokenPipe, "closed"))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let e = dump_to(&mut Fail, &nodes, &[], DumpOptions::new()).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn test_dump_quote() {
+    use crate::dumper::{dump_with, DumpOptions, QuotePolicy, QuoteStyle};
+    let strings = [
+        "no", "1.0", "0x10", "*a", "&a", "!a", "null", "", "a: b", "- a", "a #b", "it's", "plain",
+    ];
+    let nodes = [strings.into_iter().map(NodeRc::from).collect()];
+    let doc = dump(&nodes, &[]);
+    let ans = r#"
+- "no"
+- "1.0"
+- "0x10"
+- "*a"
+- "&a"
+- "!a"
+- "null"
+- ""
+- "a: b"
+- "- a"
+- "a #b"
+- it's
+- plain
+"#;
+    assert_eq!(doc.replace("\r\n", "\n"), ans);
+    assert_eq!(parse(&doc).unwrap_or_else(show_err), nodes);
+    let opts = DumpOptions::new()
+        .quote(QuotePolicy::Custom(|s| s.starts_with('p')))
+        .quote_style(QuoteStyle::Single);
+    let doc = dump_with(&nodes, &[], opts);
+    let ans = r#"
+- 'no'
+- '1.0'
+- '0x10'
+- '*a'
+- '&a'
+- '!a'
+- 'null'
+- ''
+- 'a: b'
+- '- a'
+- 'a #b'
+- it's
+- 'plain'
+"#;
+    assert_eq!(doc.replace("\r\n", "\n"), ans);
+    assert_eq!(parse(&doc).unwrap_or_else(show_err), nodes);
+}
+
+#[test]
+fn test_dump_block() {
+    use crate::dumper::{dump_with, BlockStyle, DumpOptions};
+    let strings = ["a\nb", "a\nb\n", "a\nb\n\n", " a\nb", "a\r\nb"];
+    let nodes = [strings.into_iter().map(NodeRc::from).collect()];
+    let doc = dump(&nodes, &[]);
+    let ans = r#"
+- |-
+  a
+  b
+- |
+  a
+  b
+- |+
+  a
+  b
+
+- |2-
+   a
+  b
+- "a\r\nb"
+"#;
+    assert_eq!(doc.replace("\r\n", "\n"), ans);
+    assert_eq!(parse(&doc).unwrap_or_else(show_err), nodes);
+    let nodes = [node!({"k\n" => "aaa bbb ccc\nddd\n"})];
+    let opts = DumpOptions::new().block(BlockStyle::Folded).width(5);
+    let doc = dump_with(&nodes, &[], opts);
+    let ans = r#"
+"k\n": >
+  aaa
+  bbb
+  ccc
+
+  ddd
+"#;
+    assert_eq!(doc.replace("\r\n", "\n"), &ans[1..]);
+    assert_eq!(parse(&doc).unwrap_or_else(show_err), nodes);
+}
+
+#[test]
+fn test_block_scalar() {
+    // YAML 1.2 spec, example 8.1
+    let doc = "- | # Empty header\n literal\n- >1 # Indentation indicator\n  folded\n- |+ # Chomping indicator\n keep\n\n- >1- # Both indicators\n  strip\n";
+    let ans = node!(["literal\n", " folded\n", "keep\n\n", " strip"]);
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    // Example 8.2
+    let doc = "- |\n detected\n- >\n \n  \n  # detected\n- |1\n  explicit\n- >\n \t\n detected\n";
+    let ans = node!([
+        "detected\n",
+        "\n\n# detected\n",
+        " explicit\n",
+        "\t\ndetected\n"
+    ]);
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    // Example 8.3
+    for doc in ["- |\n  \n text\n", "- >\n  text\n text\n", "- |2\n text\n"] {
+        assert!(parse::<repr::RcRepr>(doc).is_err());
+    }
+    // Example 8.4, 8.6
+    let doc = "strip: |-\n  text\nclip: |\n  text\nkeep: |+\n  text\n";
+    let ans = node!({"strip" => "text", "clip" => "text\n", "keep" => "text\n"});
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    let doc = "strip: >-\n\nclip: >\n\nkeep: |+\n\n";
+    let ans = node!({"strip" => "", "clip" => "", "keep" => "\n"});
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    // Example 8.10
+    let doc = ">\n\n folded\n line\n\n next\n line\n   * bullet\n\n   * list\n   * lines\n\n last\n line\n\n# Comment\n";
+    let ans = "\nfolded line\nnext line\n  * bullet\n\n  * list\n  * lines\n\nlast line\n";
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [node!(ans)]);
+    // Empty lines with spaces between the nodes
+    let doc = "a: |-\n  x\n  \n  y\n \nb: >+\n  x\n\n";
+    let ans = node!({"a" => "x\n\ny", "b" => "x\n\n"});
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    // The deprecated sub-parsers
+    #[allow(deprecated)]
+    for (doc, sep, leading, ans) in [
+        ("|-\n  a\n   b\n", b'\n', true, "a\n b"),
+        (">\n  a\n  b\n", b' ', false, "a b\n"),
+    ] {
+        let mut p = crate::parser::Parser::new(doc.as_bytes());
+        p.take_while(|c| matches!(c, b'|' | b'>'), crate::parser::TakeOpt::One)
+            .unwrap();
+        let chomp = p.chomp();
+        let s = p.string_wrapped(1, sep, leading).unwrap();
+        assert_eq!(chomp(s), ans);
+    }
+}
+
+#[test]
+fn test_block_scalar_round_trip() {
+    use crate::dumper::{dump_with, BlockStyle, DumpOptions};
+    let strings = ["a\n\n b\nc", "\n\na\n\n\n", " a\n\tb\n", "a \nb  \n"];
+    let seq = strings.into_iter().map(NodeRc::from).collect::<NodeRc>();
+    let nodes = [node!({
+        "a" => strings[0],
+        "b" => strings[1],
+        "c" => strings[2],
+        "d" => strings[3],
+        "e" => node!([seq.clone(), node!({"f" => seq})]),
+    })];
+    for block in [BlockStyle::Literal, BlockStyle::Folded] {
+        let opts = DumpOptions::new().block(block).indent(4);
+        let doc = dump_with(&nodes, &[], opts);
+        assert_eq!(parse(&doc).unwrap_or_else(show_err), nodes, "{}", doc);
+    }
+}
+
+#[test]
+fn test_escape() {
+    // YAML 1.2 spec, example 5.13
+    let doc = r#""Fun with \\ \" \a \b \e \f \n \r \t \v \0 \  \_ \N \L \P \x41 A \U00000041""#;
+    let ans = "Fun with \\ \" \x07 \x08 \x1B \x0C \n \r \t \x0B \0 \u{20} \u{A0} \u{85} \u{2028} \u{2029} A A A";
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [node!(ans)]);
+    // Example 7.5
+    let doc = "\"folded \nto a space,\t\n \nto a line feed, or \t\\\n \\ \tnon-content\"";
+    let ans = "folded to a space,\nto a line feed, or \t \tnon-content";
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [node!(ans)]);
+    let doc = r#"["a\\", "☺\U0001F600"]"#;
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [node!(["a\\", "☺😀"])]);
+    // Invalid escapes
+    for doc in [r#""\c""#, r#""\x4""#, r#""\uD800""#, r#""a"#] {
+        assert!(parse::<repr::RcRepr>(doc).is_err(), "{}", doc);
+    }
+    let s = "\0\x07\x1B\u{85}\"\\";
+    let doc = dump(&[node!(s)], &[]);
+    assert_eq!(parse(&doc).unwrap_or_else(show_err), [node!(s)]);
+}
+
+#[test]
+fn test_complex_key() {
+    let doc = "\
+? - a
+  - b
+: - c
+  - d
+? x: 1
+  y: 2
+: z
+? lonely
+? [e, f]
+: g
+?
+: empty
+";
+    let ans = [node!({
+        node!(["a", "b"]) => node!(["c", "d"]),
+        node!({"x" => 1, "y" => 2}) => "z",
+        "lonely" => (),
+        node!(["e", "f"]) => "g",
+        () => "empty",
+    })];
+    assert_eq!(parse(doc).unwrap_or_else(show_err), ans);
+    assert_eq!(parse(&dump(&ans, &[])).unwrap_or_else(show_err), ans);
+    let doc = "{? [a, b]: c, {x: y} : d, ? e, f}";
+    let ans = node!({
+        node!(["a", "b"]) => "c",
+        node!({"x" => "y"}) => "d",
+        "e" => (),
+        "f" => (),
+    });
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    let doc = "[? a : b, c: d, [e]: f, g]";
+    let ans = node!([
+        node!({"a" => "b"}),
+        node!({"c" => "d"}),
+        node!({node!(["e"]) => "f"}),
+        "g"
+    ]);
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+}
+
+#[test]
+fn test_line_ending() {
+    use crate::dumper::{dump_with, DumpOptions, LineEnding};
+    for doc in [
+        include_str!("complete_doc.yaml"),
+        include_str!("dump_result.yaml"),
+        include_str!("indent.yaml"),
+        include_str!("anchor.yaml"),
+        "a: |+\n  b\n\n  c\n\n\nd: >\n  e\n  f\n\n   g\nh: \"i\\\n  j\n\n  k\"\nl: m\n  n\n...\n--- # o\np\n",
+    ] {
+        let ans: Vec<NodeRc> = parse(doc).unwrap_or_else(show_err);
+        let crlf = doc.replace('\n', "\r\n");
+        assert_eq!(parse(&crlf).unwrap_or_else(show_err), ans);
+        let cr = doc.replace('\n', "\r");
+        assert_eq!(parse(&cr).unwrap_or_else(show_err), ans);
+    }
+    let opts = DumpOptions::new().line_ending(LineEnding::CrLf);
+    let ans = [node!({"a" => node!(["b\nc", "d"])}), node!("e")];
+    let doc = dump_with(&ans, &[], opts);
+    assert!(!doc.replace("\r\n", "").contains('\n'));
+    assert_eq!(parse(&doc).unwrap_or_else(show_err), ans);
+}
+
+#[test]
+#[cfg(feature = "arbitrary-precision")]
+fn test_big_int_ord() {
+    let v = [
+        "-0x100000000000000000000000000000000",
+        "-100",
+        "-99",
+        "-0012",
+        "-0",
+        "0x0",
+        "9",
+        "0o12",
+        "99",
+        "100",
+        "0x100000000000000000000000000000000",
+    ]
+    .map(|s| s.parse::<BigInt>().unwrap());
+    for (i, a) in v.iter().enumerate() {
+        for (j, b) in v.iter().enumerate() {
+            let ord = match (a.to_i128(), b.to_i128()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => i.cmp(&j),
+            };
+            assert_eq!(a.cmp(b), ord, "{a} {b}");
+        }
+    }
+    assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(v.iter().max(), v.last());
+}
+
+#[test]
+#[cfg(all(feature = "serde", feature = "arbitrary-precision"))]
+fn test_arbitrary_precision() {
+    use ::serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Numbers {
+        big: BigInt,
+        small: BigInt,
+        pi: Decimal,
+        node: NodeRc,
+    }
+
+    let doc = "\
+big: 123456789012345678901234567890123456789012345678901234567890
+small: -12
+pi: 3.14159265358979323846264338
+node:
+  - 0x100000000000000000000000000000000
+  - 2.718281828459045235360287471352
+  - 1.5
+";
+    let n: NodeRc = doc.parse().unwrap_or_else(show_err);
+    let data = Numbers::deserialize(n.clone()).unwrap();
+    assert_eq!(
+        data.big.as_str(),
+        "123456789012345678901234567890123456789012345678901234567890"
+    );
+    assert_eq!(data.small.to_i128(), Some(-12));
+    assert_eq!(data.pi.as_str(), "3.14159265358979323846264338");
+    assert_eq!(
+        data.node[Ind(0)].as_big_int().unwrap().as_str(),
+        "340282366920938463463374607431768211456"
+    );
+    assert_eq!(
+        data.node[Ind(1)].as_decimal().unwrap().as_str(),
+        "2.718281828459045235360287471352"
+    );
+    assert_eq!(data.node[Ind(2)].as_float(), Ok(1.5));
+    let doc = crate::serde::to_string(&data).unwrap();
+    assert_eq!(crate::serde::from_str::<Numbers>(&doc).unwrap(), [data]);
+    let m = crate::serde::to_node(&n).unwrap();
+    assert_eq!(m["big"].as_big_int(), n["big"].as_big_int());
+    assert_eq!(m["pi"].as_decimal(), n["pi"].as_decimal());
+}
+
+#[test]
+fn test_malformed() {
+    let docs: [&[u8]; 8] = [
+        b"a: !x!b c\n",
+        b"a: 'b",
+        b"'a\n b",
+        b"a:\nc\n---e\n",
+        b":\n-\n---[",
+        b":\nc\n---]",
+        b"- \"\\x\xff",
+        b"\xe4\xb8: |\r",
+    ];
+    for doc in docs {
+        let _ = parser::Loader::<repr::RcRepr>::new(doc).parse();
+    }
+    assert!(parse::<repr::RcRepr>("a: 'b").is_err());
+    assert!(parse::<repr::RcRepr>("a: !x!b c").is_err());
+}
+
+#[test]
+fn test_error_kind() {
+    use crate::parser::{ErrorKind, PError};
+    let docs = [
+        ("a: 'b", ErrorKind::UnclosedQuote, None),
+        ("a: !x!b c", ErrorKind::UndeclaredTagHandle, Some('b')),
+        ("[a, b", ErrorKind::UnclosedFlow, None),
+        ("a: \"b\\qc\"", ErrorKind::BadEscape, Some('\\')),
+        ("a\n---\nb", ErrorKind::MultipleDocuments, Some('\n')),
+    ];
+    for (doc, kind, found) in docs {
+        let e = doc.parse::<NodeRc>().unwrap_err();
+        assert_eq!(e.kind(), Some(kind), "{}", doc);
+        match e {
+            PError::Terminate { found: f, .. } => assert_eq!(f, found, "{}", doc),
+            PError::Mismatch => unreachable!(),
+        }
+    }
+    assert_eq!(indicated_msg(b"ab\ncd", 100), "2:3\ncd\n  ^");
+    let mut p = parser::Parser::new(b"ab").pos(100);
+    p.back(100);
+    assert_eq!(p.food(), b"ab");
+}
+
+#[test]
+fn test_checkpoint() {
+    let doc = "a\u{e9}b".as_bytes();
+    assert_eq!(parser::Parser::new(doc).pos(2).food(), "\u{e9}b".as_bytes());
+    assert_eq!(parser::Parser::new(doc).pos(3).food(), b"b");
+    let mut p = parser::Parser::new(doc);
+    p.sym(b'a').unwrap();
+    p.forward();
+    {
+        let mut cp = p.checkpoint();
+        cp.take_while_char(|c| c == '\u{e9}', parser::TakeOpt::One)
+            .unwrap();
+        assert_eq!(cp.eaten, 1);
+        cp.sym(b'x').unwrap_err();
+    }
+    assert_eq!((p.pos, p.eaten), (1, 1));
+    let mut cp = p.checkpoint();
+    cp.take_while_char(|c| c == '\u{e9}', parser::TakeOpt::One)
+        .unwrap();
+    cp.commit();
+    assert_eq!((p.pos, p.eaten), (3, 1));
+    assert_eq!(p.context(|p| p.sym(b'b').map(|_| p.eaten).ok()), Some(3));
+    assert_eq!((p.pos, p.eaten), (4, 1));
+}
+
+#[test]
+fn test_combinator() {
+    use crate::parser::{ErrorKind, Parser, TakeOpt};
+    // key=value pairs split by `;`
+    let mut p = Parser::new(b"a=1;bc=2;d;");
+    let pairs = p
+        .many(
+            |p| {
+                let k = p.count(|p| p.take_while(u8::is_ascii_alphabetic, TakeOpt::More(1)))?;
+                let v = p
+                    .opt(|p| {
+                        p.sym(b'=')?;
+                        p.count(|p| p.take_while(u8::is_ascii_digit, TakeOpt::More(1)))
+                    })?
+                    .unwrap_or_default();
+                p.alt(|p| p.sym(b';'), |p| p.not(|p| p.sym(b'=')))?;
+                Ok((k, v))
+            },
+            1,
+        )
+        .unwrap_or_else(show_err);
+    assert_eq!(pairs, [(1, 1), (2, 1), (1, 0)]);
+    assert!(p.food().is_empty());
+    // Rewind on mismatch
+    let mut p = Parser::new(b"a=");
+    assert!(p.many(|p| p.sym_seq(b"a=1"), 1).is_err());
+    assert_eq!(p.peek(|p| p.sym_seq(b"a=")).unwrap_or_else(show_err), ());
+    assert_eq!(p.food(), b"a=");
+    // Terminate is passed through
+    let e = p
+        .opt(|p| p.sym(b'a').and_then(|_| p.err::<()>(ErrorKind::MapValue)))
+        .unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::MapValue));
+}
+
+#[test]
+fn test_warnings() {
+    use crate::parser::{Loader, WarningKind};
+    let doc = b"%TAG x y\n---\n- &a 1\n- &a 2\n- *a\n";
+    let mut loader = Loader::<repr::RcRepr>::new(doc);
+    assert_eq!(loader.parse().unwrap(), vec![node!([1, 2, 2])]);
+    let kinds = loader
+        .take_warnings()
+        .into_iter()
+        .map(|w| w.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            WarningKind::InvalidTagDirective,
+            WarningKind::RedefinedAnchor
+        ]
+    );
+    assert!(loader.warnings().is_empty());
+    // The messages of the many warnings
+    let doc = "- yes\n".repeat(20000) + "- [\r\n  no]\n";
+    let mut loader = Loader::<repr::RcRepr>::new(doc.as_bytes());
+    loader.parse().unwrap();
+    let warnings = loader.warnings();
+    assert_eq!(warnings.len(), 20001);
+    for w in [&warnings[0], &warnings[12345], &warnings[20000]] {
+        assert_eq!(w.msg, indicated_msg(doc.as_bytes(), w.pos));
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_de_in_place() {
+    use crate::serde::{from_node_in_place_with, from_str_in_place, DeOptions};
+    use alloc::collections::BTreeMap;
+    let mut m = BTreeMap::<String, Vec<String>>::new();
+    from_str_in_place("a: [x, y]\nb: []", &mut m).unwrap();
+    assert_eq!(m["a"], ["x", "y"]);
+    let mut v = Vec::<String>::new();
+    from_str_in_place("[abcdef, b]", &mut v).unwrap();
+    let (ptr, s_ptr) = (v.as_ptr(), v[0].as_ptr());
+    from_str_in_place("[abc]", &mut v).unwrap();
+    assert_eq!(v, ["abc"]);
+    assert_eq!((v.as_ptr(), v[0].as_ptr()), (ptr, s_ptr));
+    // Single document only
+    assert!(from_str_in_place("[a]\n---\n[b]", &mut v).is_err());
+    let e = from_str_in_place("[a, [b]]", &mut v).unwrap_err();
+    assert_eq!(e.pos, 4);
+    let n = "[[[1]]]".parse::<NodeRc>().unwrap();
+    let mut v = Vec::<Vec<Vec<i32>>>::new();
+    let opts = DeOptions::new().recursion_limit(Some(2));
+    assert!(from_node_in_place_with(n.clone(), &mut v, opts).is_err());
+    from_node_in_place_with(n, &mut v, DeOptions::new()).unwrap();
+    assert_eq!(v, [[[1]]]);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_anchors_serde() {
+    use crate::parser::Anchors;
+    use ::serde::Deserialize;
+    let (_, mut anchors) = parse_cyclic::<repr::RcRepr>("- &a [1, 2]\n- &b {c: d}").unwrap();
+    let anchors = anchors.remove(0);
+    let n = crate::serde::to_node(&anchors).unwrap();
+    assert_eq!(n, node!({"a" => node!([1, 2]), "b" => node!({"c" => "d"})}));
+    assert_eq!(Anchors::deserialize(n).unwrap(), anchors);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_error_path() {
+    #[derive(Debug, ::serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Container {
+        image: String,
+        port: u16,
+    }
+    #[derive(Debug, ::serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Spec {
+        containers: Vec<Container>,
+    }
+    let doc = "spec:\n  containers:\n  - image: a\n    port: 80\n  - image: b\n";
+    let e = crate::serde::from_str::<alloc::collections::BTreeMap<String, Spec>>(doc).unwrap_err();
+    assert_eq!(e.msg, "missing field `port`");
+    assert_eq!(e.path.as_deref(), Some("spec.containers[1]"));
+    let doc = "spec:\n  containers:\n  - image: a\n    port: x\n";
+    let e = crate::serde::from_str::<alloc::collections::BTreeMap<String, Spec>>(doc).unwrap_err();
+    assert_eq!(e.path.as_deref(), Some("spec.containers[0].port"));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_deny_unknown() {
+    use crate::serde::{from_str, from_str_with, DeOptions};
+    #[derive(Debug, ::serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Container {
+        image: String,
+        #[serde(default)]
+        port: u16,
+    }
+    #[derive(Debug, ::serde::Deserialize)]
+    #[allow(dead_code)]
+    enum Spec {
+        Pod { containers: Vec<Container> },
+    }
+    let opts = DeOptions::new().deny_unknown(true);
+    let doc = "Pod:\n  containers:\n  - image: a\n    prot: 80\n";
+    assert!(from_str::<Spec>(doc).is_ok());
+    let e = from_str_with::<Spec>(doc, opts).unwrap_err();
+    assert_eq!(e.msg, "unknown field `prot`, expected `image` or `port`");
+    assert_eq!(e.pos, 36);
+    assert_eq!(e.path.as_deref(), Some("Pod.containers[0].prot"));
+    let doc = "Pod:\n  containers: []\n  volumes: []\n";
+    let e = from_str_with::<Spec>(doc, opts).unwrap_err();
+    assert_eq!(e.path.as_deref(), Some("Pod.volumes"));
+    let doc = "Pod:\n  containers:\n  - image: a\n";
+    assert!(from_str_with::<Spec>(doc, opts).is_ok());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_recursion_limit() {
+    use crate::{
+        parser::{Loader, ParseLimits},
+        serde::{from_node_with, from_str, DeOptions},
+    };
+    let doc = "[".repeat(140) + &"]".repeat(140);
+    let e = from_str::<NodeRc>(&doc).unwrap_err();
+    assert_eq!(e.pos, 65);
+    let limits = ParseLimits { max_depth: None, ..ParseLimits::new() };
+    let n = Loader::<repr::RcRepr>::new(doc.as_bytes())
+        .limits(limits)
+        .parse()
+        .unwrap()
+        .remove(0);
+    let e = from_node_with::<NodeRc, _>(n.clone(), DeOptions::new()).unwrap_err();
+    assert_eq!(e.msg, "recursion limit exceeded");
+    assert_eq!(e.pos, 128);
+    let opts = DeOptions::new().recursion_limit(None);
+    assert_eq!(from_node_with::<NodeRc, _>(n.clone(), opts).unwrap(), n);
+    let doc = "a: {b: [{c: 1}]}";
+    let n = doc.parse::<NodeRc>().unwrap();
+    let opts = DeOptions::new().recursion_limit(Some(3));
+    assert!(from_node_with::<NodeRc, _>(n.clone(), opts).is_err());
+    let opts = DeOptions::new().recursion_limit(Some(4));
+    assert!(from_node_with::<NodeRc, _>(n, opts).is_ok());
+    // The nested enums
+    #[derive(Debug, PartialEq, ::serde::Deserialize)]
+    enum Expr {
+        Neg(Box<Expr>),
+        Lit,
+    }
+    let doc = "{Neg: ".repeat(3) + "Lit" + &"}".repeat(3);
+    let n = doc.parse::<NodeRc>().unwrap();
+    let opts = DeOptions::new().recursion_limit(Some(2));
+    let e = from_node_with::<Expr, _>(n.clone(), opts).unwrap_err();
+    assert_eq!(e.msg, "recursion limit exceeded");
+    assert_eq!(e.pos, 12);
+    let opts = DeOptions::new().recursion_limit(Some(3));
+    let e = Expr::Neg(Box::new(Expr::Neg(Box::new(Expr::Neg(Box::new(
+        Expr::Lit,
+    ))))));
+    assert_eq!(from_node_with::<Expr, _>(n, opts).unwrap(), e);
+    let n = (0..200).fold(node!("Lit"), |n, _| node!({"Neg" => n}));
+    let e = from_node_with::<Expr, _>(n, DeOptions::new()).unwrap_err();
+    assert_eq!(e.msg, "recursion limit exceeded");
+}
+
+#[test]
+#[cfg(all(feature = "std", feature = "serde"))]
+fn test_std_error() {
+    use std::error::Error;
+    fn load(doc: &str) -> Result<Vec<NodeRc>, Box<dyn Error>> {
+        Ok(crate::serde::from_str(doc)?)
+    }
+    let e = load("a: [b").unwrap_err();
+    let source = e.source().unwrap().downcast_ref::<PError>().unwrap();
+    assert_eq!(source.kind(), Some(crate::parser::ErrorKind::UnclosedFlow));
+    let e = crate::serde::from_str::<u8>("a").unwrap_err();
+    assert!(e.source().is_none());
+    let e = std::io::Error::from(e);
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_canonical() {
+    use crate::dumper::{dump_with, DumpOptions};
+    let opts = DumpOptions::new().canonical(true);
+    let a = parse::<repr::RcRepr>("a: [1, 2.5, {b: c}]\n--- !x d").unwrap_or_else(show_err);
+    let b = parse::<repr::RcRepr>("\"a\":\n- 0x1\n- 25e-1\n- b: 'c'\n--- !x\n\"d\"")
+        .unwrap_or_else(show_err);
+    let doc = dump_with(&a, &[], opts.clone());
+    assert_eq!(doc, dump_with(&b, &[], opts.clone()));
+    let c = parse::<repr::RcRepr>(&doc).unwrap_or_else(show_err);
+    assert_eq!(dump_with(&c, &[], opts), doc);
+}
+
+#[test]
+fn test_digest() {
+    let anchors = parser::Anchors::new();
+    let digest = |doc: &str| {
+        let (root, anchors) = parse_cyclic::<repr::RcRepr>(doc).unwrap_or_else(show_err);
+        root[0].digest(&anchors[0])
+    };
+    assert_eq!(digest("[1.0, -0.0, 0x3e8]"), digest("[1e0, 0.0, 1000]"));
+    assert_eq!(digest("{a: {b: 1}, c: null}"), digest("c: ~\na:\n  b: 0o1"));
+    assert_ne!(digest("[1, 2]"), digest("[2, 1]"));
+    assert_ne!(digest("[1]"), digest("['1']"));
+    assert_ne!(digest("{a: b}"), digest("{a: b, c: d}"));
+    assert_eq!(digest("&a [*a]"), digest("&b [*b]"));
+    assert_eq!(
+        digest("[*a]"),
+        node!([Yaml::Alias("a".into())]).digest(&anchors)
+    );
+    assert_ne!(digest("[*a]"), digest("[*b]"));
+    // The shared nodes are hashed and compared once
+    let mut doc = "- &a0 [x]\n".to_string();
+    for i in 1..40 {
+        doc += &format!(
+            "- &a{i} [{}]\n",
+            vec![format!("*a{}", i - 1); 10].join(", ")
+        );
+    }
+    let root = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(root.digest(&anchors), root.clone().digest(&anchors));
+    assert!(root.structural_eq(&doc.parse().unwrap(), &anchors));
+    assert_eq!(root.dedup(&anchors).unwrap().as_seq().unwrap().len(), 40);
+    let (root, anchors) = parse_cyclic::<repr::RcRepr>(&doc).unwrap_or_else(show_err);
+    assert_eq!(root[0].digest(&anchors[0]), digest(&doc));
+    let (other, _) = parse_cyclic(&doc).unwrap_or_else(show_err);
+    assert!(root[0].structural_eq(&other[0], &anchors[0]));
+    // The cyclic nodes are hashed once per alias path
+    let doc = doc.replace("[x]", "[*a0]");
+    let (root, anchors) = parse_cyclic::<repr::RcRepr>(&doc).unwrap_or_else(show_err);
+    assert_eq!(root[0].digest(&anchors[0]), digest(&doc));
+    assert_eq!(
+        root[0].dedup(&anchors[0]).unwrap().as_seq().unwrap().len(),
+        40
+    );
+}
+
+#[test]
+fn test_sci_float() {
+    let n = "[1.0e3, -2.50E-4, 1.e+2, 6e0, 1.0e]"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    let ans = node!([
+        node!(Yaml::Float("1.0e3".into())),
+        node!(Yaml::Float("-2.50E-4".into())),
+        node!(Yaml::Float("1.e+2".into())),
+        node!(Yaml::Float("6e0".into())),
+        "1.0e"
+    ]);
+    assert_eq!(n, ans);
+    assert_eq!(n[Ind(0)].as_float(), Ok(1000.));
+}
+
+#[test]
+fn test_redact() {
+    use crate::redact::{redact, Matcher};
+    let n = node!({
+        "a" => node!({"b" => node!({"key" => 1, "c" => 2}), "key" => node!([3, node!({"d" => 4})])}),
+        "e" => 5,
+    });
+    let masked = redact(&n, &Matcher::new().key("key"), ());
+    assert_eq!(
+        masked,
+        node!({
+            "a" => node!({"b" => node!({"key" => (), "c" => 2}), "key" => node!([(), node!({"d" => ()})])}),
+            "e" => 5,
+        })
+    );
+    let masked = redact(&n, &Matcher::new().path("**.c").path("a.*.1.d"), "x");
+    assert_eq!(masked["a"]["b"]["c"], node!("x"));
+    assert_eq!(masked["a"]["key"][Ind(1)]["d"], node!("x"));
+    assert_eq!(masked["a"]["key"][Ind(0)], node!(3));
+    assert_eq!(masked["e"], node!(5));
+    assert_eq!(redact(&n, &Matcher::new().path("**"), 0)["e"], node!(0));
+}
+
+#[test]
+fn test_float_dump() {
+    use crate::dumper::{dump_with, DumpOptions, FloatNotation};
+    let values = [-1.25e-3, 0.1, -0., 123456.789, 1e300, 5e-324, f64::MAX];
+    let n = [node!({"a" => values.iter().map(|v| NodeRc::from(*v)).collect::<NodeRc>()})];
+    for notation in [
+        FloatNotation::Fixed,
+        FloatNotation::Scientific,
+        FloatNotation::Auto { min: -4, max: 16 },
+    ] {
+        let opts = DumpOptions::new()
+            .float_notation(notation)
+            .float_point(true);
+        let doc = dump_with(&n, &[], opts);
+        let root = parse::<repr::RcRepr>(&doc).unwrap_or_else(show_err);
+        let v = root[0]["a"]
+            .as_seq()
+            .unwrap()
+            .iter()
+            .map(|n| n.as_float().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(v, values);
+    }
+    let opts = DumpOptions::new()
+        .float_notation(FloatNotation::Fixed)
+        .float_precision(Some(2));
+    let doc = dump_with(&[node!({"a" => -0.000123, "b" => 995.})], &[], opts);
+    assert_eq!(doc, "a: -0.00012\nb: 1000\n".replace('\n', dumper::NL));
+}
+
+#[test]
+fn test_node_ord() {
+    use alloc::collections::BTreeSet;
+    use core::cmp::Ordering;
+    let set = [
+        node!(0.),
+        node!(-0.),
+        node!(f64::NAN),
+        node!(f64::NAN),
+        node!(0x10),
+        node!(16),
+    ]
+    .into_iter()
+    .collect::<BTreeSet<_>>();
+    assert_eq!(set.len(), 3);
+    assert_eq!(node!(1).cmp(&node!(1.)), Ordering::Less);
+    assert_eq!(node!(-2).cmp(&node!(-2.5)), Ordering::Greater);
+    assert_eq!(node!(-3).cmp(&node!(-2.5)), Ordering::Less);
+    assert_eq!(node!(i128::MAX).cmp(&node!(1e300)), Ordering::Less);
+    assert_eq!(node!(2.5).cmp(&node!(f64::NAN)), Ordering::Less);
+    assert!(node!([1, 2]) < node!([1, 2, 0]));
+    assert!(node!({"a" => 1}) < node!({"a" => 2}));
+    assert!(node!("z") < node!([]));
+}
+
+#[test]
+fn test_query() {
+    use crate::query::query;
+    let root = node!({"a" => node!([node!({"b" => 1}), node!({"b" => "x", "c" => true})])});
+    let paths = |q| {
+        query(&root, q)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.path)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(paths("..b"), ["a[0].b", "a[1].b"]);
+    assert_eq!(paths("a[?(@.c == true)].b"), ["a[1].b"]);
+    assert_eq!(paths("a[?(@.b != 1)]"), ["a[1]"]);
+    assert_eq!(paths("a[?(@.b < 2)]"), ["a[0]"]);
+    assert_eq!(paths("$.a[-3]"), Vec::<String>::new());
+    for q in ["a[", ".", "a[?(@.b ==)]", "a[?(b)]", "a[\"b]", "a b"] {
+        assert!(query(&root, q).is_err(), "{}", q);
+    }
+}
+
+#[test]
+fn test_patch() {
+    use crate::patch::{Patch, PatchError, PatchOp};
+    let mut n = node!({"a/b" => node!([1, 3]), "~" => 0});
+    let patch = Patch(vec![
+        PatchOp::Add { path: "/a~1b/1".into(), value: node!(2) },
+        PatchOp::Replace { path: "/~0".into(), value: node!(true) },
+        PatchOp::Add { path: "".into(), value: n.clone() },
+    ]);
+    let mut m = n.clone();
+    m.apply_patch(&Patch(patch.0[..2].to_vec())).unwrap();
+    assert_eq!(m, node!({"a/b" => node!([1, 2, 3]), "~" => true}));
+    n.apply_patch(&patch).unwrap();
+    assert_eq!(n, node!({"a/b" => node!([1, 3]), "~" => 0}));
+    let err = |op| n.clone().apply_patch(&Patch(vec![op])).unwrap_err();
+    assert_eq!(
+        err(PatchOp::Replace { path: "/x".into(), value: node!(1) }),
+        PatchError::NotFound("/x".into())
+    );
+    assert_eq!(
+        err(PatchOp::Remove { path: "/a~1b/01".into() }),
+        PatchError::NotFound("/a~1b/01".into())
+    );
+    assert_eq!(
+        err(PatchOp::Move { from: "/a~1b".into(), path: "/a~1b/0".into() }),
+        PatchError::MoveIntoChild("/a~1b/0".into())
+    );
+    assert_eq!(
+        err(PatchOp::Remove { path: "a".into() }),
+        PatchError::InvalidPointer("a".into())
+    );
+    let mut n = node!([1]);
+    n.merge_patch(&node!({"a" => node!({"b" => ()})}));
+    assert_eq!(n, node!({"a" => node!({})}));
+}
+
+#[test]
+fn test_node_mut() {
+    let mut n = node!({"a" => node!([1, node!({"b" => 2})]), "c" => 3});
+    let shared = n["c"].clone_yaml();
+    {
+        let mut g = n.get_mut(&["a", "1", "b"]).unwrap();
+        assert!(!g.is_dirty());
+        *g = node!(20);
+        assert!(g.is_dirty());
+    }
+    assert_eq!(n, node!({"a" => node!([1, node!({"b" => 20})]), "c" => 3}));
+    assert!(alloc::rc::Rc::ptr_eq(&shared, n["c"].rc_ref()));
+    assert!(n.get_mut(&["a", "01"]).is_none());
+    assert!(n.get_mut(&["c", "x"]).is_none());
+    // The whole node is replaced by an empty path
+    *n.get_mut::<&str>(&[]).unwrap() = node!(());
+    assert!(n.is_null());
+    assert_eq!(n.entry("a").unwrap_err(), 0);
+    let mut n = node!({});
+    n.entry("a").unwrap().or_default();
+    let before = n.clone_yaml();
+    assert!(n.entry("a").unwrap().is_occupied());
+    n.entry("a").unwrap().or_insert(1);
+    assert!(alloc::rc::Rc::ptr_eq(&before, n.rc_ref()));
+    n.entry("a").unwrap().and_modify(|v| *v = node!(1));
+    assert_eq!(n, node!({"a" => 1}));
+}
+
+#[test]
+fn test_dump_preserving() {
+    use crate::{dumper::dump_preserving, parser::parse_round_trip, repr::RcRepr};
+    let doc = "\
+a: 1   # c
+b:
+  - x
+  - &q !t y
+  # cmt
+  - z
+c: &z
+  d: 'q'
+  e: [1, {f: g}]
+g:
+
+# end
+";
+    let (root, map) = parse_round_trip::<RcRepr>(doc).unwrap();
+    assert_eq!(dump_preserving(doc, &root, &map), doc);
+    let mut n = root.clone();
+    *n[0].get_mut(&["a"]).unwrap() = node!("two words");
+    *n[0].get_mut(&["c", "e", "1", "f"]).unwrap() = node!("h, i");
+    {
+        let mut b = n[0].get_mut(&["b"]).unwrap();
+        let mut v = b.as_seq().unwrap();
+        v.remove(0);
+        v.push(node!("w"));
+        b.set_yaml(v);
+    }
+    *n[0].get_mut(&["g"]).unwrap() = node!(["a"]);
+    n.push(node!({"h" => node!([])}));
+    let ans = "\
+a: two words   # c
+b:
+  - &q !t y
+  # cmt
+  - z
+  - w
+c: &z
+  d: 'q'
+  e: [1, {f: \"h, i\"}]
+g:
+  - a
+
+# end
+---
+h: []
+";
+    let s = dump_preserving(doc, &n, &map);
+    assert_eq!(s, ans);
+    assert_eq!(parse::<RcRepr>(&s).unwrap(), n);
+    assert_eq!(dump_preserving(doc, &[], &map), "");
+    // The long sequences are aligned without the quadratic table
+    let doc = (0..3000)
+        .map(|i| format!("- {i}  # c{i}\n"))
+        .collect::<String>();
+    let (root, map) = parse_round_trip::<RcRepr>(&doc).unwrap();
+    let mut v = root[0].as_seq().unwrap();
+    v[1500] = node!("x");
+    let n = [node!(v.clone())];
+    let s = dump_preserving(&doc, &n, &map);
+    assert_eq!(s, doc.replace("- 1500 ", "- x "));
+    v.reverse();
+    let n = [node!(v)];
+    let s = dump_preserving(&doc, &n, &map);
+    assert_eq!(parse::<RcRepr>(&s).unwrap(), n);
+}
+
+#[test]
+fn test_lexer() {
+    use crate::parser::{Lexer, TokenKind::*};
+    let doc = "\
+%YAML 1.2
+---
+? [a, {b: c}, \"d\":e]
+: !!str &x 'it''s' # c
+lit: |-
+  line 1
+
+  line 2
+url: http://x.y/z
+...
+bad: 'open
+";
+    let tokens = Lexer::new(doc.as_bytes()).collect::<Vec<_>>();
+    let text = tokens
+        .iter()
+        .map(|t| &doc[t.start as usize..t.end as usize])
+        .collect::<String>();
+    assert_eq!(text, doc);
+    let kinds = tokens
+        .iter()
+        .map(|t| t.kind)
+        .filter(|k| !matches!(k, Whitespace | Newline))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            Directive,
+            DocStart,
+            Question,
+            SeqStart,
+            Plain,
+            Comma,
+            MapStart,
+            Plain,
+            Colon,
+            Plain,
+            MapEnd,
+            Comma,
+            DoubleQuoted,
+            Colon,
+            Plain,
+            SeqEnd,
+            Colon,
+            Tag,
+            Anchor,
+            SingleQuoted,
+            Comment,
+            Plain,
+            Colon,
+            Literal,
+            Plain,
+            Colon,
+            Plain,
+            DocEnd,
+            Plain,
+            Colon,
+            Error,
+        ]
+    );
+    // The indicators without the names
+    for (doc, ans) in [
+        ("*", [Plain].as_slice()),
+        ("&", &[Plain]),
+        ("[*", &[SeqStart, Plain]),
+        // The unclosed verbatim tags
+        ("!<", &[Error]),
+        ("!<<", &[Error]),
+        ("!<a", &[Error]),
+        ("a: !<x", &[Plain, Colon, Whitespace, Error]),
+        ("!<a>", &[Tag]),
+    ] {
+        let tokens = Lexer::new(doc.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(tokens.iter().map(|t| t.kind).collect::<Vec<_>>(), ans);
+        assert_eq!(tokens.last().map(|t| t.end as usize), Some(doc.len()));
+    }
+}
+
+#[test]
+fn test_format() {
+    use crate::{
+        format::{format, FormatOptions},
+        repr::RcRepr,
+    };
+    // Invalid document is returned as is
+    assert_eq!(
+        format("a:   [1,  2\n", FormatOptions::new()),
+        "a:   [1,  2\n"
+    );
+    // The malformed or unclosed tags
+    for doc in ["~: !,?>+%TAG ! !<<", "a: !<x", "[!<a, b]\n"] {
+        let ans = format(doc, FormatOptions::new());
+        assert_eq!(
+            parse::<RcRepr>(&ans).ok(),
+            parse::<RcRepr>(doc).ok(),
+            "{doc:?}"
+        );
+    }
+    let doc = "\
+a:
+    # lead
+    b: |
+        line1
+          line2
+
+        line3
+    c:   >-
+       x
+    d: \"multi
+        line\"
+e:
+- - x:   1
+    y: {k: [1,2]}  # flow
+  -   z
+- ? complex
+  : value
+---
+- &k !!str 1 # anchor
+- *k
+";
+    let ans = "\
+a:
+    # lead
+    b: |
+        line1
+          line2
+
+        line3
+    c: >-
+        x
+    d: \"multi
+        line\"
+e:
+- - x: 1
+    y: {k: [1, 2]} # flow
+  - z
+- ? complex
+  : value
+---
+- &k !!str 1 # anchor
+- *k
+";
+    let opts = FormatOptions::new().indent(4);
+    assert_eq!(format(doc, opts.clone()), ans);
+    assert_eq!(format(ans, opts), ans);
+    let ans2 = format(doc, FormatOptions::new());
+    assert_eq!(format(&ans2, FormatOptions::new()), ans2);
+    assert_eq!(
+        parse::<RcRepr>(&ans2).unwrap(),
+        parse::<RcRepr>(doc).unwrap()
+    );
+    // The indentation indicators which cannot be re-based
+    for doc in ["-     |1\n  text\n", "?     |2\n  >-\n"] {
+        let ans = format(doc, FormatOptions::new());
+        assert_eq!(
+            parse::<RcRepr>(&ans).unwrap(),
+            parse::<RcRepr>(doc).unwrap(),
+            "{ans}"
+        );
+    }
+}
+
+#[test]
+fn test_stream_loader() {
+    use crate::{parser::StreamLoader, repr::RcRepr};
+    let doc = "\
+# head
+%YAML 1.2
+---
+a: |
+  --- not a marker
+b: [1,
+  2]
+...
+# comment
+--- !!str c
+---
+- x: ---
+---\r
+d: 4";
+    let mut loader = StreamLoader::<RcRepr>::new();
+    let mut docs = Vec::new();
+    for i in 0..doc.len() {
+        loader.feed(&doc.as_bytes()[i..i + 1]);
+        while let Some(n) = loader.next_document() {
+            docs.push(n.unwrap());
+        }
+    }
+    assert!(!loader.is_done());
+    loader.finish();
+    while let Some(n) = loader.next_document() {
+        docs.push(n.unwrap());
+    }
+    assert!(loader.is_done());
+    assert_eq!(docs, parse::<RcRepr>(doc).unwrap());
+    assert_eq!(loader.pos(), doc.find("---\r").unwrap() as u64);
+    // The error does not stop the stream
+    let mut loader = StreamLoader::<RcRepr>::new();
+    loader.feed(b"[1\n---\n2\n");
+    loader.finish();
+    assert!(loader.next_document().unwrap().is_err());
+    assert_eq!(loader.next_document().unwrap().unwrap(), node!(2));
+    assert!(loader.next_document().is_none());
+}
+
+#[test]
+fn test_to_json() {
+    use crate::{json::to_json, repr::RcRepr};
+    let doc = "\
+int: [0, -12, 0o17, 0x1F, 010]
+float: [1.5e3, -0.25, .nan, -.inf, 1.]
+str: [\"a\\\"b\", \"\\\\\", \"\\x01\\u00e9\", '']
+keys: {1: a, true: b, [1, 2]: c, {x: y}: d}
+empty: [[], {}]
+";
+    let n = doc.parse::<NodeRc>().unwrap();
+    let ans = concat!(
+        r#"{"int":[0,-12,15,31,10],"#,
+        r#""float":[1.5e3,-0.25,null,null,1],"#,
+        r#""str":["a\"b","\\","\u0001é",""],"#,
+        r#""keys":{"1":"a","true":"b","[1,2]":"c","{\"x\":\"y\"}":"d"},"#,
+        r#""empty":[[],{}]}"#,
+    );
+    assert_eq!(to_json(&n), ans);
+    let back = ans.parse::<NodeRc>().unwrap();
+    assert_eq!(back["str"], n["str"]);
+    assert_eq!(back["int"], n["int"]);
+    assert_eq!(to_json::<RcRepr>(&back), ans);
+}
+
+/// A tiny xorshift generator of the random nodes, for the property tests.
+struct Gen(u64);
+
+impl Gen {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn string(&mut self) -> String {
+        const PARTS: &[&str] = &[
+            "a", "key", " ", ": ", "- ", "#", " #", "'", "\"", "\\", "\n", "\t", "null", "~",
+            "true", "yes", "1", "0x1", "1.5", ".inf", "[", "]", "{", "}", ",", "&", "*", "!", "|",
+            ">", "%", "@", "`", "---", "...", "é", "日本", "\u{1}", "?",
+        ];
+        (0..self.below(5))
+            .map(|_| PARTS[self.below(PARTS.len() as u64) as usize])
+            .collect()
+    }
+
+    fn scalar(&mut self) -> NodeRc {
+        match self.below(6) {
+            0 => node!(()),
+            1 => node!(self.below(2) == 0),
+            2 => node!(self.next() as i64 >> self.below(64)),
+            3 => node!((self.next() as i64 >> 11) as f64 / 1024.),
+            _ => node!(self.string()),
+        }
+    }
+
+    fn node(&mut self, depth: u32) -> NodeRc {
+        if depth == 0 {
+            return self.scalar();
+        }
+        match self.below(4) {
+            0 => node!(Yaml::Seq(
+                (0..self.below(4)).map(|_| self.node(depth - 1)).collect()
+            )),
+            1 => node!(Yaml::Map(
+                (0..self.below(4))
+                    .map(|_| (self.scalar(), self.node(depth - 1)))
+                    .collect()
+            )),
+            _ => self.scalar(),
+        }
+    }
+}
+
+#[test]
+fn test_dump_round_trip_property() {
+    use crate::dumper::{dump_with, DumpOptions};
+    let mut gen = Gen(0x2545_F491_4F6C_DD1D);
+    // The integral floats are dumped as integers by default
+    let opts = [
+        DumpOptions::new().float_point(true),
+        DumpOptions::new().float_point(true).indent(4),
+        DumpOptions::new().canonical(true),
+    ];
+    for _ in 0..1000 {
+        let n = gen.node(4);
+        for opts in &opts {
+            let doc = dump_with(core::slice::from_ref(&n), &[], opts.clone());
+            let back = match parse::<repr::RcRepr>(&doc) {
+                Ok(back) => back,
+                Err(e) => panic!("{e}\n{n:?}\n{doc}"),
+            };
+            assert_eq!(back, core::slice::from_ref(&n), "{doc}");
+        }
+    }
+}
+
+#[test]
+fn test_core_tags() {
+    let doc = "[!!int \"12\", !!float '1.5', !!float 2, !!null '', !!bool \"true\", !!str 3, !!str ~, !!int x]";
+    let n = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    let ans = node!([
+        12,
+        1.5,
+        node!(Yaml::Float("2".into())),
+        (),
+        true,
+        "3",
+        "~",
+        "x"
+    ]);
+    assert_eq!(n, ans);
+    assert_eq!(n[Ind(5)].as_str(), Ok("3"));
+    // The original text in the block context
+    let n = "- !!str 1.50\n- !!str 3\n"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    assert_eq!(n, node!(["1.50", "3"]));
+    // Not a document marker
+    let n = "...[1]: ---x\n".parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(n, node!({"...[1]" => "---x"}));
+}
+
+#[test]
+fn test_flow_key_props() {
+    let n = "!x [1, 2]: a\n!!map {b: c}: d\n"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    let keys = n.as_map().unwrap().keys().cloned().collect::<Vec<_>>();
+    assert_eq!(keys, [node!([1, 2]), node!({"b" => "c"})]);
+    assert_eq!(keys[0].tag(), "x");
+    assert_eq!(keys[1].tag(), "tag:yaml.org,2002:map");
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_tagged_serde() {
+    use crate::serde::{to_node, to_string, Tagged};
+    let v = (
+        Tagged("!<tag:x.org,2000:a>", 1),
+        Tagged("!x", node!({"a" => "b"})),
+        Tagged("!!binary", "AAAA"),
+    );
+    let n = to_node(&v).unwrap();
+    assert_eq!(n[Ind(0)].tag(), "tag:x.org,2000:a");
+    assert_eq!(n[Ind(1)].tag(), "x");
+    assert_eq!(n[Ind(2)].tag(), "tag:yaml.org,2002:binary");
+    let doc = to_string(&v).unwrap();
+    let m = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(m, n);
+    assert_eq!(m[Ind(1)].tag(), "x");
+    assert_eq!(m[Ind(2)].tag(), "tag:yaml.org,2002:binary");
+    // Default tags are not written
+    assert_eq!(
+        to_string(&Tagged("!!str", "a")).unwrap(),
+        "a".to_string() + crate::dumper::NL
+    );
+}
+
+#[test]
+fn test_auto_anchor() {
+    use crate::dumper::{dump_with, DumpOptions};
+    let mut gen = Gen(0x9E37_79B9_7F4A_7C15);
+    let opts = [
+        DumpOptions::new().float_point(true).auto_anchor(true),
+        DumpOptions::new().canonical(true).auto_anchor(true),
+    ];
+    for _ in 0..200 {
+        // The tagged scalars are quoted in the canonical form
+        let shared = node!(Yaml::Seq(alloc::vec![gen.node(2)]));
+        let tagged = Node::new_repr(shared.clone_yaml(), 0, "x");
+        let n = node!({
+            "a" => shared.clone(),
+            "b" => node!([gen.node(2), shared.clone(), tagged]),
+            "c" => shared,
+        });
+        for opts in &opts {
+            let doc = dump_with(core::slice::from_ref(&n), &[], opts.clone());
+            assert!(doc.contains("*id001"), "{doc}");
+            let back = parse::<repr::RcRepr>(&doc).unwrap_or_else(|e| panic!("{e}\n{doc}"));
+            assert_eq!(back, core::slice::from_ref(&n), "{doc}");
+            assert_eq!(back[0]["b"][Ind(2)].tag(), "x");
+        }
+    }
+    // The given anchor names are used
+    let shared = node!([1]);
+    let mut anchors = crate::parser::Anchors::new();
+    anchors.insert("s".into(), shared.clone());
+    let n = node!({"a" => shared.clone(), "b" => shared});
+    let doc = dump_with(&[n], &[anchors], DumpOptions::new().auto_anchor(true));
+    assert_eq!(
+        doc,
+        "a: &s\n  - 1\nb: *s\n".replace('\n', crate::dumper::NL)
+    );
+}
+
+#[test]
+fn test_suggest_anchors() {
+    let a = node!({"k" => node!([1, 2])});
+    let x = node!([a.clone(), a.clone()]);
+    let n = node!({"x" => x.clone(), "y" => x, "z" => a});
+    let v = n.suggest_anchors(4);
+    let path = |s: &str| s.to_string();
+    assert_eq!(
+        v,
+        [
+            (path("x"), vec![path("y")]),
+            (path("x[0]"), vec![path("x[1]"), path("z")])
+        ]
+    );
+    // The tags are compared
+    let n = node!([
+        node!([1, 1]),
+        Node::new(Yaml::Seq(vec![node!(1), node!(1)]), 0, "t")
+    ]);
+    assert_eq!(n.suggest_anchors(3), []);
+    assert_eq!(n.suggest_anchors(1).len(), 1);
+    assert_eq!(n.suggest_anchors(1)[0].1.len(), 3);
+}
+
+#[test]
+fn test_map_ext() {
+    use crate::MapExt;
+    let mut m = (0..100)
+        .map(|i| (node!(alloc::format!("k{i}")), node!(i)))
+        .chain([(node!(Yaml::Int("0x10".into())), node!("int"))])
+        .collect::<Map<repr::RcRepr>>();
+    for i in 0..100 {
+        let key = alloc::format!("k{i}");
+        assert_eq!(m.get_key(key.as_str()), Some(&node!(i)));
+        assert_eq!(m.get_key(&key), Some(&node!(i)));
+    }
+    assert!(!m.has_key("k100"));
+    assert!(!m.has_key("16"));
+    assert_eq!(m.get_key(&node!(16)), Some(&node!("int")));
+    assert_eq!(m.remove_key("k0"), Some(node!(0)));
+    assert_eq!(m.remove_key("k0"), None);
+    assert_eq!(m.keys().next(), Some(&node!("k1")));
+    assert_eq!(m.len(), 100);
+}
+
+#[test]
+fn test_expand_anchors() {
+    use crate::parser::ExpandError;
+    let doc = "a: &a {k: [1, 2]}\nb: *a\nc: *x\n";
+    let (root, mut anchors) = parse_cyclic::<repr::RcRepr>(doc).unwrap_or_else(show_err);
+    let anchors = anchors.remove(0);
+    let e = anchors.expand(&root[0], 100).unwrap_err();
+    assert_eq!(e, ExpandError::Undefined { name: "x".into(), pos: 27 });
+    let doc = "a: &a {k: [1, 2]}\nb: *a\n";
+    let (root, mut anchors) = parse_cyclic::<repr::RcRepr>(doc).unwrap_or_else(show_err);
+    let anchors = anchors.remove(0);
+    // The copy of the map has 5 nodes
+    let ans = node!({"a" => node!({"k" => node!([1, 2])}), "b" => node!({"k" => node!([1, 2])})});
+    assert_eq!(anchors.expand(&root[0], 5).unwrap(), ans);
+    assert_eq!(anchors.resolve_all(&root[0]).unwrap(), ans);
+    let e = anchors.expand(&root[0], 4).unwrap_err();
+    assert_eq!(e, ExpandError::TooLarge { name: "a".into(), pos: 21 });
+    let (root, mut anchors) = parse_cyclic::<repr::RcRepr>("&a [1, *a]").unwrap_or_else(show_err);
+    let e = anchors.remove(0).expand(&root[0], 100).unwrap_err();
+    assert!(matches!(e, ExpandError::Cyclic { .. }), "{e}");
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn test_to_toml() {
+    use crate::toml::{to_toml, TomlError};
+    let doc = "\
+a b: \"x\\ty\\u0001\"
+1: [1.5, .inf, 0x10, [], {}, {k: v, n: [true]}]
+t:
+  u:
+    v: 1
+arr:
+  - {x: 1}
+  - x: 2
+    sub: {y: 3}
+";
+    let n = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    let ans = "\
+\"a b\" = \"x\\ty\\u0001\"
+1 = [1.5, inf, 16, [], {}, { k = \"v\", n = [true] }]
+
+[t]
+
+[t.u]
+v = 1
+
+[[arr]]
+x = 1
+
+[[arr]]
+x = 2
+
+[arr.sub]
+y = 3
+";
+    assert_eq!(to_toml(&n).unwrap(), ans);
+    let n = "a:\n  - [1, *x]\n".parse::<NodeRc>();
+    assert!(n.is_err());
+    let (n, _) = parse_cyclic::<repr::RcRepr>("a:\n  - [1, *x]\n").unwrap_or_else(show_err);
+    let e = to_toml(&n[0]).unwrap_err();
+    assert_eq!(
+        e,
+        TomlError { msg: "alias is not supported".into(), pos: 11 }
+    );
+    let n = "? [1]\n: 2\n".parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(to_toml(&n).unwrap_err().pos, 2);
+    let n = "a: 99999999999999999999\n"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    assert_eq!(to_toml(&n).unwrap_err().msg, "integer out of range");
+}
+
+#[test]
+fn test_flatten() {
+    use crate::flatten::{flatten, unflatten};
+    let doc = "\
+a:
+  - {b: 1, c: [x, ~]}
+  - []
+d: {}
+e: 2.5
+f: 'yes no'
+0: true
+";
+    let n = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    let pairs = flatten(&n, ".");
+    let ans = [
+        ("a.0.b", "1"),
+        ("a.0.c.0", "x"),
+        ("a.0.c.1", ""),
+        ("a.1", "[]"),
+        ("d", "{}"),
+        ("e", "2.5"),
+        ("f", "yes no"),
+        ("0", "true"),
+    ];
+    assert_eq!(
+        pairs,
+        ans.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>()
+    );
+    // The root map is not a sequence since the keys are not all the indices
+    let m = unflatten::<repr::RcRepr, _, _, _>(pairs.clone(), ".");
+    assert_eq!(m["a"], n["a"]);
+    assert_eq!(m["0"], node!(true));
+    assert_eq!(m["e"], n["e"]);
+    let m = unflatten::<repr::RcRepr, _, _, _>([("1", "a"), ("0", "b")], ".");
+    assert_eq!(m, node!(["b", "a"]));
+    let m = unflatten::<repr::RcRepr, _, _, _>([("0", "a"), ("00", "b")], ".");
+    assert_eq!(m, node!({"0" => "a", "00" => "b"}));
+    let m = unflatten::<repr::RcRepr, _, _, _>([("a", "1"), ("a.b", "x: y")], ".");
+    assert_eq!(m, node!({"a" => node!({"b" => "x: y"})}));
+    assert_eq!(
+        flatten(&node!("s"), "."),
+        [(String::new(), "s".to_string())]
+    );
+}
+
+#[test]
+fn test_semantic_eq() {
+    let opts = SemanticOptions::new();
+    let a = "{a: 1.0, b: {c: [1, 2], d: ~}, 2: x}"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    let b = "2.0: x\nb: {d: null, c: [1, 2.0]}\na: !!int 1\n"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    assert!(semantic_eq(&a, &b, opts));
+    assert!(!semantic_eq(&a, &b, opts.numeric(false)));
+    assert!(!semantic_eq(&a, &b, opts.ordered_maps(true)));
+    assert!(!semantic_eq(&node!(1), &node!(1.5), opts));
+    assert!(!semantic_eq(&node!([1, 2]), &node!([2, 1]), opts));
+    let c = "{b: {d: ~, c: [1, 2]}, a: 1.0, 2: x}"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    assert!(semantic_eq(&a, &c, opts));
+    assert!(semantic_eq(&a, &c, opts.tags(true)));
+    assert!(!semantic_eq(&a, &b, opts.tags(true)));
+    // Each key is matched once
+    let d = "{1: a, 1.0: a}".parse::<NodeRc>().unwrap_or_else(show_err);
+    let e = "{1: a, 2: a}".parse::<NodeRc>().unwrap_or_else(show_err);
+    assert!(!semantic_eq(&d, &e, opts));
+    assert!(semantic_eq(&d, &d, opts));
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn test_trace() {
+    use crate::parser::{Loader, TraceEvent};
+    let mut events = Vec::new();
+    let e = Loader::<repr::RcRepr>::new(b"a:\n  - [b, c\n")
+        .trace(|t| events.push(*t))
+        .parse();
+    assert!(e.is_err());
+    // Each entrance has an exit at the same depth
+    let mut stack = Vec::new();
+    for t in &events {
+        if t.event == TraceEvent::Enter {
+            assert_eq!(t.depth, stack.len());
+            stack.push(t.rule);
+        } else {
+            assert_eq!(stack.pop(), Some(t.rule));
+            assert_eq!(t.depth, stack.len());
+        }
+    }
+    assert!(stack.is_empty());
+    let failed = events
+        .iter()
+        .filter(|t| t.event == TraceEvent::Failed)
+        .map(|t| t.rule)
+        .collect::<Vec<_>>();
+    assert_eq!(failed.first(), Some(&"seq_flow"));
+    assert_eq!(failed.last(), Some(&"doc"));
+}
+
+#[cfg(feature = "profile")]
+#[test]
+fn test_profile() {
+    use crate::parser::Loader;
+    let mut loader = Loader::<repr::RcRepr>::new(b"- [a, b]\n- {c: d}\n- e\n");
+    loader.parse().unwrap_or_else(show_err);
+    let profile = loader.profile();
+    assert_eq!(profile["doc"].calls, 1);
+    assert_eq!(profile["seq"].matched, 1);
+    assert_eq!(profile["seq_flow"].matched, 1);
+    assert_eq!(profile["map_flow"].matched, 1);
+    for stats in profile.values() {
+        assert!(stats.matched <= stats.calls);
+        assert!(stats.self_time <= stats.total);
+    }
+    let doc = profile["doc"];
+    assert!(profile.values().all(|s| s.total <= doc.total));
+}
+
+#[test]
+fn test_max_aliases() {
+    use crate::parser::{ErrorKind, Loader};
+    // The key alias is parsed again by the sub-parsers, but counted once
+    let doc = b"- &a {x: 1}\n- {*a : b}\n- [*a]\n---\n- &b 1\n- *b\n- *b\n";
+    let n = Loader::<repr::RcRepr>::new(doc)
+        .max_aliases(2)
+        .parse()
+        .unwrap_or_else(show_err);
+    assert_eq!(n.len(), 2);
+    let e = Loader::<repr::RcRepr>::new(doc)
+        .max_aliases(1)
+        .parse()
+        .unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::TooManyAliases));
+    let e = Loader::<repr::RcRepr>::new(doc)
+        .cyclic_mode(true)
+        .max_aliases(1)
+        .parse()
+        .unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::TooManyAliases));
+}
+
+#[test]
+fn test_non_printable() {
+    use crate::parser::{ErrorKind, Loader, PError, WarningKind};
+    for (doc, pos) in [
+        ("a: 'x\x7f'\n", 5),
+        ("- |\n  b\x01\n", 7),
+        ("k\x1b: v\n", 1),
+        ("- \"\u{9f}\"\n", 3),
+    ] {
+        match parse::<repr::RcRepr>(doc) {
+            Err(PError::Terminate { kind, pos: p, .. }) => {
+                assert_eq!(kind, ErrorKind::NonPrintable, "{:?}", doc);
+                assert_eq!(p, pos, "{:?}", doc);
+            }
+            r => panic!("{:?}: {:?}", doc, r),
+        }
+        let mut loader = Loader::<repr::RcRepr>::new(doc.as_bytes()).allow_non_printable(true);
+        loader.parse().unwrap_or_else(show_err);
+        let w = loader.take_warnings();
+        assert_eq!(w.len(), 1, "{:?}", doc);
+        assert_eq!((w[0].kind, w[0].pos), (WarningKind::NonPrintable, pos));
+    }
+    // The escapes, tabs, line breaks and the other characters are allowed
+    let doc = "- \"\\x01\\e\"\n- a\tb\n- \u{85}\u{a0}é\u{feff}😀\n";
+    let ans = node!(["\x01\x1b", "a\tb", "\u{85}\u{a0}é\u{feff}😀"]);
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+}
+
+#[test]
+fn test_get_path() {
+    let doc = "\
+server:
+  host: localhost
+  port: 8080
+  debug: false
+  ratio: 1
+  tags: [a, ~]
+";
+    let root = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(root.get_str(&["server", "host"]), Ok("localhost"));
+    assert_eq!(root.get_int(&["server", "port"]), Ok(8080));
+    assert_eq!(root.get_bool(&["server", "debug"]), Ok(false));
+    assert_eq!(root.get_float(&["server", "ratio"]), Ok(1.));
+    assert_eq!(root.get_str(&["server", "tags", "1"]), Ok(""));
+    assert_eq!(root.get_path(&[]), Ok(&root));
+    let e = root.get_str(&["server", "user"]).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        format!("missing `server.user` at {}", root["server"].pos())
+    );
+    let e = root.get_int(&["server", "host"]).unwrap_err();
+    assert_eq!(e.pos(), root["server"]["host"].pos());
+    assert!(matches!(e, GetError::WrongType { expected: "int", .. }));
+    // The scalar is not a collection
+    let e = root.get_str(&["server", "port", "x"]).unwrap_err();
+    assert_eq!(
+        e,
+        GetError::WrongType {
+            path: "server.port".into(),
+            expected: "map or seq",
+            pos: root["server"]["port"].pos(),
+        }
+    );
+    assert!(matches!(
+        root.get_str(&["server", "tags", "x"]),
+        Err(GetError::Missing { .. })
+    ));
+}
+
+#[test]
+fn test_load_layered() {
+    let base = "a: {b: 1, c: [1, 2]}\nd: x\n";
+    let docs = "---\na: {b: 2}\n---\na: {c: [3]}\nd: ~\n";
+    let n = load_layered::<repr::RcRepr>(&[base, "", "# only a comment\n", docs])
+        .unwrap_or_else(|e| panic!("{}", e));
+    assert_eq!(n, node!({"a" => node!({"b" => 2, "c" => node!([3])})}));
+    assert_eq!(
+        load_layered::<repr::RcRepr>(&[]).unwrap_or_else(|e| panic!("{}", e)),
+        node!(())
+    );
+    // The later scalar root replaces the map
+    let n = load_layered::<repr::RcRepr>(&[base, "1"]).unwrap_or_else(|e| panic!("{}", e));
+    assert_eq!(n, node!(1));
+    let e = load_layered::<repr::RcRepr>(&[base, docs, "a: 'b"]).unwrap_err();
+    assert_eq!(e.index, 2);
+    assert!(e.to_string().starts_with("source 2: invalid quoted string"));
+}
+
+#[test]
+fn test_anchors_macro() {
+    use crate::parser::Anchors;
+    let empty: Anchors<repr::RcRepr> = anchors![];
+    assert!(empty.is_empty());
+    let a = anchors!["x" => 1, "y" => node!([node!(*"x")]),];
+    let (_, mut docs) = parse_cyclic::<repr::RcRepr>("- &y 2\n- &z 3\n").unwrap_or_else(show_err);
+    let b = anchors![..a.clone(), ..docs.remove(0), "w" => String::from("s")];
+    assert_eq!(b.keys().collect::<Vec<_>>(), ["w", "x", "y", "z"]);
+    assert_eq!(b["x"], node!(1));
+    assert_eq!(b["y"], node!(2));
+    assert_eq!(a.resolve_all(&a["y"]), Ok(node!([1])));
+}
+
+#[test]
+fn test_meta() {
+    use crate::{
+        parser::{Anchors, Loader},
+        query::Query,
+    };
+    use alloc::rc::Rc;
+    struct LineRepr;
+    impl repr::Repr for LineRepr {
+        type Rc = Rc<Yaml<Self>>;
+        type Meta = usize;
+        fn new_rc(yaml: Yaml<Self>) -> Self::Rc {
+            Rc::new(yaml)
+        }
+    }
+    const DOC: &str = "a: &x [1, 2]\nb:\n  c: *x\n";
+    let line = |pos: u64| DOC[..pos as usize].matches('\n').count() + 1;
+    let root = Loader::<LineRepr>::new(DOC.as_bytes())
+        .meta(|n| line(n.pos()))
+        .parse()
+        .unwrap_or_else(show_err)
+        .remove(0);
+    assert_eq!(*root["a"].meta(), 1);
+    assert_eq!(*root["a"][Ind(1)].meta(), 1);
+    assert_eq!(*root["b"]["c"].meta(), 3);
+    let n = root.clone().with_meta(0);
+    assert_eq!(*n.meta(), 0);
+    assert_eq!(n, root);
+    // The rebuilt collections keep the metadata
+    let a = &root["a"];
+    let q = "[0]".parse::<Query>().unwrap();
+    for n in [
+        a.sorted_by(|a, b| b.cmp(a)).unwrap(),
+        a.sorted_by_query(&q).unwrap(),
+        a.dedup(&Anchors::new()).unwrap(),
+        a.map_values(|_, v| v.clone()),
+        root.map_values_deep(|_, v| v.clone())["a"].clone(),
+    ] {
+        assert_eq!(*n.meta(), 1);
+    }
+    assert_eq!(*root["b"].map_keys(|k| k.clone()).meta(), 2);
+    assert_eq!(*root.map_keys_deep(|k| k.clone())["b"].meta(), 2);
+    // The default metadata without the callback
+    let root = Loader::<LineRepr>::new(DOC.as_bytes())
+        .parse()
+        .unwrap_or_else(show_err);
+    assert_eq!(*root[0]["b"].meta(), 0);
+}
+
+#[test]
+fn test_sources() {
+    use crate::parser::Sources;
+    let mut sources = Sources::new();
+    assert_eq!(sources.locate(0), None);
+    let a = sources.add("a.yaml", "a: [1, 2]\n");
+    let b = sources.add("b.yaml", "b: {c: x}\n");
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources.name(b), "b.yaml");
+    let na = sources
+        .parse::<repr::RcRepr>(a)
+        .unwrap_or_else(show_err)
+        .remove(0);
+    let nb = sources
+        .parse::<repr::RcRepr>(b)
+        .unwrap_or_else(show_err)
+        .remove(0);
+    assert_eq!(sources.locate(na["a"][Ind(1)].pos()), Some((a, 7)));
+    assert_eq!(sources.locate(nb["b"]["c"].pos()), Some((b, 7)));
+    // The end position of each source is still in the source
+    assert_eq!(sources.locate(10), Some((a, 10)));
+    assert_eq!(sources.locate(11), Some((b, 0)));
+    assert_eq!(sources.locate(100), None);
+    // The error is located in the source
+    let c = sources.add("c.yaml", "c: [x\n");
+    let e = sources.load_layered::<repr::RcRepr>().unwrap_err();
+    assert_eq!(e.index, c.index());
+    let PError::Terminate { pos, msg, .. } = e.error else {
+        panic!("{}", e)
+    };
+    assert_eq!(sources.locate(pos), Some((c, 5)));
+    assert_eq!(msg, "1:6\nc: [x\n     ^");
+    assert_eq!(
+        sources.indicated_msg(pos).unwrap(),
+        format!("c.yaml:{}", msg)
+    );
+}
+
+#[test]
+fn test_line_index() {
+    const DOC: &[u8] = b"a: 1\r\n\nb:\n  - c";
+    let index = LineIndex::new(DOC);
+    assert_eq!(index.line_count(), 4);
+    for pos in 0..=DOC.len() as u64 {
+        let (line, col) = index.offset_to_line_col(pos);
+        assert!(indicated_msg(DOC, pos).starts_with(&format!("{}:{}\n", line, col)));
+        assert_eq!(index.line_col_to_offset(line, col), Some(pos));
+    }
+    assert_eq!(index.offset_to_line_col(100), (4, 6));
+    assert_eq!(index.line_col_to_offset(2, 1), Some(6));
+    assert_eq!(index.line_col_to_offset(2, 2), None);
+    assert_eq!(index.line_col_to_offset(0, 1), None);
+    assert_eq!(index.line_col_to_offset(1, 0), None);
+    assert_eq!(LineIndex::new(b"").offset_to_line_col(0), (1, 1));
+}
+
+#[test]
+fn test_directive_policy() {
+    use crate::parser::{ErrorKind, Loader, WarningKind};
+    let load = |doc: &str| {
+        let mut loader = Loader::<repr::RcRepr>::new(doc.as_bytes());
+        let r = loader.parse();
+        let warnings = loader
+            .warnings()
+            .iter()
+            .map(|w| (w.kind, w.pos))
+            .collect::<Vec<_>>();
+        (r, warnings)
+    };
+    let doc = "%TAG !e! tag:e.com,2000:\n%TAG !e! tag:f.com,2000:\n--- !e!a b\n";
+    match load(doc).0 {
+        Err(PError::Terminate { kind: ErrorKind::DuplicatedTagHandle, pos: 30, .. }) => {}
+        r => panic!("{:?}", r.map_err(|e| e.to_string())),
+    }
+    // The same handle in the different documents
+    let doc = "%TAG !e! tag:e.com,2000:\n--- !e!a b\n...\n%TAG !e! tag:f.com,2000:\n--- !e!a c\n";
+    let (r, warnings) = load(doc);
+    assert_eq!(r.unwrap_or_else(show_err)[1].tag(), "tag:f.com,2000:a");
+    assert!(warnings.is_empty());
+    // The unknown directives are ignored, even if they start with a known name
+    let doc = "%FOO bar baz\n%YAMLX 1.2\n%TAGX ! !\n%\n--- a\n";
+    let (r, warnings) = load(doc);
+    assert_eq!(r.unwrap_or_else(show_err), vec![node!("a")]);
+    assert_eq!(
+        warnings,
+        [
+            (WarningKind::UnknownDirective, 1),
+            (WarningKind::UnknownDirective, 14),
+            (WarningKind::UnknownDirective, 25),
+            (WarningKind::UnknownDirective, 35),
+        ]
+    );
+    // The handle is declared, but the prefix is invalid
+    let (r, warnings) = load("%TAG !e!\n--- a\n");
+    assert_eq!(r.unwrap_or_else(show_err), vec![node!("a")]);
+    assert_eq!(warnings, [(WarningKind::InvalidTagDirective, 1)]);
+}
+
+#[test]
+fn test_collection_tags() {
+    let doc = "\
+set: !!set
+  ? a
+  ? [1, 2]
+omap: !!omap
+  - a: 1
+  - b: 2
+pairs: !!pairs [a: 1, a: 2]
+";
+    let n = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(n["set"].as_set(), Ok(vec![node!("a"), node!([1, 2])]));
+    assert_eq!(n["omap"].tag(), concat!(parser::tag_prefix!(), "omap"));
+    assert_eq!(
+        n["omap"].as_pairs(),
+        Ok(vec![(node!("a"), node!(1)), (node!("b"), node!(2))])
+    );
+    assert_eq!(
+        n["pairs"].as_pairs(),
+        Ok(vec![(node!("a"), node!(1)), (node!("a"), node!(2))])
+    );
+    assert_eq!(n["pairs"].as_set(), Err(n["pairs"].pos()));
+    // Round trip with the explicit keys of the set
+    let s = dump(core::slice::from_ref(&n), &[]);
+    assert!(
+        s.contains(&"set: !!set\n  ? a\n  ?\n    - 1".replace('\n', dumper::NL)),
+        "{}",
+        s
+    );
+    let n2 = s.parse::<NodeRc>().unwrap_or_else(show_err);
+    assert!(semantic_eq(
+        &n,
+        &n2,
+        SemanticOptions::new().tags(true).ordered_maps(true)
+    ));
+    // The set with a value is dumped as a map
+    let n = "!!set {a: 1}".parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(n.as_set(), Err(10));
+    assert_eq!(dump(&[n], &[]), "!!set\na: 1\n".replace('\n', dumper::NL));
+}
+
+#[test]
+fn test_str_map() {
+    let n = "a: 1\n'b': [2]\n? c\n"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    let m = n.as_str_map().unwrap_or_else(|pos| panic!("{}", pos));
+    assert_eq!(m.map(|(k, _)| k).collect::<Vec<_>>(), ["a", "b", "c"]);
+    let n = "a: 1\n2: b\n".parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(n.as_str_map().err(), Some(5));
+    assert_eq!(node!([1]).as_str_map().err(), Some(0));
+    assert_eq!(node!({}).as_str_map().map(Iterator::count), Ok(0));
+}
+
+#[test]
+fn test_lenient() {
+    let doc = "\
+port: \"8080\"
+retries: 3.0
+ratio: ' 0.5'
+debug: ON
+verbose: 0
+cache: ~
+name: [x]
+";
+    let n = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(n["port"].as_int_lenient(), Ok(8080));
+    assert_eq!(n["retries"].as_int_lenient(), Ok(3));
+    assert_eq!(n["ratio"].as_float_lenient(), Ok(0.5));
+    assert_eq!(n["ratio"].as_int_lenient(), Err(n["ratio"].pos()));
+    assert_eq!(n["debug"].as_bool_lenient(), Ok(true));
+    assert_eq!(n["verbose"].as_bool_lenient(), Ok(false));
+    assert_eq!(n["cache"].as_bool_lenient(), Ok(false));
+    assert_eq!(n["name"].as_bool_lenient(), Err(n["name"].pos()));
+    assert_eq!(n["cache"].as_int_lenient(), Err(n["cache"].pos()));
+    // The out of range values
+    assert!(node!(1e19).as_int_lenient().is_err());
+    assert!(node!("9223372036854775808").as_int_lenient().is_err());
+    assert_eq!(node!(i64::MIN as f64).as_int_lenient(), Ok(i64::MIN));
+}
+
+#[test]
+fn test_seq_map_ext() {
+    let n = "z: [3, 1, 2]\na: {y: ~, x: s}\n"
+        .parse::<NodeRc>()
+        .unwrap_or_else(show_err);
+    let m = n.as_map().unwrap();
+    let sorted = m.sorted();
+    assert_eq!(
+        sorted.keys().collect::<Vec<_>>(),
+        [&node!("a"), &node!("z")]
+    );
+    assert_eq!(sorted.positions(), [(13, 16), (0, 3)]);
+    let v = m.get_key("z").unwrap().as_seq().unwrap();
+    assert_eq!(v.sorted(), [node!(1), node!(2), node!(3)]);
+    assert_eq!(v.get_str(3), None);
+    let inner = m.get_key("a").unwrap().as_map().unwrap();
+    assert_eq!(inner.get_str("x"), Some("s"));
+    // The null value is represented as an empty string
+    assert_eq!(inner.get_str("y"), Some(""));
+    assert_eq!(inner.get_str("w"), None);
+    assert_eq!(inner.to_node().pos(), 0);
+}
+
+#[test]
+fn test_flow_whitespace() {
+    use crate::parser::{ErrorKind, Loader};
+    for doc in [
+        "key: [a, # c\n  b,\n  c\n]\n",
+        "key:\n  [a,\n b, c]\n",
+        "key: [\n  a,\n  b,\n  c,\n]\n",
+        "key: [a\n  , b, # c\n\n  # d\n  c]\n",
+    ] {
+        let ans = node!({"key" => node!(["a", "b", "c"])});
+        assert_eq!(parse(doc).unwrap_or_else(show_err), [ans], "{:?}", doc);
+    }
+    let doc = "- {a: 1, # c\n   b: [2,\n    3]}\n- [a,\nb]\n";
+    let n = parse::<repr::RcRepr>(doc).unwrap_err();
+    assert_eq!(n.kind(), Some(ErrorKind::FlowIndent));
+    let doc = "[{a: 1, # c\nb: [2,\n3]}\n]\n";
+    let ans = node!([node!({"a" => 1, "b" => node!([2, 3])})]);
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    for (doc, kind) in [
+        ("key:\n  - [a,\n  b]\n", ErrorKind::FlowIndent),
+        ("a: {b: 1,\n}\nc: {d: 2,\nd: 3}\n", ErrorKind::FlowIndent),
+        ("[a,,b]", ErrorKind::FlowSeqItem),
+        ("[,]", ErrorKind::FlowSeqItem),
+        ("{a: b,,}", ErrorKind::FlowMapKey),
+        ("[a #]", ErrorKind::UnclosedFlow),
+    ] {
+        let e = parse::<repr::RcRepr>(doc).unwrap_err();
+        assert_eq!(e.kind(), Some(kind), "{:?}", doc);
+    }
+    for (doc, kind) in [
+        ("[a, b,]", ErrorKind::FlowSeqItem),
+        ("{a: b, }", ErrorKind::FlowMapKey),
+    ] {
+        let e = Loader::<repr::RcRepr>::new(doc.as_bytes())
+            .allow_trailing_comma(false)
+            .parse()
+            .unwrap_err();
+        assert_eq!(e.kind(), Some(kind), "{:?}", doc);
+    }
+    let n = Loader::<repr::RcRepr>::new(b"[a, b]")
+        .allow_trailing_comma(false)
+        .parse()
+        .unwrap_or_else(show_err);
+    assert_eq!(n, [node!(["a", "b"])]);
+}
+
+#[test]
+fn test_compact_collection() {
+    let doc = "\
+-   - a
+    - b
+-  k1: v1
+   k2:
+   - - c
+     -  d: 1
+        e: 2
+- - - f
+    - g
+  - h
+";
+    let ans = node!([
+        node!(["a", "b"]),
+        node!({"k1" => "v1", "k2" => node!([node!(["c", node!({"d" => 1, "e" => 2})])])}),
+        node!([node!(["f", "g"]), "h"]),
+    ]);
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    // Zero-indented sequences
+    let doc = "spec:\n  ports:\n  - 80\n  - 443\n  name: a\nitems:\n- x\n";
+    let ans = node!({
+        "spec" => node!({"ports" => node!([80, 443]), "name" => "a"}),
+        "items" => node!(["x"]),
+    });
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    // The indented root collection
+    let doc = "  a: 1\n  b: 2\n---\n  - c\n  - d\n";
+    let ans = [node!({"a" => 1, "b" => 2}), node!(["c", "d"])];
+    assert_eq!(parse(doc).unwrap_or_else(show_err), ans);
+    assert!(parse::<repr::RcRepr>("- - a\n - b\n").is_err());
+}
+
+#[test]
+fn test_indent_detection() {
+    use crate::parser::{ErrorKind, Loader};
+    let doc = "-\n    a: 1\n    b:\n        - c\n-\n   d\n   e\n-\n- a:\n   - f\n";
+    let ans = node!([
+        node!({"a" => 1, "b" => node!(["c"])}),
+        "d e",
+        (),
+        node!({"a" => node!(["f"])}),
+    ]);
+    assert_eq!(parse(doc).unwrap_or_else(show_err), [ans]);
+    let e = Loader::<repr::RcRepr>::new(doc.as_bytes())
+        .strict_indent(true)
+        .parse()
+        .unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::InconsistentIndent));
+    const DOC: &str = include_str!("indent.yaml");
+    let n = Loader::<repr::RcRepr>::new(DOC.as_bytes())
+        .strict_indent(true)
+        .parse()
+        .unwrap_or_else(show_err);
+    assert_eq!(n, parse(DOC).unwrap_or_else(show_err));
+}
+
+#[test]
+fn test_tag_handler() {
+    use crate::parser::{ErrorKind, Loader, PError};
+    let doc = b"\
+port: !u32 8080
+mode: !enum debug
+name: !u32 [not, scalar]
+empty: !enum
+";
+    let mut loader = Loader::<repr::RcRepr>::new(doc)
+        .tag_handler("u32", |s| match s.parse::<u32>() {
+            Ok(n) => Ok(Yaml::Int(n.to_string())),
+            Err(e) => Err(e.to_string()),
+        })
+        .tag_handler("enum", |s| match s {
+            "debug" | "release" => Ok(Yaml::Str(s.to_uppercase())),
+            "" => Ok(Yaml::Str("RELEASE".into())),
+            _ => Err("expect debug or release".into()),
+        });
+    let n = loader.parse().unwrap_or_else(show_err).remove(0);
+    let ans = node!({
+        "port" => 8080,
+        "mode" => "DEBUG",
+        "name" => node!(["not", "scalar"]),
+        "empty" => "RELEASE",
+    });
+    assert_eq!(n, ans);
+    assert_eq!(n["mode"].tag(), "enum");
+    let doc = b"- !enum debug\n- !enum other\n";
+    let e = Loader::<repr::RcRepr>::new(doc)
+        .tag_handler("enum", |s| match s {
+            "debug" => Ok(Yaml::Str(s.into())),
+            _ => Err("expect debug".into()),
+        })
+        .parse()
+        .unwrap_err();
+    let PError::Terminate { kind, pos, msg, .. } = e else {
+        unreachable!()
+    };
+    assert_eq!((kind, pos), (ErrorKind::TagValue, 22));
+    assert!(msg.ends_with("enum: expect debug"), "{}", msg);
+}
+
+#[test]
+#[cfg(feature = "units")]
+fn test_units() {
+    use core::time::Duration;
+    let doc = "\
+timeout: 1h30m
+retry: 1.5s
+tick: 100us
+wait: 2
+limit: 512 KiB
+disk: 1.5GB
+buffer: 4096
+bad: 10 apples
+";
+    let n = doc.parse::<NodeRc>().unwrap_or_else(show_err);
+    assert_eq!(n["timeout"].as_duration(), Ok(Duration::from_secs(5400)));
+    assert_eq!(n["retry"].as_duration(), Ok(Duration::from_millis(1500)));
+    assert_eq!(n["tick"].as_duration(), Ok(Duration::from_micros(100)));
+    assert_eq!(n["wait"].as_duration(), Ok(Duration::from_secs(2)));
+    assert_eq!(n["limit"].as_byte_size(), Ok(512 << 10));
+    assert_eq!(n["disk"].as_byte_size(), Ok(1_500_000_000));
+    assert_eq!(n["buffer"].as_byte_size(), Ok(4096));
+    let e = n["bad"].as_byte_size().unwrap_err();
+    assert_eq!(
+        e,
+        UnitError::Invalid { value: "10 apples".into(), pos: n["bad"].pos() }
+    );
+    assert_eq!(e.pos(), n["bad"].pos());
+    assert!(matches!(
+        n["bad"].as_duration(),
+        Err(UnitError::Invalid { .. })
+    ));
+    assert_eq!(node!([]).as_duration(), Err(UnitError::NotScalar(0)));
+    for s in ["", "s", "1.s", "-1s", "1h30", "1x", "-1"] {
+        assert!(node!(s).as_duration().is_err(), "{:?}", s);
+    }
+    assert!(matches!(
+        node!("20000000PiB").as_byte_size(),
+        Err(UnitError::OutOfRange { .. })
+    ));
+    assert!(matches!(
+        node!("1e30").as_byte_size(),
+        Err(UnitError::Invalid { .. })
+    ));
+    // The numbers without unit
+    let doc = "[30, '30', 0x10, '1.5', 1.5, 1e3, -1, -0.5, .nan, .inf, 0x10000000000000000]";
+    let v = parse::<repr::RcRepr>(doc).unwrap_or_else(show_err)[0]
+        .as_seq()
+        .unwrap();
+    let secs = v[..6].iter().map(|n| n.as_duration()).collect::<Vec<_>>();
+    let ms = [30000, 30000, 16000, 1500, 1500, 1000000];
+    assert_eq!(secs, ms.map(|ms| Ok(Duration::from_millis(ms))));
+    let bytes = v[..6].iter().map(|n| n.as_byte_size()).collect::<Vec<_>>();
+    assert_eq!(bytes, [30, 30, 16, 1, 1, 1000].map(Ok));
+    for (n, invalid) in v[6..].iter().zip([true, true, true, false, false]) {
+        for e in [n.as_duration().unwrap_err(), n.as_byte_size().unwrap_err()] {
+            assert_eq!(matches!(e, UnitError::Invalid { .. }), invalid, "{e}");
+        }
+    }
+    assert_eq!(node!(true).as_byte_size(), Err(UnitError::NotScalar(0)));
+}
+
+#[test]
+fn test_parse_recover() {
+    use crate::parser::{ErrorKind, Loader};
+    let doc = b"\
+a:
+  b: [1
+  c: 2
+d e
+f: *x
+g: 'h'i
+j: 1
+---
+- k: 'l
+- m
+---
+[n
+...
+%YAML 1.2
+---
+o
+";
+    let (nodes, errors) = Loader::<repr::RcRepr>::new(doc).parse_recover();
+    let ans = [
+        node!({"a" => node!({"b" => (), "c" => 2}), "f" => (), "g" => "h", "j" => 1}),
+        node!([node!({"k" => ()}), "m"]),
+        node!("o"),
+    ];
+    assert_eq!(nodes, ans);
+    let kinds = errors.iter().map(|e| e.kind().unwrap()).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ErrorKind::FlowIndent,
+            ErrorKind::MapSplitter,
+            ErrorKind::UndefinedAnchor,
+            ErrorKind::MapTerminator,
+            ErrorKind::UnclosedQuote,
+            ErrorKind::UnclosedFlow,
+        ]
+    );
+    // The normal mode fails at the first error
+    let e = Loader::<repr::RcRepr>::new(doc).parse().unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::FlowIndent));
+    let (nodes, errors) = Loader::<repr::RcRepr>::new(b"a: 1").parse_recover();
+    assert_eq!((nodes, errors.len()), (vec![node!({"a" => 1})], 0));
+}
+
+#[test]
+fn test_parse_limits() {
+    use crate::parser::{ErrorKind, Loader, ParseLimits};
+    let limits = ParseLimits::new().max_documents(2).max_keys(2);
+    let parse = |doc: &[u8]| Loader::<repr::RcRepr>::new(doc).limits(limits).parse();
+    assert_eq!(parse(b"a: 1\nb: 2\n---\n{c: 3, d: 4}").unwrap().len(), 2);
+    match parse(b"a: 1\nb: 2\nc: 3") {
+        Err(PError::Terminate { kind: ErrorKind::TooManyKeys, pos: 10, .. }) => {}
+        r => panic!("{:?}", r),
+    }
+    let e = parse(b"[{a: 1, b: 2, c: 3}]").unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::TooManyKeys));
+    let e = parse(b"a\n---\nb\n---\nc").unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::TooManyDocuments));
+    // Recovery stops at the document limit
+    let (nodes, errors) = Loader::<repr::RcRepr>::new(b"a\n---\nb\n---\nc")
+        .limits(limits)
+        .parse_recover();
+    assert_eq!(nodes, [node!("a"), node!("b")]);
+    assert_eq!(errors.len(), 1);
+    // The default nesting depth limit
+    for doc in [b"[".repeat(5000), b"{a: ".repeat(5000), b"- ".repeat(5000)] {
+        let e = parse(&doc).unwrap_err();
+        assert_eq!(e.kind(), Some(ErrorKind::TooDeep));
+    }
+    let doc = "[".repeat(64) + &"]".repeat(64);
+    assert!(parse(doc.as_bytes()).is_ok());
+    let parse = |doc: &[u8]| {
+        Loader::<repr::RcRepr>::new(doc)
+            .limits(ParseLimits::new().max_depth(3))
+            .parse()
+    };
+    assert_eq!(
+        parse(b"- [1, {a: b}]").unwrap(),
+        [node!([node!([1, node!({"a" => "b"})])])]
+    );
+    let e = parse(b"- [1, [[2]]]").unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::TooDeep));
+}
+
+#[test]
+fn test_split_documents() {
+    assert!(split_documents("").is_empty());
+    assert!(split_documents("# comment\n\n").is_empty());
+    assert_eq!(split_documents("a"), ["a"]);
+    assert_eq!(split_documents("---\n---\n"), ["---\n", "---\n"]);
+    // Quoted and block scalars, and the trailing comment
+    let doc = "a: \"--- x\"\nb: >\n  ---\n...\n# end\n";
+    assert_eq!(split_documents(doc), [doc]);
+    let doc = "%YAML 1.2\n---\na\n...\n%TAG !x! tag:x:\n--- !x!b\nc\n---\nd\n";
+    let docs = split_documents(doc);
+    assert_eq!(docs.len(), 3);
+    assert_eq!(docs[1], "%TAG !x! tag:x:\n--- !x!b\nc\n");
+    for d in &docs {
+        assert_eq!(parse::<repr::RcRepr>(d).unwrap().len(), 1);
+    }
+    // Round trip
+    let docs = docs.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let joined = join_documents(&docs);
+    assert_eq!(joined, doc);
+    assert_eq!(parse::<repr::RcRepr>(&joined).unwrap().len(), 3);
+    let docs = [
+        "".to_string(),
+        "a".to_string(),
+        "%YAML 1.2\n---\nb".to_string(),
+    ];
+    let joined = join_documents(&docs);
+    assert_eq!(joined, "--- null\n---\na\n...\n%YAML 1.2\n---\nb\n");
+    assert_eq!(
+        parse::<repr::RcRepr>(&joined).unwrap(),
+        [node!(()), node!("a"), node!("b")]
+    );
+}
+
+#[test]
+fn test_flow_capacity() {
+    let doc = "[1, [a, b, c], {x: 1, y: 2}, 'a,''b', \"c,\\\"d\", # e, f\n  g,]";
+    let n = doc.parse::<NodeRc>().unwrap();
+    assert_eq!(n.as_seq().unwrap().len(), 6);
+    assert_eq!(n.yaml().capacity(), Some(6));
+    assert_eq!(n[Ind(1)].yaml().capacity(), Some(3));
+    assert_eq!(n[Ind(3)], node!("a,'b"));
+    let n = "[[], [[]], {a: []}]".parse::<NodeRc>().unwrap();
+    assert_eq!(n.yaml().capacity(), Some(3));
+    assert_eq!(n[Ind(0)].yaml().capacity(), Some(0));
+    assert_eq!(n[Ind(1)].yaml().capacity(), Some(1));
+    let mut y = YamlRc::map_with_capacity(4);
+    assert!(y.capacity().unwrap() >= 4);
+    y.reserve(10);
+    assert!(y.capacity().unwrap() >= 10);
+    assert_eq!(node!("a").yaml().capacity(), None);
+}
+
+#[test]
+fn test_scalar_constructors() {
+    assert!(NodeRc::null().is_null());
+    assert_eq!(NodeRc::bool(false).as_bool(), Ok(false));
+    assert_eq!(NodeRc::int(i64::MIN).as_int(), Ok(i64::MIN));
+    assert_eq!(NodeRc::float(1.5).as_float(), Ok(1.5));
+    assert!(NodeRc::float(f64::NAN).as_float().unwrap().is_nan());
+    assert_eq!(NodeRc::float(f64::INFINITY).as_float(), Ok(f64::INFINITY));
+    assert_eq!(NodeRc::str(String::from("x")).as_str(), Ok("x"));
+    let n: NodeRc = [(NodeRc::str("a"), NodeRc::int(1))].into_iter().collect();
+    assert_eq!(n, node!({"a" => 1}));
+}
+
+#[test]
+fn test_node_builders() {
+    use crate::parser::Anchors;
+    let n = parse::<repr::RcRepr>("a: !x &y 1").unwrap().remove(0);
+    let v = n["a"].clone().with_yaml(2);
+    assert_eq!((v.pos(), v.tag(), v.as_int()), (n["a"].pos(), "x", Ok(2)));
+    // The default tag
+    let v = v.with_tag("").with_pos(0);
+    assert_eq!(
+        (v.pos(), v.tag()),
+        (0, concat!(parser::tag_prefix!(), "int"))
+    );
+    let mut anchors = Anchors::new();
+    let v = v.with_anchor("z", &mut anchors);
+    assert_eq!(anchors["z"], v);
+    assert_eq!(dump(&[v], &[anchors]), "&z 2\n".replace('\n', dumper::NL));
+}
+
+#[test]
+fn test_key_policy() {
+    use crate::parser::{ErrorKind, KeyPolicy, Loader};
+    let parse = |doc: &[u8], policy| Loader::<repr::RcRepr>::new(doc).key_policy(policy).parse();
+    let doc = b"{1: a, \"1\": b, 01: c}";
+    assert_eq!(
+        parse(doc, KeyPolicy::LastWins).unwrap()[0]
+            .as_map()
+            .unwrap()
+            .len(),
+        2
+    );
+    match parse(doc, KeyPolicy::Value) {
+        Err(PError::Terminate { kind: ErrorKind::DuplicatedKey, pos: 15, msg, .. }) => {
+            assert!(msg.ends_with("first defined at 1:2\n{1: a, \"1\": b, 01: c}\n ^"));
+        }
+        r => panic!("{:?}", r),
+    }
+    match parse(doc, KeyPolicy::Text) {
+        Err(PError::Terminate { kind: ErrorKind::DuplicatedKey, pos: 7, .. }) => {}
+        r => panic!("{:?}", r),
+    }
+    assert!(parse(b"1: a\n01: b\n0x1: c", KeyPolicy::Text).is_ok());
+    assert!(parse(b"1: a\n1.0: b", KeyPolicy::Text).is_ok());
+    let e = parse(b"a:\n  b: 1\n  b: 2", KeyPolicy::Text).unwrap_err();
+    assert_eq!(e.kind(), Some(ErrorKind::DuplicatedKey));
+    // The keys of different maps are not compared
+    assert!(parse(b"- a: 1\n- a: 2", KeyPolicy::Value).is_ok());
+}
+
+#[test]
+#[cfg(feature = "arena")]
+fn test_arena() {
+    use crate::repr::ArenaRepr;
+    let doc = (0..500)
+        .map(|i| format!("k{}: [{}, v{}]\n", i, i, i))
+        .collect::<String>();
+    let n = parse::<ArenaRepr>(&doc).unwrap().remove(0);
+    assert_eq!(n.as_map().unwrap().len(), 500);
+    assert_eq!(
+        dump(core::slice::from_ref(&n), &[]),
+        dump(&parse::<repr::RcRepr>(&doc).unwrap(), &[])
+    );
+    // The children outlive their parents
+    let child = n["k499"].clone();
+    let first = n["k0"].clone();
+    drop(n);
+    assert_eq!(child[Ind(1)], Node::from("v499"));
+    assert_eq!(first[Ind(0)].as_int(), Ok(0));
+    // Mixed with the new nodes
+    let root = Node::<ArenaRepr>::from(Yaml::Seq(vec![child.clone(), first, child]));
+    assert_eq!(root[Ind(2)][Ind(0)].as_int(), Ok(499));
+}
+
+#[test]
+#[cfg(feature = "siphash")]
+fn test_siphash() {
+    use core::hash::BuildHasher;
+    let n = parse::<repr::RcRepr>("{a: 1, b: 2, 3: c}")
+        .unwrap()
+        .remove(0);
+    let m = n
+        .as_map()
+        .unwrap()
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect::<SipMap<_>>();
+    assert_eq!(m.get_key("a"), Some(&node!(1)));
+    assert_eq!(m.get_key(&node!(3)), Some(&node!("c")));
+    assert_eq!(m.to_node(), n);
+    // The hashers are keyed randomly
+    let (h1, h2) = (
+        SipMap::<repr::RcRepr>::default(),
+        SipMap::<repr::RcRepr>::default(),
+    );
+    assert_ne!(h1.hasher().hash_one("a"), h2.hasher().hash_one("a"));
+    // The default map is not changed by the feature
+    assert!(Map::<repr::RcRepr>::new().is_empty());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_ser_options() {
+    use crate::serde::{to_node, to_node_with, RenamePolicy, SerOptions};
+    use alloc::collections::BTreeMap;
+    #[derive(::serde::Serialize)]
+    struct Container {
+        image_name: &'static str,
+        pull_policy: Option<&'static str>,
+        extra_args: Vec<&'static str>,
+        env_vars: BTreeMap<&'static str, Option<&'static str>>,
+    }
+    #[derive(::serde::Serialize)]
+    enum Spec {
+        Pod {
+            init_containers: Vec<Container>,
+            host_network: Option<bool>,
+        },
+    }
+    let c = Container {
+        image_name: "nginx",
+        pull_policy: None,
+        extra_args: vec![],
+        env_vars: BTreeMap::from([("LOG_LEVEL", None)]),
+    };
+    let spec = Spec::Pod { init_containers: vec![c], host_network: None };
+    let opts = SerOptions::new()
+        .skip_none(true)
+        .skip_empty(true)
+        .rename(RenamePolicy::KebabCase);
+    // The map keys are kept, and the null map values are not fields
+    let ans = node!({"Pod" => node!({"init-containers" => node!([node!({
+        "image-name" => "nginx",
+        "env-vars" => node!({"LOG_LEVEL" => ()}),
+    })])})});
+    assert_eq!(to_node_with(&spec, opts).unwrap(), ans);
+    let n = to_node(&spec).unwrap();
+    let c = &n["Pod"]["init_containers"][Ind(0)];
+    assert_eq!(c["pull_policy"], node!(()));
+    assert_eq!(c["extra_args"], node!([]));
+    assert_eq!(RenamePolicy::CamelCase.apply("host_network"), "hostNetwork");
+    assert_eq!(
+        RenamePolicy::ScreamingSnakeCase.apply("host_network"),
+        "HOST_NETWORK"
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_dump_comments() {
+    use crate::{
+        dumper::{DumpOptions, NL},
+        serde::to_string_with,
+    };
+    #[derive(::serde::Serialize)]
+    struct Member {
+        name: &'static str,
+        pets: Vec<Pet>,
+    }
+    #[derive(::serde::Serialize)]
+    struct Pet {
+        kind: &'static str,
+        age: u8,
+    }
+    let m = Member {
+        name: "Bob",
+        pets: vec![Pet { kind: "cat", age: 3 }],
+    };
+    let opts = DumpOptions::new().comments(&[
+        ("name", "The member name"),
+        ("pets[0]", "First pet"),
+        ("pets[0].age", "In years\n\nApproximately"),
+        ("missing", "Not written"),
+    ]);
+    let doc = to_string_with(&m, opts).unwrap();
+    let ans = "\
+# The member name
+name: Bob
+pets:
+  # First pet
+  - kind: cat
+    # In years
+    #
+    # Approximately
+    age: 3
+";
+    assert_eq!(doc, ans.replace('\n', NL));
+    assert_eq!(
+        parse::<repr::RcRepr>(&doc).unwrap(),
+        [crate::serde::to_node(&m).unwrap()]
+    );
+}
+
+#[test]
+fn test_tag_uri() {
+    use crate::parser::ErrorKind;
+    let doc = "\
+%TAG !e! tag:example.com,2000:app/
+%TAG !my-lib! !lib-
+---
+!e!tag%21
+- !foo.bar/baz a
+- !<tag:yaml.org,2002:str> b
+- !my-lib!x c
+- !<!local> d
+";
+    let n = parse::<repr::RcRepr>(doc)
+        .unwrap_or_else(show_err)
+        .remove(0);
+    assert_eq!(n.tag(), "tag:example.com,2000:app/tag!");
+    let tags = n
+        .as_seq()
+        .unwrap()
+        .iter()
+        .map(|n| n.resolved_tag().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tags,
+        ["!foo.bar/baz", "tag:yaml.org,2002:str", "!lib-x", "!local"]
+    );
+    // The tags are escaped by the dumper
+    let n = node!({"a" => Node::new("a", 0, "a b"), "b" => Node::new("b", 0, "!x,y")});
+    let doc = dump(&[n], &[]);
+    assert_eq!(doc, "a: !a%20b a\nb: !<!x,y> b\n".replace('\n', dumper::NL));
+    let back = parse::<repr::RcRepr>(&doc)
+        .unwrap_or_else(show_err)
+        .remove(0);
+    assert_eq!(back["a"].tag(), "a b");
+    assert_eq!(back["b"].tag(), "!x,y");
+    for (doc, kind) in [
+        ("!<!> a", ErrorKind::InvalidTag),
+        ("!<foo> a", ErrorKind::InvalidTag),
+        ("!<tag:a b> a", ErrorKind::InvalidTag),
+        ("!a%2 b", ErrorKind::InvalidTag),
+        ("!a%ff b", ErrorKind::InvalidTag),
+        ("!x!y a", ErrorKind::UndeclaredTagHandle),
+    ] {
+        let e = parse::<repr::RcRepr>(doc).unwrap_err();
+        assert_eq!(e.kind(), Some(kind), "{}", doc);
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_key_order() {
+    use crate::{
+        dumper::NL,
+        serde::{from_node_with, from_str, to_arc_node, to_string, DeOptions},
+    };
+    let doc = "\
+zone: b
+2: int
+name: a
+spec:
+  replicas: 3
+  containers:
+    - port: 80
+      image: x
+  affinity: {}
+";
+    let n = from_str::<NodeRc>(doc).unwrap().remove(0);
+    assert_eq!(to_string(&n).unwrap(), doc.replace('\n', NL));
+    // Across the representations
+    let n = from_node_with::<NodeArc, _>(n, DeOptions::new()).unwrap();
+    let n = to_arc_node(n).unwrap();
+    assert_eq!(n.to_string() + NL, doc.replace('\n', NL));
+    // The rest keys of the flatten fields
+    #[derive(::serde::Deserialize, ::serde::Serialize)]
+    struct Manifest {
+        name: String,
+        #[serde(flatten)]
+        rest: NodeRc,
+    }
+    let m = from_str::<Manifest>(&doc.replace("2: int\n", ""))
+        .unwrap()
+        .remove(0);
+    let keys = m.rest.as_map().unwrap().keys().cloned().collect::<Vec<_>>();
+    assert_eq!(keys, [node!("zone"), node!("spec")]);
+    let ans = "name: a\nzone: b\nspec:";
+    assert!(to_string(&m).unwrap().starts_with(&ans.replace('\n', NL)));
+}
+
+#[test]
+fn test_dump_anchors() {
+    use crate::{
+        dumper::{dump, dump_with, DumpOptions, NL},
+        parser::{parse_cyclic, Anchors},
+    };
+    // The equal nodes are not aliased
+    let doc = "a: &x\n  k: *x\nb: *x\nc:\n  - 1\nd: &y\n  - 1\ne: *y\n";
+    let (root, anchors) = parse_cyclic::<repr::RcRepr>(doc).unwrap();
+    assert_eq!(dump(&root, &anchors), doc.replace('\n', NL));
+    // The anchor is defined at the first alias
+    let alias = || Node::new(Yaml::Alias("x".into()), 0, "");
+    let mut anchors = Anchors::new();
+    anchors.insert("x".into(), node!({"k" => 1}));
+    let n: NodeRc = node!({"a" => alias(), "b" => node!([alias()])});
+    let doc = dump(core::slice::from_ref(&n), core::slice::from_ref(&anchors));
+    assert_eq!(doc, "a: &x\n  k: 1\nb:\n  - *x\n".replace('\n', NL));
+    let opts = DumpOptions::new().canonical(true);
+    let doc = dump_with(&[n], &[anchors], opts);
+    let back = parse::<repr::RcRepr>(&doc).unwrap_or_else(|e| panic!("{e}\n{doc}"));
+    assert_eq!(
+        back[0],
+        node!({"a" => node!({"k" => 1}), "b" => node!([node!({"k" => 1})])})
+    );
+}
+
+#[test]
+fn test_analyze() {
+    let stats = analyze(&node!({"a" => node!([1, "bc", ()]), "b" => node!([true, 1.5])}));
+    assert_eq!((stats.maps, stats.seqs, stats.nodes()), (1, 2, 10));
+    assert_eq!((stats.nulls, stats.bools, stats.floats), (1, 1, 1));
+    assert_eq!((stats.max_depth, stats.scalar_bytes), (3, 8));
+    assert_eq!((stats.anchors, stats.aliases, stats.duplicates), (0, 0, 0));
+    // The expanded sizes of the nested aliases
+    let mut doc = "a0: &a0 [x, x, x, x, x, x, x, x, x, x]\n".to_string();
+    for i in 1..40 {
+        let refs = alloc::vec![format!("*a{}", i - 1); 10].join(", ");
+        doc += &format!("a{i}: &a{i} [{refs}]\n");
+    }
+    let root = parser::Loader::<repr::RcRepr>::new(doc.as_bytes())
+        .max_aliases(usize::MAX)
+        .parse()
+        .unwrap_or_else(show_err)
+        .remove(0);
+    let stats = analyze(&root);
+    assert_eq!((stats.strs, stats.max_depth), (usize::MAX, 42));
+    assert_eq!((stats.anchors, stats.aliases), (39, 39 * 10));
+    // The unresolved aliases and the nested duplicates
+    let (root, _) =
+        parse_cyclic::<repr::RcRepr>("a: &x\n  - *x\n  - *x\nb: [[1], [1]]\nc: [[1], [1]]\n")
+            .unwrap_or_else(show_err);
+    let stats = analyze(&root[0]);
+    assert_eq!((stats.anchors, stats.aliases), (1, 2));
+    assert_eq!((stats.duplicates, stats.duplicate_nodes), (2, 7));
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn test_testing() {
+    use crate::testing::*;
+    let found: NodeRc = "a: [1, {b: x}]\nc: !t 1\n".parse().unwrap();
+    let expected = node!({"a" => node!([1.0, node!({"b" => "y"}), 2]), "c" => 1});
+    let v = diff(&found, &expected, SemanticOptions::new().tags(true))
+        .into_iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        v,
+        [
+            "a[0]: expected !!float 1, found !!int 1",
+            "a[1].b: expected \"y\", found x",
+            "a[2]: missing 2",
+            "c: expected 1, found !t 1",
+        ]
+    );
+    let e = std::panic::catch_unwind(|| assert_tree_eq(&found, &expected)).unwrap_err();
+    let msg = e.downcast_ref::<String>().unwrap();
+    assert_eq!(
+        msg,
+        "the trees are different:\n  a[1].b: expected \"y\", found x\n  a[2]: missing 2\n"
+    );
+    // The golden file is written then compared
+    let path = std::env::temp_dir().join(format!("yaml-peg-golden-{}.yaml", std::process::id()));
+    std::fs::write(&path, "- 1\n- [a]\n").unwrap();
+    let docs = load_fixture::<repr::RcRepr>(&path);
+    std::fs::write(
+        &path,
+        "%YAML 1.2\n--- !!seq [\n  !!int \"1\",\n  !!seq [\n    !!str \"b\",\n  ],\n]\n",
+    )
+    .unwrap();
+    let e = std::panic::catch_unwind(|| assert_golden(&docs, &path)).unwrap_err();
+    let msg = e.downcast_ref::<String>().unwrap();
+    assert!(
+        msg.ends_with("  !!seq [\n-     !!str \"b\",\n+     !!str \"a\",\n    ],\n  ]\n"),
+        "{msg}"
+    );
+    // Only the true values bless the golden file
+    std::env::set_var("YAML_PEG_BLESS", "0");
+    assert!(std::panic::catch_unwind(|| assert_golden(&docs, &path)).is_err());
+    std::env::set_var("YAML_PEG_BLESS", "1");
+    assert_golden(&docs, &path);
+    std::env::remove_var("YAML_PEG_BLESS");
+    assert_golden(&docs, &path);
+    std::fs::remove_file(&path).unwrap();
+    // The large changes are not aligned line by line
+    let opts = dumper::DumpOptions::new().canonical(true);
+    let found = node!((0..3000).map(|i| node!(i)).collect::<Seq<_>>());
+    let expected = node!((1..3001).map(|i| node!(i)).collect::<Seq<_>>());
+    let expected = dumper::dump_with(&[expected], &[], opts);
+    let e = std::panic::catch_unwind(|| assert_canonical(&[found], &expected)).unwrap_err();
+    let msg = e.downcast_ref::<String>().unwrap();
+    assert!(msg.contains("-   !!int \"1\",\n"), "{msg}");
+    assert!(msg.ends_with("+   !!int \"2999\",\n  ]\n"), "{msg}");
+}
@@ -0,0 +1,236 @@
+//! Convert the nodes to the TOML text, for the consumers that only read
+//! TOML configs.
+//!
+//! ```
+//! use yaml_peg::{toml::to_toml, NodeRc};
+//!
+//! let doc = "\
+//! title: demo
+//! server:
+//!   ports: [80, 443]
+//!   tls: {enabled: true}
+//! users:
+//! - name: alice
+//! - name: bob
+//! ";
+//! let n: NodeRc = doc.parse().unwrap();
+//! let ans = "\
+//! title = \"demo\"
+//!
+//! [server]
+//! ports = [80, 443]
+//!
+//! [server.tls]
+//! enabled = true
+//!
+//! [[users]]
+//! name = \"alice\"
+//!
+//! [[users]]
+//! name = \"bob\"
+//! ";
+//! assert_eq!(to_toml(&n).unwrap(), ans);
+//! ```
+//!
+//! The maps are written as the tables, and the sequences of maps are
+//! written as the arrays of tables, otherwise the values are written
+//! inline. The constructs that TOML can't express are errors, see
+//! [`to_toml`].
+use crate::{repr::Repr, *};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Formatter, Write};
+
+/// The error of [`to_toml`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TomlError {
+    /// Message.
+    pub msg: String,
+    /// The position of the node.
+    pub pos: u64,
+}
+
+impl TomlError {
+    /// Create the error.
+    pub fn new(msg: impl ToString, pos: u64) -> Self {
+        Self { msg: msg.to_string(), pos }
+    }
+}
+
+impl Display for TomlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at {}", self.msg, self.pos)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TomlError {}
+
+fn err<T, R: Repr>(msg: &str, node: &Node<R>) -> Result<T, TomlError> {
+    Err(TomlError::new(msg, node.pos()))
+}
+
+/// Convert the map node to a TOML document.
+///
+/// Return `Err` with the node position if:
+///
+/// + The root node is not a map.
+/// + The node is null or an alias, please resolve the aliases before
+///   converting, see [`Anchors::expand`](crate::parser::Anchors::expand).
+/// + The integer is out of the 64-bit range.
+/// + The map key is not a string, an integer or a boolean.
+///
+/// ```
+/// use yaml_peg::{node, toml::to_toml};
+///
+/// let e = to_toml(&node!({"a" => ()})).unwrap_err();
+/// assert_eq!(e.msg, "null is not supported");
+/// assert!(to_toml(&node!([1])).is_err());
+/// ```
+pub fn to_toml<R: Repr>(node: &Node<R>) -> Result<String, TomlError> {
+    let m = match node.yaml() {
+        Yaml::Map(m) => m,
+        _ => return err("the root must be a map", node),
+    };
+    let mut doc = String::new();
+    table(&mut doc, &mut Vec::new(), m)?;
+    Ok(doc)
+}
+
+/// Return true if the node is written as an array of tables.
+fn is_table_array<R: Repr>(node: &Node<R>) -> bool {
+    match node.yaml() {
+        Yaml::Seq(v) => !v.is_empty() && v.iter().all(|n| matches!(n.yaml(), Yaml::Map(_))),
+        _ => false,
+    }
+}
+
+/// Write the key/value pairs, then the sub-tables.
+fn table<R: Repr>(doc: &mut String, path: &mut Vec<String>, m: &Map<R>) -> Result<(), TomlError> {
+    let mut subs = Vec::new();
+    for (k, v) in m.iter() {
+        let k = key(k)?;
+        if matches!(v.yaml(), Yaml::Map(_)) || is_table_array(v) {
+            subs.push((k, v));
+        } else {
+            write!(doc, "{} = ", k).unwrap();
+            value(doc, v)?;
+            doc.push('\n');
+        }
+    }
+    for (k, v) in subs {
+        path.push(k);
+        let header = path.join(".");
+        match v.yaml() {
+            Yaml::Map(m) => {
+                header_line(doc, &alloc::format!("[{}]", header));
+                table(doc, path, m)?;
+            }
+            Yaml::Seq(v) => {
+                for n in v {
+                    header_line(doc, &alloc::format!("[[{}]]", header));
+                    if let Yaml::Map(m) = n.yaml() {
+                        table(doc, path, m)?;
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+fn header_line(doc: &mut String, header: &str) {
+    if !doc.is_empty() {
+        doc.push('\n');
+    }
+    doc.push_str(header);
+    doc.push('\n');
+}
+
+fn key<R: Repr>(node: &Node<R>) -> Result<String, TomlError> {
+    let s = match node.yaml() {
+        Yaml::Str(s) => s.clone(),
+        Yaml::Int(s) => match yaml::to_i128(s) {
+            Ok(n) => n.to_string(),
+            Err(_) => s.clone(),
+        },
+        Yaml::Bool(b) => b.to_string(),
+        _ => return err("the key must be a string, an integer or a boolean", node),
+    };
+    let bare = s
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare && !s.is_empty() {
+        Ok(s)
+    } else {
+        let mut doc = String::new();
+        string(&mut doc, &s);
+        Ok(doc)
+    }
+}
+
+fn value<R: Repr>(doc: &mut String, node: &Node<R>) -> Result<(), TomlError> {
+    match node.yaml() {
+        Yaml::Null => return err("null is not supported", node),
+        Yaml::Alias(_) => return err("alias is not supported", node),
+        Yaml::Bool(b) => *doc += if *b { "true" } else { "false" },
+        Yaml::Int(s) => match yaml::to_i64(s) {
+            Ok(n) => write!(doc, "{}", n).unwrap(),
+            Err(_) => return err("integer out of range", node),
+        },
+        Yaml::Float(s) => match yaml::to_f64(s) {
+            Ok(f) if f.is_nan() => *doc += "nan",
+            Ok(f) if f.is_infinite() => *doc += if f > 0. { "inf" } else { "-inf" },
+            Ok(f) => write!(doc, "{:?}", f).unwrap(),
+            Err(_) => return err("invalid float", node),
+        },
+        Yaml::Str(s) => string(doc, s),
+        Yaml::Seq(v) => {
+            doc.push('[');
+            for (i, n) in v.iter().enumerate() {
+                if i > 0 {
+                    *doc += ", ";
+                }
+                value(doc, n)?;
+            }
+            doc.push(']');
+        }
+        Yaml::Map(m) if m.is_empty() => *doc += "{}",
+        Yaml::Map(m) => {
+            *doc += "{ ";
+            for (i, (k, v)) in m.iter().enumerate() {
+                if i > 0 {
+                    *doc += ", ";
+                }
+                write!(doc, "{} = ", key(k)?).unwrap();
+                value(doc, v)?;
+            }
+            *doc += " }";
+        }
+    }
+    Ok(())
+}
+
+/// Write the basic string.
+fn string(doc: &mut String, s: &str) {
+    doc.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => *doc += "\\\\",
+            '"' => *doc += "\\\"",
+            '\n' => *doc += "\\n",
+            '\r' => *doc += "\\r",
+            '\t' => *doc += "\\t",
+            '\x08' => *doc += "\\b",
+            '\x0C' => *doc += "\\f",
+            c if c.is_control() => write!(doc, "\\u{:04X}", u32::from(c)).unwrap(),
+            c => doc.push(c),
+        }
+    }
+    doc.push('"');
+}
@@ -0,0 +1,214 @@
+use alloc::string::String;
+use core::{
+    fmt::{Display, Formatter},
+    num::IntErrorKind,
+    time::Duration,
+};
+
+/// The error of the unit conversions, see
+/// [`Node::as_duration`](crate::Node::as_duration) and
+/// [`Node::as_byte_size`](crate::Node::as_byte_size).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnitError {
+    /// The node is not a string or a number, with node position.
+    NotScalar(u64),
+    /// The value is not a number followed by a known unit.
+    Invalid {
+        /// The original value.
+        value: String,
+        /// Node position.
+        pos: u64,
+    },
+    /// The value is too large for the target type.
+    OutOfRange {
+        /// The original value.
+        value: String,
+        /// Node position.
+        pos: u64,
+    },
+}
+
+impl UnitError {
+    /// Node position.
+    pub fn pos(&self) -> u64 {
+        match self {
+            Self::NotScalar(pos) | Self::Invalid { pos, .. } | Self::OutOfRange { pos, .. } => *pos,
+        }
+    }
+}
+
+impl Display for UnitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotScalar(pos) => write!(f, "not a string or number at {}", pos),
+            Self::Invalid { value, pos } => {
+                write!(f, "invalid value with unit `{}` at {}", value, pos)
+            }
+            Self::OutOfRange { value, pos } => {
+                write!(f, "value with unit `{}` out of range at {}", value, pos)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnitError {}
+
+/// The conversion failure, the error is filled by the caller.
+pub(crate) enum Fail {
+    Invalid,
+    OutOfRange,
+}
+
+impl Fail {
+    pub(crate) fn into_error(self, value: &str, pos: u64) -> UnitError {
+        let value = value.into();
+        match self {
+            Self::Invalid => UnitError::Invalid { value, pos },
+            Self::OutOfRange => UnitError::OutOfRange { value, pos },
+        }
+    }
+}
+
+const NANOS: u128 = 1_000_000_000;
+
+/// Parse the duration like `30s`, `5m` and `1h30m`, the units are `ns`,
+/// `us` (or `µs`), `ms`, `s`, `m`, `h` and `d`.
+pub(crate) fn to_duration(s: &str) -> Result<Duration, Fail> {
+    let mut s = s.trim();
+    if s.is_empty() {
+        return Err(Fail::Invalid);
+    }
+    let mut total = 0u128;
+    if let (int, frac, "") = split_number(s)? {
+        // The number without unit is in seconds
+        total = scale(int, frac, NANOS).ok_or(Fail::OutOfRange)?;
+        s = "";
+    }
+    while !s.is_empty() {
+        let (int, frac, rest) = split_number(s)?;
+        let end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..end] {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => NANOS,
+            "m" => 60 * NANOS,
+            "h" => 3600 * NANOS,
+            "d" => 86400 * NANOS,
+            _ => return Err(Fail::Invalid),
+        };
+        total = scale(int, frac, unit)
+            .and_then(|n| total.checked_add(n))
+            .ok_or(Fail::OutOfRange)?;
+        s = &rest[end..];
+    }
+    let secs = u64::try_from(total / NANOS).map_err(|_| Fail::OutOfRange)?;
+    Ok(Duration::new(secs, (total % NANOS) as u32))
+}
+
+/// Parse the byte size like `10MiB` and `1GB`, the decimal units are `k`,
+/// `M`, `G`, `T` and `P`, the binary units are `Ki`, `Mi`, `Gi`, `Ti` and
+/// `Pi`, with an optional `B` suffix. The units are case-insensitive.
+pub(crate) fn to_byte_size(s: &str) -> Result<u64, Fail> {
+    let (int, frac, rest) = split_number(s.trim())?;
+    let unit = rest.trim_start().to_ascii_lowercase();
+    let unit = unit.strip_suffix('b').unwrap_or(&unit);
+    let unit: u128 = match unit {
+        "" => 1,
+        "k" => 1_000,
+        "m" => 1_000_000,
+        "g" => 1_000_000_000,
+        "t" => 1_000_000_000_000,
+        "p" => 1_000_000_000_000_000,
+        "ki" => 1 << 10,
+        "mi" => 1 << 20,
+        "gi" => 1 << 30,
+        "ti" => 1 << 40,
+        "pi" => 1 << 50,
+        _ => return Err(Fail::Invalid),
+    };
+    scale(int, frac, unit)
+        .and_then(|n| u64::try_from(n).ok())
+        .ok_or(Fail::OutOfRange)
+}
+
+/// Convert the text of the integer node, the negative numbers are invalid.
+pub(crate) fn int_to_u64(s: &str) -> Result<u64, Fail> {
+    match crate::yaml::to_i128(s) {
+        Ok(n) if n < 0 => Err(Fail::Invalid),
+        Ok(n) => u64::try_from(n).map_err(|_| Fail::OutOfRange),
+        Err(e) if matches!(e.kind(), IntErrorKind::PosOverflow) => Err(Fail::OutOfRange),
+        Err(_) => Err(Fail::Invalid),
+    }
+}
+
+/// Convert the text of the float node, the fraction is truncated, the
+/// negative numbers and NaN are invalid.
+pub(crate) fn float_to_u64(s: &str) -> Result<u64, Fail> {
+    let f = float_value(s)?;
+    if f < u64::MAX as f64 {
+        Ok(f as u64)
+    } else {
+        Err(Fail::OutOfRange)
+    }
+}
+
+/// Convert the text of the float node in seconds.
+pub(crate) fn float_to_duration(s: &str) -> Result<Duration, Fail> {
+    Duration::try_from_secs_f64(float_value(s)?).map_err(|_| Fail::OutOfRange)
+}
+
+fn float_value(s: &str) -> Result<f64, Fail> {
+    crate::yaml::to_f64(s)
+        .ok()
+        .filter(|f| *f >= 0.)
+        .ok_or(Fail::Invalid)
+}
+
+/// Split the leading number into the integer part, the fractional part and
+/// the rest string.
+fn split_number(s: &str) -> Result<(&str, &str, &str), Fail> {
+    let int_end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (int, rest) = s.split_at(int_end);
+    let (frac, rest) = match rest.strip_prefix('.') {
+        Some(rest) => {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if end == 0 {
+                return Err(Fail::Invalid);
+            }
+            rest.split_at(end)
+        }
+        None => ("", rest),
+    };
+    if int.is_empty() && frac.is_empty() {
+        Err(Fail::Invalid)
+    } else {
+        Ok((int, frac, rest))
+    }
+}
+
+/// Multiply the decimal number by the unit, the fraction under the unit is
+/// truncated.
+fn scale(int: &str, frac: &str, unit: u128) -> Option<u128> {
+    let int = if int.is_empty() {
+        0
+    } else {
+        int.parse::<u128>().ok()?
+    };
+    let mut n = int.checked_mul(unit)?;
+    // The digits under the smallest unit are meaningless
+    let mut div = 1u128;
+    let mut value = 0u128;
+    for c in frac.bytes().take(18) {
+        div *= 10;
+        value = value * 10 + u128::from(c - b'0');
+    }
+    n = n.checked_add(value * unit / div)?;
+    Some(n)
+}
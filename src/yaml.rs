@@ -4,11 +4,12 @@ use alloc::{
     vec::Vec,
 };
 use core::{
-    fmt::{Debug, Formatter},
-    hash::{Hash, Hasher},
+    cmp::Ordering,
+    fmt::{Debug, Display, Formatter},
+    hash::{BuildHasher, Hash, Hasher},
     iter::FromIterator,
 };
-use ritelinked::LinkedHashMap;
+use ritelinked::{linked_hash_map::RawEntryMut, LinkedHashMap};
 
 macro_rules! impl_from {
     ($(impl $($from_ty:ty),+ => $ty:ident)+) => {
@@ -35,11 +36,241 @@ pub type YamlRc = Yaml<RcRepr>;
 /// A YAML data with [`alloc::sync::Arc`] holder.
 pub type YamlArc = Yaml<ArcRepr>;
 /// The sequence data structure of YAML.
+///
+/// The YAML helpers are provided by [`SeqExt`].
 pub type Seq<R> = Vec<Node<R>>;
 /// The map data structure of YAML.
+///
+/// The keys can be looked up by the string slices with [`MapExt`].
 pub type Map<R> = LinkedHashMap<Node<R>, Node<R>>;
+/// The map hashed by the randomly keyed SipHash of the standard library, for
+/// the lookup tables built from the untrusted documents.
+///
+/// The keys can be looked up by the string slices with [`MapExt`] as well.
+///
+/// ```
+/// use yaml_peg::{node, MapExt, NodeRc, SipMap};
+///
+/// let n = "{a: 1, b: 2}".parse::<NodeRc>().unwrap();
+/// let m = n.as_map().unwrap().iter().map(|(k, v)| (k.clone(), v.clone())).collect::<SipMap<_>>();
+/// assert_eq!(m.get_key("b"), Some(&node!(2)));
+/// assert_eq!(m.to_node(), n);
+/// ```
+#[cfg(feature = "siphash")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "siphash")))]
+pub type SipMap<R> = LinkedHashMap<Node<R>, Node<R>, std::collections::hash_map::RandomState>;
+
+/// The borrowed form of the map keys, so the keys can be looked up without
+/// creating the key nodes, see [`MapExt`].
+///
+/// The strings are equal to the string nodes only.
+pub trait MapKey<R: Repr> {
+    /// Write the hash as the equal nodes.
+    fn hash_key<H: Hasher>(&self, state: &mut H);
+    /// Return true if the key node is equal to this key.
+    fn eq_key(&self, node: &Node<R>) -> bool;
+}
+
+impl<R: Repr> MapKey<R> for str {
+    fn hash_key<H: Hasher>(&self, state: &mut H) {
+        hash_str(self, state)
+    }
+
+    fn eq_key(&self, node: &Node<R>) -> bool {
+        matches!(node.yaml(), Yaml::Str(s) if s == self)
+    }
+}
+
+impl<R: Repr> MapKey<R> for String {
+    fn hash_key<H: Hasher>(&self, state: &mut H) {
+        hash_str(self, state)
+    }
+
+    fn eq_key(&self, node: &Node<R>) -> bool {
+        MapKey::<R>::eq_key(self.as_str(), node)
+    }
+}
+
+impl<R: Repr> MapKey<R> for Node<R> {
+    fn hash_key<H: Hasher>(&self, state: &mut H) {
+        self.hash(state)
+    }
+
+    fn eq_key(&self, node: &Node<R>) -> bool {
+        self == node
+    }
+}
+
+impl<R: Repr> MapKey<R> for Yaml<R> {
+    fn hash_key<H: Hasher>(&self, state: &mut H) {
+        self.hash(state)
+    }
+
+    fn eq_key(&self, node: &Node<R>) -> bool {
+        self == node.yaml()
+    }
+}
+
+/// The lookups of the [`Map`] by the borrowed keys, such as the string
+/// slices.
+///
+/// ```
+/// use yaml_peg::{node, MapExt};
+///
+/// let mut m = node!({"a" => 1, "b" => 2, 3 => 4}).as_map().unwrap();
+/// assert_eq!(m.get_key("a"), Some(&node!(1)));
+/// assert!(m.has_key("b"));
+/// assert!(!m.has_key("3"));
+/// *m.get_key_mut("a").unwrap() = node!(10);
+/// assert_eq!(m.remove_key("a"), Some(node!(10)));
+/// assert_eq!(m.get_key(&node!(3)), Some(&node!(4)));
+/// ```
+///
+/// The helpers of the YAML data are also provided.
+///
+/// ```
+/// use yaml_peg::{node, MapExt, NodeRc};
+///
+/// let n = "b: x\na: [1]\n".parse::<NodeRc>().unwrap();
+/// let m = n.as_map().unwrap();
+/// assert_eq!(m.get_str("b"), Some("x"));
+/// assert_eq!(m.get_str("a"), None);
+/// assert_eq!(m.positions(), [(0, 3), (5, 8)]);
+/// let sorted = m.sorted();
+/// assert_eq!(sorted.keys().collect::<Vec<_>>(), [&node!("a"), &node!("b")]);
+/// assert_eq!(m.to_node(), n);
+/// ```
+pub trait MapExt<R: Repr> {
+    /// Return the value of the key.
+    fn get_key<Q: MapKey<R> + ?Sized>(&self, key: &Q) -> Option<&Node<R>>;
+    /// Return the mutable value of the key.
+    fn get_key_mut<Q: MapKey<R> + ?Sized>(&mut self, key: &Q) -> Option<&mut Node<R>>;
+    /// Return true if the key is existing.
+    fn has_key<Q: MapKey<R> + ?Sized>(&self, key: &Q) -> bool {
+        self.get_key(key).is_some()
+    }
+    /// Remove the item of the key and return the value, the order of the
+    /// other items is kept.
+    fn remove_key<Q: MapKey<R> + ?Sized>(&mut self, key: &Q) -> Option<Node<R>>;
+    /// Return the string value of the key, see [`Node::as_str`].
+    fn get_str<Q: MapKey<R> + ?Sized>(&self, key: &Q) -> Option<&str>;
+    /// Clone the map with the items sorted by the keys, see [`Node`] for the
+    /// ordering.
+    fn sorted(&self) -> Map<R>;
+    /// Clone the map into a node without the position and the tag.
+    fn to_node(&self) -> Node<R>;
+    /// The positions of the keys and the values in order.
+    fn positions(&self) -> Vec<(u64, u64)>;
+}
+
+impl<R: Repr, S: BuildHasher> MapExt<R> for LinkedHashMap<Node<R>, Node<R>, S> {
+    fn get_key<Q: MapKey<R> + ?Sized>(&self, key: &Q) -> Option<&Node<R>> {
+        let hash = key_hash(self, key);
+        self.raw_entry()
+            .from_hash(hash, |k| key.eq_key(k))
+            .map(|(_, v)| v)
+    }
+
+    fn get_key_mut<Q: MapKey<R> + ?Sized>(&mut self, key: &Q) -> Option<&mut Node<R>> {
+        let hash = key_hash(self, key);
+        match self.raw_entry_mut().from_hash(hash, |k| key.eq_key(k)) {
+            RawEntryMut::Occupied(e) => Some(e.into_mut()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    fn remove_key<Q: MapKey<R> + ?Sized>(&mut self, key: &Q) -> Option<Node<R>> {
+        let hash = key_hash(self, key);
+        match self.raw_entry_mut().from_hash(hash, |k| key.eq_key(k)) {
+            RawEntryMut::Occupied(e) => Some(e.remove()),
+            RawEntryMut::Vacant(_) => None,
+        }
+    }
+
+    fn get_str<Q: MapKey<R> + ?Sized>(&self, key: &Q) -> Option<&str> {
+        self.get_key(key)?.as_str().ok()
+    }
+
+    fn sorted(&self) -> Map<R> {
+        let mut items = self.iter().collect::<Vec<_>>();
+        items.sort_by_key(|(k, _)| *k);
+        items
+            .into_iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    fn to_node(&self) -> Node<R> {
+        let m = self.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        Node::from(Yaml::Map(m))
+    }
+
+    fn positions(&self) -> Vec<(u64, u64)> {
+        self.iter().map(|(k, v)| (k.pos(), v.pos())).collect()
+    }
+}
+
+/// The helpers of the [`Seq`] for the YAML data, same as [`MapExt`].
+///
+/// ```
+/// use yaml_peg::{node, NodeRc, SeqExt};
+///
+/// let n = "[b, 1, a]".parse::<NodeRc>().unwrap();
+/// let v = n.as_seq().unwrap();
+/// assert_eq!(v.get_str(0), Some("b"));
+/// assert_eq!(v.get_str(1), None);
+/// assert_eq!(v.positions(), [1, 4, 7]);
+/// assert_eq!(v.sorted().to_node(), node!([1, "a", "b"]));
+/// ```
+pub trait SeqExt<R: Repr> {
+    /// Return the string item of the index, see [`Node::as_str`].
+    fn get_str(&self, index: usize) -> Option<&str>;
+    /// Clone the sequence with the items sorted, see [`Node`] for the
+    /// ordering.
+    fn sorted(&self) -> Seq<R>;
+    /// Clone the sequence into a node without the position and the tag.
+    fn to_node(&self) -> Node<R>;
+    /// The positions of the items in order.
+    fn positions(&self) -> Vec<u64>;
+}
+
+impl<R: Repr> SeqExt<R> for Seq<R> {
+    fn get_str(&self, index: usize) -> Option<&str> {
+        self.get(index)?.as_str().ok()
+    }
+
+    fn sorted(&self) -> Seq<R> {
+        let mut v = self.clone();
+        v.sort();
+        v
+    }
+
+    fn to_node(&self) -> Node<R> {
+        Node::from(Yaml::Seq(self.clone()))
+    }
+
+    fn positions(&self) -> Vec<u64> {
+        self.iter().map(Node::pos).collect()
+    }
+}
+
+fn key_hash<R: Repr, S: BuildHasher, Q: MapKey<R> + ?Sized>(
+    m: &LinkedHashMap<Node<R>, Node<R>, S>,
+    key: &Q,
+) -> u64 {
+    let mut state = m.hasher().build_hasher();
+    key.hash_key(&mut state);
+    state.finish()
+}
+
+/// The hash of the string nodes.
+fn hash_str<H: Hasher>(s: &str, state: &mut H) {
+    state.write_u8(5);
+    s.hash(state)
+}
 
 pub(crate) fn to_i64(s: &str) -> Result<i64, core::num::ParseIntError> {
+    let s = s.replace('_', "");
     if s.contains("0x") {
         i64::from_str_radix(&s.replace("0x", ""), 16)
     } else if s.contains("0o") {
@@ -49,8 +280,24 @@ pub(crate) fn to_i64(s: &str) -> Result<i64, core::num::ParseIntError> {
     }
 }
 
+pub(crate) fn to_i128(s: &str) -> Result<i128, core::num::ParseIntError> {
+    let s = s.replace('_', "");
+    if s.contains("0x") {
+        i128::from_str_radix(&s.replace("0x", ""), 16)
+    } else if s.contains("0o") {
+        i128::from_str_radix(&s.replace("0o", ""), 8)
+    } else {
+        s.parse()
+    }
+}
+
 pub(crate) fn to_f64(s: &str) -> Result<f64, core::num::ParseFloatError> {
-    s.parse()
+    match s {
+        ".nan" | ".NaN" | ".NAN" => Ok(f64::NAN),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => Ok(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => Ok(f64::NEG_INFINITY),
+        s => s.replace('_', "").parse(),
+    }
 }
 
 /// YAML data types, but it is recommended to use [`Node`] for shorten code.
@@ -79,6 +326,16 @@ pub(crate) fn to_f64(s: &str) -> Result<f64, core::num::ParseFloatError> {
 /// );
 /// ```
 ///
+/// The numbers keep their original lexical form, e.g., `0x10` and `1.50`,
+/// and they are compared by the values.
+///
+/// ```
+/// use yaml_peg::{node, YamlRc};
+///
+/// assert_eq!(YamlRc::Int("0x10".to_string()), YamlRc::from(16));
+/// assert_eq!(YamlRc::Float("1.50".to_string()), YamlRc::from(1.5));
+/// ```
+///
 /// The digit NaN (not-a-number) will be equal in the comparison.
 pub enum Yaml<R: Repr> {
     /// Null
@@ -99,6 +356,61 @@ pub enum Yaml<R: Repr> {
     Alias(String),
 }
 
+impl<R: Repr> Yaml<R> {
+    /// Create an empty sequence with at least the capacity.
+    ///
+    /// ```
+    /// use yaml_peg::{node, YamlRc};
+    ///
+    /// let mut y = YamlRc::seq_with_capacity(2);
+    /// assert_eq!(y.capacity(), Some(2));
+    /// if let YamlRc::Seq(v) = &mut y {
+    ///     v.extend([node!(1), node!(2)]);
+    /// }
+    /// y.clear();
+    /// assert_eq!(y, YamlRc::Seq(Vec::new()));
+    /// assert_eq!(y.capacity(), Some(2));
+    /// ```
+    pub fn seq_with_capacity(capacity: usize) -> Self {
+        Self::Seq(Seq::with_capacity(capacity))
+    }
+
+    /// Create an empty map with at least the capacity.
+    pub fn map_with_capacity(capacity: usize) -> Self {
+        Self::Map(Map::with_capacity(capacity))
+    }
+
+    /// Return the capacity of the sequence or the map, `None` for the
+    /// scalars.
+    pub fn capacity(&self) -> Option<usize> {
+        match self {
+            Self::Seq(v) => Some(v.capacity()),
+            Self::Map(m) => Some(m.capacity()),
+            _ => None,
+        }
+    }
+
+    /// Reserve the capacity for at least `additional` more items of the
+    /// sequence or the map, no effect on the scalars.
+    pub fn reserve(&mut self, additional: usize) {
+        match self {
+            Self::Seq(v) => v.reserve(additional),
+            Self::Map(m) => m.reserve(additional),
+            _ => (),
+        }
+    }
+
+    /// Remove all items of the sequence or the map and keep the capacity, no
+    /// effect on the scalars.
+    pub fn clear(&mut self) {
+        match self {
+            Self::Seq(v) => v.clear(),
+            Self::Map(m) => m.clear(),
+            _ => (),
+        }
+    }
+}
+
 impl<R: Repr> Debug for Yaml<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -114,6 +426,54 @@ impl<R: Repr> Debug for Yaml<R> {
     }
 }
 
+/// The type and the truncated value, for the diagnostic messages.
+///
+/// Use [`Node`]'s `Display` implementation to dump the YAML text.
+///
+/// ```
+/// use yaml_peg::{node, YamlRc};
+///
+/// assert_eq!(YamlRc::from("a\tb").to_string(), r#"str "a\tb""#);
+/// assert_eq!(YamlRc::Int("0x10".into()).to_string(), "int 0x10");
+/// assert_eq!(node!([1, 2]).yaml().to_string(), "seq of 2 items");
+/// assert_eq!(node!(()).yaml().to_string(), "null");
+/// let s = "a".repeat(50);
+/// assert_eq!(YamlRc::from(s).to_string(), format!("str \"{}\"...", "a".repeat(32)));
+/// ```
+impl<R: Repr> Display for Yaml<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        fn truncated(s: &str) -> (&str, &str) {
+            match s.char_indices().nth(32) {
+                Some((i, _)) => (&s[..i], "..."),
+                None => (s, ""),
+            }
+        }
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "bool {}", b),
+            Self::Int(s) | Self::Float(s) => {
+                let name = if let Self::Int(_) = self {
+                    "int"
+                } else {
+                    "float"
+                };
+                let (s, more) = truncated(s);
+                write!(f, "{} {}{}", name, s, more)
+            }
+            Self::Str(s) => {
+                let (s, more) = truncated(s);
+                write!(f, "str {:?}{}", s, more)
+            }
+            Self::Seq(v) => write!(f, "seq of {} items", v.len()),
+            Self::Map(m) => write!(f, "map of {} items", m.len()),
+            Self::Alias(a) => {
+                let (a, more) = truncated(a);
+                write!(f, "alias *{}{}", a, more)
+            }
+        }
+    }
+}
+
 impl<R: Repr> Clone for Yaml<R> {
     fn clone(&self) -> Self {
         match self {
@@ -139,16 +499,21 @@ impl<R: Repr> Hash for Yaml<R> {
             }
             Self::Int(s) => {
                 state.write_u8(3);
-                s.hash(state)
+                match to_i128(s) {
+                    Ok(n) => n.hash(state),
+                    Err(_) => s.hash(state),
+                }
             }
             Self::Float(s) => {
                 state.write_u8(4);
-                s.hash(state)
-            }
-            Self::Str(s) => {
-                state.write_u8(5);
-                s.hash(state)
+                match to_f64(s) {
+                    Ok(f) if f.is_nan() => state.write_u64(f64::NAN.to_bits()),
+                    // Positive and negative zeros are equal
+                    Ok(f) => state.write_u64((f + 0.).to_bits()),
+                    Err(_) => s.hash(state),
+                }
             }
+            Self::Str(s) => hash_str(s, state),
             Self::Seq(s) => {
                 state.write_u8(6);
                 s.hash(state)
@@ -170,16 +535,15 @@ impl<R: Repr> PartialEq for Yaml<R> {
         match (self, other) {
             (Self::Null, Self::Null) => true,
             (Self::Bool(b1), Self::Bool(b2)) => b1 == b2,
-            (Self::Int(s1), Self::Int(s2)) => to_i64(s1).unwrap() == to_i64(s2).unwrap(),
-            (Self::Float(s1), Self::Float(s2)) => {
-                let f1 = to_f64(s1).unwrap();
-                let f2 = to_f64(s2).unwrap();
-                if f1.is_nan() && f2.is_nan() {
-                    true
-                } else {
-                    f1 == f2
-                }
-            }
+            (Self::Int(s1), Self::Int(s2)) => match (to_i128(s1), to_i128(s2)) {
+                (Ok(n1), Ok(n2)) => n1 == n2,
+                _ => s1 == s2,
+            },
+            (Self::Float(s1), Self::Float(s2)) => match (to_f64(s1), to_f64(s2)) {
+                (Ok(f1), Ok(f2)) if f1.is_nan() && f2.is_nan() => true,
+                (Ok(f1), Ok(f2)) => f1 == f2,
+                _ => s1 == s2,
+            },
             (Self::Str(s1), Self::Str(s2)) => s1 == s2,
             (Self::Seq(s1), Self::Seq(s2)) => s1 == s2,
             (Self::Map(m1), Self::Map(m2)) => m1 == m2,
@@ -191,6 +555,102 @@ impl<R: Repr> PartialEq for Yaml<R> {
 
 impl<R: Repr> Eq for Yaml<R> {}
 
+/// The numbers of the total ordering.
+enum Number<'a> {
+    Int(i128),
+    Float(f64),
+    Invalid(u8, &'a str),
+}
+
+impl<'a> Number<'a> {
+    fn new<R: Repr>(yaml: &'a Yaml<R>) -> Option<Self> {
+        match yaml {
+            Yaml::Int(s) => Some(to_i128(s).map_or(Self::Invalid(0, s), Self::Int)),
+            Yaml::Float(s) => Some(to_f64(s).map_or(Self::Invalid(1, s), Self::Float)),
+            _ => None,
+        }
+    }
+
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        match (self, rhs) {
+            (Self::Int(a), Self::Int(b)) => a.cmp(b),
+            (Self::Float(a), Self::Float(b)) => match (a.is_nan(), b.is_nan()) {
+                (false, false) => a.partial_cmp(b).unwrap(),
+                (a, b) => a.cmp(&b),
+            },
+            (Self::Int(a), Self::Float(b)) => cmp_int_float(*a, *b),
+            (Self::Float(a), Self::Int(b)) => cmp_int_float(*b, *a).reverse(),
+            (Self::Invalid(r1, s1), Self::Invalid(r2, s2)) => (r1, s1).cmp(&(r2, s2)),
+            (Self::Invalid(..), _) => Ordering::Greater,
+            (_, Self::Invalid(..)) => Ordering::Less,
+        }
+    }
+}
+
+/// Compare the integer and the float exactly, the integer is less if they
+/// have the same value.
+fn cmp_int_float(a: i128, b: f64) -> Ordering {
+    if b.is_nan() {
+        return Ordering::Less;
+    }
+    // Saturating and truncating toward zero
+    let t = b as i128;
+    match a.cmp(&t) {
+        Ordering::Equal if b < t as f64 => Ordering::Greater,
+        Ordering::Equal => Ordering::Less,
+        ord => ord,
+    }
+}
+
+/// The total ordering of the YAML data, which is consistent with the
+/// equality.
+///
+/// The data are ordered by their types first: null, boolean, number, string,
+/// sequence, map and alias. The integers and floats are compared by their
+/// values, and the integer is placed first if they have the same value. NaN
+/// is greater than any number, and the numbers that cannot be parsed are
+/// placed last. The sequences and maps are compared item by item in the
+/// order of insertion.
+///
+/// ```
+/// use yaml_peg::node;
+///
+/// let mut v = vec![node!("a"), node!(2.5), node!(()), node!(2), node!(0x1), node!(false)];
+/// v.sort();
+/// assert_eq!(v, [node!(()), node!(false), node!(1), node!(2), node!(2.5), node!("a")]);
+/// ```
+impl<R: Repr> Ord for Yaml<R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn rank<R: Repr>(yaml: &Yaml<R>) -> u8 {
+            match yaml {
+                Yaml::Null => 0,
+                Yaml::Bool(_) => 1,
+                Yaml::Int(_) | Yaml::Float(_) => 2,
+                Yaml::Str(_) => 3,
+                Yaml::Seq(_) => 4,
+                Yaml::Map(_) => 5,
+                Yaml::Alias(_) => 6,
+            }
+        }
+        if let (Some(n1), Some(n2)) = (Number::new(self), Number::new(other)) {
+            return n1.cmp(&n2);
+        }
+        match (self, other) {
+            (Self::Bool(b1), Self::Bool(b2)) => b1.cmp(b2),
+            (Self::Str(s1), Self::Str(s2)) | (Self::Alias(s1), Self::Alias(s2)) => s1.cmp(s2),
+            (Self::Seq(s1), Self::Seq(s2)) => s1.cmp(s2),
+            (Self::Map(m1), Self::Map(m2)) => m1.iter().cmp(m2.iter()),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl<R: Repr> PartialOrd for Yaml<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl<R: Repr> From<()> for Yaml<R> {
     fn from(_: ()) -> Self {
         Self::Null